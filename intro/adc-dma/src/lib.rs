@@ -0,0 +1,239 @@
+//! The hardware-independent half of continuous ADC sampling through DMA.
+//!
+//! The DMA engine writes conversion results into a buffer split in two
+//! halves, alternating 0, 1, 0, 1, ... and raising an interrupt each time a
+//! half is full. This crate has:
+//!
+//! * [`ResultFormat`]: the layout of one conversion result in memory, per
+//!   chip, decoded into a [`Sample`];
+//! * [`HalfBuffers`]: the bookkeeping between the interrupt handler and the
+//!   main loop, which tells the main loop which half it may read and whether
+//!   the DMA engine overwrote it while it was being read.
+//!
+//! The main loop never reads the buffer the DMA engine is writing. It
+//! [`acquire`](HalfBuffers::acquire)s a full half, copies it out with
+//! volatile reads ([`HalfBuffers::copy_half`]), and only uses the copy if the
+//! DMA engine had not come back to that half by the end of the copy.
+//!
+//! ```rust
+//! use adc_dma::{HalfBuffers, ResultFormat, Sample, Samples};
+//!
+//! static HALVES: HalfBuffers = HalfBuffers::new();
+//! // Two halves of two samples: ADC1 channel 0 = 1000, channel 3 = 4095
+//! let dma = [[0x0000_03E8, 0x0000_7FFF], [0; 2]];
+//!
+//! HALVES.on_complete(0); // from the interrupt handler
+//! let half = HALVES.acquire().unwrap();
+//! let mut copy = [0; 2];
+//! let intact = unsafe { HALVES.copy_half(half, dma[half.index].as_ptr(), &mut copy) };
+//! assert!(intact);
+//!
+//! let samples: Vec<Sample> = Samples::new(&copy, ResultFormat::Esp32S3).collect();
+//! assert_eq!(samples[0], Sample { unit: 0, channel: 0, raw: 1000 });
+//! assert_eq!(samples[1], Sample { unit: 0, channel: 3, raw: 4095 });
+//! ```
+//!
+//! The `esp32s3-demo` crate sets up the ADC controller, the DMA descriptors
+//! and the interrupt handler around these.
+
+#![no_std]
+
+use core::sync::atomic::{fence, AtomicU32, Ordering};
+
+/// One conversion result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sample {
+    /// 0 for ADC1, 1 for ADC2.
+    pub unit: u8,
+    /// Same as `AdcChannel::adc_channel()` in esp-hal.
+    pub channel: u8,
+    /// The 12-bit reading.
+    pub raw: u16,
+}
+
+/// How the ADC controller lays out a conversion result in a 32-bit word.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResultFormat {
+    /// data\[11:0\], bit 12 reserved, channel\[16:13\], unit\[17\].
+    Esp32S3,
+    /// data\[11:0\], bit 12 reserved, channel\[15:13\], unit\[16\].
+    Esp32C3,
+}
+
+impl ResultFormat {
+    pub const fn decode(self, word: u32) -> Sample {
+        match self {
+            ResultFormat::Esp32S3 => Sample {
+                raw: (word & 0xFFF) as u16,
+                channel: ((word >> 13) & 0xF) as u8,
+                unit: ((word >> 17) & 0x1) as u8,
+            },
+            ResultFormat::Esp32C3 => Sample {
+                raw: (word & 0xFFF) as u16,
+                channel: ((word >> 13) & 0x7) as u8,
+                unit: ((word >> 16) & 0x1) as u8,
+            },
+        }
+    }
+}
+
+/// The samples in a copied half buffer.
+#[derive(Debug, Clone)]
+pub struct Samples<'a> {
+    words: core::slice::Iter<'a, u32>,
+    format: ResultFormat,
+}
+
+impl<'a> Samples<'a> {
+    pub fn new(words: &'a [u32], format: ResultFormat) -> Self {
+        Self {
+            words: words.iter(),
+            format,
+        }
+    }
+
+    /// Only the readings of one channel.
+    pub fn channel(self, channel: u8) -> impl Iterator<Item = u16> + 'a {
+        self.filter(move |s| s.channel == channel).map(|s| s.raw)
+    }
+}
+
+impl Iterator for Samples<'_> {
+    type Item = Sample;
+
+    fn next(&mut self) -> Option<Sample> {
+        self.words.next().map(|&w| self.format.decode(w))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.words.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Samples<'_> {}
+
+/// A full half buffer the main loop may read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadyHalf {
+    /// 0 or 1.
+    pub index: usize,
+    /// Counts the halves filled since the start.
+    pub seq: u32,
+}
+
+/// Bookkeeping between the DMA interrupt handler and the main loop.
+///
+/// The handler calls [`on_complete`](Self::on_complete) each time a half is
+/// full, which only bumps a counter. The main loop takes the next full half
+/// with [`acquire`](Self::acquire) and hands it back with
+/// [`release`](Self::release) or [`copy_half`](Self::copy_half). Since the
+/// DMA engine fills the halves in turn:
+///
+/// * if the main loop is more than one half behind at `acquire`, the older
+///   halves have been overwritten: they are skipped and counted as overruns;
+/// * if by `release` the DMA engine has filled the other half too, it is
+///   writing the acquired one again: the half is counted as an overrun and
+///   whatever was read from it is not to be trusted.
+///
+/// The second check relies on the interrupt handler running as soon as a
+/// half is full, i.e. at a higher priority than the main loop and outside
+/// long critical sections.
+pub struct HalfBuffers {
+    completed: AtomicU32,
+    consumed: AtomicU32,
+    overruns: AtomicU32,
+}
+
+impl HalfBuffers {
+    pub const fn new() -> Self {
+        Self {
+            completed: AtomicU32::new(0),
+            consumed: AtomicU32::new(0),
+            overruns: AtomicU32::new(0),
+        }
+    }
+
+    /// Starts over, before the DMA engine is started.
+    pub fn reset(&self) {
+        self.completed.store(0, Ordering::Relaxed);
+        self.consumed.store(0, Ordering::Relaxed);
+        self.overruns.store(0, Ordering::Relaxed);
+    }
+
+    /// From the interrupt handler: half `index` is full.
+    ///
+    /// If the interrupt came so late that both halves are full, `index` is
+    /// not the expected one and the missed half is counted too.
+    pub fn on_complete(&self, index: usize) {
+        let completed = self.completed.load(Ordering::Relaxed);
+        let expected = (completed % 2) as usize;
+        let step = if index == expected { 1 } else { 2 };
+        // Release: the samples are visible before the new count
+        self.completed
+            .store(completed.wrapping_add(step), Ordering::Release);
+    }
+
+    /// The next full half, skipping those already overwritten.
+    pub fn acquire(&self) -> Option<ReadyHalf> {
+        let completed = self.completed.load(Ordering::Acquire);
+        let mut consumed = self.consumed.load(Ordering::Relaxed);
+        let behind = completed.wrapping_sub(consumed);
+        if behind == 0 {
+            return None;
+        }
+        if behind > 1 {
+            // Only the half filled last is still whole
+            self.overruns.fetch_add(behind - 1, Ordering::Relaxed);
+            consumed = completed.wrapping_sub(1);
+            self.consumed.store(consumed, Ordering::Relaxed);
+        }
+        Some(ReadyHalf {
+            index: (consumed % 2) as usize,
+            seq: consumed,
+        })
+    }
+
+    /// Done with `half`; returns whether the DMA engine left it alone until
+    /// now.
+    pub fn release(&self, half: ReadyHalf) -> bool {
+        let completed = self.completed.load(Ordering::Acquire);
+        let intact = completed.wrapping_sub(half.seq) < 2;
+        if !intact {
+            self.overruns.fetch_add(1, Ordering::Relaxed);
+        }
+        self.consumed
+            .store(half.seq.wrapping_add(1), Ordering::Relaxed);
+        intact
+    }
+
+    /// Copies `half` from the DMA buffer into `copy` and releases it.
+    /// Returns whether the copy is intact; if not, it holds a mix of old and
+    /// new samples and should be dropped.
+    ///
+    /// The buffer is read with volatile reads, as the DMA engine writes it
+    /// behind the compiler's back, and only the acquired half is read.
+    ///
+    /// # Safety
+    ///
+    /// `src` must point to the start of half `half.index` of the DMA buffer,
+    /// valid for `copy.len()` reads of `u32`.
+    pub unsafe fn copy_half(&self, half: ReadyHalf, src: *const u32, copy: &mut [u32]) -> bool {
+        for (i, word) in copy.iter_mut().enumerate() {
+            *word = core::ptr::read_volatile(src.add(i));
+        }
+        // The copy happens before the check in release, as in a seqlock
+        fence(Ordering::Acquire);
+        self.release(half)
+    }
+
+    /// Halves lost since the start, because the main loop was too slow.
+    pub fn overrun_count(&self) -> u32 {
+        self.overruns.load(Ordering::Relaxed)
+    }
+}
+
+impl Default for HalfBuffers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
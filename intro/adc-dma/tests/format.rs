@@ -0,0 +1,86 @@
+use adc_dma::{ResultFormat, Sample, Samples};
+
+const S3: ResultFormat = ResultFormat::Esp32S3;
+const C3: ResultFormat = ResultFormat::Esp32C3;
+
+fn s3_word(unit: u32, channel: u32, raw: u32) -> u32 {
+    (unit << 17) | (channel << 13) | raw
+}
+
+#[test]
+fn s3_fields() {
+    assert_eq!(
+        S3.decode(s3_word(0, 0, 0)),
+        Sample {
+            unit: 0,
+            channel: 0,
+            raw: 0
+        }
+    );
+    assert_eq!(
+        S3.decode(s3_word(1, 9, 4095)),
+        Sample {
+            unit: 1,
+            channel: 9,
+            raw: 4095
+        }
+    );
+    assert_eq!(S3.decode(s3_word(0, 15, 1)).channel, 15);
+}
+
+#[test]
+fn s3_reserved_bit_is_not_part_of_the_reading() {
+    // Bit 12 is reserved: set, it must not add 4096 to the reading
+    let word = s3_word(0, 2, 1234) | 1 << 12;
+    assert_eq!(S3.decode(word).raw, 1234);
+    assert_eq!(S3.decode(word).channel, 2);
+    assert!(Samples::new(&[0xFFFF_FFFF; 4], S3).all(|s| s.raw == 4095));
+}
+
+#[test]
+fn s3_upper_bits_are_ignored() {
+    let word = 0xFFFC_0000 | s3_word(0, 5, 77);
+    assert_eq!(
+        S3.decode(word),
+        Sample {
+            unit: 0,
+            channel: 5,
+            raw: 77
+        }
+    );
+}
+
+#[test]
+fn c3_fields() {
+    let word = (1 << 16) | (4 << 13) | (1 << 12) | 3000;
+    assert_eq!(
+        C3.decode(word),
+        Sample {
+            unit: 1,
+            channel: 4,
+            raw: 3000
+        }
+    );
+    // Three channel bits only
+    assert_eq!(C3.decode(7 << 13).channel, 7);
+    assert_eq!(C3.decode(1 << 16).channel, 0);
+}
+
+#[test]
+fn samples_split_by_channel() {
+    let words = [
+        s3_word(0, 0, 100),
+        s3_word(0, 1, 2000),
+        s3_word(0, 0, 101),
+        s3_word(0, 1, 2001),
+        s3_word(0, 0, 102),
+    ];
+    let samples = Samples::new(&words, S3);
+    assert_eq!(samples.len(), 5);
+    assert_eq!(
+        samples.clone().channel(0).collect::<Vec<_>>(),
+        [100, 101, 102]
+    );
+    assert_eq!(samples.clone().channel(1).collect::<Vec<_>>(), [2000, 2001]);
+    assert_eq!(samples.channel(2).count(), 0);
+}
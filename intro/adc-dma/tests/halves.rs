@@ -0,0 +1,205 @@
+use adc_dma::{HalfBuffers, ReadyHalf};
+
+const HALF: usize = 8;
+
+/// The DMA engine and its interrupt: fills the halves in turn, one word per
+/// step, and calls `on_complete` as soon as a half is full.
+struct Dma {
+    buffer: [[u32; HALF]; 2],
+    seq: u32,
+    pos: usize,
+}
+
+impl Dma {
+    fn new() -> Self {
+        Self {
+            buffer: [[u32::MAX; HALF]; 2],
+            seq: 0,
+            pos: 0,
+        }
+    }
+
+    fn word(seq: u32, pos: usize) -> u32 {
+        seq * 100 + pos as u32
+    }
+
+    fn expected(seq: u32) -> [u32; HALF] {
+        core::array::from_fn(|pos| Self::word(seq, pos))
+    }
+
+    fn step(&mut self, halves: &HalfBuffers) {
+        let index = (self.seq % 2) as usize;
+        self.buffer[index][self.pos] = Self::word(self.seq, self.pos);
+        self.pos += 1;
+        if self.pos == HALF {
+            halves.on_complete(index);
+            self.seq += 1;
+            self.pos = 0;
+        }
+    }
+
+    fn fill(&mut self, halves: &HalfBuffers, count: usize) {
+        for _ in 0..count * HALF {
+            self.step(halves);
+        }
+    }
+
+    fn copy(&self, halves: &HalfBuffers, half: ReadyHalf) -> Option<[u32; HALF]> {
+        let mut copy = [0; HALF];
+        let intact = unsafe { halves.copy_half(half, self.buffer[half.index].as_ptr(), &mut copy) };
+        intact.then_some(copy)
+    }
+}
+
+#[test]
+fn nothing_before_the_first_half() {
+    let halves = HalfBuffers::new();
+    let mut dma = Dma::new();
+    assert_eq!(halves.acquire(), None);
+    for _ in 0..HALF - 1 {
+        dma.step(&halves);
+    }
+    assert_eq!(halves.acquire(), None);
+    dma.step(&halves);
+    assert_eq!(halves.acquire(), Some(ReadyHalf { index: 0, seq: 0 }));
+}
+
+#[test]
+fn keeping_up_gets_every_half_in_order() {
+    let halves = HalfBuffers::new();
+    let mut dma = Dma::new();
+    dma.fill(&halves, 1);
+    for seq in 0..10 {
+        // The DMA engine is already halfway through the next half
+        for _ in 0..HALF / 2 {
+            dma.step(&halves);
+        }
+        let half = halves.acquire().unwrap();
+        assert_eq!(
+            half,
+            ReadyHalf {
+                index: seq as usize % 2,
+                seq
+            }
+        );
+        assert_eq!(dma.copy(&halves, half), Some(Dma::expected(seq)));
+        assert_eq!(halves.acquire(), None);
+        for _ in HALF / 2..HALF {
+            dma.step(&halves);
+        }
+    }
+    assert_eq!(halves.overrun_count(), 0);
+}
+
+#[test]
+fn falling_behind_skips_to_the_last_full_half() {
+    let halves = HalfBuffers::new();
+    let mut dma = Dma::new();
+    dma.fill(&halves, 4);
+    let half = halves.acquire().unwrap();
+    assert_eq!(half, ReadyHalf { index: 1, seq: 3 });
+    assert_eq!(halves.overrun_count(), 3);
+    assert_eq!(dma.copy(&halves, half), Some(Dma::expected(3)));
+    assert_eq!(halves.acquire(), None);
+    assert_eq!(halves.overrun_count(), 3);
+}
+
+#[test]
+fn half_overwritten_during_processing_is_rejected() {
+    let halves = HalfBuffers::new();
+    let mut dma = Dma::new();
+    dma.fill(&halves, 1);
+    let half = halves.acquire().unwrap();
+    // The other half fills up and the DMA engine starts on ours again
+    dma.fill(&halves, 1);
+    dma.step(&halves);
+    assert_eq!(dma.buffer[0][0], Dma::word(2, 0));
+    assert_eq!(dma.copy(&halves, half), None);
+    assert_eq!(halves.overrun_count(), 1);
+
+    // The next acquire picks up the half that just filled
+    assert_eq!(halves.acquire(), Some(ReadyHalf { index: 1, seq: 1 }));
+}
+
+#[test]
+fn release_without_a_copy() {
+    let halves = HalfBuffers::new();
+    let mut dma = Dma::new();
+    dma.fill(&halves, 1);
+    let half = halves.acquire().unwrap();
+    assert!(halves.release(half));
+    dma.fill(&halves, 1);
+    let half = halves.acquire().unwrap();
+    dma.fill(&halves, 1);
+    assert!(!halves.release(half));
+    assert_eq!(halves.overrun_count(), 1);
+}
+
+#[test]
+fn missed_interrupt_counts_the_skipped_half() {
+    let halves = HalfBuffers::new();
+    // Half 0 full, but the interrupt only runs once half 1 is full too
+    halves.on_complete(1);
+    let half = halves.acquire().unwrap();
+    assert_eq!(half, ReadyHalf { index: 1, seq: 1 });
+    assert_eq!(halves.overrun_count(), 1);
+}
+
+#[test]
+fn reset_starts_over() {
+    let halves = HalfBuffers::new();
+    let mut dma = Dma::new();
+    dma.fill(&halves, 5);
+    halves.acquire().unwrap();
+    halves.reset();
+    assert_eq!(halves.overrun_count(), 0);
+    assert_eq!(halves.acquire(), None);
+    halves.on_complete(0);
+    assert_eq!(halves.acquire(), Some(ReadyHalf { index: 0, seq: 0 }));
+}
+
+/// Random schedules of DMA steps against a main loop that acquires, waits
+/// and copies: every intact copy holds exactly the half it claims to, and
+/// every filled half is either delivered or counted as an overrun.
+#[test]
+fn random_schedules_never_deliver_torn_halves() {
+    let mut rng = 0x2545_F491u32;
+    let mut next = move |n: u32| {
+        rng ^= rng << 13;
+        rng ^= rng >> 17;
+        rng ^= rng << 5;
+        rng % n
+    };
+
+    for _ in 0..200 {
+        let halves = HalfBuffers::new();
+        let mut dma = Dma::new();
+        let mut delivered = 0;
+        let mut held: Option<ReadyHalf> = None;
+
+        for _ in 0..2_000 {
+            match next(4) {
+                0 | 1 => dma.step(&halves),
+                2 if held.is_none() => held = halves.acquire(),
+                _ => {
+                    if let Some(half) = held.take() {
+                        if let Some(copy) = dma.copy(&halves, half) {
+                            assert_eq!(copy, Dma::expected(half.seq));
+                            delivered += 1;
+                        }
+                    }
+                }
+            }
+        }
+        if let Some(half) = held.take() {
+            if dma.copy(&halves, half).is_some() {
+                delivered += 1;
+            }
+        }
+        while let Some(half) = halves.acquire() {
+            assert!(dma.copy(&halves, half).is_some());
+            delivered += 1;
+        }
+        assert_eq!(delivered + halves.overrun_count(), dma.seq);
+    }
+}
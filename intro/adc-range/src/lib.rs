@@ -0,0 +1,98 @@
+//! Usable input range of the ESP32-S3 SAR ADC per attenuation, and the
+//! nominal raw-to-millivolt conversion for chips without eFuse calibration.
+//!
+//! Each attenuation has its own full scale, none of them 3.3 V, so
+//! `raw * 3300 / 4095` is wrong at every setting. And the ends of each scale
+//! are unreliable:
+//!
+//! * above [`UsableRange::max_mv`] the ADC compresses and then saturates;
+//! * below [`UsableRange::min_mv`] the reading is no larger than the
+//!   calibrated error the datasheet gives for that attenuation, and
+//!   everything at or below the zero offset reads 0 mV. A smaller
+//!   attenuation measures such signals properly.
+//!
+//! ```rust
+//! use adc_range::{AdcError, Attenuation, UsableRange};
+//!
+//! let range = UsableRange::for_attenuation(Attenuation::_11dB);
+//! assert_eq!(range.check(1650), Ok(1650));
+//! assert_eq!(range.check(3200), Err(AdcError::AboveRange { mv: 3200, max_mv: 3100 }));
+//! assert_eq!(range.check(20), Err(AdcError::BelowRange { mv: 20, min_mv: 50 }));
+//! ```
+//!
+//! The `esp32s3-demo` crate applies the checks to calibrated readings.
+
+#![no_std]
+
+/// Largest raw reading (12 bits).
+pub const ADC_MAX_RAW: u16 = 4095;
+
+/// Input attenuation, mirroring `esp_hal::analog::adc::Attenuation`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Attenuation {
+    _0dB,
+    _2p5dB,
+    _6dB,
+    _11dB,
+}
+
+/// Input voltages, in millivolts, that read reliably at one attenuation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UsableRange {
+    pub min_mv: u16,
+    pub max_mv: u16,
+}
+
+impl UsableRange {
+    /// The upper ends are the measurable ranges from the ESP-IDF ADC
+    /// documentation for the ESP32-S3; the lower ends are the calibrated
+    /// total error from the datasheet (±5, ±6, ±10 and ±50 mV).
+    pub const fn for_attenuation(attenuation: Attenuation) -> Self {
+        let (min_mv, max_mv) = match attenuation {
+            Attenuation::_0dB => (5, 950),
+            Attenuation::_2p5dB => (6, 1250),
+            Attenuation::_6dB => (10, 1750),
+            Attenuation::_11dB => (50, 3100),
+        };
+        Self { min_mv, max_mv }
+    }
+
+    pub const fn contains(&self, mv: u16) -> bool {
+        mv >= self.min_mv && mv <= self.max_mv
+    }
+
+    /// Passes `mv` through if it is in range.
+    pub fn check(&self, mv: u16) -> Result<u16, AdcError> {
+        if mv < self.min_mv {
+            Err(AdcError::BelowRange {
+                mv,
+                min_mv: self.min_mv,
+            })
+        } else if mv > self.max_mv {
+            Err(AdcError::AboveRange {
+                mv,
+                max_mv: self.max_mv,
+            })
+        } else {
+            Ok(mv)
+        }
+    }
+}
+
+/// A reading outside the usable range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdcError {
+    /// Too close to ground for this attenuation: use a smaller one.
+    BelowRange { mv: u16, min_mv: u16 },
+    /// Above the range: use a larger attenuation or a resistor divider.
+    AboveRange { mv: u16, max_mv: u16 },
+}
+
+/// Raw reading to millivolts, scaled linearly to the top of the usable
+/// range. Only for comparison, or for chips without calibration data in
+/// eFuse: it can be off by tens of millivolts.
+pub fn nominal_mv(raw: u16, attenuation: Attenuation) -> u16 {
+    let range = UsableRange::for_attenuation(attenuation);
+    let raw = raw.min(ADC_MAX_RAW) as u32;
+    ((raw * range.max_mv as u32 + ADC_MAX_RAW as u32 / 2) / ADC_MAX_RAW as u32) as u16
+}
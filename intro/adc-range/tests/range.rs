@@ -0,0 +1,98 @@
+use adc_range::{nominal_mv, AdcError, Attenuation, UsableRange, ADC_MAX_RAW};
+
+const ALL: [Attenuation; 4] = [
+    Attenuation::_0dB,
+    Attenuation::_2p5dB,
+    Attenuation::_6dB,
+    Attenuation::_11dB,
+];
+
+#[test]
+fn ranges_per_attenuation() {
+    let ranges = ALL.map(UsableRange::for_attenuation);
+    assert_eq!(
+        ranges.map(|r| (r.min_mv, r.max_mv)),
+        [(5, 950), (6, 1250), (10, 1750), (50, 3100)]
+    );
+    // Each step up reaches higher, and none reaches the 3.3 V supply
+    for w in ranges.windows(2) {
+        assert!(w[0].max_mv < w[1].max_mv);
+        assert!(w[0].min_mv <= w[1].min_mv);
+    }
+    assert!(ranges.iter().all(|r| r.max_mv < 3300 && r.min_mv > 0));
+}
+
+#[test]
+fn every_attenuation_can_report_below_range() {
+    for attenuation in ALL {
+        let range = UsableRange::for_attenuation(attenuation);
+        assert_eq!(
+            range.check(0),
+            Err(AdcError::BelowRange {
+                mv: 0,
+                min_mv: range.min_mv
+            }),
+            "{attenuation:?}"
+        );
+        assert_eq!(
+            range.check(range.min_mv - 1),
+            Err(AdcError::BelowRange {
+                mv: range.min_mv - 1,
+                min_mv: range.min_mv
+            })
+        );
+    }
+}
+
+#[test]
+fn bounds_are_inclusive() {
+    for attenuation in ALL {
+        let range = UsableRange::for_attenuation(attenuation);
+        assert_eq!(range.check(range.min_mv), Ok(range.min_mv));
+        assert_eq!(range.check(range.max_mv), Ok(range.max_mv));
+        assert!(range.contains(range.min_mv) && range.contains(range.max_mv));
+        assert_eq!(
+            range.check(range.max_mv + 1),
+            Err(AdcError::AboveRange {
+                mv: range.max_mv + 1,
+                max_mv: range.max_mv
+            })
+        );
+        assert!(!range.contains(range.max_mv + 1));
+    }
+}
+
+#[test]
+fn small_signal_fits_a_smaller_attenuation() {
+    // 30 mV is lost in the error at 11 dB but fine at 0 dB
+    assert!(!UsableRange::for_attenuation(Attenuation::_11dB).contains(30));
+    assert!(UsableRange::for_attenuation(Attenuation::_0dB).contains(30));
+}
+
+#[test]
+fn nominal_conversion_spans_the_range() {
+    for attenuation in ALL {
+        let max = UsableRange::for_attenuation(attenuation).max_mv;
+        assert_eq!(nominal_mv(0, attenuation), 0);
+        assert_eq!(nominal_mv(ADC_MAX_RAW, attenuation), max);
+        // Out-of-range raw values are clamped
+        assert_eq!(nominal_mv(u16::MAX, attenuation), max);
+    }
+    // Rounded to the nearest millivolt: 2048 * 3100 / 4095 = 1550.4
+    assert_eq!(nominal_mv(2048, Attenuation::_11dB), 1550);
+    // 1 * 950 / 4095 = 0.23
+    assert_eq!(nominal_mv(1, Attenuation::_0dB), 0);
+    assert_eq!(nominal_mv(3, Attenuation::_0dB), 1);
+}
+
+#[test]
+fn nominal_conversion_is_monotonic() {
+    for attenuation in ALL {
+        let mut last = 0;
+        for raw in 0..=ADC_MAX_RAW {
+            let mv = nominal_mv(raw, attenuation);
+            assert!(mv >= last);
+            last = mv;
+        }
+    }
+}
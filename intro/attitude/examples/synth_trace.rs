@@ -0,0 +1,190 @@
+//! Writes the IMU traces in `tests/traces/` to stdout.
+//!
+//! ```text
+//! cargo run --example synth_trace -- still > tests/traces/still.csv
+//! cargo run --example synth_trace -- motion > tests/traces/motion.csv
+//! cargo run --example synth_trace -- bumps > tests/traces/bumps.csv
+//! ```
+//!
+//! Each trace follows a known motion and records what an MPU-6050 at ±4 g,
+//! ±500 °/s and 100 Hz would read: gravity and linear acceleration in the
+//! body frame, the body rates plus a constant bias, white noise, and the
+//! quantization to raw counts. The true Euler angles are stored next to
+//! the readings. These simulated traces are the only ones in the repo; logs
+//! of a real sensor in the same format, with reference angles from another
+//! source, can be dropped next to them.
+
+use std::f64::consts::PI;
+
+const RATE_HZ: f64 = 100.0;
+const ACCEL_LSB_PER_G: f64 = 8192.0;
+const GYRO_LSB_PER_DPS: f64 = 65.5;
+const ACCEL_NOISE_G: f64 = 0.004;
+const GYRO_NOISE_DPS: f64 = 0.05;
+
+/// Truth at time `t`: Euler angles in degrees and linear acceleration in g,
+/// in the world frame.
+type Motion = fn(f64) -> ([f64; 3], [f64; 3]);
+
+struct Trace {
+    description: &'static str,
+    seconds: f64,
+    gyro_bias_dps: [f64; 3],
+    motion: Motion,
+}
+
+fn still(_t: f64) -> ([f64; 3], [f64; 3]) {
+    ([15.0, -25.0, 40.0], [0.0; 3])
+}
+
+fn motion(t: f64) -> ([f64; 3], [f64; 3]) {
+    // Level for 3 s, then rolling, pitching and a slow yaw turn and back
+    let s = (t - 3.0).max(0.0);
+    let ramp = (s / 2.0).min(1.0);
+    let roll = 40.0 * ramp * (2.0 * PI * 0.2 * s).sin();
+    let pitch = 25.0 * ramp * (2.0 * PI * 0.13 * s).sin();
+    let yaw = 90.0 * (1.0 - (2.0 * PI * 0.05 * s).cos());
+    ([roll, pitch, yaw], [0.0; 3])
+}
+
+fn bumps(t: f64) -> ([f64; 3], [f64; 3]) {
+    // Tilted and at rest, pushed sideways for 0.2 s every 2 s
+    let phase = t % 2.0;
+    let push = if (1.0..1.2).contains(&phase) {
+        0.5
+    } else {
+        0.0
+    };
+    ([10.0, 5.0, 0.0], [push, -push, 0.0])
+}
+
+fn trace(name: &str) -> Option<Trace> {
+    Some(match name {
+        "still" => Trace {
+            description: "at rest, roll 15, pitch -25, yaw 40 degrees",
+            seconds: 20.0,
+            gyro_bias_dps: [0.8, -0.5, 0.3],
+            motion: still,
+        },
+        "motion" => Trace {
+            description:
+                "level for 3 s, then +-40 degrees roll, +-25 pitch and a 180 degree yaw swing",
+            seconds: 30.0,
+            gyro_bias_dps: [0.3, -0.2, 0.1],
+            motion,
+        },
+        "bumps" => Trace {
+            description:
+                "at rest, roll 10, pitch 5, with 0.5 g sideways pushes for 0.2 s every 2 s",
+            seconds: 10.0,
+            gyro_bias_dps: [0.0; 3],
+            motion: bumps,
+        },
+        _ => return None,
+    })
+}
+
+/// xorshift64* with Box-Muller, so that the traces are reproducible.
+struct Noise(u64);
+
+impl Noise {
+    fn uniform(&mut self) -> f64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        let bits = self.0.wrapping_mul(0x2545_f491_4f6c_dd1d) >> 11;
+        (bits as f64 + 0.5) / (1u64 << 53) as f64
+    }
+
+    fn gaussian(&mut self, sigma: f64) -> f64 {
+        let (u, v) = (self.uniform(), self.uniform());
+        sigma * (-2.0 * u.ln()).sqrt() * (2.0 * PI * v).cos()
+    }
+}
+
+/// Rows of the world-to-body rotation for Z-Y-X Euler angles in radians.
+fn world_to_body([roll, pitch, yaw]: [f64; 3]) -> [[f64; 3]; 3] {
+    let (sr, cr) = roll.sin_cos();
+    let (sp, cp) = pitch.sin_cos();
+    let (sy, cy) = yaw.sin_cos();
+    [
+        [cp * cy, cp * sy, -sp],
+        [sr * sp * cy - cr * sy, sr * sp * sy + cr * cy, sr * cp],
+        [cr * sp * cy + sr * sy, cr * sp * sy - sr * cy, cr * cp],
+    ]
+}
+
+fn radians(degrees: [f64; 3]) -> [f64; 3] {
+    degrees.map(f64::to_radians)
+}
+
+/// Body rates in rad/s from the Euler angles and their derivatives.
+fn body_rates([roll, pitch, _]: [f64; 3], [dr, dp, dy]: [f64; 3]) -> [f64; 3] {
+    let (sr, cr) = roll.sin_cos();
+    let (sp, cp) = pitch.sin_cos();
+    [
+        dr - dy * sp,
+        dp * cr + dy * cp * sr,
+        -dp * sr + dy * cp * cr,
+    ]
+}
+
+fn wrap_degrees(angle: f64) -> f64 {
+    (angle + 180.0).rem_euclid(360.0) - 180.0
+}
+
+fn main() {
+    let name = std::env::args().nth(1).unwrap_or_default();
+    let Some(trace) = trace(&name) else {
+        eprintln!("usage: synth_trace still|motion|bumps");
+        std::process::exit(2);
+    };
+
+    println!("# simulated by examples/synth_trace.rs, not a sensor log");
+    println!("# {}", trace.description);
+    println!(
+        "# {RATE_HZ} Hz, accel {ACCEL_LSB_PER_G} LSB/g, gyro {GYRO_LSB_PER_DPS} LSB/(deg/s), gyro bias {:?} deg/s",
+        trace.gyro_bias_dps
+    );
+    println!("t_ms,ax,ay,az,gx,gy,gz,roll,pitch,yaw");
+
+    let mut noise = Noise(0x9e37_79b9_7f4a_7c15 ^ name.len() as u64);
+    let dt = 1.0 / RATE_HZ;
+    let h = dt / 100.0;
+    for n in 0..=(trace.seconds * RATE_HZ) as u32 {
+        let t = n as f64 * dt;
+        let (degrees, linear) = (trace.motion)(t);
+        let angles = radians(degrees);
+        let (before, _) = (trace.motion)((t - h).max(0.0));
+        let (after, _) = (trace.motion)(t + h);
+        let span = t + h - (t - h).max(0.0);
+        let rates = std::array::from_fn(|i| (after[i] - before[i]).to_radians() / span);
+        let omega = body_rates(angles, rates);
+
+        // Specific force: gravity pointing up plus the linear acceleration
+        let rotation = world_to_body(angles);
+        let force = [linear[0], linear[1], linear[2] + 1.0];
+        let accel: [f64; 3] =
+            std::array::from_fn(|i| (0..3).map(|j| rotation[i][j] * force[j]).sum());
+
+        let accel_raw = accel.map(|a| (a + noise.gaussian(ACCEL_NOISE_G)) * ACCEL_LSB_PER_G);
+        let gyro_raw: [f64; 3] = std::array::from_fn(|i| {
+            (omega[i].to_degrees() + trace.gyro_bias_dps[i] + noise.gaussian(GYRO_NOISE_DPS))
+                * GYRO_LSB_PER_DPS
+        });
+        let raw = |v: f64| v.round().clamp(i16::MIN as f64, i16::MAX as f64) as i16;
+        println!(
+            "{},{},{},{},{},{},{},{:.3},{:.3},{:.3}",
+            n * 10,
+            raw(accel_raw[0]),
+            raw(accel_raw[1]),
+            raw(accel_raw[2]),
+            raw(gyro_raw[0]),
+            raw(gyro_raw[1]),
+            raw(gyro_raw[2]),
+            degrees[0],
+            degrees[1],
+            wrap_degrees(degrees[2]),
+        );
+    }
+}
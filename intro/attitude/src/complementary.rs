@@ -0,0 +1,88 @@
+//! Complementary filter on Euler angles.
+
+use crate::{
+    quaternion::{tilt, wrap_pi},
+    Estimator, Euler, Quaternion, Scalar,
+};
+
+/// Integrates the gyro into roll, pitch and yaw and pulls roll and pitch
+/// towards the accelerometer's tilt with a first-order low-pass.
+///
+/// The cheapest of the three filters and easy to reason about: with time
+/// constant `tau`, gyro errors slower than `tau` are replaced by the
+/// accelerometer, accelerometer disturbances faster than `tau` are ignored.
+/// It does not estimate gyro bias, so yaw drifts at the bias rate, and the
+/// Euler kinematics become singular at ±90° pitch.
+#[derive(Clone, Debug)]
+pub struct Complementary<T> {
+    tau: T,
+    angles: Euler<T>,
+}
+
+impl<T: Scalar> Complementary<T> {
+    /// Typical time constants are 0.5 to 2 s.
+    pub fn new(tau_s: f32) -> Self {
+        Self {
+            tau: T::from_f32(tau_s),
+            angles: Euler {
+                roll: T::ZERO,
+                pitch: T::ZERO,
+                yaw: T::ZERO,
+            },
+        }
+    }
+
+    pub fn set_tau(&mut self, tau_s: f32) {
+        self.tau = T::from_f32(tau_s);
+    }
+
+    pub fn set_euler(&mut self, angles: Euler<T>) {
+        self.angles = angles;
+    }
+}
+
+impl<T: Scalar> Estimator<T> for Complementary<T> {
+    fn update(&mut self, gyro: [T; 3], accel: [T; 3], dt: T) {
+        // Cosine of pitch near zero makes tan/sec explode; this limits the
+        // damage to a few degrees near the singularity
+        const MIN_COS: f32 = 0.01;
+
+        let Euler { roll, pitch, yaw } = self.angles;
+        let [p, q, r] = gyro;
+        let (sr, cr) = roll.sin_cos();
+        let (sp, cp) = pitch.sin_cos();
+        let min_cos = T::from_f32(MIN_COS);
+        let cp = if cp.abs() < min_cos { min_cos } else { cp };
+
+        // Body rates to Euler angle rates
+        let qs_rc = q * sr + r * cr;
+        let roll_rate = p + qs_rc * sp / cp;
+        let pitch_rate = q * cr - r * sr;
+        let yaw_rate = qs_rc / cp;
+
+        let mut roll = wrap_pi(roll + roll_rate * dt);
+        let mut pitch = (pitch + pitch_rate * dt).clamp(-T::PI * T::HALF, T::PI * T::HALF);
+        let yaw = wrap_pi(yaw + yaw_rate * dt);
+
+        if accel != [T::ZERO; 3] {
+            let measured = tilt(accel);
+            // Discrete low-pass weight dt / (tau + dt)
+            let k = dt / (self.tau + dt);
+            roll = wrap_pi(roll + k * wrap_pi(measured.roll - roll));
+            pitch += k * (measured.pitch - pitch);
+        }
+        self.angles = Euler { roll, pitch, yaw };
+    }
+
+    fn quaternion(&self) -> Quaternion<T> {
+        Quaternion::from_euler(self.angles)
+    }
+
+    fn euler(&self) -> Euler<T> {
+        self.angles
+    }
+
+    fn set_quaternion(&mut self, q: Quaternion<T>) {
+        self.angles = q.normalize().to_euler();
+    }
+}
@@ -0,0 +1,65 @@
+//! Attitude estimation from a 6-axis IMU.
+//!
+//! A gyroscope alone drifts and an accelerometer alone is disturbed by every
+//! bump; the filters here fuse the two into roll, pitch and yaw:
+//!
+//! * [`Complementary`] blends gyro-integrated Euler angles with the
+//!   accelerometer's tilt; cheap and easy to tune.
+//! * [`Mahony`] does the same on a quaternion with a PI correction that
+//!   also learns the gyro bias.
+//! * [`Madgwick`] corrects the quaternion with a gradient-descent step of
+//!   fixed rate.
+//!
+//! All of them implement [`Estimator`] and are generic over [`Scalar`]:
+//! use `f32` on chips with an FPU (the ESP32-S3 has one) and the [`Q24`]
+//! fixed-point type on chips without. Yaw is only the integrated gyro, as
+//! gravity carries no heading information; it drifts slowly unless a
+//! magnetometer corrects it.
+//!
+//! Inputs are in the body frame: angular rate in rad/s and acceleration in
+//! any unit (only its direction is used), with +Z reading +1 g when the
+//! sensor lies flat, as the MPU-6050 does.
+//!
+//! ```rust
+//! use attitude::{Estimator, Mahony};
+//!
+//! let mut filter = Mahony::<f32>::new(1.0, 0.05);
+//! // Level and at rest for one second at 100 Hz
+//! for _ in 0..100 {
+//!     filter.update([0.0; 3], [0.0, 0.0, 1.0], 0.01);
+//! }
+//! let angles = filter.euler().to_degrees();
+//! assert!(angles.roll.abs() < 0.01 && angles.pitch.abs() < 0.01);
+//! ```
+
+#![no_std]
+
+mod complementary;
+mod madgwick;
+mod mahony;
+mod quaternion;
+mod scalar;
+
+pub use complementary::Complementary;
+pub use madgwick::Madgwick;
+pub use mahony::Mahony;
+pub use quaternion::{Euler, Quaternion};
+pub use scalar::{Scalar, Q24};
+
+/// An attitude filter fed with one IMU sample at a time.
+pub trait Estimator<T: Scalar> {
+    /// Advances the estimate by `dt` seconds with `gyro` in rad/s and
+    /// `accel` in any unit. An all-zero `accel` (e.g. free fall or a failed
+    /// read) skips the correction and only integrates the gyro.
+    fn update(&mut self, gyro: [T; 3], accel: [T; 3], dt: T);
+
+    fn quaternion(&self) -> Quaternion<T>;
+
+    fn euler(&self) -> Euler<T> {
+        self.quaternion().to_euler()
+    }
+
+    /// Replaces the estimate, e.g. with [`Quaternion::from_accel`] of the
+    /// first sample to skip the initial convergence.
+    fn set_quaternion(&mut self, q: Quaternion<T>);
+}
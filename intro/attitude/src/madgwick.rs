@@ -0,0 +1,91 @@
+//! Madgwick's gradient-descent filter.
+
+use crate::{mahony::integrate, quaternion::normalize, Estimator, Quaternion, Scalar};
+
+/// Quaternion filter that removes a fixed-rate step along the gradient of
+/// the gravity error from the gyro-propagated rate.
+///
+/// `beta` is the correction rate in rad/s. It should be about the gyro
+/// noise level, `sqrt(3/4)` times the noise in rad/s; larger values
+/// converge faster but let linear acceleration tilt the estimate. There is
+/// no bias estimation, calibrate the gyro first.
+#[derive(Clone, Debug)]
+pub struct Madgwick<T> {
+    beta: T,
+    q: Quaternion<T>,
+}
+
+impl<T: Scalar> Madgwick<T> {
+    /// `beta = 0.1` is the classic default.
+    pub fn new(beta: f32) -> Self {
+        Self {
+            beta: T::from_f32(beta),
+            q: Quaternion::IDENTITY,
+        }
+    }
+
+    pub fn set_beta(&mut self, beta: f32) {
+        self.beta = T::from_f32(beta);
+    }
+}
+
+impl<T: Scalar> Estimator<T> for Madgwick<T> {
+    fn update(&mut self, gyro: [T; 3], accel: [T; 3], dt: T) {
+        let Quaternion { w, x, y, z } = self.q;
+        let Some([ax, ay, az]) = normalize(accel) else {
+            self.q = integrate(self.q, gyro, dt);
+            return;
+        };
+
+        let two = T::TWO;
+        let four = two + two;
+        let eight = four + four;
+        let (ww, xx, yy, zz) = (w * w, x * x, y * y, z * z);
+
+        // Gradient of |q* ⊗ (0, 0, 0, 1) ⊗ q - a|², the Jacobian transposed
+        // times the error
+        let gradient = [
+            four * w * yy + two * y * ax + four * w * xx - two * x * ay,
+            four * x * zz - two * z * ax + four * ww * x - two * w * ay - four * x
+                + eight * x * xx
+                + eight * x * yy
+                + four * x * az,
+            four * ww * y + two * w * ax + four * y * zz - two * z * ay - four * y
+                + eight * y * xx
+                + eight * y * yy
+                + four * y * az,
+            four * xx * z - two * x * ax + four * yy * z - two * y * ay,
+        ];
+
+        // Rate of change from the gyro, minus the correction step
+        let h = T::HALF;
+        let [gx, gy, gz] = gyro;
+        let mut rate = [
+            h * (-x * gx - y * gy - z * gz),
+            h * (w * gx + y * gz - z * gy),
+            h * (w * gy - x * gz + z * gx),
+            h * (w * gz + x * gy - y * gx),
+        ];
+        if let Some(step) = normalize(gradient) {
+            for (r, s) in rate.iter_mut().zip(step) {
+                *r -= self.beta * s;
+            }
+        }
+
+        self.q = Quaternion::new(
+            w + rate[0] * dt,
+            x + rate[1] * dt,
+            y + rate[2] * dt,
+            z + rate[3] * dt,
+        )
+        .normalize();
+    }
+
+    fn quaternion(&self) -> Quaternion<T> {
+        self.q
+    }
+
+    fn set_quaternion(&mut self, q: Quaternion<T>) {
+        self.q = q.normalize();
+    }
+}
@@ -0,0 +1,85 @@
+//! Mahony's nonlinear complementary filter.
+
+use crate::{quaternion::normalize, Estimator, Quaternion, Scalar};
+
+/// Quaternion filter that feeds the angle between measured and estimated
+/// gravity back into the gyro rate through a PI controller.
+///
+/// The proportional gain `kp` sets how fast the accelerometer corrects the
+/// tilt (roughly `1 / tau` of [`Complementary`](crate::Complementary)); the
+/// integral gain `ki` slowly learns the gyro bias on the roll and pitch
+/// axes. Yaw is not observable from gravity, so its bias is only learnt
+/// while the sensor is tilted.
+#[derive(Clone, Debug)]
+pub struct Mahony<T> {
+    kp: T,
+    ki: T,
+    q: Quaternion<T>,
+    bias: [T; 3],
+}
+
+impl<T: Scalar> Mahony<T> {
+    /// `kp = 1.0`, `ki = 0.05` are reasonable for a 100 Hz MEMS IMU.
+    pub fn new(kp: f32, ki: f32) -> Self {
+        Self {
+            kp: T::from_f32(kp),
+            ki: T::from_f32(ki),
+            q: Quaternion::IDENTITY,
+            bias: [T::ZERO; 3],
+        }
+    }
+
+    pub fn set_gains(&mut self, kp: f32, ki: f32) {
+        self.kp = T::from_f32(kp);
+        self.ki = T::from_f32(ki);
+    }
+
+    /// Estimated gyro bias in rad/s, already subtracted from the input.
+    pub fn gyro_bias(&self) -> [T; 3] {
+        self.bias.map(|b| -b)
+    }
+
+    pub fn reset_bias(&mut self) {
+        self.bias = [T::ZERO; 3];
+    }
+}
+
+impl<T: Scalar> Estimator<T> for Mahony<T> {
+    fn update(&mut self, gyro: [T; 3], accel: [T; 3], dt: T) {
+        let mut omega = gyro;
+        if let Some([ax, ay, az]) = normalize(accel) {
+            let [vx, vy, vz] = self.q.gravity();
+            // Rotation from estimated to measured gravity
+            let error = [ay * vz - az * vy, az * vx - ax * vz, ax * vy - ay * vx];
+            for axis in 0..3 {
+                if self.ki > T::ZERO {
+                    self.bias[axis] += self.ki * error[axis] * dt;
+                }
+                omega[axis] += self.kp * error[axis] + self.bias[axis];
+            }
+        }
+        self.q = integrate(self.q, omega, dt);
+    }
+
+    fn quaternion(&self) -> Quaternion<T> {
+        self.q
+    }
+
+    fn set_quaternion(&mut self, q: Quaternion<T>) {
+        self.q = q.normalize();
+    }
+}
+
+/// One Euler step of `q' = q ⊗ (0, ω) / 2`, renormalized.
+pub(crate) fn integrate<T: Scalar>(q: Quaternion<T>, omega: [T; 3], dt: T) -> Quaternion<T> {
+    let h = dt * T::HALF;
+    let [gx, gy, gz] = omega.map(|g| g * h);
+    let Quaternion { w, x, y, z } = q;
+    Quaternion::new(
+        w - x * gx - y * gy - z * gz,
+        x + w * gx + y * gz - z * gy,
+        y + w * gy - x * gz + z * gx,
+        z + w * gz + x * gy - y * gx,
+    )
+    .normalize()
+}
@@ -0,0 +1,188 @@
+//! Orientation representations.
+
+use core::ops::Mul;
+
+use crate::Scalar;
+
+/// Unit quaternion rotating body-frame vectors into the world frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quaternion<T> {
+    pub w: T,
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+/// Aerospace (Z-Y-X) Euler angles in radians: yaw about Z, then pitch about
+/// the new Y, then roll about the new X.
+///
+/// Roll and yaw are in `-π..=π`, pitch in `-π/2..=π/2`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Euler<T> {
+    pub roll: T,
+    pub pitch: T,
+    pub yaw: T,
+}
+
+impl<T: Scalar> Quaternion<T> {
+    pub const IDENTITY: Self = Self {
+        w: T::ONE,
+        x: T::ZERO,
+        y: T::ZERO,
+        z: T::ZERO,
+    };
+
+    pub fn new(w: T, x: T, y: T, z: T) -> Self {
+        Self { w, x, y, z }
+    }
+
+    pub fn conjugate(self) -> Self {
+        Self::new(self.w, -self.x, -self.y, -self.z)
+    }
+
+    /// Scales to unit length; a zero quaternion becomes the identity.
+    pub fn normalize(self) -> Self {
+        let [w, x, y, z] = normalize([self.w, self.x, self.y, self.z]).unwrap_or([
+            T::ONE,
+            T::ZERO,
+            T::ZERO,
+            T::ZERO,
+        ]);
+        Self::new(w, x, y, z)
+    }
+
+    pub fn from_euler(euler: Euler<T>) -> Self {
+        let (sr, cr) = (euler.roll * T::HALF).sin_cos();
+        let (sp, cp) = (euler.pitch * T::HALF).sin_cos();
+        let (sy, cy) = (euler.yaw * T::HALF).sin_cos();
+        Self::new(
+            cr * cp * cy + sr * sp * sy,
+            sr * cp * cy - cr * sp * sy,
+            cr * sp * cy + sr * cp * sy,
+            cr * cp * sy - sr * sp * cy,
+        )
+    }
+
+    /// Roll and pitch that make a resting accelerometer read `accel`, with
+    /// zero yaw; a good starting point for the filters.
+    pub fn from_accel(accel: [T; 3]) -> Self {
+        Self::from_euler(tilt(accel))
+    }
+
+    pub fn to_euler(self) -> Euler<T> {
+        let Self { w, x, y, z } = self;
+        let roll = (T::TWO * (w * x + y * z)).atan2(T::ONE - T::TWO * (x * x + y * y));
+        // asin through atan2, which stays accurate near ±90°
+        let sin_pitch = (T::TWO * (w * y - z * x)).clamp(-T::ONE, T::ONE);
+        let pitch = sin_pitch.atan2((T::ONE - sin_pitch * sin_pitch).sqrt());
+        let yaw = (T::TWO * (w * z + x * y)).atan2(T::ONE - T::TWO * (y * y + z * z));
+        Euler { roll, pitch, yaw }
+    }
+
+    /// Direction of gravity (world +Z) seen from the body frame, i.e. what a
+    /// resting accelerometer reads in g.
+    pub fn gravity(self) -> [T; 3] {
+        let Self { w, x, y, z } = self;
+        [
+            T::TWO * (x * z - w * y),
+            T::TWO * (w * x + y * z),
+            w * w - x * x - y * y + z * z,
+        ]
+    }
+
+    pub fn to_f32(self) -> Quaternion<f32> {
+        Quaternion::new(
+            self.w.to_f32(),
+            self.x.to_f32(),
+            self.y.to_f32(),
+            self.z.to_f32(),
+        )
+    }
+}
+
+impl<T: Scalar> Mul for Quaternion<T> {
+    type Output = Self;
+
+    /// Hamilton product: `a * b` applies `b` first, then `a`.
+    fn mul(self, rhs: Self) -> Self {
+        let (a, b) = (self, rhs);
+        Self::new(
+            a.w * b.w - a.x * b.x - a.y * b.y - a.z * b.z,
+            a.w * b.x + a.x * b.w + a.y * b.z - a.z * b.y,
+            a.w * b.y - a.x * b.z + a.y * b.w + a.z * b.x,
+            a.w * b.z + a.x * b.y - a.y * b.x + a.z * b.w,
+        )
+    }
+}
+
+impl<T: Scalar> Default for Quaternion<T> {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl<T: Scalar> Euler<T> {
+    pub fn to_degrees(self) -> Euler<f32> {
+        let degrees = |rad: T| rad.to_f32().to_degrees();
+        Euler {
+            roll: degrees(self.roll),
+            pitch: degrees(self.pitch),
+            yaw: degrees(self.yaw),
+        }
+    }
+
+    pub fn to_f32(self) -> Euler<f32> {
+        Euler {
+            roll: self.roll.to_f32(),
+            pitch: self.pitch.to_f32(),
+            yaw: self.yaw.to_f32(),
+        }
+    }
+}
+
+/// Roll and pitch of a resting accelerometer reading, yaw is zero.
+pub(crate) fn tilt<T: Scalar>(accel: [T; 3]) -> Euler<T> {
+    let [ax, ay, az] = accel;
+    Euler {
+        roll: ay.atan2(az),
+        pitch: (-ax).atan2((ay * ay + az * az).sqrt()),
+        yaw: T::ZERO,
+    }
+}
+
+/// Scales `v` to unit length, or `None` if it is zero.
+///
+/// Divides by the largest component first so that squaring cannot overflow
+/// a fixed-point type even for ±16 g readings.
+pub(crate) fn normalize<T: Scalar, const N: usize>(mut v: [T; N]) -> Option<[T; N]> {
+    let max = v.iter().fold(T::ZERO, |max, c| {
+        let c = c.abs();
+        if c > max {
+            c
+        } else {
+            max
+        }
+    });
+    if max == T::ZERO {
+        return None;
+    }
+    v.iter_mut().for_each(|c| *c = *c / max);
+    let norm = v.iter().fold(T::ZERO, |sum, &c| sum + c * c).sqrt();
+    if norm == T::ZERO {
+        return None;
+    }
+    v.iter_mut().for_each(|c| *c = *c / norm);
+    Some(v)
+}
+
+/// Wraps an angle into `-π..=π`.
+pub(crate) fn wrap_pi<T: Scalar>(mut angle: T) -> T {
+    let tau = T::TWO * T::PI;
+    while angle > T::PI {
+        angle -= tau;
+    }
+    while angle < -T::PI {
+        angle += tau;
+    }
+    angle
+}
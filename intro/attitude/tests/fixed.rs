@@ -0,0 +1,105 @@
+//! `Q24` arithmetic against `f32`.
+
+use attitude::{Euler, Quaternion, Scalar, Q24};
+
+fn q(v: f32) -> Q24 {
+    Q24::from_num(v)
+}
+
+#[test]
+fn arithmetic_rounds_and_saturates() {
+    assert_eq!((q(1.5) * q(-2.25)).to_num(), -3.375);
+    assert_eq!((q(1.0) / q(3.0)).to_bits(), 5_592_405);
+    assert_eq!(q(100.0) + q(100.0), Q24::MAX);
+    assert_eq!(q(-100.0) * q(2.0), Q24::MIN);
+    assert_eq!(q(1.0) / Q24::ZERO, Q24::MAX);
+    assert_eq!(q(-1.0) / Q24::ZERO, Q24::MIN);
+    assert_eq!(-Q24::MIN, Q24::MAX);
+    assert_eq!(Q24::from_num(f32::NAN), Q24::ZERO);
+}
+
+#[test]
+fn sqrt_is_exact_to_the_last_bit() {
+    for v in [0.0f32, 1e-6, 0.25, 0.5, 1.0, 2.0, 3.0, 100.0, 127.9] {
+        let fixed = q(v).sqrt().to_num();
+        assert!(
+            (fixed - q(v).to_num().sqrt()).abs() < 2e-7,
+            "sqrt({v}) = {fixed}"
+        );
+    }
+    assert_eq!(q(-1.0).sqrt(), Q24::ZERO);
+}
+
+#[test]
+fn atan2_matches_libm_in_every_quadrant() {
+    for step in 0..720 {
+        let angle = (step as f32 * 0.5 - 180.0).to_radians();
+        for radius in [0.001f32, 0.5, 1.0, 16.0] {
+            let (y, x) = (radius * angle.sin(), radius * angle.cos());
+            let fixed = q(y).atan2(q(x)).to_num();
+            let expected = y.atan2(x);
+            let error = (fixed - expected + 3.0 * core::f32::consts::PI)
+                .rem_euclid(core::f32::consts::TAU)
+                - core::f32::consts::PI;
+            // The smallest radius is only a few hundred LSB
+            let tolerance = if radius < 0.01 { 2e-3 } else { 2e-5 };
+            assert!(error.abs() < tolerance, "atan2({y}, {x}) = {fixed}");
+        }
+    }
+    assert_eq!(Q24::ZERO.atan2(Q24::ZERO), Q24::ZERO);
+}
+
+#[test]
+fn sin_cos_matches_libm_over_several_turns() {
+    for step in -2000..=2000 {
+        let x = step as f32 * 0.01;
+        let (s, c) = q(x).sin_cos();
+        assert!((s.to_num() - x.sin()).abs() < 2e-5, "sin({x})");
+        assert!((c.to_num() - x.cos()).abs() < 2e-5, "cos({x})");
+    }
+}
+
+#[test]
+fn euler_round_trip() {
+    for (roll, pitch, yaw) in [
+        (0.0f32, 0.0f32, 0.0f32),
+        (30.0, -20.0, 120.0),
+        (-170.0, 60.0, -45.0),
+        (5.0, 89.0, 10.0),
+    ] {
+        let euler = Euler {
+            roll: roll.to_radians(),
+            pitch: pitch.to_radians(),
+            yaw: yaw.to_radians(),
+        };
+        let float = Quaternion::from_euler(euler).to_euler().to_degrees();
+        let fixed = Quaternion::from_euler(Euler {
+            roll: q(euler.roll),
+            pitch: q(euler.pitch),
+            yaw: q(euler.yaw),
+        })
+        .to_euler()
+        .to_degrees();
+        // Near ±90° pitch asin is ill-conditioned and roll and yaw trade off
+        let (pitch_tolerance, tolerance) = if pitch.abs() > 80.0 {
+            (0.05, 0.5)
+        } else {
+            (0.01, 0.01)
+        };
+        for result in [float, fixed] {
+            assert!((result.pitch - pitch).abs() < pitch_tolerance, "{result:?}");
+            assert!((result.roll - roll).abs() < tolerance, "{result:?}");
+            assert!((result.yaw - yaw).abs() < tolerance, "{result:?}");
+        }
+    }
+}
+
+#[test]
+fn gravity_matches_accelerometer_tilt() {
+    let accel = [-0.3f32, 0.4, 0.866];
+    let gravity = Quaternion::from_accel(accel).gravity();
+    let norm = accel.iter().map(|a| a * a).sum::<f32>().sqrt();
+    for i in 0..3 {
+        assert!((gravity[i] - accel[i] / norm).abs() < 1e-5, "{gravity:?}");
+    }
+}
@@ -0,0 +1,281 @@
+//! The filters replayed over the IMU traces in `tests/traces/` and compared
+//! with the true angles. The traces are simulated by
+//! `examples/synth_trace.rs`, not recorded from a sensor, which is how the
+//! true angles are known.
+
+use attitude::{Complementary, Estimator, Euler, Madgwick, Mahony, Quaternion, Scalar, Q24};
+
+const DT: f32 = 0.01;
+const ACCEL_LSB_PER_G: f32 = 8192.0;
+const GYRO_LSB_PER_DPS: f32 = 65.5;
+
+const STILL: &str = include_str!("traces/still.csv");
+const MOTION: &str = include_str!("traces/motion.csv");
+const BUMPS: &str = include_str!("traces/bumps.csv");
+
+struct Sample {
+    t: f32,
+    accel_g: [f32; 3],
+    gyro_dps: [f32; 3],
+    truth: Euler<f32>,
+}
+
+fn parse(trace: &str) -> Vec<Sample> {
+    trace
+        .lines()
+        .filter(|line| !line.starts_with('#') && !line.starts_with("t_ms"))
+        .map(|line| {
+            let v: Vec<f32> = line.split(',').map(|f| f.parse().unwrap()).collect();
+            Sample {
+                t: v[0] / 1000.0,
+                accel_g: [v[1], v[2], v[3]].map(|a| a / ACCEL_LSB_PER_G),
+                gyro_dps: [v[4], v[5], v[6]].map(|g| g / GYRO_LSB_PER_DPS),
+                truth: Euler {
+                    roll: v[7],
+                    pitch: v[8],
+                    yaw: v[9],
+                },
+            }
+        })
+        .collect()
+}
+
+/// Mean gyro reading over the first `seconds`, like `Mpu6050::calibrate`.
+fn gyro_offsets(samples: &[Sample], seconds: f32) -> [f32; 3] {
+    let still: Vec<_> = samples.iter().filter(|s| s.t < seconds).collect();
+    [0, 1, 2].map(|i| still.iter().map(|s| s.gyro_dps[i]).sum::<f32>() / still.len() as f32)
+}
+
+fn angle_error(estimate: f32, truth: f32) -> f32 {
+    (estimate - truth + 540.0).rem_euclid(360.0) - 180.0
+}
+
+#[derive(Debug, Default)]
+struct Errors {
+    /// Worst absolute error in degrees per axis after the settling time.
+    max: [f32; 3],
+    /// RMS error in degrees per axis after the settling time.
+    rms: [f32; 3],
+    /// Estimates at the end of the trace.
+    last: Euler<f32>,
+}
+
+/// Replays `samples` with the gyro offsets subtracted, starting from the
+/// first sample's accelerometer tilt and true yaw.
+fn replay<T: Scalar>(
+    filter: &mut impl Estimator<T>,
+    samples: &[Sample],
+    gyro_offsets: [f32; 3],
+    settle_s: f32,
+) -> Errors {
+    let first = &samples[0];
+    let tilt = Quaternion::<f32>::from_accel(first.accel_g).to_euler();
+    let start = Euler {
+        yaw: first.truth.yaw.to_radians(),
+        ..tilt
+    };
+    filter.set_quaternion(Quaternion::from_euler(Euler {
+        roll: T::from_f32(start.roll),
+        pitch: T::from_f32(start.pitch),
+        yaw: T::from_f32(start.yaw),
+    }));
+
+    let dt = T::from_f32(DT);
+    let mut errors = Errors::default();
+    let mut squares = [0.0f32; 3];
+    let mut count = 0;
+    for s in samples {
+        let gyro: [f32; 3] =
+            std::array::from_fn(|i| (s.gyro_dps[i] - gyro_offsets[i]).to_radians());
+        filter.update(gyro.map(T::from_f32), s.accel_g.map(T::from_f32), dt);
+        let e = filter.euler().to_degrees();
+        errors.last = e;
+        if s.t < settle_s {
+            continue;
+        }
+        let diff = [
+            angle_error(e.roll, s.truth.roll),
+            angle_error(e.pitch, s.truth.pitch),
+            angle_error(e.yaw, s.truth.yaw),
+        ];
+        for i in 0..3 {
+            errors.max[i] = errors.max[i].max(diff[i].abs());
+            squares[i] += diff[i] * diff[i];
+        }
+        count += 1;
+    }
+    errors.rms = squares.map(|sq| (sq / count as f32).sqrt());
+    errors
+}
+
+fn assert_within(errors: &Errors, max: [f32; 3], rms: [f32; 3]) {
+    for i in 0..3 {
+        assert!(
+            errors.max[i] <= max[i] && errors.rms[i] <= rms[i],
+            "axis {i}: {errors:?}, allowed max {max:?} rms {rms:?}"
+        );
+    }
+}
+
+#[test]
+fn still_uncalibrated_tilt_is_held() {
+    // No gyro calibration: the 0.8 °/s bias must not tilt the estimate
+    let samples = parse(STILL);
+    let yaw_drift = 25.0;
+    assert_within(
+        &replay(&mut Complementary::<f32>::new(0.5), &samples, [0.0; 3], 2.0),
+        [1.0, 1.0, yaw_drift],
+        [0.5, 0.5, yaw_drift],
+    );
+    assert_within(
+        &replay(&mut Mahony::<f32>::new(1.0, 0.05), &samples, [0.0; 3], 2.0),
+        [1.0, 1.0, yaw_drift],
+        [0.5, 0.5, yaw_drift],
+    );
+    assert_within(
+        &replay(&mut Madgwick::<f32>::new(0.1), &samples, [0.0; 3], 2.0),
+        [1.5, 1.5, yaw_drift],
+        [1.0, 1.0, yaw_drift],
+    );
+}
+
+#[test]
+fn mahony_learns_gyro_bias() {
+    let samples = parse(STILL);
+    let mut filter = Mahony::<f32>::new(1.0, 0.1);
+    let errors = replay(&mut filter, &samples, [0.0; 3], 2.0);
+    let bias = filter.gyro_bias().map(f32::to_degrees);
+    // The trace was simulated with a bias of 0.8, -0.5, 0.3 °/s. Only the
+    // component along gravity is unobservable; the sensor is tilted, so
+    // yaw drift stays well below the 0.3 °/s × 20 s of open-loop
+    // integration.
+    let gravity = Quaternion::<f32>::from_euler(Euler {
+        roll: 15f32.to_radians(),
+        pitch: (-25f32).to_radians(),
+        yaw: 0.0,
+    })
+    .gravity();
+    let truth = [0.8f32, -0.5, 0.3];
+    let along = |v: [f32; 3]| v.iter().zip(gravity).map(|(a, g)| a * g).sum::<f32>();
+    let perpendicular: [f32; 3] = std::array::from_fn(|i| {
+        (bias[i] - along(bias) * gravity[i]) - (truth[i] - along(truth) * gravity[i])
+    });
+    for component in perpendicular {
+        assert!(component.abs() < 0.1, "bias {bias:?}");
+    }
+    assert!(errors.max[0] < 1.0 && errors.max[1] < 1.0, "{errors:?}");
+}
+
+#[test]
+fn still_calibrated_yaw_barely_drifts() {
+    // Calibrating over the whole still trace leaves only noise
+    let samples = parse(STILL);
+    let offsets = gyro_offsets(&samples, 20.0);
+    for errors in [
+        replay(&mut Complementary::<f32>::new(0.5), &samples, offsets, 2.0),
+        replay(&mut Mahony::<f32>::new(1.0, 0.0), &samples, offsets, 2.0),
+        replay(&mut Madgwick::<f32>::new(0.1), &samples, offsets, 2.0),
+    ] {
+        assert_within(&errors, [1.0, 1.0, 0.5], [0.5, 0.5, 0.3]);
+    }
+}
+
+#[test]
+fn motion_is_tracked() {
+    let samples = parse(MOTION);
+    // Calibrate over the level part at the start, as the examples do
+    let offsets = gyro_offsets(&samples, 3.0);
+    assert_within(
+        &replay(&mut Complementary::<f32>::new(0.5), &samples, offsets, 0.0),
+        [2.0, 2.0, 3.0],
+        [1.0, 1.0, 2.0],
+    );
+    assert_within(
+        &replay(&mut Mahony::<f32>::new(1.0, 0.05), &samples, offsets, 0.0),
+        [2.0, 2.0, 3.0],
+        [1.0, 1.0, 2.0],
+    );
+    assert_within(
+        &replay(&mut Madgwick::<f32>::new(0.1), &samples, offsets, 0.0),
+        [2.0, 2.0, 3.0],
+        [1.0, 1.0, 2.0],
+    );
+}
+
+#[test]
+fn bumps_are_rejected() {
+    // A 0.5 g push reads as a 27° tilt; the filters should move far less
+    let samples = parse(BUMPS);
+    for errors in [
+        replay(&mut Complementary::<f32>::new(1.0), &samples, [0.0; 3], 0.0),
+        replay(&mut Mahony::<f32>::new(0.5, 0.0), &samples, [0.0; 3], 0.0),
+        replay(&mut Madgwick::<f32>::new(0.05), &samples, [0.0; 3], 0.0),
+    ] {
+        assert_within(&errors, [6.0, 6.0, 1.0], [3.0, 3.0, 0.5]);
+    }
+}
+
+#[test]
+fn converges_from_identity() {
+    // Without the initial tilt the filters have to find it themselves
+    let samples = parse(STILL);
+    let offsets = gyro_offsets(&samples, 20.0);
+    let converge =
+        |filter: &mut dyn FnMut(&Sample) -> Euler<f32>| samples.iter().map(filter).last().unwrap();
+    let mut mahony = Mahony::<f32>::new(1.0, 0.0);
+    let mut madgwick = Madgwick::<f32>::new(0.1);
+    let mut complementary = Complementary::<f32>::new(0.5);
+    let step = |f: &mut dyn Estimator<f32>, s: &Sample| {
+        let gyro: [f32; 3] = std::array::from_fn(|i| (s.gyro_dps[i] - offsets[i]).to_radians());
+        f.update(gyro, s.accel_g, DT);
+        f.euler().to_degrees()
+    };
+    for last in [
+        converge(&mut |s| step(&mut mahony, s)),
+        converge(&mut |s| step(&mut madgwick, s)),
+        converge(&mut |s| step(&mut complementary, s)),
+    ] {
+        assert!((last.roll - 15.0).abs() < 1.0, "{last:?}");
+        assert!((last.pitch + 25.0).abs() < 1.0, "{last:?}");
+    }
+}
+
+#[test]
+fn fixed_point_matches_f32() {
+    for trace in [STILL, MOTION, BUMPS] {
+        let samples = parse(trace);
+        let offsets = gyro_offsets(&samples, 3.0);
+        let pairs = [
+            (
+                replay(&mut Complementary::<f32>::new(0.5), &samples, offsets, 0.0),
+                replay(&mut Complementary::<Q24>::new(0.5), &samples, offsets, 0.0),
+            ),
+            (
+                replay(&mut Mahony::<f32>::new(1.0, 0.05), &samples, offsets, 0.0),
+                replay(&mut Mahony::<Q24>::new(1.0, 0.05), &samples, offsets, 0.0),
+            ),
+            (
+                replay(&mut Madgwick::<f32>::new(0.1), &samples, offsets, 0.0),
+                replay(&mut Madgwick::<Q24>::new(0.1), &samples, offsets, 0.0),
+            ),
+        ];
+        for (float, fixed) in pairs {
+            for i in 0..3 {
+                assert!(
+                    (float.rms[i] - fixed.rms[i]).abs() < 0.1,
+                    "axis {i}: f32 {float:?}, Q24 {fixed:?}"
+                );
+            }
+            for (a, b) in [
+                (float.last.roll, fixed.last.roll),
+                (float.last.pitch, fixed.last.pitch),
+                (float.last.yaw, fixed.last.yaw),
+            ] {
+                assert!(
+                    angle_error(a, b).abs() < 0.1,
+                    "f32 {float:?}, Q24 {fixed:?}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,298 @@
+//! Boot diagnostics: why did the chip (re)boot?
+//!
+//! Reads the reset and wakeup cause, keeps a boot counter and the summary of
+//! the last panic in RTC fast memory (which survives a soft reset), and
+//! prints a structured report with `esp-println` or `defmt`.
+//!
+//! Works on the ESP32-C3 and the ESP32-S3; select the chip with the
+//! `esp32c3` or `esp32s3` feature.
+//!
+//! ```rust,ignore
+//! let report = boot_diag::init();
+//! report.print();
+//! ```
+//!
+//! The panic summary is only recorded if your panic handler calls
+//! [`record_panic`], so use it instead of `esp-backtrace`'s `panic-handler`:
+//!
+//! ```rust,ignore
+//! #[panic_handler]
+//! fn panic(info: &core::panic::PanicInfo) -> ! {
+//!     boot_diag::record_panic(info);
+//!     esp_hal::system::software_reset()
+//! }
+//! ```
+
+#![no_std]
+
+use core::fmt::{self, Write};
+
+use esp_hal::{
+    ram,
+    rtc_cntl::{reset_reason, wakeup_cause, SocResetReason},
+    system::{Cpu, SleepSource},
+};
+
+/// Maximum number of bytes of the panic summary that are kept.
+pub const PANIC_SUMMARY_LEN: usize = 96;
+
+/// Marks the persistent RTC memory as initialized. After a power-on reset the
+/// contents of RTC fast memory are random.
+const MAGIC: u32 = 0xB007_D1A6;
+
+/// Layout of the state kept in RTC fast memory.
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct Persistent {
+    magic: u32,
+    boot_count: u32,
+    panic_len: u32,
+    panic: [u8; PANIC_SUMMARY_LEN],
+}
+
+impl Persistent {
+    const EMPTY: Self = Self {
+        magic: 0,
+        boot_count: 0,
+        panic_len: 0,
+        panic: [0; PANIC_SUMMARY_LEN],
+    };
+}
+
+// Stored as plain words so that the `persistent` section only holds a
+// primitive type.
+const PERSISTENT_WORDS: usize = core::mem::size_of::<Persistent>() / 4;
+
+#[ram(unstable(rtc_fast, persistent))]
+static mut PERSISTENT: [u32; PERSISTENT_WORDS] = [0; PERSISTENT_WORDS];
+
+fn load() -> Persistent {
+    // SAFETY: `Persistent` is `repr(C)`, made of plain integers and its size
+    // is a multiple of 4, so any bit pattern is a valid value.
+    unsafe {
+        core::ptr::addr_of!(PERSISTENT)
+            .cast::<Persistent>()
+            .read_volatile()
+    }
+}
+
+fn store(state: &Persistent) {
+    // SAFETY: see `load`.
+    unsafe {
+        core::ptr::addr_of_mut!(PERSISTENT)
+            .cast::<Persistent>()
+            .write_volatile(*state)
+    }
+}
+
+/// Chip-independent classification of the reset reason.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ResetCause {
+    /// Power-on or the EN/RST pin.
+    PowerOn,
+    /// `software_reset()` or a reset requested by the ROM.
+    Software,
+    /// Software reset after a panic recorded by [`record_panic`].
+    Panic,
+    /// Wakeup from deep sleep.
+    DeepSleep,
+    /// Timer group watchdog (MWDT).
+    TimerGroupWatchdog,
+    /// RTC watchdog.
+    RtcWatchdog,
+    /// Super watchdog.
+    SuperWatchdog,
+    /// Supply voltage dropped below the brownout threshold.
+    Brownout,
+    /// Clock or power glitch detector.
+    Glitch,
+    /// eFuse CRC error.
+    EfuseCrc,
+    /// Reset requested over USB (UART or JTAG).
+    Usb,
+    /// A reason code this crate does not know about.
+    Unknown(u8),
+}
+
+impl ResetCause {
+    /// Classifies a raw ROM reset reason code.
+    ///
+    /// The codes are shared between the ESP32-C3 and the ESP32-S3, only the
+    /// names of the `SocResetReason` variants differ.
+    pub fn from_code(code: u8) -> Self {
+        match code {
+            0x01 => ResetCause::PowerOn,
+            0x03 | 0x0C => ResetCause::Software,
+            0x05 => ResetCause::DeepSleep,
+            0x07 | 0x08 | 0x0B | 0x11 => ResetCause::TimerGroupWatchdog,
+            0x09 | 0x0D | 0x10 => ResetCause::RtcWatchdog,
+            0x12 => ResetCause::SuperWatchdog,
+            0x0F => ResetCause::Brownout,
+            0x13 | 0x17 => ResetCause::Glitch,
+            0x14 => ResetCause::EfuseCrc,
+            0x15 | 0x16 => ResetCause::Usb,
+            other => ResetCause::Unknown(other),
+        }
+    }
+
+    /// Returns `true` if the reset was caused by any watchdog.
+    pub fn is_watchdog(self) -> bool {
+        matches!(
+            self,
+            ResetCause::TimerGroupWatchdog | ResetCause::RtcWatchdog | ResetCause::SuperWatchdog
+        )
+    }
+}
+
+impl fmt::Display for ResetCause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResetCause::PowerOn => f.write_str("power-on"),
+            ResetCause::Software => f.write_str("software reset"),
+            ResetCause::Panic => f.write_str("panic"),
+            ResetCause::DeepSleep => f.write_str("deep sleep wakeup"),
+            ResetCause::TimerGroupWatchdog => f.write_str("timer group watchdog"),
+            ResetCause::RtcWatchdog => f.write_str("RTC watchdog"),
+            ResetCause::SuperWatchdog => f.write_str("super watchdog"),
+            ResetCause::Brownout => f.write_str("brownout"),
+            ResetCause::Glitch => f.write_str("clock/power glitch"),
+            ResetCause::EfuseCrc => f.write_str("eFuse CRC error"),
+            ResetCause::Usb => f.write_str("USB reset"),
+            ResetCause::Unknown(code) => write!(f, "unknown (0x{:02x})", code),
+        }
+    }
+}
+
+/// Everything known about the current boot.
+#[derive(Debug, Clone, Copy)]
+pub struct BootReport {
+    /// Number of boots since the last power-on, starting at 1.
+    pub boot_count: u32,
+    /// Classified reset cause.
+    pub cause: ResetCause,
+    /// Raw reset reason as reported by the HAL.
+    pub reset_reason: Option<SocResetReason>,
+    /// Wakeup source, `Undefined` unless waking up from deep sleep.
+    pub wakeup: SleepSource,
+    panic_len: usize,
+    panic: [u8; PANIC_SUMMARY_LEN],
+}
+
+impl BootReport {
+    /// Summary of the panic that caused the previous reset, if any.
+    pub fn panic_summary(&self) -> Option<&str> {
+        if self.panic_len == 0 {
+            return None;
+        }
+        // The summary may have been truncated in the middle of a character.
+        let bytes = &self.panic[..self.panic_len];
+        match core::str::from_utf8(bytes) {
+            Ok(s) => Some(s),
+            Err(e) => core::str::from_utf8(&bytes[..e.valid_up_to()]).ok(),
+        }
+    }
+
+    /// Prints the report with `esp-println`.
+    #[cfg(feature = "println")]
+    pub fn print(&self) {
+        esp_println::println!("=== Boot diagnostics ===");
+        esp_println::println!("boot count   : {}", self.boot_count);
+        esp_println::println!("reset cause  : {}", self.cause);
+        esp_println::println!("reset reason : {:?}", self.reset_reason);
+        esp_println::println!("wakeup cause : {:?}", self.wakeup);
+        if let Some(panic) = self.panic_summary() {
+            esp_println::println!("last panic   : {}", panic);
+        }
+        esp_println::println!("========================");
+    }
+
+    /// Prints the report with `defmt`.
+    #[cfg(all(feature = "defmt", not(feature = "println")))]
+    pub fn print(&self) {
+        defmt::info!(
+            "boot diagnostics: boot_count={} cause={} wakeup={}",
+            self.boot_count,
+            self.cause,
+            self.wakeup
+        );
+        if let Some(panic) = self.panic_summary() {
+            defmt::warn!("last panic: {=str}", panic);
+        }
+    }
+}
+
+/// Reads the reset cause, increments the boot counter and takes the last
+/// panic summary out of RTC memory.
+///
+/// Call this once, early in `main`.
+pub fn init() -> BootReport {
+    let reset_reason = reset_reason(Cpu::ProCpu);
+    let code = reset_reason.map(|r| r as u8).unwrap_or(0);
+    let mut cause = ResetCause::from_code(code);
+
+    let mut state = load();
+    // RTC fast memory is not retained across power cycles and brownouts.
+    if state.magic != MAGIC || matches!(cause, ResetCause::PowerOn | ResetCause::Brownout) {
+        state = Persistent::EMPTY;
+        state.magic = MAGIC;
+    }
+
+    state.boot_count = state.boot_count.wrapping_add(1);
+
+    let panic_len = (state.panic_len as usize).min(PANIC_SUMMARY_LEN);
+    if panic_len > 0 && cause == ResetCause::Software {
+        cause = ResetCause::Panic;
+    }
+    let report = BootReport {
+        boot_count: state.boot_count,
+        cause,
+        reset_reason,
+        wakeup: wakeup_cause(),
+        panic_len,
+        panic: state.panic,
+    };
+
+    // The panic summary is reported once.
+    state.panic_len = 0;
+    store(&state);
+
+    report
+}
+
+/// Saves a short summary (`file:line: message`) of the panic in RTC memory
+/// so that it can be reported after the next reset.
+pub fn record_panic(info: &core::panic::PanicInfo) {
+    let mut writer = TruncatingWriter {
+        buf: [0; PANIC_SUMMARY_LEN],
+        len: 0,
+    };
+    if let Some(location) = info.location() {
+        let _ = write!(writer, "{}:{}: ", location.file(), location.line());
+    }
+    let _ = write!(writer, "{}", info.message());
+
+    let mut state = load();
+    if state.magic != MAGIC {
+        state = Persistent::EMPTY;
+        state.magic = MAGIC;
+    }
+    state.panic = writer.buf;
+    state.panic_len = writer.len as u32;
+    store(&state);
+}
+
+/// `fmt::Write` into a fixed buffer, silently dropping what does not fit.
+struct TruncatingWriter {
+    buf: [u8; PANIC_SUMMARY_LEN],
+    len: usize,
+}
+
+impl Write for TruncatingWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let n = s.len().min(PANIC_SUMMARY_LEN - self.len);
+        self.buf[self.len..self.len + n].copy_from_slice(&s.as_bytes()[..n]);
+        self.len += n;
+        Ok(())
+    }
+}
@@ -0,0 +1,287 @@
+//! Debounced push-button gestures: press, release, click, double click, long
+//! press and auto-repeat.
+//!
+//! [`Gestures`] is a state machine fed with raw levels and millisecond
+//! timestamps, either sampled by a polling loop ([`Gestures::sample`]) or
+//! recorded by an edge interrupt ([`Gestures::on_edge`]) and processed later
+//! by the main loop ([`Gestures::poll`]). It never reads a pin or a clock
+//! itself, so a gesture can be tested on the host as a scripted timeline of
+//! edges:
+//!
+//! ```rust
+//! use button_gestures::{ButtonConfig, ButtonEvent, Gestures};
+//!
+//! let mut gestures = Gestures::new(ButtonConfig::default());
+//! gestures.on_edge(true, 0);
+//! gestures.on_edge(false, 5); // bounce
+//! gestures.on_edge(true, 8);
+//! assert_eq!(gestures.poll(20), None);
+//! assert_eq!(gestures.poll(28), Some(ButtonEvent::Press));
+//! gestures.on_edge(false, 100);
+//! assert_eq!(gestures.poll(120), Some(ButtonEvent::Release));
+//! // A click is only reported once no second click followed
+//! assert_eq!(gestures.poll(400), None);
+//! assert_eq!(gestures.poll(420), Some(ButtonEvent::Click));
+//! ```
+//!
+//! The `esp32s3-demo` crate wraps it in a polling driver for an
+//! `embedded_hal` input pin.
+
+#![no_std]
+
+use heapless::Deque;
+
+/// What the button did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ButtonEvent {
+    /// Pressed, after debouncing.
+    Press,
+    /// Released, after debouncing.
+    Release,
+    /// Pressed and released once. Reported when the double-click window
+    /// closes without a second press.
+    Click,
+    /// A second press within the double-click window, reported on release.
+    DoubleClick,
+    /// Held for [`ButtonConfig::long_press_ms`]. The press is then no click.
+    LongPress,
+    /// Still held, every [`ButtonConfig::repeat_ms`] after the long press.
+    Repeat,
+}
+
+/// Timings, all in milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ButtonConfig {
+    /// How long a level must stay stable to count.
+    pub debounce_ms: u64,
+    /// Longest gap between a release and the next press of a double click;
+    /// 0 disables double clicks and reports every click at once.
+    pub double_click_ms: u64,
+    pub long_press_ms: u64,
+    /// 0 disables auto-repeat.
+    pub repeat_ms: u64,
+    /// The pin reads low while pressed (button to ground, pull-up).
+    pub active_low: bool,
+}
+
+impl Default for ButtonConfig {
+    fn default() -> Self {
+        Self {
+            debounce_ms: 20,
+            double_click_ms: 300,
+            long_press_ms: 800,
+            repeat_ms: 200,
+            active_low: true,
+        }
+    }
+}
+
+/// Events buffered between two polls; one poll produces at most a few.
+const EVENT_QUEUE_LEN: usize = 8;
+
+/// The gesture state machine.
+pub struct Gestures {
+    config: ButtonConfig,
+    // Latest raw level and since when
+    raw_pressed: bool,
+    raw_since: u64,
+    // Debounced state
+    pressed: bool,
+    pressed_at: u64,
+    next_hold_event: u64,
+    long_fired: bool,
+    // The press was already there at start: it makes no gesture
+    ignore_press: bool,
+    // A released click waiting for the double-click window to close
+    pending_clicks: u8,
+    released_at: u64,
+    events: Deque<ButtonEvent, EVENT_QUEUE_LEN>,
+}
+
+impl Gestures {
+    /// Starts released.
+    pub const fn new(config: ButtonConfig) -> Self {
+        Self {
+            config,
+            raw_pressed: false,
+            raw_since: 0,
+            pressed: false,
+            pressed_at: 0,
+            next_hold_event: 0,
+            long_fired: false,
+            ignore_press: false,
+            pending_clicks: 0,
+            released_at: 0,
+            events: Deque::new(),
+        }
+    }
+
+    /// Starts in the state the button is in at `now_ms`. A button that is
+    /// already held, e.g. since power-on, is not reported at all: no press,
+    /// no long press and no click, and not its release either.
+    pub fn with_initial_state(config: ButtonConfig, pressed: bool, now_ms: u64) -> Self {
+        let mut gestures = Self::new(config);
+        gestures.raw_pressed = pressed;
+        gestures.raw_since = now_ms;
+        gestures.pressed = pressed;
+        gestures.pressed_at = now_ms;
+        gestures.ignore_press = pressed;
+        gestures
+    }
+
+    pub fn config(&self) -> &ButtonConfig {
+        &self.config
+    }
+
+    /// Debounced state.
+    pub fn is_pressed(&self) -> bool {
+        self.pressed
+    }
+
+    /// Whether a pin level means "pressed".
+    pub fn is_pressed_level(&self, is_high: bool) -> bool {
+        is_high != self.config.active_low
+    }
+
+    /// Records a raw edge at `now_ms`; `pressed` is the state after it.
+    ///
+    /// Edges may be fed in a batch, e.g. drained from an interrupt queue
+    /// before a [`Self::poll`]: the state machine first catches up to each
+    /// edge's time, so a press and release between two polls is still a
+    /// click. Edges must come in time order.
+    pub fn on_edge(&mut self, pressed: bool, now_ms: u64) {
+        self.advance(now_ms);
+        if pressed != self.raw_pressed {
+            self.raw_pressed = pressed;
+            self.raw_since = now_ms;
+        }
+    }
+
+    /// Advances the state machine to `now_ms` and returns the oldest event.
+    pub fn poll(&mut self, now_ms: u64) -> Option<ButtonEvent> {
+        self.advance(now_ms);
+        self.events.pop_front()
+    }
+
+    /// [`Self::on_edge`] and [`Self::poll`] in one, for polling loops.
+    pub fn sample(&mut self, pressed: bool, now_ms: u64) -> Option<ButtonEvent> {
+        self.on_edge(pressed, now_ms);
+        self.poll(now_ms)
+    }
+
+    /// How long the current press has lasted, 0 when released.
+    pub fn held_ms(&self, now_ms: u64) -> u64 {
+        if self.pressed {
+            now_ms.saturating_sub(self.pressed_at)
+        } else {
+            0
+        }
+    }
+
+    fn emit(&mut self, event: ButtonEvent) {
+        // Drop the oldest event when the caller does not keep up
+        if self.events.is_full() {
+            self.events.pop_front();
+        }
+        let _ = self.events.push_back(event);
+    }
+
+    // Replays every deadline up to `now_ms` in time order, each at its own
+    // time, so edges recorded long before a poll still give the right
+    // gestures
+    fn advance(&mut self, now_ms: u64) {
+        while let Some(at) = self.next_deadline().filter(|&at| at <= now_ms) {
+            self.step(at, now_ms);
+        }
+    }
+
+    fn window_closes_at(&self) -> Option<u64> {
+        (!self.pressed && self.pending_clicks > 0)
+            .then(|| self.released_at.saturating_add(self.config.double_click_ms))
+    }
+
+    fn debounced_at(&self) -> Option<u64> {
+        (self.raw_pressed != self.pressed)
+            .then(|| self.raw_since.saturating_add(self.config.debounce_ms))
+    }
+
+    fn hold_event_at(&self) -> Option<u64> {
+        let more = !self.long_fired || self.config.repeat_ms > 0;
+        (self.pressed && !self.ignore_press && more).then_some(self.next_hold_event)
+    }
+
+    fn next_deadline(&self) -> Option<u64> {
+        [
+            self.window_closes_at(),
+            self.debounced_at(),
+            self.hold_event_at(),
+        ]
+        .into_iter()
+        .flatten()
+        .min()
+    }
+
+    // Handles one deadline due at `at`. On a tie the double-click window
+    // closes first, or a second press right at its end would still count
+    fn step(&mut self, at: u64, now_ms: u64) {
+        let cfg = self.config;
+
+        if self.window_closes_at().is_some_and(|t| t <= at) {
+            self.pending_clicks = 0;
+            self.emit(ButtonEvent::Click);
+        } else if self.debounced_at().is_some_and(|t| t <= at) {
+            self.pressed = self.raw_pressed;
+            if self.pressed {
+                self.on_press(at);
+            } else {
+                self.on_release(at);
+            }
+        } else if !self.long_fired {
+            self.long_fired = true;
+            self.emit(ButtonEvent::LongPress);
+            self.pending_clicks = 0;
+            self.next_hold_event = if cfg.repeat_ms > 0 {
+                at + cfg.repeat_ms
+            } else {
+                u64::MAX
+            };
+        } else {
+            self.emit(ButtonEvent::Repeat);
+            // Repeats missed by a late poll are skipped, not replayed
+            let next = at + cfg.repeat_ms;
+            self.next_hold_event = if next <= now_ms {
+                now_ms + cfg.repeat_ms
+            } else {
+                next
+            };
+        }
+    }
+
+    fn on_press(&mut self, now_ms: u64) {
+        self.pressed_at = now_ms;
+        self.next_hold_event = now_ms + self.config.long_press_ms;
+        self.long_fired = false;
+        self.emit(ButtonEvent::Press);
+    }
+
+    fn on_release(&mut self, now_ms: u64) {
+        if self.ignore_press {
+            self.ignore_press = false;
+            return;
+        }
+        self.emit(ButtonEvent::Release);
+        if self.long_fired {
+            return;
+        }
+
+        if self.config.double_click_ms == 0 {
+            self.emit(ButtonEvent::Click);
+        } else if self.pending_clicks > 0 {
+            self.pending_clicks = 0;
+            self.emit(ButtonEvent::DoubleClick);
+        } else {
+            self.pending_clicks = 1;
+            self.released_at = now_ms;
+        }
+    }
+}
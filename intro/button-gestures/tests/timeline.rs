@@ -0,0 +1,304 @@
+use button_gestures::{ButtonConfig, ButtonEvent, Gestures};
+use ButtonEvent::*;
+
+const CONFIG: ButtonConfig = ButtonConfig {
+    debounce_ms: 20,
+    double_click_ms: 300,
+    long_press_ms: 800,
+    repeat_ms: 200,
+    active_low: true,
+};
+
+/// Plays `edges` (time, pressed) into `gestures`, polling every millisecond
+/// up to `until`, and returns every event with the time it came out.
+fn run(gestures: &mut Gestures, edges: &[(u64, bool)], until: u64) -> Vec<(u64, ButtonEvent)> {
+    let mut events = Vec::new();
+    let mut edges = edges.iter().peekable();
+    for now in 0..=until {
+        while let Some(&&(at, pressed)) = edges.peek() {
+            if at > now {
+                break;
+            }
+            gestures.on_edge(pressed, at);
+            edges.next();
+        }
+        while let Some(event) = gestures.poll(now) {
+            events.push((now, event));
+        }
+    }
+    events
+}
+
+fn play(edges: &[(u64, bool)], until: u64) -> Vec<(u64, ButtonEvent)> {
+    run(&mut Gestures::new(CONFIG), edges, until)
+}
+
+#[test]
+fn single_click() {
+    let events = play(&[(100, true), (200, false)], 1_000);
+    assert_eq!(
+        events,
+        [(120, Press), (220, Release), (520, Click)],
+        "the click waits for the double-click window"
+    );
+}
+
+#[test]
+fn bounces_are_filtered() {
+    let edges = [
+        (100, true),
+        (103, false),
+        (105, true),
+        (108, false),
+        (110, true),
+        (200, false),
+        (202, true),
+        (204, false),
+    ];
+    assert_eq!(
+        play(&edges, 1_000),
+        [(130, Press), (224, Release), (524, Click)]
+    );
+}
+
+#[test]
+fn glitch_shorter_than_debounce_is_ignored() {
+    assert_eq!(play(&[(100, true), (119, false)], 1_000), []);
+}
+
+#[test]
+fn double_click() {
+    let edges = [(100, true), (200, false), (350, true), (450, false)];
+    assert_eq!(
+        play(&edges, 1_000),
+        [
+            (120, Press),
+            (220, Release),
+            (370, Press),
+            (470, Release),
+            (470, DoubleClick),
+        ]
+    );
+}
+
+#[test]
+fn second_press_after_the_window_is_a_new_click() {
+    // Released at 220, window closes at 520, second press confirmed at 520
+    let edges = [(100, true), (200, false), (500, true), (600, false)];
+    assert_eq!(
+        play(&edges, 1_500),
+        [
+            (120, Press),
+            (220, Release),
+            (520, Click),
+            (520, Press),
+            (620, Release),
+            (920, Click),
+        ]
+    );
+}
+
+#[test]
+fn late_second_press_seen_by_a_slow_poll_is_not_a_double_click() {
+    let mut gestures = Gestures::new(CONFIG);
+    assert_eq!(gestures.sample(true, 0), None);
+    assert_eq!(gestures.sample(true, 20), Some(Press));
+    assert_eq!(gestures.sample(false, 100), None);
+    assert_eq!(gestures.sample(false, 120), Some(Release));
+    // The main loop was busy; by the next poll the window has long closed
+    // and the button is down again
+    gestures.on_edge(true, 700);
+    assert_eq!(gestures.poll(800), Some(Click));
+    assert_eq!(gestures.poll(800), Some(Press));
+    assert_eq!(gestures.poll(800), None);
+    assert_eq!(gestures.sample(false, 850), None);
+    assert_eq!(gestures.sample(false, 870), Some(Release));
+    assert_eq!(gestures.poll(870), None);
+    assert_eq!(gestures.poll(1_170), Some(Click));
+}
+
+/// Feeds all `edges` at once, as drained from an interrupt queue, then
+/// polls once at `poll_at` and returns everything that came out.
+fn batch(gestures: &mut Gestures, edges: &[(u64, bool)], poll_at: u64) -> Vec<ButtonEvent> {
+    for &(at, pressed) in edges {
+        gestures.on_edge(pressed, at);
+    }
+    core::iter::from_fn(|| gestures.poll(poll_at)).collect()
+}
+
+#[test]
+fn press_and_release_between_two_polls_is_a_click() {
+    let mut gestures = Gestures::new(CONFIG);
+    assert_eq!(batch(&mut gestures, &[], 50), []);
+    // Both edges happened while the main loop was busy
+    assert_eq!(
+        batch(&mut gestures, &[(100, true), (200, false)], 250),
+        [Press, Release]
+    );
+    assert_eq!(batch(&mut gestures, &[], 519), []);
+    assert_eq!(batch(&mut gestures, &[], 520), [Click]);
+}
+
+#[test]
+fn bouncy_click_between_two_polls() {
+    let mut gestures = Gestures::new(CONFIG);
+    let edges = [
+        (100, true),
+        (103, false),
+        (105, true),
+        (200, false),
+        (202, true),
+        (204, false),
+    ];
+    assert_eq!(batch(&mut gestures, &edges, 1_000), [Press, Release, Click]);
+}
+
+#[test]
+fn glitch_between_two_polls_is_still_ignored() {
+    let mut gestures = Gestures::new(CONFIG);
+    assert_eq!(
+        batch(&mut gestures, &[(100, true), (119, false)], 1_000),
+        []
+    );
+}
+
+#[test]
+fn double_click_between_two_polls() {
+    let mut gestures = Gestures::new(CONFIG);
+    let edges = [(100, true), (200, false), (350, true), (450, false)];
+    assert_eq!(
+        batch(&mut gestures, &edges, 2_000),
+        [Press, Release, Press, Release, DoubleClick]
+    );
+}
+
+#[test]
+fn long_press_between_two_polls_keeps_its_timing() {
+    // Held 1 s, but both edges only reach the state machine afterwards:
+    // still a long press, not a click
+    let mut gestures = Gestures::new(CONFIG);
+    assert_eq!(
+        batch(&mut gestures, &[(100, true), (1_100, false)], 1_200),
+        [Press, LongPress, Release]
+    );
+    assert_eq!(batch(&mut gestures, &[], 2_000), []);
+}
+
+#[test]
+fn late_poll_skips_missed_repeats() {
+    let mut gestures = Gestures::new(CONFIG);
+    gestures.on_edge(true, 0);
+    // Long press at 820, repeats due every 200 ms from 1020
+    assert_eq!(batch(&mut gestures, &[], 900), [Press, LongPress]);
+    assert_eq!(batch(&mut gestures, &[], 2_000), [Repeat]);
+    assert_eq!(batch(&mut gestures, &[], 2_199), []);
+    assert_eq!(batch(&mut gestures, &[], 2_200), [Repeat]);
+}
+
+#[test]
+fn long_press_then_repeat() {
+    let events = play(&[(0, true), (1_500, false)], 2_000);
+    assert_eq!(
+        events,
+        [
+            (20, Press),
+            (820, LongPress),
+            (1_020, Repeat),
+            (1_220, Repeat),
+            (1_420, Repeat),
+            (1_520, Release),
+        ],
+        "no click after a long press"
+    );
+}
+
+#[test]
+fn long_press_without_repeat() {
+    let mut gestures = Gestures::new(ButtonConfig {
+        repeat_ms: 0,
+        ..CONFIG
+    });
+    let events = run(&mut gestures, &[(0, true), (2_000, false)], 3_000);
+    assert_eq!(events, [(20, Press), (820, LongPress), (2_020, Release)]);
+}
+
+#[test]
+fn long_press_cancels_a_pending_click() {
+    // Click, then press again within the window and hold
+    let edges = [(0, true), (100, false), (200, true), (1_200, false)];
+    assert_eq!(
+        play(&edges, 2_000),
+        [
+            (20, Press),
+            (120, Release),
+            (220, Press),
+            (1_020, LongPress),
+            (1_220, Release),
+        ]
+    );
+}
+
+#[test]
+fn double_click_disabled_reports_clicks_at_once() {
+    let mut gestures = Gestures::new(ButtonConfig {
+        double_click_ms: 0,
+        ..CONFIG
+    });
+    let edges = [(0, true), (100, false), (150, true), (250, false)];
+    assert_eq!(
+        run(&mut gestures, &edges, 1_000),
+        [
+            (20, Press),
+            (120, Release),
+            (120, Click),
+            (170, Press),
+            (270, Release),
+            (270, Click),
+        ]
+    );
+}
+
+#[test]
+fn button_held_at_start_makes_no_gesture() {
+    let mut gestures = Gestures::with_initial_state(CONFIG, true, 5_000);
+    assert!(gestures.is_pressed());
+    // Held well past the long press time: no LongPress, no Repeat
+    let events = run(&mut gestures, &[(7_000, false)], 8_000);
+    assert_eq!(events, []);
+    assert!(!gestures.is_pressed());
+
+    // The next press is a normal one
+    let events = run(&mut gestures, &[(8_100, true), (8_200, false)], 9_000);
+    assert_eq!(events, [(8_120, Press), (8_220, Release), (8_520, Click)]);
+}
+
+#[test]
+fn button_released_at_start_behaves_like_new() {
+    let mut gestures = Gestures::with_initial_state(CONFIG, false, 1_000);
+    let events = run(&mut gestures, &[(1_100, true), (2_000, false)], 2_500);
+    assert_eq!(
+        events,
+        [(1_120, Press), (1_920, LongPress), (2_020, Release)]
+    );
+}
+
+#[test]
+fn active_level() {
+    let low = Gestures::new(CONFIG);
+    assert!(low.is_pressed_level(false));
+    assert!(!low.is_pressed_level(true));
+    let high = Gestures::new(ButtonConfig {
+        active_low: false,
+        ..CONFIG
+    });
+    assert!(high.is_pressed_level(true));
+}
+
+#[test]
+fn held_time() {
+    let mut gestures = Gestures::new(CONFIG);
+    run(&mut gestures, &[(10, true)], 100);
+    assert_eq!(gestures.held_ms(100), 70);
+    run(&mut gestures, &[(150, false)], 200);
+    assert_eq!(gestures.held_ms(200), 0);
+}
@@ -0,0 +1,152 @@
+//! DS1307: 2000..=2099, SQW/OUT pin and 56 bytes of RAM.
+
+use embedded_hal::i2c::I2c;
+
+use crate::{
+    decode_datetime, decode_hour, encode_datetime, encode_hour, register, DateTime, Error,
+    HourMode, SquareWave, ADDRESS,
+};
+
+pub const CONTROL: u8 = 0x07;
+pub const RAM_START: u8 = 0x08;
+/// Bytes of battery-backed RAM, 0x08..=0x3F.
+pub const RAM_SIZE: usize = 56;
+
+const CLOCK_HALT: u8 = 0x80;
+const CONTROL_OUT: u8 = 0x80;
+const CONTROL_SQWE: u8 = 0x10;
+
+/// DS1307 on a blocking I2C bus.
+pub struct Ds1307<I2C> {
+    i2c: I2C,
+    hour_mode: HourMode,
+}
+
+impl<I2C: I2c> Ds1307<I2C> {
+    /// Does not touch the bus.
+    pub fn new(i2c: I2C) -> Self {
+        Self {
+            i2c,
+            hour_mode: HourMode::default(),
+        }
+    }
+
+    /// Reads the date and time in one burst and remembers the chip's hour
+    /// mode. A halted clock still returns the time it stopped at, see
+    /// [`Ds1307::is_running`].
+    pub fn datetime(&mut self) -> Result<DateTime, Error<I2C::Error>> {
+        let mut regs = [0; 7];
+        self.read_registers(register::SECONDS, &mut regs)?;
+        let (dt, mode) = decode_datetime(&regs, false)?;
+        self.hour_mode = mode;
+        Ok(dt)
+    }
+
+    /// Writes the date and time in one burst, in the current hour mode, and
+    /// starts the clock.
+    pub fn set_datetime(&mut self, dt: &DateTime) -> Result<(), Error<I2C::Error>> {
+        let regs = encode_datetime(dt, self.hour_mode, false)?;
+        let mut buf = [0; 8];
+        buf[0] = register::SECONDS;
+        buf[1..].copy_from_slice(&regs);
+        self.i2c.write(ADDRESS, &buf).map_err(Error::I2c)
+    }
+
+    /// The mode read by the last [`Ds1307::datetime`] or set since.
+    pub fn hour_mode(&self) -> HourMode {
+        self.hour_mode
+    }
+
+    /// Switches the chip to `mode`, keeping the current hour.
+    pub fn set_hour_mode(&mut self, mode: HourMode) -> Result<(), Error<I2C::Error>> {
+        let (hour, _) = decode_hour(self.read_register(register::HOURS)?)?;
+        self.write_register(register::HOURS, encode_hour(hour, mode)?)?;
+        self.hour_mode = mode;
+        Ok(())
+    }
+
+    /// `false` while the clock-halt bit is set, as it is on first power-up.
+    pub fn is_running(&mut self) -> Result<bool, Error<I2C::Error>> {
+        Ok(self.read_register(register::SECONDS)? & CLOCK_HALT == 0)
+    }
+
+    /// Stops the oscillator; the time is kept and RAM stays accessible.
+    pub fn halt(&mut self) -> Result<(), Error<I2C::Error>> {
+        let seconds = self.read_register(register::SECONDS)?;
+        self.write_register(register::SECONDS, seconds | CLOCK_HALT)
+    }
+
+    /// Restarts the oscillator from the time it halted at.
+    pub fn start(&mut self) -> Result<(), Error<I2C::Error>> {
+        let seconds = self.read_register(register::SECONDS)?;
+        self.write_register(register::SECONDS, seconds & !CLOCK_HALT)
+    }
+
+    /// [`SquareWave::Hz1024`] is not available.
+    pub fn set_square_wave(&mut self, wave: SquareWave) -> Result<(), Error<I2C::Error>> {
+        let control = match wave {
+            SquareWave::Off => 0,
+            SquareWave::Hz1 => CONTROL_SQWE,
+            SquareWave::Hz4096 => CONTROL_SQWE | 0x01,
+            SquareWave::Hz8192 => CONTROL_SQWE | 0x02,
+            SquareWave::Hz32768 => CONTROL_SQWE | 0x03,
+            SquareWave::Hz1024 => return Err(Error::Unsupported),
+        };
+        self.write_register(CONTROL, control)
+    }
+
+    /// Drives SQW/OUT to a fixed level with the square wave off.
+    pub fn set_output(&mut self, high: bool) -> Result<(), Error<I2C::Error>> {
+        self.write_register(CONTROL, if high { CONTROL_OUT } else { 0 })
+    }
+
+    /// Reads `buf.len()` bytes of RAM from `offset` in `0..RAM_SIZE`.
+    pub fn read_ram(&mut self, offset: usize, buf: &mut [u8]) -> Result<(), Error<I2C::Error>> {
+        check_ram(offset, buf.len())?;
+        if buf.is_empty() {
+            return Ok(());
+        }
+        self.read_registers(RAM_START + offset as u8, buf)
+    }
+
+    /// Writes `data` to RAM from `offset` in `0..RAM_SIZE`, in one burst.
+    pub fn write_ram(&mut self, offset: usize, data: &[u8]) -> Result<(), Error<I2C::Error>> {
+        check_ram(offset, data.len())?;
+        if data.is_empty() {
+            return Ok(());
+        }
+        let mut buf = [0; RAM_SIZE + 1];
+        buf[0] = RAM_START + offset as u8;
+        buf[1..=data.len()].copy_from_slice(data);
+        self.i2c
+            .write(ADDRESS, &buf[..=data.len()])
+            .map_err(Error::I2c)
+    }
+
+    pub fn release(self) -> I2C {
+        self.i2c
+    }
+
+    fn read_register(&mut self, reg: u8) -> Result<u8, Error<I2C::Error>> {
+        let mut value = [0];
+        self.read_registers(reg, &mut value)?;
+        Ok(value[0])
+    }
+
+    fn write_register(&mut self, reg: u8, value: u8) -> Result<(), Error<I2C::Error>> {
+        self.i2c.write(ADDRESS, &[reg, value]).map_err(Error::I2c)
+    }
+
+    fn read_registers(&mut self, reg: u8, buf: &mut [u8]) -> Result<(), Error<I2C::Error>> {
+        self.i2c
+            .write_read(ADDRESS, &[reg], buf)
+            .map_err(Error::I2c)
+    }
+}
+
+fn check_ram<E>(offset: usize, len: usize) -> Result<(), Error<E>> {
+    match offset.checked_add(len) {
+        Some(end) if end <= RAM_SIZE => Ok(()),
+        _ => Err(Error::OutOfRange),
+    }
+}
@@ -0,0 +1,340 @@
+//! DS3231: 2000..=2199 with the century bit, alarms, temperature and aging
+//! offset.
+
+use embedded_hal::i2c::I2c;
+
+use crate::{
+    decode_datetime, decode_hour, encode, encode_datetime, encode_hour, register, DateTime, Error,
+    HourMode, SquareWave, Weekday, ADDRESS,
+};
+
+pub const ALARM1: u8 = 0x07;
+pub const ALARM2: u8 = 0x0b;
+pub const CONTROL: u8 = 0x0e;
+pub const STATUS: u8 = 0x0f;
+pub const AGING_OFFSET: u8 = 0x10;
+pub const TEMPERATURE: u8 = 0x11;
+
+const ALARM_MASK: u8 = 0x80;
+const ALARM_WEEKDAY: u8 = 0x40;
+
+const CONTROL_INTCN: u8 = 0x04;
+const CONTROL_RS_MASK: u8 = 0x18;
+const CONTROL_A2IE: u8 = 0x02;
+const CONTROL_A1IE: u8 = 0x01;
+
+const STATUS_OSF: u8 = 0x80;
+const STATUS_EN32KHZ: u8 = 0x08;
+const STATUS_A2F: u8 = 0x02;
+const STATUS_A1F: u8 = 0x01;
+/// Writing 1 to an alarm flag leaves it unchanged, so a read-modify-write of
+/// the status register cannot lose an alarm that fires in between.
+const STATUS_KEEP_FLAGS: u8 = STATUS_A1F | STATUS_A2F;
+
+/// When alarm 1 fires; the fields that are not listed are ignored.
+/// Hours are 24-hour and written in the driver's [`HourMode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alarm1 {
+    EverySecond,
+    Second {
+        second: u8,
+    },
+    MinuteSecond {
+        minute: u8,
+        second: u8,
+    },
+    Time {
+        hour: u8,
+        minute: u8,
+        second: u8,
+    },
+    /// Day of the month.
+    Date {
+        day: u8,
+        hour: u8,
+        minute: u8,
+        second: u8,
+    },
+    Weekday {
+        weekday: Weekday,
+        hour: u8,
+        minute: u8,
+        second: u8,
+    },
+}
+
+/// When alarm 2 fires, always at second 00.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alarm2 {
+    EveryMinute,
+    Minute {
+        minute: u8,
+    },
+    Time {
+        hour: u8,
+        minute: u8,
+    },
+    Date {
+        day: u8,
+        hour: u8,
+        minute: u8,
+    },
+    Weekday {
+        weekday: Weekday,
+        hour: u8,
+        minute: u8,
+    },
+}
+
+/// Alarm flags, set when an alarm matches whether or not its interrupt is
+/// enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AlarmFlags {
+    pub alarm1: bool,
+    pub alarm2: bool,
+}
+
+impl AlarmFlags {
+    fn bits(self) -> u8 {
+        (if self.alarm1 { STATUS_A1F } else { 0 }) | (if self.alarm2 { STATUS_A2F } else { 0 })
+    }
+}
+
+/// DS3231 on a blocking I2C bus.
+pub struct Ds3231<I2C> {
+    i2c: I2C,
+    hour_mode: HourMode,
+}
+
+impl<I2C: I2c> Ds3231<I2C> {
+    /// Does not touch the bus.
+    pub fn new(i2c: I2C) -> Self {
+        Self {
+            i2c,
+            hour_mode: HourMode::default(),
+        }
+    }
+
+    /// Reads the date and time in one burst and remembers the chip's hour
+    /// mode.
+    pub fn datetime(&mut self) -> Result<DateTime, Error<I2C::Error>> {
+        let mut regs = [0; 7];
+        self.read_registers(register::SECONDS, &mut regs)?;
+        let (dt, mode) = decode_datetime(&regs, true)?;
+        self.hour_mode = mode;
+        Ok(dt)
+    }
+
+    /// Writes the date and time in one burst, in the current hour mode, and
+    /// clears the oscillator-stop flag.
+    pub fn set_datetime(&mut self, dt: &DateTime) -> Result<(), Error<I2C::Error>> {
+        let regs = encode_datetime(dt, self.hour_mode, true)?;
+        let mut buf = [0; 8];
+        buf[0] = register::SECONDS;
+        buf[1..].copy_from_slice(&regs);
+        self.i2c.write(ADDRESS, &buf).map_err(Error::I2c)?;
+        let status = self.read_register(STATUS)?;
+        self.write_register(STATUS, (status | STATUS_KEEP_FLAGS) & !STATUS_OSF)
+    }
+
+    /// The mode read by the last [`Ds3231::datetime`] or set since.
+    pub fn hour_mode(&self) -> HourMode {
+        self.hour_mode
+    }
+
+    /// Switches the chip to `mode`, keeping the current hour. Alarms set
+    /// before keep their old format; set them again.
+    pub fn set_hour_mode(&mut self, mode: HourMode) -> Result<(), Error<I2C::Error>> {
+        let (hour, _) = decode_hour(self.read_register(register::HOURS)?)?;
+        self.write_register(register::HOURS, encode_hour(hour, mode)?)?;
+        self.hour_mode = mode;
+        Ok(())
+    }
+
+    /// `true` if the oscillator stopped since the time was last set, e.g.
+    /// because both supply and battery were lost; the time is then invalid.
+    pub fn lost_power(&mut self) -> Result<bool, Error<I2C::Error>> {
+        Ok(self.read_register(STATUS)? & STATUS_OSF != 0)
+    }
+
+    /// Outputs `wave` on INT/SQW, which disables the alarm interrupts on the
+    /// pin. [`SquareWave::Hz32768`] is not available here, see
+    /// [`Ds3231::set_32khz_output`].
+    pub fn set_square_wave(&mut self, wave: SquareWave) -> Result<(), Error<I2C::Error>> {
+        let rate = match wave {
+            SquareWave::Off => None,
+            SquareWave::Hz1 => Some(0x00),
+            SquareWave::Hz1024 => Some(0x08),
+            SquareWave::Hz4096 => Some(0x10),
+            SquareWave::Hz8192 => Some(0x18),
+            SquareWave::Hz32768 => return Err(Error::Unsupported),
+        };
+        let control = self.read_register(CONTROL)? & !(CONTROL_RS_MASK | CONTROL_INTCN);
+        let control = match rate {
+            Some(rs) => control | rs,
+            None => control | CONTROL_INTCN,
+        };
+        self.write_register(CONTROL, control)
+    }
+
+    pub fn set_32khz_output(&mut self, enabled: bool) -> Result<(), Error<I2C::Error>> {
+        let status = self.read_register(STATUS)? & !STATUS_EN32KHZ;
+        let en = if enabled { STATUS_EN32KHZ } else { 0 };
+        self.write_register(STATUS, status | STATUS_KEEP_FLAGS | en)
+    }
+
+    pub fn set_alarm1(&mut self, alarm: Alarm1) -> Result<(), Error<I2C::Error>> {
+        const OFF: u8 = ALARM_MASK;
+        let mode = self.hour_mode;
+        let regs = match alarm {
+            Alarm1::EverySecond => [OFF; 4],
+            Alarm1::Second { second } => [seconds(second)?, OFF, OFF, OFF],
+            Alarm1::MinuteSecond { minute, second } => {
+                [seconds(second)?, seconds(minute)?, OFF, OFF]
+            }
+            Alarm1::Time {
+                hour,
+                minute,
+                second,
+            } => [
+                seconds(second)?,
+                seconds(minute)?,
+                encode_hour(hour, mode)?,
+                OFF,
+            ],
+            Alarm1::Date {
+                day,
+                hour,
+                minute,
+                second,
+            } => [
+                seconds(second)?,
+                seconds(minute)?,
+                encode_hour(hour, mode)?,
+                date(day)?,
+            ],
+            Alarm1::Weekday {
+                weekday,
+                hour,
+                minute,
+                second,
+            } => [
+                seconds(second)?,
+                seconds(minute)?,
+                encode_hour(hour, mode)?,
+                ALARM_WEEKDAY | weekday.number(),
+            ],
+        };
+        let [a, b, c, d] = regs;
+        self.i2c
+            .write(ADDRESS, &[ALARM1, a, b, c, d])
+            .map_err(Error::I2c)
+    }
+
+    pub fn set_alarm2(&mut self, alarm: Alarm2) -> Result<(), Error<I2C::Error>> {
+        const OFF: u8 = ALARM_MASK;
+        let mode = self.hour_mode;
+        let regs = match alarm {
+            Alarm2::EveryMinute => [OFF; 3],
+            Alarm2::Minute { minute } => [seconds(minute)?, OFF, OFF],
+            Alarm2::Time { hour, minute } => [seconds(minute)?, encode_hour(hour, mode)?, OFF],
+            Alarm2::Date { day, hour, minute } => {
+                [seconds(minute)?, encode_hour(hour, mode)?, date(day)?]
+            }
+            Alarm2::Weekday {
+                weekday,
+                hour,
+                minute,
+            } => [
+                seconds(minute)?,
+                encode_hour(hour, mode)?,
+                ALARM_WEEKDAY | weekday.number(),
+            ],
+        };
+        let [a, b, c] = regs;
+        self.i2c
+            .write(ADDRESS, &[ALARM2, a, b, c])
+            .map_err(Error::I2c)
+    }
+
+    /// Switches INT/SQW to interrupt mode, active low, and enables the
+    /// selected alarms on it. This turns the square wave off.
+    pub fn set_alarm_interrupts(&mut self, enabled: AlarmFlags) -> Result<(), Error<I2C::Error>> {
+        let control = self.read_register(CONTROL)? & !(CONTROL_A1IE | CONTROL_A2IE);
+        let ie = (if enabled.alarm1 { CONTROL_A1IE } else { 0 })
+            | (if enabled.alarm2 { CONTROL_A2IE } else { 0 });
+        self.write_register(CONTROL, control | CONTROL_INTCN | ie)
+    }
+
+    pub fn alarm_flags(&mut self) -> Result<AlarmFlags, Error<I2C::Error>> {
+        let status = self.read_register(STATUS)?;
+        Ok(AlarmFlags {
+            alarm1: status & STATUS_A1F != 0,
+            alarm2: status & STATUS_A2F != 0,
+        })
+    }
+
+    /// Clears the selected flags, which releases INT/SQW.
+    pub fn clear_alarm_flags(&mut self, flags: AlarmFlags) -> Result<(), Error<I2C::Error>> {
+        let status = self.read_register(STATUS)?;
+        self.write_register(STATUS, (status | STATUS_KEEP_FLAGS) & !flags.bits())
+    }
+
+    /// Die temperature in °C, 0.25 °C resolution, updated every 64 s.
+    pub fn temperature(&mut self) -> Result<f32, Error<I2C::Error>> {
+        let mut bytes = [0; 2];
+        self.read_registers(TEMPERATURE, &mut bytes)?;
+        Ok(temperature_c(bytes))
+    }
+
+    /// Crystal trim; one step is about 0.1 ppm at 25 °C, positive values
+    /// slow the clock down.
+    pub fn aging_offset(&mut self) -> Result<i8, Error<I2C::Error>> {
+        Ok(self.read_register(AGING_OFFSET)? as i8)
+    }
+
+    pub fn set_aging_offset(&mut self, offset: i8) -> Result<(), Error<I2C::Error>> {
+        self.write_register(AGING_OFFSET, offset as u8)
+    }
+
+    pub fn release(self) -> I2C {
+        self.i2c
+    }
+
+    fn read_register(&mut self, reg: u8) -> Result<u8, Error<I2C::Error>> {
+        let mut value = [0];
+        self.read_registers(reg, &mut value)?;
+        Ok(value[0])
+    }
+
+    fn write_register(&mut self, reg: u8, value: u8) -> Result<(), Error<I2C::Error>> {
+        self.i2c.write(ADDRESS, &[reg, value]).map_err(Error::I2c)
+    }
+
+    fn read_registers(&mut self, reg: u8, buf: &mut [u8]) -> Result<(), Error<I2C::Error>> {
+        self.i2c
+            .write_read(ADDRESS, &[reg], buf)
+            .map_err(Error::I2c)
+    }
+}
+
+/// Temperature registers 0x11 (integer part) and 0x12 (quarters in the top
+/// two bits) in °C.
+pub fn temperature_c(bytes: [u8; 2]) -> f32 {
+    (i16::from_be_bytes(bytes) >> 6) as f32 * 0.25
+}
+
+/// Seconds or minutes field of an alarm.
+fn seconds<E>(value: u8) -> Result<u8, Error<E>> {
+    if value >= 60 {
+        return Err(Error::InvalidDateTime);
+    }
+    encode(value)
+}
+
+fn date<E>(day: u8) -> Result<u8, Error<E>> {
+    if !(1..=31).contains(&day) {
+        return Err(Error::InvalidDateTime);
+    }
+    encode(day)
+}
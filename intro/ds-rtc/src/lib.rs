@@ -0,0 +1,292 @@
+//! `no_std` drivers for the Maxim DS1307 and DS3231 I2C real-time clocks.
+//!
+//! Both chips keep the time in the same seven BCD registers at 0x00..=0x06,
+//! so the encoding lives here and [`ds1307::Ds1307`] and [`ds3231::Ds3231`]
+//! only add what is specific to each chip:
+//!
+//! * DS1307: the clock-halt bit, the SQW/OUT pin and 56 bytes of
+//!   battery-backed RAM.
+//! * DS3231: the century bit, two alarms, the square wave or interrupt on
+//!   INT/SQW, the 32 kHz output, the temperature sensor and the aging
+//!   offset. It has no user RAM.
+//!
+//! [`DateTime`] is always in 24-hour form; whether the chip counts in 12 or
+//! 24-hour mode is a separate [`HourMode`], detected on every read and
+//! chosen on every write. The weekday register is written from the date
+//! (1 = Sunday) and ignored when reading.
+//!
+//! ```rust
+//! use ds_rtc::{DateTime, Weekday};
+//!
+//! let dt = DateTime::new(2024, 2, 29, 23, 59, 59).unwrap();
+//! assert_eq!(dt.weekday(), Weekday::Thursday);
+//! assert!(DateTime::new(2023, 2, 29, 0, 0, 0).is_none());
+//! ```
+
+#![no_std]
+
+use nobcd::BcdNumber;
+
+pub mod ds1307;
+pub mod ds3231;
+
+/// Both chips answer at this fixed address.
+pub const ADDRESS: u8 = 0x68;
+
+/// Register addresses shared by both chips.
+pub mod register {
+    pub const SECONDS: u8 = 0x00;
+    pub const MINUTES: u8 = 0x01;
+    pub const HOURS: u8 = 0x02;
+    pub const WEEKDAY: u8 = 0x03;
+    pub const DATE: u8 = 0x04;
+    pub const MONTH: u8 = 0x05;
+    pub const YEAR: u8 = 0x06;
+}
+
+pub(crate) mod bits {
+    pub const HOUR_12: u8 = 0x40;
+    pub const HOUR_PM: u8 = 0x20;
+    pub const CENTURY: u8 = 0x80;
+}
+
+/// How the chip counts hours.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HourMode {
+    #[default]
+    H24,
+    /// 1..=12 with an AM/PM flag.
+    H12,
+}
+
+/// Day of the week as the weekday register counts it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Weekday {
+    Sunday = 1,
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+}
+
+impl Weekday {
+    /// `1` = Sunday to `7` = Saturday.
+    pub fn from_number(number: u8) -> Option<Self> {
+        Some(match number {
+            1 => Self::Sunday,
+            2 => Self::Monday,
+            3 => Self::Tuesday,
+            4 => Self::Wednesday,
+            5 => Self::Thursday,
+            6 => Self::Friday,
+            7 => Self::Saturday,
+            _ => return None,
+        })
+    }
+
+    pub fn number(self) -> u8 {
+        self as u8
+    }
+}
+
+/// Calendar date and 24-hour time of day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DateTime {
+    pub year: u16,
+    /// 1..=12
+    pub month: u8,
+    /// 1..=31
+    pub day: u8,
+    /// 0..=23
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl DateTime {
+    /// `None` unless every field is in range, including February 29th only
+    /// in leap years. The years the chips can hold are checked when writing.
+    pub fn new(year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> Option<Self> {
+        let dt = Self {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+        };
+        dt.is_valid().then_some(dt)
+    }
+
+    pub fn is_valid(&self) -> bool {
+        (1..=12).contains(&self.month)
+            && self.day >= 1
+            && self.day <= days_in_month(self.year, self.month)
+            && self.hour < 24
+            && self.minute < 60
+            && self.second < 60
+    }
+
+    /// Proleptic Gregorian weekday, for every year from 0 to 65535. The
+    /// result is meaningless, but does not panic, for an invalid date.
+    pub fn weekday(&self) -> Weekday {
+        // Sakamoto's method, 0 = Sunday. Shifting by one 400-year cycle,
+        // which is a whole number of weeks, keeps year 0 from underflowing
+        const OFFSETS: [u32; 12] = [0, 3, 2, 5, 0, 3, 5, 1, 4, 6, 2, 4];
+        let year = u32::from(self.year) + 400 - u32::from(self.month < 3);
+        let days = year + year / 4 - year / 100
+            + year / 400
+            + OFFSETS[usize::from(self.month.wrapping_sub(1)) % 12]
+            + u32::from(self.day);
+        // Every value in 0..7 is a weekday
+        Weekday::from_number((days % 7) as u8 + 1).unwrap_or(Weekday::Sunday)
+    }
+}
+
+/// Gregorian leap year.
+///
+/// Both chips treat every year divisible by four as a leap year, so they
+/// count a February 29th in 2100; reading that date back fails with
+/// [`Error::InvalidDateTime`].
+pub fn is_leap_year(year: u16) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+pub fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => 0,
+    }
+}
+
+/// Square-wave frequency on the SQW pin, or [`SquareWave::Off`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SquareWave {
+    /// DS1307: the pin is driven low. DS3231: INT/SQW is left to the alarm
+    /// interrupts.
+    #[default]
+    Off,
+    Hz1,
+    /// DS3231 only.
+    Hz1024,
+    Hz4096,
+    Hz8192,
+    /// DS1307 only; the DS3231 has a separate 32 kHz pin.
+    Hz32768,
+}
+
+/// Driver error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error<E> {
+    I2c(E),
+    /// A register held this byte, which is not valid BCD.
+    InvalidBcd(u8),
+    /// A date or time out of range, read from the chip or passed in,
+    /// including years the chip cannot hold.
+    InvalidDateTime,
+    /// The chip does not have this square-wave frequency.
+    Unsupported,
+    /// The RAM access does not fit in the RAM.
+    OutOfRange,
+}
+
+/// Packs `0..=99` into one BCD byte.
+pub(crate) fn encode<E>(value: u8) -> Result<u8, Error<E>> {
+    BcdNumber::<1>::new(value)
+        .map(|n| n.bcd_bytes()[0])
+        .map_err(|_| Error::InvalidDateTime)
+}
+
+pub(crate) fn decode<E>(byte: u8) -> Result<u8, Error<E>> {
+    BcdNumber::<1>::from_bcd_bytes([byte])
+        .map(|n| n.value::<u8>())
+        .map_err(|_| Error::InvalidBcd(byte))
+}
+
+/// Hours register (and alarm hours, without the mask bit) for 24-hour
+/// `hour` in `mode`.
+pub(crate) fn encode_hour<E>(hour: u8, mode: HourMode) -> Result<u8, Error<E>> {
+    if hour >= 24 {
+        return Err(Error::InvalidDateTime);
+    }
+    match mode {
+        HourMode::H24 => encode(hour),
+        HourMode::H12 => {
+            // 00:xx is 12 AM, 12:xx is 12 PM
+            let pm = if hour >= 12 { bits::HOUR_PM } else { 0 };
+            let h12 = match hour % 12 {
+                0 => 12,
+                h => h,
+            };
+            Ok(bits::HOUR_12 | pm | encode::<E>(h12)?)
+        }
+    }
+}
+
+/// 24-hour value and mode of an hours register.
+pub(crate) fn decode_hour<E>(byte: u8) -> Result<(u8, HourMode), Error<E>> {
+    if byte & bits::HOUR_12 != 0 {
+        let h12 = decode(byte & 0x1f)?;
+        if !(1..=12).contains(&h12) {
+            return Err(Error::InvalidDateTime);
+        }
+        let pm = byte & bits::HOUR_PM != 0;
+        Ok((h12 % 12 + if pm { 12 } else { 0 }, HourMode::H12))
+    } else {
+        let hour = decode(byte & 0x3f)?;
+        if hour >= 24 {
+            return Err(Error::InvalidDateTime);
+        }
+        Ok((hour, HourMode::H24))
+    }
+}
+
+/// Registers 0x00..=0x06 for `dt`. Years from 2100 need the century bit,
+/// which only `with_century` chips have.
+pub(crate) fn encode_datetime<E>(
+    dt: &DateTime,
+    mode: HourMode,
+    with_century: bool,
+) -> Result<[u8; 7], Error<E>> {
+    let last_year = if with_century { 2199 } else { 2099 };
+    if !dt.is_valid() || !(2000..=last_year).contains(&dt.year) {
+        return Err(Error::InvalidDateTime);
+    }
+    let century = if dt.year >= 2100 { bits::CENTURY } else { 0 };
+    Ok([
+        encode(dt.second)?,
+        encode(dt.minute)?,
+        encode_hour(dt.hour, mode)?,
+        dt.weekday().number(),
+        encode(dt.day)?,
+        century | encode::<E>(dt.month)?,
+        encode((dt.year % 100) as u8)?,
+    ])
+}
+
+/// Inverse of [`encode_datetime`]; bit 7 of the seconds (the DS1307 clock
+/// halt) is ignored.
+pub(crate) fn decode_datetime<E>(
+    regs: &[u8; 7],
+    with_century: bool,
+) -> Result<(DateTime, HourMode), Error<E>> {
+    let (hour, mode) = decode_hour(regs[2])?;
+    let century = with_century && regs[5] & bits::CENTURY != 0;
+    let dt = DateTime {
+        year: 2000 + if century { 100 } else { 0 } + u16::from(decode::<E>(regs[6])?),
+        month: decode(regs[5] & 0x1f)?,
+        day: decode(regs[4] & 0x3f)?,
+        hour,
+        minute: decode(regs[1] & 0x7f)?,
+        second: decode(regs[0] & 0x7f)?,
+    };
+    if !dt.is_valid() {
+        return Err(Error::InvalidDateTime);
+    }
+    Ok((dt, mode))
+}
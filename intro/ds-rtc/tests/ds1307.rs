@@ -0,0 +1,233 @@
+//! DS1307 driver against scripted I2C transactions.
+
+use ds_rtc::{
+    ds1307::{Ds1307, CONTROL, RAM_SIZE, RAM_START},
+    register, DateTime, Error, HourMode, SquareWave, ADDRESS,
+};
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction};
+
+fn write(bytes: &[u8]) -> Transaction {
+    Transaction::write(ADDRESS, bytes.to_vec())
+}
+
+fn read(reg: u8, response: &[u8]) -> Transaction {
+    Transaction::write_read(ADDRESS, vec![reg], response.to_vec())
+}
+
+fn driver(script: &[Transaction]) -> Ds1307<I2cMock> {
+    Ds1307::new(I2cMock::new(script))
+}
+
+fn done(rtc: Ds1307<I2cMock>) {
+    rtc.release().done();
+}
+
+fn dt(year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> DateTime {
+    DateTime::new(year, month, day, hour, minute, second).unwrap()
+}
+
+#[test]
+fn reads_bcd_date_and_time() {
+    // 2015-05-15, a Friday, 23:59:58
+    let mut rtc = driver(&[read(
+        register::SECONDS,
+        &[0x58, 0x59, 0x23, 0x06, 0x15, 0x05, 0x15],
+    )]);
+    assert_eq!(rtc.datetime(), Ok(dt(2015, 5, 15, 23, 59, 58)));
+    assert_eq!(rtc.hour_mode(), HourMode::H24);
+    done(rtc);
+}
+
+#[test]
+fn writes_weekday_from_date_and_clears_clock_halt() {
+    // 2000-01-01 was a Saturday (7)
+    let mut rtc = driver(&[write(&[
+        register::SECONDS,
+        0x00,
+        0x00,
+        0x00,
+        0x07,
+        0x01,
+        0x01,
+        0x00,
+    ])]);
+    rtc.set_datetime(&dt(2000, 1, 1, 0, 0, 0)).unwrap();
+    done(rtc);
+}
+
+#[test]
+fn halted_clock_still_reads_and_reports_halt() {
+    let mut rtc = driver(&[
+        read(
+            register::SECONDS,
+            &[0x80 | 0x30, 0x00, 0x12, 0x01, 0x01, 0x01, 0x00],
+        ),
+        read(register::SECONDS, &[0xb0]),
+        read(register::SECONDS, &[0xb0]),
+        write(&[register::SECONDS, 0x30]),
+    ]);
+    assert_eq!(rtc.datetime(), Ok(dt(2000, 1, 1, 12, 0, 30)));
+    assert_eq!(rtc.is_running(), Ok(false));
+    rtc.start().unwrap();
+    done(rtc);
+}
+
+#[test]
+fn leap_days() {
+    // 2024-02-29 is valid, 2023-02-29 and 2100-02-29 are not
+    assert!(DateTime::new(2024, 2, 29, 0, 0, 0).is_some());
+    assert!(DateTime::new(2023, 2, 29, 0, 0, 0).is_none());
+    assert!(DateTime::new(2100, 2, 29, 0, 0, 0).is_none());
+    assert!(DateTime::new(2000, 2, 29, 0, 0, 0).is_some());
+
+    let mut rtc = driver(&[
+        read(
+            register::SECONDS,
+            &[0x00, 0x00, 0x00, 0x05, 0x29, 0x02, 0x24],
+        ),
+        read(
+            register::SECONDS,
+            &[0x00, 0x00, 0x00, 0x04, 0x29, 0x02, 0x23],
+        ),
+        write(&[register::SECONDS, 0x59, 0x59, 0x23, 0x05, 0x29, 0x02, 0x24]),
+    ]);
+    assert_eq!(rtc.datetime(), Ok(dt(2024, 2, 29, 0, 0, 0)));
+    assert_eq!(rtc.datetime(), Err(Error::InvalidDateTime));
+    // Thursday
+    rtc.set_datetime(&dt(2024, 2, 29, 23, 59, 59)).unwrap();
+    done(rtc);
+}
+
+#[test]
+fn no_century_bit() {
+    let mut rtc = driver(&[
+        // Bit 7 of the month is not a century bit on the DS1307
+        read(
+            register::SECONDS,
+            &[0x00, 0x00, 0x00, 0x01, 0x31, 0x12, 0x99],
+        ),
+    ]);
+    assert_eq!(rtc.datetime(), Ok(dt(2099, 12, 31, 0, 0, 0)));
+    assert_eq!(
+        rtc.set_datetime(&dt(2100, 1, 1, 0, 0, 0)),
+        Err(Error::InvalidDateTime)
+    );
+    assert_eq!(
+        rtc.set_datetime(&dt(1999, 12, 31, 0, 0, 0)),
+        Err(Error::InvalidDateTime)
+    );
+    done(rtc);
+}
+
+#[test]
+fn twelve_hour_mode() {
+    let mut rtc = driver(&[
+        // 12:05 AM is 00:05, 12:05 PM is 12:05, 11 PM is 23
+        read(
+            register::SECONDS,
+            &[0x00, 0x05, 0x40 | 0x12, 0x01, 0x01, 0x01, 0x00],
+        ),
+        read(
+            register::SECONDS,
+            &[0x00, 0x05, 0x60 | 0x12, 0x01, 0x01, 0x01, 0x00],
+        ),
+        read(
+            register::SECONDS,
+            &[0x00, 0x00, 0x60 | 0x11, 0x01, 0x01, 0x01, 0x00],
+        ),
+        // Written back in 12-hour mode: 13:00 is 1 PM, on a Sunday
+        write(&[register::SECONDS, 0x00, 0x00, 0x61, 0x01, 0x02, 0x01, 0x00]),
+    ]);
+    assert_eq!(rtc.datetime(), Ok(dt(2000, 1, 1, 0, 5, 0)));
+    assert_eq!(rtc.hour_mode(), HourMode::H12);
+    assert_eq!(rtc.datetime(), Ok(dt(2000, 1, 1, 12, 5, 0)));
+    assert_eq!(rtc.datetime(), Ok(dt(2000, 1, 1, 23, 0, 0)));
+    rtc.set_datetime(&dt(2000, 1, 2, 13, 0, 0)).unwrap();
+    done(rtc);
+}
+
+#[test]
+fn switching_hour_mode_keeps_the_hour() {
+    let mut rtc = driver(&[
+        read(register::HOURS, &[0x00]),
+        write(&[register::HOURS, 0x52]),
+        read(register::HOURS, &[0x52]),
+        write(&[register::HOURS, 0x00]),
+        read(register::HOURS, &[0x17]),
+        write(&[register::HOURS, 0x65]),
+    ]);
+    // Midnight is 12 AM and back
+    rtc.set_hour_mode(HourMode::H12).unwrap();
+    rtc.set_hour_mode(HourMode::H24).unwrap();
+    // 17:00 is 5 PM
+    rtc.set_hour_mode(HourMode::H12).unwrap();
+    assert_eq!(rtc.hour_mode(), HourMode::H12);
+    done(rtc);
+}
+
+#[test]
+fn rejects_invalid_bcd_and_hours() {
+    let mut rtc = driver(&[
+        read(
+            register::SECONDS,
+            &[0x5a, 0x00, 0x00, 0x01, 0x01, 0x01, 0x00],
+        ),
+        // 24 o'clock
+        read(
+            register::SECONDS,
+            &[0x00, 0x00, 0x24, 0x01, 0x01, 0x01, 0x00],
+        ),
+        // 13 PM
+        read(
+            register::SECONDS,
+            &[0x00, 0x00, 0x73, 0x01, 0x01, 0x01, 0x00],
+        ),
+        // 0 AM
+        read(
+            register::SECONDS,
+            &[0x00, 0x00, 0x40, 0x01, 0x01, 0x01, 0x00],
+        ),
+    ]);
+    assert_eq!(rtc.datetime(), Err(Error::InvalidBcd(0x5a)));
+    assert_eq!(rtc.datetime(), Err(Error::InvalidDateTime));
+    assert_eq!(rtc.datetime(), Err(Error::InvalidDateTime));
+    assert_eq!(rtc.datetime(), Err(Error::InvalidDateTime));
+    done(rtc);
+}
+
+#[test]
+fn square_wave() {
+    let mut rtc = driver(&[
+        write(&[CONTROL, 0x10]),
+        write(&[CONTROL, 0x13]),
+        write(&[CONTROL, 0x00]),
+        write(&[CONTROL, 0x80]),
+    ]);
+    rtc.set_square_wave(SquareWave::Hz1).unwrap();
+    rtc.set_square_wave(SquareWave::Hz32768).unwrap();
+    assert_eq!(
+        rtc.set_square_wave(SquareWave::Hz1024),
+        Err(Error::Unsupported)
+    );
+    rtc.set_square_wave(SquareWave::Off).unwrap();
+    rtc.set_output(true).unwrap();
+    done(rtc);
+}
+
+#[test]
+fn ram() {
+    let mut buf = [0; 3];
+    let mut rtc = driver(&[
+        write(&[RAM_START + 4, 1, 2, 3]),
+        read(RAM_START + 4, &[1, 2, 3]),
+        write(&[RAM_START + RAM_SIZE as u8 - 1, 0xaa]),
+    ]);
+    rtc.write_ram(4, &[1, 2, 3]).unwrap();
+    rtc.read_ram(4, &mut buf).unwrap();
+    assert_eq!(buf, [1, 2, 3]);
+    rtc.write_ram(RAM_SIZE - 1, &[0xaa]).unwrap();
+    assert_eq!(rtc.write_ram(RAM_SIZE - 1, &[0, 0]), Err(Error::OutOfRange));
+    assert_eq!(rtc.read_ram(RAM_SIZE, &mut buf), Err(Error::OutOfRange));
+    assert_eq!(rtc.read_ram(usize::MAX, &mut buf), Err(Error::OutOfRange));
+    done(rtc);
+}
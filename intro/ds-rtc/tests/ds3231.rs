@@ -0,0 +1,294 @@
+//! DS3231 driver against scripted I2C transactions.
+
+use ds_rtc::{
+    ds3231::{
+        temperature_c, Alarm1, Alarm2, AlarmFlags, Ds3231, AGING_OFFSET, ALARM1, ALARM2, CONTROL,
+        STATUS, TEMPERATURE,
+    },
+    is_leap_year, register, DateTime, Error, HourMode, SquareWave, Weekday, ADDRESS,
+};
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction};
+
+fn write(bytes: &[u8]) -> Transaction {
+    Transaction::write(ADDRESS, bytes.to_vec())
+}
+
+fn read(reg: u8, response: &[u8]) -> Transaction {
+    Transaction::write_read(ADDRESS, vec![reg], response.to_vec())
+}
+
+fn driver(script: &[Transaction]) -> Ds3231<I2cMock> {
+    Ds3231::new(I2cMock::new(script))
+}
+
+fn done(rtc: Ds3231<I2cMock>) {
+    rtc.release().done();
+}
+
+fn dt(year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> DateTime {
+    DateTime::new(year, month, day, hour, minute, second).unwrap()
+}
+
+#[test]
+fn century_bit() {
+    let mut rtc = driver(&[
+        read(
+            register::SECONDS,
+            &[0x59, 0x59, 0x23, 0x05, 0x31, 0x12, 0x99],
+        ),
+        // After the roll-over the chip sets the century bit
+        read(
+            register::SECONDS,
+            &[0x00, 0x00, 0x00, 0x06, 0x01, 0x81, 0x00],
+        ),
+        // 2199-12-31 is a Tuesday
+        write(&[register::SECONDS, 0x00, 0x00, 0x00, 0x03, 0x31, 0x92, 0x99]),
+        read(STATUS, &[0x88]),
+        write(&[STATUS, 0x0b]),
+    ]);
+    assert_eq!(rtc.datetime(), Ok(dt(2099, 12, 31, 23, 59, 59)));
+    assert_eq!(rtc.datetime(), Ok(dt(2100, 1, 1, 0, 0, 0)));
+    rtc.set_datetime(&dt(2199, 12, 31, 0, 0, 0)).unwrap();
+    assert_eq!(
+        rtc.set_datetime(&dt(2200, 1, 1, 0, 0, 0)),
+        Err(Error::InvalidDateTime)
+    );
+    done(rtc);
+}
+
+#[test]
+fn leap_days_across_centuries() {
+    let mut rtc = driver(&[
+        // 2000 is a leap year
+        read(
+            register::SECONDS,
+            &[0x00, 0x00, 0x00, 0x03, 0x29, 0x02, 0x00],
+        ),
+        // The chip counts 2100-02-29, which does not exist
+        read(
+            register::SECONDS,
+            &[0x00, 0x00, 0x00, 0x01, 0x29, 0x82, 0x00],
+        ),
+        // 2104 is
+        read(
+            register::SECONDS,
+            &[0x00, 0x00, 0x00, 0x06, 0x29, 0x82, 0x04],
+        ),
+    ]);
+    assert_eq!(rtc.datetime(), Ok(dt(2000, 2, 29, 0, 0, 0)));
+    assert_eq!(rtc.datetime(), Err(Error::InvalidDateTime));
+    assert_eq!(rtc.datetime(), Ok(dt(2104, 2, 29, 0, 0, 0)));
+    assert_eq!(dt(2104, 2, 29, 0, 0, 0).weekday(), Weekday::Friday);
+    done(rtc);
+}
+
+#[test]
+fn weekday_over_every_year() {
+    assert_eq!(dt(0, 1, 1, 0, 0, 0).weekday(), Weekday::Saturday);
+    assert_eq!(dt(0, 2, 29, 0, 0, 0).weekday(), Weekday::Tuesday);
+    assert_eq!(dt(65535, 12, 31, 0, 0, 0).weekday(), Weekday::Tuesday);
+    // Each January 1st and March 1st follows the previous one by the
+    // length of the year in between
+    let next = |weekday: Weekday, days: u16| {
+        Weekday::from_number(((u16::from(weekday.number()) - 1 + days) % 7) as u8 + 1).unwrap()
+    };
+    let mut january = Weekday::Saturday;
+    for year in 0..=u16::MAX {
+        let feb = if is_leap_year(year) { 29 } else { 28 };
+        assert_eq!(dt(year, 1, 1, 0, 0, 0).weekday(), january, "{year}");
+        assert_eq!(
+            dt(year, 3, 1, 0, 0, 0).weekday(),
+            next(january, 31 + feb),
+            "{year}"
+        );
+        january = next(january, 337 + feb);
+    }
+}
+
+#[test]
+fn twelve_hour_mode_with_century() {
+    let mut rtc = driver(&[
+        // 11:30:15 PM, 2150-06-15
+        read(
+            register::SECONDS,
+            &[0x15, 0x30, 0x71, 0x01, 0x15, 0x86, 0x50],
+        ),
+        // Written back as 12 AM, 2150-06-16, a Tuesday
+        write(&[register::SECONDS, 0x00, 0x00, 0x52, 0x03, 0x16, 0x86, 0x50]),
+        read(STATUS, &[0x00]),
+        write(&[STATUS, 0x03]),
+    ]);
+    assert_eq!(rtc.datetime(), Ok(dt(2150, 6, 15, 23, 30, 15)));
+    assert_eq!(rtc.hour_mode(), HourMode::H12);
+    rtc.set_datetime(&dt(2150, 6, 16, 0, 0, 0)).unwrap();
+    done(rtc);
+}
+
+#[test]
+fn oscillator_stop_flag() {
+    let mut rtc = driver(&[read(STATUS, &[0x80]), read(STATUS, &[0x08])]);
+    assert_eq!(rtc.lost_power(), Ok(true));
+    assert_eq!(rtc.lost_power(), Ok(false));
+    done(rtc);
+}
+
+#[test]
+fn alarm1_match_modes() {
+    let mut rtc = driver(&[
+        write(&[ALARM1, 0x80, 0x80, 0x80, 0x80]),
+        write(&[ALARM1, 0x30, 0x80, 0x80, 0x80]),
+        write(&[ALARM1, 0x30, 0x15, 0x80, 0x80]),
+        write(&[ALARM1, 0x30, 0x15, 0x07, 0x80]),
+        write(&[ALARM1, 0x30, 0x15, 0x07, 0x31]),
+        write(&[ALARM1, 0x30, 0x15, 0x07, 0x42]),
+    ]);
+    rtc.set_alarm1(Alarm1::EverySecond).unwrap();
+    rtc.set_alarm1(Alarm1::Second { second: 30 }).unwrap();
+    rtc.set_alarm1(Alarm1::MinuteSecond {
+        minute: 15,
+        second: 30,
+    })
+    .unwrap();
+    rtc.set_alarm1(Alarm1::Time {
+        hour: 7,
+        minute: 15,
+        second: 30,
+    })
+    .unwrap();
+    rtc.set_alarm1(Alarm1::Date {
+        day: 31,
+        hour: 7,
+        minute: 15,
+        second: 30,
+    })
+    .unwrap();
+    rtc.set_alarm1(Alarm1::Weekday {
+        weekday: Weekday::Monday,
+        hour: 7,
+        minute: 15,
+        second: 30,
+    })
+    .unwrap();
+    assert_eq!(
+        rtc.set_alarm1(Alarm1::Second { second: 60 }),
+        Err(Error::InvalidDateTime)
+    );
+    assert_eq!(
+        rtc.set_alarm1(Alarm1::Date {
+            day: 0,
+            hour: 0,
+            minute: 0,
+            second: 0
+        }),
+        Err(Error::InvalidDateTime)
+    );
+    done(rtc);
+}
+
+#[test]
+fn alarm2_uses_the_hour_mode() {
+    let mut rtc = driver(&[
+        write(&[ALARM2, 0x80, 0x80, 0x80]),
+        write(&[ALARM2, 0x45, 0x80, 0x80]),
+        write(&[ALARM2, 0x45, 0x18, 0x80]),
+        read(register::HOURS, &[0x09]),
+        write(&[register::HOURS, 0x49]),
+        // 18:45 is 6:45 PM
+        write(&[ALARM2, 0x45, 0x66, 0x80]),
+        write(&[ALARM2, 0x45, 0x66, 0x47]),
+        write(&[ALARM2, 0x00, 0x52, 0x15]),
+    ]);
+    rtc.set_alarm2(Alarm2::EveryMinute).unwrap();
+    rtc.set_alarm2(Alarm2::Minute { minute: 45 }).unwrap();
+    let evening = Alarm2::Time {
+        hour: 18,
+        minute: 45,
+    };
+    rtc.set_alarm2(evening).unwrap();
+    rtc.set_hour_mode(HourMode::H12).unwrap();
+    rtc.set_alarm2(evening).unwrap();
+    rtc.set_alarm2(Alarm2::Weekday {
+        weekday: Weekday::Saturday,
+        hour: 18,
+        minute: 45,
+    })
+    .unwrap();
+    rtc.set_alarm2(Alarm2::Date {
+        day: 15,
+        hour: 0,
+        minute: 0,
+    })
+    .unwrap();
+    done(rtc);
+}
+
+#[test]
+fn alarm_interrupts_and_flags() {
+    let mut rtc = driver(&[
+        // Power-on control: INTCN and 8 kHz selected
+        read(CONTROL, &[0x1c]),
+        write(&[CONTROL, 0x1d]),
+        read(STATUS, &[0x89]),
+        // OSF and EN32kHz are written back as read, A2F as 1
+        read(STATUS, &[0x8b]),
+        write(&[STATUS, 0x8a]),
+    ]);
+    rtc.set_alarm_interrupts(AlarmFlags {
+        alarm1: true,
+        alarm2: false,
+    })
+    .unwrap();
+    assert_eq!(
+        rtc.alarm_flags(),
+        Ok(AlarmFlags {
+            alarm1: true,
+            alarm2: false,
+        })
+    );
+    rtc.clear_alarm_flags(AlarmFlags {
+        alarm1: true,
+        alarm2: false,
+    })
+    .unwrap();
+    done(rtc);
+}
+
+#[test]
+fn square_wave_and_32khz() {
+    let mut rtc = driver(&[
+        read(CONTROL, &[0x1f]),
+        write(&[CONTROL, 0x0b]),
+        read(CONTROL, &[0x0b]),
+        write(&[CONTROL, 0x07]),
+        read(STATUS, &[0x08]),
+        write(&[STATUS, 0x03]),
+        read(STATUS, &[0x00]),
+        write(&[STATUS, 0x0b]),
+    ]);
+    // The alarm enables stay set but have no effect while INTCN is 0
+    rtc.set_square_wave(SquareWave::Hz1024).unwrap();
+    rtc.set_square_wave(SquareWave::Off).unwrap();
+    assert_eq!(
+        rtc.set_square_wave(SquareWave::Hz32768),
+        Err(Error::Unsupported)
+    );
+    rtc.set_32khz_output(false).unwrap();
+    rtc.set_32khz_output(true).unwrap();
+    done(rtc);
+}
+
+#[test]
+fn temperature_and_aging() {
+    let mut rtc = driver(&[
+        read(TEMPERATURE, &[0x19, 0x40]),
+        read(TEMPERATURE, &[0xf6, 0xc0]),
+        read(AGING_OFFSET, &[0xfe]),
+        write(&[AGING_OFFSET, 0x05]),
+    ]);
+    assert_eq!(rtc.temperature(), Ok(25.25));
+    assert_eq!(rtc.temperature(), Ok(-9.25));
+    assert_eq!(rtc.aging_offset(), Ok(-2));
+    rtc.set_aging_offset(5).unwrap();
+    assert_eq!(temperature_c([0x00, 0x80]), 0.5);
+    done(rtc);
+}
@@ -0,0 +1,40 @@
+// 启动诊断：打印复位原因、唤醒原因、启动次数以及上一次 panic 的摘要
+// 运行后每 5 秒触发一次 panic，panic 处理函数记录摘要后软复位，重启后可以看到报告
+
+#![no_std]
+#![no_main]
+
+// 注意：这里不使用 esp_backtrace 的 panic 处理函数，而是自己记录 panic 摘要
+use esp_hal::{delay::Delay, main, system::software_reset};
+use esp_println::println;
+
+esp_bootloader_esp_idf::esp_app_desc!();
+
+#[panic_handler]
+fn panic(info: &core::panic::PanicInfo) -> ! {
+    println!("PANIC: {}", info);
+    // 把 panic 摘要保存到 RTC FAST 内存，复位后依然可读
+    boot_diag::record_panic(info);
+    software_reset()
+}
+
+#[main]
+fn main() -> ! {
+    let _peripherals = esp_hal::init(esp_hal::Config::default());
+
+    // 尽早读取复位原因并打印报告
+    let report = boot_diag::init();
+    report.print();
+
+    if report.cause.is_watchdog() {
+        println!("上一次复位是看门狗触发的！");
+    }
+
+    let delay = Delay::new();
+    for i in (1..=5).rev() {
+        println!("panic in {}s...", i);
+        delay.delay_millis(1000);
+    }
+
+    panic!("boot #{} panicked on purpose", report.boot_count);
+}
@@ -0,0 +1,118 @@
+// 多级看门狗监控：每个任务按各自的 deadline 报到，全部健康才喂狗
+// 任务超时后 Stage0 中断记录卡死的任务，Stage1 复位系统，重启后打印记录
+
+#![no_std]
+#![no_main]
+
+use core::cell::RefCell;
+use critical_section::Mutex;
+use esp_backtrace as _;
+use esp32s3_demo::watchdog::{
+    configure_mwdt, feed_if_healthy, record_stall, take_last_stall, Health, Supervisor,
+};
+use esp_hal::{
+    delay::Delay,
+    handler,
+    interrupt::{self, InterruptConfigurable as _},
+    main,
+    peripherals::Interrupt,
+    system::Cpu,
+    time::{Duration, Instant},
+    timer::timg::TimerGroup,
+};
+use esp_println::println;
+
+esp_bootloader_esp_idf::esp_app_desc!();
+
+// 最多监控 4 个任务
+static SUPERVISOR: Mutex<RefCell<Supervisor<4>>> = Mutex::new(RefCell::new(Supervisor::new()));
+
+fn now_ms() -> u64 {
+    Instant::now().duration_since_epoch().as_millis()
+}
+
+// Stage0 警告中断：记录是哪一个任务卡住了，等待 Stage1 复位
+#[handler]
+fn wdt_warning() {
+    // 关闭该中断，避免在复位前反复进入
+    interrupt::disable(Cpu::ProCpu, Interrupt::TG0_WDT_LEVEL);
+
+    critical_section::with(|cs| {
+        let supervisor = SUPERVISOR.borrow_ref(cs);
+        match supervisor.health(now_ms()) {
+            Health::Stalled { task, overdue_ms } => {
+                let name = supervisor.task(task).map(|t| t.name).unwrap_or("?");
+                record_stall(task, name, overdue_ms);
+                println!("!! Watchdog warning: task '{}' overdue by {}ms", name, overdue_ms);
+            }
+            // 所有任务都正常，说明是喂狗的主循环本身卡住了
+            Health::Healthy => println!("!! Watchdog warning: main loop stopped feeding"),
+        }
+    });
+}
+
+#[main]
+fn main() -> ! {
+    let peripherals = esp_hal::init(esp_hal::Config::default());
+
+    // 先检查上一次是否因为任务卡死而复位
+    match take_last_stall() {
+        Some(stall) => println!(
+            "Last reset caused by stalled task #{} '{}' (overdue {}ms)",
+            stall.task_index,
+            stall.name(),
+            stall.overdue_ms
+        ),
+        None => println!("No stall recorded before this boot"),
+    }
+
+    // 注册任务：名字 + 各自的 deadline
+    let (sensor, blink, comm) = critical_section::with(|cs| {
+        let mut supervisor = SUPERVISOR.borrow_ref_mut(cs);
+        let now = now_ms();
+        (
+            supervisor.register("sensor", 1_000, now).unwrap(),
+            supervisor.register("blink", 2_000, now).unwrap(),
+            supervisor.register("comm", 5_000, now).unwrap(),
+        )
+    });
+
+    // 配置 MWDT：3 秒没喂狗触发警告中断，再过 2 秒复位
+    let timer_group0 = TimerGroup::new(peripherals.TIMG0);
+    let mut wdt0 = timer_group0.wdt;
+    wdt0.set_interrupt_handler(wdt_warning);
+    configure_mwdt(&mut wdt0, Duration::from_secs(3), Duration::from_secs(2));
+    println!("Watchdog supervisor started");
+
+    let delay = Delay::new();
+    let mut counter: u32 = 0;
+
+    loop {
+        let now = now_ms();
+        critical_section::with(|cs| {
+            let mut supervisor = SUPERVISOR.borrow_ref_mut(cs);
+            // 模拟任务：sensor 每轮都报到，blink 每两轮报到一次
+            supervisor.check_in(sensor, now);
+            if counter % 2 == 0 {
+                supervisor.check_in(blink, now);
+            }
+            // comm 任务在第 20 轮之后“卡死”，不再报到
+            if counter < 20 {
+                supervisor.check_in(comm, now);
+            }
+
+            match feed_if_healthy(&mut wdt0, &*supervisor, now) {
+                Health::Healthy => println!("[{}] all tasks healthy, watchdog fed", counter),
+                Health::Stalled { task, overdue_ms } => println!(
+                    "[{}] task '{}' overdue by {}ms, watchdog NOT fed",
+                    counter,
+                    supervisor.task(task).map(|t| t.name).unwrap_or("?"),
+                    overdue_ms
+                ),
+            }
+        });
+
+        counter += 1;
+        delay.delay_millis(500);
+    }
+}
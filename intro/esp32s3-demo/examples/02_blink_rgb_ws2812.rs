@@ -8,6 +8,7 @@ Programming GPIO - Blinky Application Example
 #![no_main]
 
 use esp_backtrace as _;
+use esp32s3_demo::ws2812::{buffer_size, Ws2812};
 use esp_hal::{
     delay::Delay,
     gpio::{
@@ -15,51 +16,17 @@ use esp_hal::{
         OutputConfig, Pull,
     },
     main,
+    rmt::Rmt,
+    time::Rate,
     timer::timg::TimerGroup,
 };
 use esp_println::println;
-use smart_leds::RGB8;
+use smart_leds::{SmartLedsWrite, RGB8};
 
-esp_bootloader_esp_idf::esp_app_desc!();
-
-// 使用WS2812协议关闭开发板上的彩色 RGB LED (GPIO48)
-// 简化的WS2812控制函数
-fn send_ws2812_reset(pin: &mut Output, delay: &mut Delay) {
-    // 发送复位信号：保持低电平超过50us
-    pin.set_low();
-    delay.delay_micros(100); // 100us复位信号
-}
-
-fn send_ws2812_bit(pin: &mut Output, bit: bool) {
-    if bit {
-        // 发送1: 高电平0.8us, 低电平0.4us
-        pin.set_high();
-        // 调整时序 - 更保守的延迟
-        for _ in 0..60 { } // ~0.8us
-        pin.set_low();
-        for _ in 0..30 { } // ~0.4us
-    } else {
-        // 发送0: 高电平0.4us, 低电平0.8us  
-        pin.set_high();
-        for _ in 0..30 { } // ~0.4us
-        pin.set_low();
-        for _ in 0..60 { } // ~0.8us
-    }
-}
-
-fn send_ws2812_byte(pin: &mut Output, byte: u8) {
-    for i in (0..8).rev() {
-        let bit = (byte >> i) & 1 == 1;
-        send_ws2812_bit(pin, bit);
-    }
-}
+// 开发板上只有一颗 RGB LED
+const NUM_LEDS: usize = 1;
 
-fn send_ws2812_color(pin: &mut Output, color: RGB8) {
-    // WS2812使用GRB顺序
-    send_ws2812_byte(pin, color.g);
-    send_ws2812_byte(pin, color.r);
-    send_ws2812_byte(pin, color.b);
-}
+esp_bootloader_esp_idf::esp_app_desc!();
 
 #[main]
 fn main() -> ! {
@@ -81,7 +48,7 @@ fn main() -> ! {
     println!("Watchdog timers disabled!");
 
     // Create a delay handle
-    let mut delay = Delay::new();
+    let delay = Delay::new();
 
     // Create output pin configuration
     let led_pin_conf = OutputConfig::default()
@@ -97,32 +64,18 @@ fn main() -> ! {
     );
 
     // 使用WS2812协议关闭开发板上的RGB LED (GPIO48)
-    let rgb_led_conf = OutputConfig::default()
-        .with_drive_mode(DriveMode::PushPull)
-        .with_drive_strength(DriveStrength::_20mA)
-        .with_pull(Pull::None);
-    
-    let mut rgb_led_pin = Output::new(
-        peripherals.GPIO48,
-        Level::Low,
-        rgb_led_conf,
-    );
-    
+    // 由 RMT 外设产生 WS2812 时序，不再依赖空循环延时
+    let rmt = Rmt::new(peripherals.RMT, Rate::from_mhz(80)).unwrap();
+    let mut rmt_buffer = [0u32; buffer_size(NUM_LEDS)];
+    let mut rgb_led = Ws2812::new(rmt.channel0, peripherals.GPIO48, &mut rmt_buffer).unwrap();
+
     println!("Sending WS2812 black color to turn off RGB LED...");
-    
-    // 先发送复位信号
-    send_ws2812_reset(&mut rgb_led_pin, &mut delay);
-    
-    // 发送多个黑色(0,0,0)像素，确保所有可能的LED都被关闭
-    // let black_color = RGB8::new(0, 0, 0);
-    // for _ in 0..8 { // 发送8个黑色像素
-    //     send_ws2812_color(&mut rgb_led_pin, black_color);
-    // }
-    
-    // 最后再发送复位信号
-    // send_ws2812_reset(&mut rgb_led_pin, &mut delay);
-    
-    println!("WS2812 RGB LED should now be OFF (sent 8 black pixels)");
+
+    // 发送黑色(0,0,0)像素关闭 LED，复位信号由 RMT 空闲时的低电平产生
+    let black_color = RGB8::new(0, 0, 0);
+    rgb_led.write([black_color; NUM_LEDS]).unwrap();
+
+    println!("WS2812 RGB LED should now be OFF (sent {} black pixels)", NUM_LEDS);
     println!("Starting LED blink loop on GPIO7...");
     let mut counter = 0;
     
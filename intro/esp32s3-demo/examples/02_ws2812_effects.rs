@@ -0,0 +1,71 @@
+// WS2812 灯效：彩虹、呼吸、跑马、彗星、火焰轮流播放
+
+#![no_std]
+#![no_main]
+
+use esp_backtrace as _;
+use esp32s3_demo::{
+    effects::{Breathe, Comet, Effect, Fire, Limits, Rainbow, TheaterChase},
+    ws2812::{buffer_size, Ws2812},
+};
+use esp_hal::{delay::Delay, main, rmt::Rmt, time::Rate};
+use esp_println::println;
+use smart_leds::{SmartLedsWrite, RGB8};
+
+esp_bootloader_esp_idf::esp_app_desc!();
+
+// 灯带上的 LED 数量
+const NUM_LEDS: usize = 16;
+// 每帧间隔
+const FRAME_MS: u32 = 20;
+// 每个灯效播放的帧数
+const FRAMES_PER_EFFECT: u32 = 500;
+
+#[main]
+fn main() -> ! {
+    // 获取外设工具箱
+    let peripherals = esp_hal::init(esp_hal::Config::default());
+
+    // RMT 驱动 WS2812 灯带，数据线接 GPIO48
+    let rmt = Rmt::new(peripherals.RMT, Rate::from_mhz(80)).unwrap();
+    let mut rmt_buffer = [0u32; buffer_size(NUM_LEDS)];
+    let mut strip = Ws2812::new(rmt.channel0, peripherals.GPIO48, &mut rmt_buffer).unwrap();
+
+    let delay = Delay::new();
+
+    // 限制全局亮度，避免 USB 供电不足
+    let limits = Limits {
+        brightness: 64,
+        gamma: true,
+    };
+
+    let mut rainbow = Rainbow::default();
+    let mut breathe = Breathe::new(RGB8::new(0, 120, 255), 200);
+    let mut chase = TheaterChase::new(RGB8::new(255, 80, 0));
+    let mut comet = Comet::new(RGB8::new(255, 255, 255), 5);
+    let mut fire = Fire::<NUM_LEDS>::new(0x1234_5678);
+
+    let mut frame = [RGB8::default(); NUM_LEDS];
+    let mut tick: u32 = 0;
+
+    loop {
+        let effect_index = (tick / FRAMES_PER_EFFECT) % 5;
+        if tick % FRAMES_PER_EFFECT == 0 {
+            println!("Effect #{}", effect_index);
+        }
+
+        match effect_index {
+            0 => rainbow.render(tick, &mut frame),
+            1 => breathe.render(tick, &mut frame),
+            2 => chase.render(tick / 5, &mut frame),
+            3 => comet.render(tick / 2, &mut frame),
+            _ => fire.render(tick, &mut frame),
+        }
+        limits.apply(&mut frame);
+
+        strip.write(frame).unwrap();
+
+        tick = tick.wrapping_add(1);
+        delay.delay_millis(FRAME_MS);
+    }
+}
@@ -4,9 +4,13 @@
 // 导入核心库、临界区、Mutex 和原子类型
 use core::cell::RefCell;
 use critical_section::Mutex;
-use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use core::sync::atomic::{AtomicU32, Ordering};
 // 导入 esp-hal 相关库
 use esp_backtrace as _;
+use esp32s3_demo::{
+    clock::{Clock, SystemClock},
+    event_queue::EventQueue,
+};
 use esp_hal::{
     delay::Delay,
     gpio::{Event, Input, InputConfig, Io, Pull},
@@ -22,13 +26,19 @@ esp_bootloader_esp_idf::esp_app_desc!();
 // Option: 允许在 main 中初始化它
 static G_PIN: Mutex<RefCell<Option<Input>>> = Mutex::new(RefCell::new(None));
 
-// 2. 定义原子变量用于 ISR 和主循环之间的通信
-// 中断触发标志：当发生中断时设置为 true
-static INTERRUPT_FLAG: AtomicBool = AtomicBool::new(false);
+// 2. 定义 ISR 和主循环之间的通信方式
+// 每次中断都作为一个带时间戳的事件放进队列，主循环按顺序处理，
+// 即使两次轮询之间发生了多次中断也不会被合并成一次
+#[derive(Debug, Clone, Copy)]
+struct GpioEvent {
+    // 中断序号
+    number: u32,
+    // 中断发生时的GPIO状态 (true=HIGH, false=LOW)
+    is_high: bool,
+}
+static GPIO_EVENTS: EventQueue<GpioEvent, 16> = EventQueue::new();
 // 中断计数器：记录中断发生的次数
 static INTERRUPT_COUNT: AtomicU32 = AtomicU32::new(0);
-// GPIO状态：记录最后一次中断时的GPIO状态 (true=HIGH, false=LOW)
-static LAST_GPIO_STATE: AtomicBool = AtomicBool::new(true);
 
 // 3. 定义轻量化的中断服务例程 (ISR)
 // ISR 原则：快速进入，快速退出，不做阻塞性操作
@@ -36,15 +46,14 @@ static LAST_GPIO_STATE: AtomicBool = AtomicBool::new(true);
 fn gpio_handler() {
     // 进入临界区，只做必要的硬件操作
     critical_section::with(|cs| {
-        // 读取当前GPIO状态并保存到原子变量中
+        // 读取当前GPIO状态
         let is_high = G_PIN.borrow_ref(cs).as_ref().unwrap().is_high();
-        LAST_GPIO_STATE.store(is_high, Ordering::Relaxed);
-        
+
         // 增加中断计数器
-        INTERRUPT_COUNT.fetch_add(1, Ordering::Relaxed);
-        
-        // 设置中断标志，通知主循环处理
-        INTERRUPT_FLAG.store(true, Ordering::Relaxed);
+        let number = INTERRUPT_COUNT.fetch_add(1, Ordering::Relaxed) + 1;
+
+        // 把事件放入队列，通知主循环处理（队列满时会记录溢出次数）
+        let _ = GPIO_EVENTS.push(SystemClock.now_ms(), GpioEvent { number, is_high });
 
         // 清除中断标志 - 这是硬件必需的操作
         G_PIN.borrow_ref_mut(cs).as_mut().unwrap().clear_interrupt();
@@ -88,18 +97,13 @@ fn main() -> ! {
     // 程序在这里进入一个空的无限循环，CPU 可以“休息”
     // 所有工作都将由中断触发
     loop {
-        // 检查是否有中断发生
-        if INTERRUPT_FLAG.load(Ordering::Relaxed) {
-            // 重置中断标志
-            INTERRUPT_FLAG.store(false, Ordering::Relaxed);
-            
-            // 读取中断相关数据
-            let gpio_state = LAST_GPIO_STATE.load(Ordering::Relaxed);
-            let interrupt_count = INTERRUPT_COUNT.load(Ordering::Relaxed);
-            
+        // 按发生顺序处理所有中断事件
+        for stamped in GPIO_EVENTS.drain() {
+            let event = stamped.event;
+
             // 在这里处理业务逻辑（之前在ISR中的代码移到这里）
-            println!("🚨 GPIO4 Interrupt #{} detected!", interrupt_count);
-            println!("   └─ GPIO4 state: {}", if gpio_state { "HIGH" } else { "LOW" });
+            println!("🚨 GPIO4 Interrupt #{} detected at {}ms!", event.number, stamped.timestamp_ms);
+            println!("   └─ GPIO4 state: {}", if event.is_high { "HIGH" } else { "LOW" });
             
             // 这里可以添加你的具体业务逻辑，比如：
             // - 更新状态机
@@ -109,6 +113,12 @@ fn main() -> ! {
             
             println!("   └─ Business logic executed successfully");
         }
+
+        // 队列满时丢弃的事件数
+        let dropped = GPIO_EVENTS.take_overflow_count();
+        if dropped > 0 {
+            println!("⚠️ {} GPIO4 events dropped (queue full)", dropped);
+        }
         
         // 主循环的正常工作
         println!("Hello world! Counter: {} (GPIO4 interrupt count: {})", 
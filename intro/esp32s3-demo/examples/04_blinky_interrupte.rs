@@ -4,6 +4,11 @@
 #![no_main]
 
 use esp_backtrace as _;
+use esp32s3_demo::{
+    button::{ButtonConfig, ButtonEvent, Gestures},
+    clock::{Clock, SystemClock},
+    event_queue::EventQueue,
+};
 use esp_hal::{
     gpio::{Event, Input, InputConfig, Io, Level, Output, OutputConfig, Pull},
     handler, main,
@@ -17,8 +22,8 @@ esp_bootloader_esp_idf::esp_app_desc!();
 
 // 用于在 ISR 和 main 之间共享引脚实例，以便在 ISR 中清除中断标志
 static G_PIN: Mutex<RefCell<Option<Input>>> = Mutex::new(RefCell::new(None));
-// 用于在 ISR 和 main 之间传递“按钮被按下”这一事件的标志位
-static G_FLAG: Mutex<Cell<bool>> = Mutex::new(Cell::new(false));
+// ISR 把每一个原始边沿（引脚是否为高电平）带上时间戳放进队列，main 循环按顺序交给手势状态机
+static EDGES: EventQueue<bool, 32> = EventQueue::new();
 // 全局变量：用于存储闪烁延时，AtomicU32 是线程安全的整数类型
 static BLINK_DELAY: AtomicU32 = AtomicU32::new(500); // 初始延时 500ms
 // 按钮按下触发的中断次数
@@ -28,10 +33,12 @@ static COUNTER: Mutex<Cell<u32>> = Mutex::new(Cell::new(0));
 fn gpio_handler() {
     // 进入临界区，只做必要的硬件操作
     critical_section::with(|cs| {
+        let mut pin = G_PIN.borrow_ref_mut(cs);
+        let pin = pin.as_mut().unwrap();
         // 清除中断标志 - 这是硬件必需的操作
-        G_PIN.borrow_ref_mut(cs).as_mut().unwrap().clear_interrupt();
-        // 设置标志位，通知 main 循环
-        G_FLAG.borrow(cs).set(true);
+        pin.clear_interrupt();
+        // 只记录原始边沿和时间戳，不在 ISR 中延时消抖
+        let _ = EDGES.push(SystemClock.now_ms(), pin.is_high());
         // 在 ISR 内部直接计数
         COUNTER.borrow(cs).set(COUNTER.borrow(cs).get() + 1);
     });
@@ -58,62 +65,66 @@ fn main() -> ! {
     // 配置按钮：将 gpio9 设置为输入引脚，并启用内部上拉电阻
     let mut button = Input::new(peripherals.GPIO9, config);
     println!("button level {:?} - {:?} - {:#?}", button.is_low(), button.is_high(), button.level());
-    // 3. 监听下降沿事件
-    button.listen(Event::FallingEdge);
+    // 3. 监听双边沿事件：按下和松开都需要记录，才能识别单击/双击/长按
+    button.listen(Event::AnyEdge);
 
     // 4. 将配置好的引脚移入全局变量
     critical_section::with(|cs| G_PIN.borrow_ref_mut(cs).replace(button));
 
     let delay = esp_hal::delay::Delay::new();
+    let mut last_toggle = SystemClock.now_ms();
+
+    // 按键手势状态机只在 main 中使用，不需要放进全局变量
+    let mut gestures = Gestures::new(ButtonConfig {
+        debounce_ms: 20,
+        double_click_ms: 300,
+        long_press_ms: 800,
+        repeat_ms: 0,
+        active_low: true,
+    });
 
     // 进入主循环
     loop {
-        // 延时循环结束后（也就是过了一段时间后），翻转 LED 的状态
-        led.toggle();
+        let now = SystemClock.now_ms();
 
-        // 获取当前的延时值
+        // 不再用一次长延时阻塞主循环，而是检查是否到了翻转 LED 的时间
         let current_delay = BLINK_DELAY.load(Ordering::Relaxed);
-        // 硬件延时
-        delay.delay_millis(current_delay);
-        
-        /*         
-        critical_section::with(|cs| {            
-            if G_FLAG.borrow(cs).get() {
-                // Clear global flag
-                G_FLAG.borrow(cs).set(false);
-
-                // 修改延时值
+        if now - last_toggle >= current_delay as u64 {
+            led.toggle();
+            last_toggle = now;
+        }
+
+        // 按发生顺序把边沿交给状态机，时间戳来自 ISR
+        for edge in EDGES.drain() {
+            let pressed = gestures.is_pressed_level(edge.event);
+            gestures.on_edge(pressed, edge.timestamp_ms);
+        }
+
+        // 按键消抖在状态机中完成：只有电平稳定 20ms 后才会产生事件
+        let event = gestures.poll(now);
+
+        match event {
+            // 单击：加快闪烁
+            Some(ButtonEvent::Click) => {
                 let mut new_delay = BLINK_DELAY.load(Ordering::Relaxed);
                 new_delay = if new_delay <= 100 { 500 } else { new_delay - 100 };
                 BLINK_DELAY.store(new_delay, Ordering::Relaxed);
-                esp_println::println!("Delay changed to: {}", new_delay);
+                println!("Delay changed to: {}", new_delay);
             }
-        });
-        */
-
-        // 按键软件消抖处理方法：合并在短时间内发生的多次中断
-        let mut flag = false;
-        critical_section::with(|cs| {
-            if G_FLAG.borrow(cs).get() {
-                flag = true;
+            // 双击：恢复默认速度
+            Some(ButtonEvent::DoubleClick) => {
+                BLINK_DELAY.store(500, Ordering::Relaxed);
+                println!("Delay reset to: 500");
             }
-        });
-
-        if flag {
-            // 按键消抖延时
-            delay.delay_millis(50);
-
-            // 清除全局标志位
-            critical_section::with(|cs| {
-                G_FLAG.borrow(cs).set(false);
-            });
-
-            // 修改延时值
-            let mut new_delay = BLINK_DELAY.load(Ordering::Relaxed);
-            new_delay = if new_delay <= 100 { 500 } else { new_delay - 100 };
-            BLINK_DELAY.store(new_delay, Ordering::Relaxed);
-            esp_println::println!("Delay changed to: {}", new_delay);
+            // 长按：打印中断次数
+            Some(ButtonEvent::LongPress) => {
+                let count = critical_section::with(|cs| COUNTER.borrow(cs).get());
+                println!("Long press, {} interrupts so far", count);
+            }
+            _ => {}
         }
 
+        // 每 5ms 轮询一次即可
+        delay.delay_millis(5);
     }
-}
\ No newline at end of file
+}
@@ -4,8 +4,9 @@
 #![no_main]
 
 use esp_backtrace as _;
+use esp32s3_demo::adc::{AdcError, CalibratedAdc};
 use esp_hal::{
-    analog::adc::{Adc,AdcConfig, Attenuation}, 
+    analog::adc::Attenuation,
     main,
 };
 use esp_println::println;
@@ -26,37 +27,32 @@ fn main() -> ! {
     // ADC2: GPIO11-GPIO20
     let pin_instance = peripherals.GPIO1;
 
-    // 创建ADC配置参数的实例
-    let mut adc1_config = AdcConfig::new();
-    // 通过ADC配置来处理引脚设置
-    let mut adc1_pin = adc1_config.enable_pin(
+    // 创建带校准的ADC读数器：
+    // 使用 eFuse 中的出厂校准数据（曲线校准），直接得到毫伏值
+    let mut adc1 = CalibratedAdc::new(
+        peripherals.ADC1,
         pin_instance,       // 原始GPIO外设：将此引脚作为模拟输入
-        Attenuation::_11dB  // ADC专用配置：设置衰减值，ADC的测量范围将被扩大到可以测量0-3.3V的电压。
+        Attenuation::_11dB  // 11dB 衰减的可用范围约为 0-3.1V（不是 3.3V）
     );
-
-    // 创建ADC驱动
-    let mut adc1 = Adc::new(peripherals.ADC1, adc1_config);
+    println!("Usable range: {:?}", adc1.range());
 
     let delay = esp_hal::delay::Delay::new();
 
     // 进入主循环
     loop {
 
-        // 获取ADC读数
-        let sample: u16 = nb::block!(adc1.read_oneshot(&mut adc1_pin)).unwrap();
-
-        // 转换为电压
-        // 原始值的范围是从0到4095（2^12 − 1），这是基于所选分辨率（12位）的ADC全量程。
-        // 转换后的值则以毫伏（mV）为单位，显示从 0 到 ADC 可测量的最大电压。 最大值取决于设备的校准和前面描述的衰减设置。
-        // 当前假设最大电压为3.3V。
-        // 公式：实际值 = 原始读数 * 物理量最大值 / ADC最大读数
-        let voltage: u32 = sample as u32 * 3300 / 4095;
-
-        // 打印原始读数和转换后的电压
-        println!(
-            "Raw Reading: {}, Voltage Reading: {}mV",
-            sample, voltage
-        );
+        // 获取校准后的电压读数
+        // 注意：不能再用 `sample * 3300 / 4095` 换算，每一档衰减的量程不同，而且每颗芯片都有偏差
+        match adc1.read_mv() {
+            Ok(voltage) => println!("Voltage Reading: {}mV", voltage),
+            Err(AdcError::AboveRange { mv, max_mv }) => {
+                println!("Voltage {}mV above usable range (max {}mV), reading not reliable", mv, max_mv)
+            }
+            // 接近 0V 时误差和读数本身差不多大，小信号应该换用更小的衰减档
+            Err(AdcError::BelowRange { mv, min_mv }) => {
+                println!("Voltage {}mV below usable range (min {}mV), reading not reliable", mv, min_mv)
+            }
+        }
 
         // 在下次采样前等待半秒
         delay.delay_millis(500_u32);
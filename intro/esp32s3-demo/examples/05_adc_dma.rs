@@ -0,0 +1,93 @@
+// ADC - DMA 连续采样：两个通道轮流采样，每写满半个缓冲区处理一次
+
+#![no_std]
+#![no_main]
+
+use esp_backtrace as _;
+use esp32s3_demo::adc_dma::{AdcDma, DmaBuffer, ScanConfig};
+use esp_hal::{analog::adc::Attenuation, main};
+use esp_println::println;
+use static_cell::StaticCell;
+
+esp_bootloader_esp_idf::esp_app_desc!();
+
+// 每半个缓冲区 500 个样本
+const HALF: usize = 500;
+// 两个通道合计 20kHz，每个通道 10kHz，每 25ms 写满一半
+const SAMPLE_RATE_HZ: u32 = 20_000;
+
+// DMA 缓冲区必须在内部 RAM 中，并且在整个采样期间有效
+static BUFFER: StaticCell<DmaBuffer<HALF>> = StaticCell::new();
+
+// 一个通道在半个缓冲区内的统计
+#[derive(Default)]
+struct Stats {
+    count: u32,
+    sum: u32,
+    min: u16,
+    max: u16,
+}
+
+impl Stats {
+    fn add(&mut self, raw: u16) {
+        if self.count == 0 {
+            self.min = raw;
+            self.max = raw;
+        }
+        self.count += 1;
+        self.sum += raw as u32;
+        self.min = self.min.min(raw);
+        self.max = self.max.max(raw);
+    }
+
+    fn mean(&self) -> u32 {
+        if self.count == 0 { 0 } else { self.sum / self.count }
+    }
+}
+
+#[main]
+fn main() -> ! {
+    // 获取外设工具箱
+    let peripherals = esp_hal::init(esp_hal::Config::default());
+
+    // 扫描表：GPIO1（电位器）和 GPIO2（麦克风/振动传感器）轮流转换
+    let mut config = ScanConfig::new(SAMPLE_RATE_HZ);
+    let pot = config.add_pin(peripherals.GPIO1, Attenuation::_11dB).unwrap();
+    let mic = config.add_pin(peripherals.GPIO2, Attenuation::_11dB).unwrap();
+
+    let buffer = BUFFER.init(DmaBuffer::new());
+    let mut adc = AdcDma::new(peripherals.ADC1, peripherals.DMA_CH0, config, buffer).unwrap();
+    adc.start();
+    println!("ADC DMA started: channels {} and {}, {} Hz", pot, mic, SAMPLE_RATE_HZ);
+
+    let mut halves: u32 = 0;
+    loop {
+        // 有写满的半区就处理，没有就立即返回，主循环还可以做别的事情
+        let stats = adc.poll_half(|samples| {
+            let mut stats = [Stats::default(), Stats::default()];
+            for sample in samples {
+                // 按通道号区分样本
+                if sample.channel == pot {
+                    stats[0].add(sample.raw);
+                } else if sample.channel == mic {
+                    stats[1].add(sample.raw);
+                }
+            }
+            stats
+        });
+
+        if let Some([pot_stats, mic_stats]) = stats {
+            halves += 1;
+            // 每 40 个半区（约 1 秒）打印一次
+            if halves % 40 == 0 {
+                println!(
+                    "pot: mean {:4} | mic: mean {:4}, peak-to-peak {:4} | overruns: {}",
+                    pot_stats.mean(),
+                    mic_stats.mean(),
+                    mic_stats.max - mic_stats.min,
+                    adc.overrun_count()
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,60 @@
+// ADC - 滤波流水线：过采样 + 中值滤波 + 指数平滑，再用带回差的阈值控制 LED
+
+#![no_std]
+#![no_main]
+
+use esp_backtrace as _;
+use esp32s3_demo::filter::{Ema, Filter, Hysteresis, Median, Oversample, SampleIter};
+use esp_hal::{
+    analog::adc::{Adc, AdcConfig, Attenuation},
+    gpio::{Level, Output, OutputConfig},
+    main,
+};
+use esp_println::println;
+
+esp_bootloader_esp_idf::esp_app_desc!();
+
+#[main]
+fn main() -> ! {
+    // 获取外设工具箱
+    let peripherals = esp_hal::init(esp_hal::Config::default());
+
+    // ADC1 通道：GPIO1 接电位器
+    let mut adc1_config = AdcConfig::new();
+    let mut adc1_pin = adc1_config.enable_pin(peripherals.GPIO1, Attenuation::_11dB);
+    let mut adc1 = Adc::new(peripherals.ADC1, adc1_config);
+
+    let mut led = Output::new(peripherals.GPIO7, Level::Low, OutputConfig::default());
+    let delay = esp_hal::delay::Delay::new();
+
+    // 原始样本流：每次 next() 读取一个样本，每个样本之间间隔 1ms
+    let samples = core::iter::from_fn(|| {
+        let sample: u16 = nb::block!(adc1.read_oneshot(&mut adc1_pin)).unwrap();
+        delay.delay_millis(1);
+        Some(sample)
+    });
+
+    // 滤波流水线：
+    // 1. 16 倍过采样，降低随机噪声
+    // 2. 5 点中值滤波，去掉偶发的尖峰
+    // 3. 指数平滑，alpha = 64/256
+    let pipeline = Oversample::<16>::new()
+        .then(Median::<5>::new())
+        .then(Ema::new(64));
+
+    // 阈值：超过 2500 点亮 LED，低于 2000 熄灭，中间保持不变
+    let mut threshold = Hysteresis::new(2000, 2500);
+
+    for (i, value) in samples.filtered(pipeline).enumerate() {
+        let on = threshold.update(value).unwrap_or(false);
+        led.set_level(if on { Level::High } else { Level::Low });
+
+        // 每 10 个输出打印一次（约 160ms）
+        if i % 10 == 0 {
+            println!("Filtered: {:4}  LED: {}", value, if on { "ON" } else { "OFF" });
+        }
+    }
+
+    // 样本流是无限的，不会走到这里
+    loop {}
+}
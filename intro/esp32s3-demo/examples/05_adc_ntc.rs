@@ -9,7 +9,7 @@ use esp_hal::{
     main,
 };
 use esp_println::println;
-use libm::log; // 用于计算自然对数
+use esp32s3_demo::thermistor::{Beta, Divider, Thermistor, Topology};
 esp_bootloader_esp_idf::esp_app_desc!();
 
 
@@ -39,8 +39,12 @@ fn main() -> ! {
 
     let delay = esp_hal::delay::Delay::new();
 
-    const B: f64 = 3950.0; // NTC 热敏电阻的B值，从数据手册查得
-    const VMAX: f64 = 4095.0; // 12位 ADC 最大原始读数，即 2^12 - 1
+    // NTC 热敏电阻：10kΩ@25°C，B值 3950（从数据手册查得）
+    // 接在分压电路下端（接地），串联 10kΩ 电阻，12位 ADC
+    let ntc = Thermistor::new(
+        Divider::new(Topology::NtcToGround, 10_000.0, 12),
+        Beta::new(10_000.0, 25.0, 3950.0),
+    );
 
     // 计算NTC电阻值
     // 进入主循环
@@ -49,11 +53,15 @@ fn main() -> ! {
         // 获取ADC读数
         let sample: u16 = nb::block!(adc1.read_oneshot(&mut adc1_pin)).unwrap();
 
-        // 转换温度
-        let temperature = 1.0
-            / (log(1.0 / (VMAX / sample as f64 - 1.0)) / B
-            + 1.0 / 298.15) // 298.15K 即 25°C
-            - 273.15; // 273.15 这个值是用来将开尔文温度结果转换为摄氏度的
+        // 转换温度：读数为 0 或满量程说明热敏电阻开路/短路
+        let temperature = match ntc.celsius(sample) {
+            Ok(t) => t,
+            Err(e) => {
+                println!("Raw Reading: {}, NTC error: {:?}", sample, e);
+                delay.delay_millis(500_u32);
+                continue;
+            }
+        };
 
         // 打印原始读数和转换后的电压
         println!(
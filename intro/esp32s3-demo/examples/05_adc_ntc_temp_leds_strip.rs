@@ -8,24 +8,12 @@ use esp_hal::{
     analog::adc::{Adc,AdcConfig, Attenuation}, gpio::{Level, Output, OutputConfig}, main,
 };
 use esp_println::println;
-use libm::log; // 用于计算自然对数
+use esp32s3_demo::thermistor::{Beta, Divider, Thermistor, Topology};
+use scaling::{Linear, Range};
 
 esp_bootloader_esp_idf::esp_app_desc!();
 
 
-// 将一个范围映射到另一个范围的函数
-fn map(
-    x: u32,
-    in_min: u32,
-    in_max: u32,
-    out_min: u32,
-    out_max: u32,
-) -> u32 {
-    (x - in_min) * (out_max - out_min)
-    / (in_max - in_min)
-    + out_min
-}
-
 #[main]
 fn main() -> ! {
     // 获取外设工具箱
@@ -52,8 +40,12 @@ fn main() -> ! {
 
     let delay = esp_hal::delay::Delay::new();
 
-    const B: f64 = 3950.0; // NTC 热敏电阻的B值，从数据手册查得
-    const VMAX: f64 = 4095.0; // 12位 ADC 最大原始读数，即 2^12 - 1
+    // NTC 热敏电阻：10kΩ@25°C，B值 3950（从数据手册查得）
+    // 接在分压电路下端（接地），串联 10kΩ 电阻，12位 ADC
+    let ntc = Thermistor::new(
+        Divider::new(Topology::NtcToGround, 10_000.0, 12),
+        Beta::new(10_000.0, 25.0, 3950.0),
+    );
 
     // let mut led = Output::new(peripherals.GPIO8, Level::Low, OutputConfig::default());
 
@@ -74,6 +66,7 @@ fn main() -> ! {
         Output::new(peripherals.GPIO8, Level::Low, OutputConfig::default()),
     ];
     let leds_len = leds.len();
+    let leds_scale = Linear::new(Range::new(MIN_TEMP, MAX_TEMP), Range::new(0, leds_len as u32));
 
     // 计算NTC电阻值
     // 进入主循环
@@ -82,16 +75,21 @@ fn main() -> ! {
         // 获取ADC读数
         let sample: u16 = nb::block!(adc1.read_oneshot(&mut adc1_pin)).unwrap();
 
-        // 转换温度
-        let temperature = 1.0
-            / (log(1.0 / (VMAX / sample as f64 - 1.0)) / B
-            + 1.0 / 298.15) // 298.15K 即 25°C
-            - 273.15; // 273.15 这个值是用来将开尔文温度结果转换为摄氏度的
+        // 转换温度：读数为 0 或满量程说明热敏电阻开路/短路
+        let temperature = match ntc.celsius(sample) {
+            Ok(t) => t,
+            Err(e) => {
+                println!("Raw Reading: {}, NTC error: {:?}", sample, e);
+                delay.delay_millis(500_u32);
+                continue;
+            }
+        };
 
         let temp_u32 = temperature as u32;
 
-        // 将温度范围 [10, 25] 映射到要点亮的LED数量 [0, leds_len]
-        let leds_to_light = map(temp_u32, MIN_TEMP, MAX_TEMP, 0, leds_len as u32);
+        // 将温度范围 [MIN_TEMP, MAX_TEMP] 映射到要点亮的LED数量 [0, leds_len]
+        // 超出温度范围时先限幅，低于 MIN_TEMP 不会再下溢
+        let leds_to_light = leds_scale.map_clamped(temp_u32);
         println!("Temperature: {}C, LEDs to light: {}", temp_u32, leds_to_light);
         // 根据计算结果控制LED灯条
         for i in 0..leds_len {
@@ -4,6 +4,11 @@
 #![no_main]
 
 use esp_backtrace as _;
+use esp32s3_demo::{
+    clock::{Clock, SystemClock},
+    event_queue::EventQueue,
+};
+use isr_handshake::{Handshake, Publish};
 use esp_hal::{
     gpio::{Level, Output, OutputConfig},
     handler, main,
@@ -13,7 +18,7 @@ use esp_hal::{
 use esp_println::println;
 use core::{
     cell::RefCell,
-    sync::atomic::{AtomicBool, AtomicU32, Ordering},
+    sync::atomic::{AtomicU32, Ordering},
 };
 use critical_section::Mutex;
 // 关键：引入 Timer trait 以启用其方法（如 load_value/enable_interrupt/clear_interrupt），解决私有方法报错
@@ -24,12 +29,19 @@ esp_bootloader_esp_idf::esp_app_desc!();
 // 在 ISR 与主循环之间共享定时器实例与事件标志
 static G_TIMER: Mutex<RefCell<Option<esp_hal::timer::timg::Timer>>> =
     Mutex::new(RefCell::new(None));
-static TICK: AtomicBool = AtomicBool::new(false);
-// 用于演示发布-获取：
-// - 当启用 feature `publish_before` 时：ISR 在 Release 发布前先写 COUNTER
-// - 默认情况下（未启用）：ISR 在 Release 发布后再写 COUNTER
-// 主循环使用 Acquire 获取后读取 COUNTER，用于观察可见性差异
-static COUNTER: AtomicU32 = AtomicU32::new(0);
+// 每一次定时器中断都作为一个带时间戳的事件放入队列（值为中断序号），
+// 主循环即使被阻塞错过了几次轮询，也能按顺序处理每一个 tick，而不是合并成一次
+static TICKS: EventQueue<u32, 8> = EventQueue::new();
+static TICK_SEQ: AtomicU32 = AtomicU32::new(0);
+// 用于演示发布-获取（TICK 标志 + COUNTER 数据，见 intro/isr-handshake）：
+// - 当启用 feature `publish_before` 时：ISR 在 Release 发布 TICK 前先写 COUNTER
+// - 默认情况下（未启用）：ISR 在 Release 发布 TICK 后再写 COUNTER
+// 主循环使用 Acquire 获取 TICK 后读取 COUNTER，用于观察可见性差异
+// 哪些写法能保证可见，由 isr-handshake 中的 loom 测试验证
+#[cfg(feature = "publish_before")]
+static HANDSHAKE: Handshake = Handshake::new(Publish::BeforeFlag);
+#[cfg(not(feature = "publish_before"))]
+static HANDSHAKE: Handshake = Handshake::new(Publish::AfterFlag);
 
 #[handler]
 fn timer_isr() {
@@ -39,21 +51,13 @@ fn timer_isr() {
             t.clear_interrupt();
         }
     });
+    let seq = TICK_SEQ.fetch_add(1, Ordering::Relaxed) + 1;
+    let _ = TICKS.push(SystemClock.now_ms(), seq);
+
     // 根据 feature 切换演示顺序：
-    #[cfg(feature = "publish_before")]
-    {
-        // 发布前写入：这次写入将被随后 TICK 的 Release 所发布
-        // 发布前写入，主循环通过 Acquire 能看到增量
-        let _ = COUNTER.fetch_add(1, Ordering::Relaxed);
-        TICK.store(true, Ordering::Release);
-    }
-    #[cfg(not(feature = "publish_before"))]
-    {
-        // 发布后写入：这次写入不会被上一次 Release/Acquire 所保证可见
-        TICK.store(true, Ordering::Release); // 原为 Relaxed：仅事件标志可用 Relaxed；此处改为 Release
-        // 发布后写入，Acquire 不保证可见
-        let _ = COUNTER.fetch_add(1, Ordering::Relaxed);
-    }
+    // - publish_before：发布前写入，这次写入由随后 TICK 的 Release 发布，主循环通过 Acquire 一定能看到增量
+    // - 默认：发布后写入，这次写入不受 Release/Acquire 保证，主循环可能看不到
+    HANDSHAKE.isr_tick();
 }
 
 // 默认模式：cargo run --example 06_timer_blinky_interrupte
@@ -86,14 +90,25 @@ fn main() -> ! {
     // 主循环：响应 ISR 设置的事件
     let mut count: u32 = 0;
     loop {
-        // Acquire：与 ISR 的 Release 配合，用于演示发布-获取
-        if TICK.swap(false, Ordering::Acquire) {
-            // 读取 ISR 在 Release 之后写入的数据：不保证可见
-            let before = COUNTER.load(Ordering::Relaxed);
-            // 在 main 中对该数据进行修改（演示写入）
-            let after = COUNTER.fetch_add(10, Ordering::Relaxed) + 10;
+        // LED 由事件队列驱动：每一个 tick 都翻转一次
+        for tick in TICKS.drain() {
             led.toggle();
             count += 1;
+            if tick.event != count {
+                println!("tick #{} at {}ms (expected #{})", tick.event, tick.timestamp_ms, count);
+                count = tick.event;
+            }
+        }
+        let dropped = TICKS.take_overflow_count();
+        if dropped > 0 {
+            println!("{} ticks dropped (queue full)", dropped);
+        }
+
+        // Acquire：与 ISR 的 Release 配合，用于演示发布-获取
+        // 取得 TICK 后读取 COUNTER：只有 publish_before 模式下才保证能看到 ISR 的增量
+        if let Some(before) = HANDSHAKE.main_poll() {
+            // 在 main 中对该数据进行修改（演示写入）
+            let after = HANDSHAKE.add(10);
             println!("tick={} counter_before={} counter_after={}", count, before, after);
         }
     }
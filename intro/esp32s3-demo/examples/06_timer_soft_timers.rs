@@ -0,0 +1,125 @@
+// 软件定时器示例 - 一个硬件定时器驱动多个一次性/周期软件定时器
+
+#![no_std]
+#![no_main]
+
+use esp_backtrace as _;
+use esp32s3_demo::{
+    clock::{Clock, SystemClock},
+    soft_timer::{Callback, TimerHandle, TimerWheel},
+};
+use esp_hal::{
+    gpio::{Level, Output, OutputConfig},
+    handler, main,
+    timer::timg::TimerGroup,
+    time::Duration,
+};
+use esp_println::println;
+use core::{
+    cell::RefCell,
+    sync::atomic::{AtomicBool, Ordering},
+};
+use critical_section::Mutex;
+use esp_hal::timer::Timer as _;
+
+esp_bootloader_esp_idf::esp_app_desc!();
+
+// 时间轮节拍，与硬件定时器的中断周期相同
+const TICK_MS: u32 = 10;
+
+static G_TIMER: Mutex<RefCell<Option<esp_hal::timer::timg::Timer>>> =
+    Mutex::new(RefCell::new(None));
+// ISR 只设置这个标志，所有回调都在主循环中执行
+static DUE: AtomicBool = AtomicBool::new(false);
+
+#[handler]
+fn tick_isr() {
+    critical_section::with(|cs| {
+        if let Some(t) = G_TIMER.borrow_ref_mut(cs).as_mut() {
+            t.clear_interrupt();
+        }
+    });
+    DUE.store(true, Ordering::Release);
+}
+
+// 回调共享的上下文
+struct App {
+    led_slow: Output<'static>,
+    led_fast: Output<'static>,
+    seconds: u32,
+    // 回调不能直接操作时间轮，需要调整定时器时先记下来，由主循环处理
+    speed_up: bool,
+}
+
+fn blink_slow(app: &mut App, _: TimerHandle) {
+    app.led_slow.toggle();
+}
+
+fn blink_fast(app: &mut App, _: TimerHandle) {
+    app.led_fast.toggle();
+}
+
+fn every_second(app: &mut App, _: TimerHandle) {
+    app.seconds += 1;
+    println!("uptime: {}s", app.seconds);
+}
+
+fn after_five_seconds(app: &mut App, _: TimerHandle) {
+    println!("5s elapsed, speeding up the slow LED");
+    app.speed_up = true;
+}
+
+fn never(_: &mut App, _: TimerHandle) {
+    println!("this timer was cancelled and should never fire");
+}
+
+#[main]
+fn main() -> ! {
+    // 获取外设工具箱
+    let peripherals = esp_hal::init(esp_hal::Config::default());
+
+    let mut app = App {
+        led_slow: Output::new(peripherals.GPIO4, Level::Low, OutputConfig::default()),
+        led_fast: Output::new(peripherals.GPIO5, Level::Low, OutputConfig::default()),
+        seconds: 0,
+        speed_up: false,
+    };
+
+    // 只用一个硬件定时器，周期 10ms
+    let timer_group = TimerGroup::new(peripherals.TIMG0);
+    let timer0 = timer_group.timer0;
+    timer0.enable_auto_reload(true);
+    timer0.load_value(Duration::from_millis(TICK_MS as u64)).unwrap();
+    timer0.set_interrupt_handler(tick_isr);
+    timer0.enable_interrupt(true);
+    // 先放进全局变量再启动，第一次中断时 ISR 就能清除中断标志
+    critical_section::with(|cs| G_TIMER.borrow_ref_mut(cs).insert(timer0).start());
+
+    // 最多 8 个软件定时器，时间轮 64 个槽（一圈 640ms）
+    let mut timers: TimerWheel<Callback<App>, 8, 64> = TimerWheel::new(TICK_MS);
+    let clock = SystemClock;
+    // 时间轮从当前时间开始
+    timers.advance(clock.now_ms());
+
+    let slow = timers.start_periodic(500, blink_slow).unwrap();
+    timers.start_periodic(150, blink_fast).unwrap();
+    timers.start_periodic(1000, every_second).unwrap();
+    timers.start_once(5000, after_five_seconds).unwrap();
+    let cancelled = timers.start_once(3000, never).unwrap();
+    timers.cancel(cancelled).unwrap();
+
+    println!("Soft timer example started, {} timers running", timers.len());
+
+    loop {
+        // Acquire：与 ISR 中的 Release 配对
+        if DUE.swap(false, Ordering::Acquire) {
+            timers.run(clock.now_ms(), &mut app);
+        }
+
+        if app.speed_up {
+            app.speed_up = false;
+            timers.set_period(slow, Some(100)).unwrap();
+            timers.reschedule(slow, 100).unwrap();
+        }
+    }
+}
@@ -1,60 +1,161 @@
-// PWM - LED 呼吸灯 - 使用硬件渐变功能 start_duty_fade
+// PWM - LED 呼吸灯 - 伽马校正 + 缓动曲线的多通道渐变，由定时器中断驱动
+//
+// 之前的版本用 `start_duty_fade(0, 100, 2000)` 做硬件渐变：占空比线性变化，看起来亮度变化不均匀，
+// 而且主循环要 `delay` 等待渐变结束。这里改用 `fade::FadeController`：
+// - TIMG0 每 10ms 中断一次，ISR 调用 `update()` 计算并写入 3 个 LEDC 通道的占空比
+// - 每个通道一个循环播放的渐变序列，曲线各不相同
+// - 某一步结束时 ISR 把通道位图放进事件队列，主循环不需要等待，可以做别的事情
 
 #![no_std]
 #![no_main]
 
+use core::cell::RefCell;
+
+use critical_section::Mutex;
 use esp_backtrace as _;
+use esp32s3_demo::{
+    clock::{Clock, SystemClock},
+    event_queue::EventQueue,
+    fade::{Easing, FadeController, FadeStep, DEFAULT_GAMMA},
+};
 use esp_hal::{
-    ledc::{self, channel::{self, ChannelIFace}, timer::TimerIFace, Ledc, LowSpeed},
+    handler,
+    ledc::{
+        self,
+        channel::{self, ChannelIFace},
+        timer::TimerIFace,
+        Ledc, LowSpeed,
+    },
     main,
-    time::Rate,
+    time::{Duration, Rate},
+    timer::timg::TimerGroup,
 };
+// 引入 Timer trait 以启用 load_value/enable_interrupt/clear_interrupt 等方法
+use esp_hal::timer::Timer as _;
+use esp_println::println;
+use static_cell::StaticCell;
 
 esp_bootloader_esp_idf::esp_app_desc!();
 
+// 3 个 LED
+const CHANNELS: usize = 3;
+// 每个通道的序列最多 4 步
+const STEPS: usize = 4;
+// 渐变刷新周期
+const UPDATE_PERIOD_MS: u64 = 10;
+
+type Fades = FadeController<channel::Channel<'static, LowSpeed>, CHANNELS, STEPS>;
+
+// LEDC 通道引用了定时器，定时器需要活得和通道一样久
+static LEDC_TIMER: StaticCell<ledc::timer::Timer<'static, LowSpeed>> = StaticCell::new();
+
+// ISR 需要访问的定时器和渐变控制器
+static G_TIMER: Mutex<RefCell<Option<esp_hal::timer::timg::Timer>>> =
+    Mutex::new(RefCell::new(None));
+static G_FADES: Mutex<RefCell<Option<Fades>>> = Mutex::new(RefCell::new(None));
+// 渐变步骤结束事件（通道位图）
+static FINISHED: EventQueue<u32, 8> = EventQueue::new();
+
+#[handler]
+fn fade_isr() {
+    critical_section::with(|cs| {
+        if let Some(t) = G_TIMER.borrow_ref_mut(cs).as_mut() {
+            t.clear_interrupt();
+        }
+        if let Some(fades) = G_FADES.borrow_ref_mut(cs).as_mut() {
+            let now = SystemClock.now_ms();
+            if let Ok(finished) = fades.update(now) {
+                if finished != 0 {
+                    let _ = FINISHED.push(now, finished);
+                }
+            }
+        }
+    });
+}
+
 #[main]
 fn main() -> ! {
     // 获取外设工具箱
     let peripherals = esp_hal::init(esp_hal::Config::default());
-    
-    let led_pin = peripherals.GPIO7;
 
     let mut ledc = Ledc::new(peripherals.LEDC);
     ledc.set_global_slow_clock(ledc::LSGlobalClkSource::APBClk);
 
-    let mut timer = ledc.timer::<LowSpeed>(ledc::timer::Number::Timer0);
-    timer.configure(ledc::timer::config::Config {
-        duty: ledc::timer::config::Duty::Duty14Bit, // 分辨率：14-bit
-        clock_source: ledc::timer::LSClockSource::APBClk,
-        frequency: Rate::from_khz(1), // 频率：1KHz
-    }).unwrap();
+    let timer = LEDC_TIMER.init(ledc.timer::<LowSpeed>(ledc::timer::Number::Timer0));
+    timer
+        .configure(ledc::timer::config::Config {
+            duty: ledc::timer::config::Duty::Duty14Bit, // 分辨率：14-bit，低亮度时伽马校正后仍有足够的级数
+            clock_source: ledc::timer::LSClockSource::APBClk,
+            frequency: Rate::from_khz(1), // 频率：1KHz
+        })
+        .unwrap();
 
-    let mut channel = ledc.channel(channel::Number::Channel0, led_pin);
-    channel.configure(channel::config::Config {
-        timer: &timer,
+    let channel_config = channel::config::Config {
+        timer: &*timer,
         duty_pct: 0,
         pin_config: channel::config::PinConfig::PushPull,
-    }).unwrap();
+    };
+    let mut channels = [
+        ledc.channel(channel::Number::Channel0, peripherals.GPIO7),
+        ledc.channel(channel::Number::Channel1, peripherals.GPIO8),
+        ledc.channel(channel::Number::Channel2, peripherals.GPIO9),
+    ];
+    for channel in channels.iter_mut() {
+        channel.configure(channel_config).unwrap();
+    }
+
+    let mut fades = Fades::new(channels, DEFAULT_GAMMA);
+
+    // 通道 0：呼吸灯，缓入缓出
+    let breathe = fades.fader(0);
+    breathe.enqueue_all(&[
+        FadeStep::new(100, 2000).with_easing(Easing::EaseInOut),
+        FadeStep::new(0, 2000).with_easing(Easing::EaseInOut),
+    ]);
+    breathe.set_repeat(true);
+
+    // 通道 1：缓慢点亮后保持，然后指数熄灭，熄灭后保持
+    let flash = fades.fader(1);
+    flash.enqueue_all(&[
+        FadeStep::new(100, 1500).with_easing(Easing::EaseOut),
+        FadeStep::hold(100, 500),
+        FadeStep::new(0, 1000).with_easing(Easing::Exponential),
+        FadeStep::hold(0, 1000),
+    ]);
+    flash.set_repeat(true);
+
+    // 通道 2：和旧版本一样的线性渐变，对比伽马校正后的效果
+    let linear = fades.fader(2);
+    linear.enqueue_all(&[FadeStep::new(100, 2000), FadeStep::new(0, 2000)]);
+    linear.set_repeat(true);
+
+    critical_section::with(|cs| G_FADES.borrow_ref_mut(cs).replace(fades));
+
+    // 一个硬件定时器刷新所有通道
+    let timer_group = TimerGroup::new(peripherals.TIMG0);
+    let timer0 = timer_group.timer0;
+    timer0.enable_auto_reload(true);
+    timer0
+        .load_value(Duration::from_millis(UPDATE_PERIOD_MS))
+        .unwrap();
+    timer0.set_interrupt_handler(fade_isr);
+    timer0.enable_interrupt(true);
+    // 先放进全局变量再启动，第一次中断时 ISR 就能清除中断标志
+    critical_section::with(|cs| G_TIMER.borrow_ref_mut(cs).insert(timer0).start());
 
-    // 创建一个延时器实例，用于后续的延时操作
-    let delay = esp_hal::delay::Delay::new();
-    let fade_time_ms = 2000_u16; // 渐变时间: 2秒
+    println!("Fade example started");
 
-    // 进入主循环
+    // 进入主循环：不再需要 delay 等待渐变，这里只打印渐变步骤结束的事件
     loop {
-        // 1. 开始从 0% -> 100% 的硬件渐变
-        // 这个函数会立即返回，硬件（ESP32-S3 的 LEDC（LED控制器））在后台开始工作，但不会阻塞当前线程
-        // 硬件在后台自动执行从 0% 到 100% 的 PWM 占空比渐变，这个过程与 CPU 执行代码是并行进行的
-        channel.start_duty_fade(0, 100, fade_time_ms).unwrap();
-        
-        // 2. 手动同步软硬件节奏，等待硬件渐变完成，确保呼吸灯效果的正确节奏
-        // 因为 start_duty_fade 是非阻塞的, 这里的 delay 是为了同步软件和硬件的节奏，等待上一个硬件任务完成后再发布下一个。
-        delay.delay_millis(fade_time_ms as u32);
-
-        // 3. 开始从 100% -> 0% 的硬件渐变
-        channel.start_duty_fade(100, 0, fade_time_ms).unwrap();
-
-        // 4. 再次等待
-        delay.delay_millis(fade_time_ms as u32);
+        for stamped in FINISHED.drain() {
+            for channel in 0..CHANNELS {
+                if stamped.event & (1 << channel) != 0 {
+                    println!(
+                        "{} ms: channel {} finished a step",
+                        stamped.timestamp_ms, channel
+                    );
+                }
+            }
+        }
     }
 }
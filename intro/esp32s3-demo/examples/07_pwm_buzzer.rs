@@ -0,0 +1,99 @@
+// PWM - 无源蜂鸣器播放 RTTTL 铃声
+//
+// 蜂鸣器接 GPIO7。每个音符都会修改 LEDC 定时器的频率，播放过程不阻塞：
+// 主循环每 5ms 调用一次 `buzzer.play()`，同时还让 GPIO8 上的 LED 闪烁。
+
+#![no_std]
+#![no_main]
+
+use esp_backtrace as _;
+use esp32s3_demo::{
+    buzzer::{Buzzer, Melody, Rtttl},
+    clock::{Clock, SystemClock},
+};
+use esp_hal::{
+    gpio::{Level, Output, OutputConfig},
+    ledc::{
+        self,
+        channel::{self, ChannelIFace},
+        timer::{config::Duty, TimerIFace},
+        Ledc, LowSpeed,
+    },
+    main,
+    time::Rate,
+};
+use esp_println::println;
+
+esp_bootloader_esp_idf::esp_app_desc!();
+
+const SONGS: [&str; 2] = [
+    "TakeOnMe:d=4,o=4,b=160:8f#5,8f#5,8f#5,8d5,8p,8b,8p,8e5,8p,8e5,8p,8e5,8g#5,8g#5,8a5,8b5,8a5,8a5,8a5,8e5,8p,8d5,8p,8f#5,8p,8f#5,8p,8f#5,8e5,8e5,8f#5,8e5",
+    "Indiana:d=4,o=5,b=250:e,8p,8f,8g,8p,1c6,8p.,d,8p,8e,1f,p.,g,8p,8a,8b,8p,1f6,p,a,8p,8b,2c6,2d6,2e6",
+];
+
+// 10-bit 分辨率：可用频率约 77Hz ~ 78kHz
+const DUTY: Duty = Duty::Duty10Bit;
+
+#[main]
+fn main() -> ! {
+    // 获取外设工具箱
+    let peripherals = esp_hal::init(esp_hal::Config::default());
+
+    let mut ledc = Ledc::new(peripherals.LEDC);
+    ledc.set_global_slow_clock(ledc::LSGlobalClkSource::APBClk);
+
+    // 通道配置时借用的定时器，初始频率随意
+    let mut timer = ledc.timer::<LowSpeed>(ledc::timer::Number::Timer0);
+    timer
+        .configure(ledc::timer::config::Config {
+            duty: DUTY,
+            clock_source: ledc::timer::LSClockSource::APBClk,
+            frequency: Rate::from_hz(440),
+        })
+        .unwrap();
+
+    let mut channel = ledc.channel(channel::Number::Channel0, peripherals.GPIO7);
+    channel
+        .configure(channel::config::Config {
+            timer: &timer,
+            duty_pct: 0,
+            pin_config: channel::config::PinConfig::PushPull,
+        })
+        .unwrap();
+
+    // 同一个定时器的第二个句柄，由蜂鸣器用来修改频率
+    let retune = ledc.timer::<LowSpeed>(ledc::timer::Number::Timer0);
+    let mut buzzer = Buzzer::new(retune, channel, DUTY);
+
+    let mut led = Output::new(peripherals.GPIO8, Level::Low, OutputConfig::default());
+    let delay = esp_hal::delay::Delay::new();
+
+    let mut song = 0;
+    loop {
+        let tune = Rtttl::parse(SONGS[song]).unwrap();
+        println!("Playing {}", tune.name());
+        let mut melody = Melody::new(&tune, SystemClock.now_ms());
+
+        let mut last_blink = 0;
+        loop {
+            let now = SystemClock.now_ms();
+            match buzzer.play(&mut melody, now) {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(e) => {
+                    println!("{:?}", e);
+                    break;
+                }
+            }
+            // 播放期间主循环可以做别的事情
+            if now - last_blink >= 250 {
+                led.toggle();
+                last_blink = now;
+            }
+            delay.delay_millis(5);
+        }
+
+        delay.delay_millis(2000);
+        song = (song + 1) % SONGS.len();
+    }
+}
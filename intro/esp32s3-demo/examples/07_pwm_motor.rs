@@ -0,0 +1,143 @@
+// PWM - 直流电机速度闭环：MCPWM 驱动 H 桥 + PCNT 读编码器 + PID
+//
+// 接线（H 桥由四个 MOS 管组成，或使用带上下管独立输入的驱动芯片）：
+// - GPIO4 / GPIO5：左半桥上管 / 下管（MCPWM0 operator0 的 A/B 路）
+// - GPIO6 / GPIO7：右半桥上管 / 下管（MCPWM0 operator1 的 A/B 路）
+// - GPIO15 / GPIO16：编码器 A 相 / B 相（PCNT unit0）
+//
+// PWM 频率 20kHz（人耳听不到），上下管之间的死区 500ns。
+// 主循环每 10ms 读一次编码器、运行一次 PID，目标转速按 `PROFILE` 每 3 秒切换一次，
+// 每 100ms 打印一次目标转速、实际转速、占空比和 PID 各项，方便调参。
+
+#![no_std]
+#![no_main]
+
+use esp_backtrace as _;
+use esp32s3_demo::{
+    clock::{Clock, SystemClock},
+    motor::{dead_time_ticks, Encoder, HBridge, PidConfig, SpeedLoop},
+};
+use esp_hal::{
+    gpio::{Input, InputConfig, Pull},
+    main,
+    mcpwm::{
+        operator::{DeadTimeCfg, PwmPinConfig},
+        timer::PwmWorkingMode,
+        McPwm, PeripheralClockConfig,
+    },
+    pcnt::Pcnt,
+    time::Rate,
+};
+use esp_println::println;
+
+esp_bootloader_esp_idf::esp_app_desc!();
+
+// MCPWM 外设时钟 40MHz，定时器计数 0..=1999 → 20kHz，占空比分辨率 0.05%
+const PWM_CLOCK_HZ: u32 = 40_000_000;
+const PWM_PERIOD: u16 = 1999;
+const DEAD_TIME_NS: u32 = 500;
+
+// 编码器 11 线、四倍频，减速比 30:1 → 输出轴每圈 1320 个计数
+const COUNTS_PER_REV: u32 = 11 * 4 * 30;
+
+const LOOP_MS: u64 = 10;
+const PRINT_MS: u64 = 100;
+// 目标转速（rpm），每个持续 3 秒
+const PROFILE: [f32; 6] = [0.0, 60.0, 150.0, 60.0, -100.0, 0.0];
+const STEP_MS: u64 = 3000;
+
+#[main]
+fn main() -> ! {
+    // 获取外设工具箱
+    let peripherals = esp_hal::init(esp_hal::Config::default());
+
+    // MCPWM：两个 operator 共用 timer0，保证两个半桥的 PWM 同步
+    let clock_cfg = PeripheralClockConfig::with_frequency(Rate::from_hz(PWM_CLOCK_HZ)).unwrap();
+    let mut mcpwm = McPwm::new(peripherals.MCPWM0, clock_cfg);
+    mcpwm.operator0.set_timer(&mcpwm.timer0);
+    mcpwm.operator1.set_timer(&mcpwm.timer0);
+
+    // A 路输出 PWM，B 路由死区发生器生成互补信号（AHC）
+    let left = mcpwm.operator0.with_linked_pins(
+        peripherals.GPIO4,
+        PwmPinConfig::UP_ACTIVE_HIGH,
+        peripherals.GPIO5,
+        PwmPinConfig::EMPTY,
+        DeadTimeCfg::new_ahc(),
+    );
+    let right = mcpwm.operator1.with_linked_pins(
+        peripherals.GPIO6,
+        PwmPinConfig::UP_ACTIVE_HIGH,
+        peripherals.GPIO7,
+        PwmPinConfig::EMPTY,
+        DeadTimeCfg::new_ahc(),
+    );
+    let dead_time = dead_time_ticks(DEAD_TIME_NS, PWM_CLOCK_HZ);
+    let mut bridge = HBridge::new(left, right, PWM_PERIOD, dead_time);
+
+    let timer_cfg = clock_cfg
+        .timer_clock_with_frequency(PWM_PERIOD, PwmWorkingMode::Increase, Rate::from_khz(20))
+        .unwrap();
+    mcpwm.timer0.start(timer_cfg);
+
+    // PCNT：编码器输出一般是开漏，需要上拉
+    let pcnt = Pcnt::new(peripherals.PCNT);
+    let input_config = InputConfig::default().with_pull(Pull::Up);
+    let phase_a = Input::new(peripherals.GPIO15, input_config);
+    let phase_b = Input::new(peripherals.GPIO16, input_config);
+    // 滤掉短于 1us 的毛刺（80 个 APB 时钟周期）
+    let mut encoder = Encoder::new(
+        pcnt.unit0,
+        phase_a.peripheral_input(),
+        phase_b.peripheral_input(),
+        COUNTS_PER_REV,
+        Some(80),
+    )
+    .unwrap();
+
+    // PID 的输出就是占空比（%）；kd 为 0，编码器测速的噪声较大，只用 PI
+    let mut speed_loop = SpeedLoop::new(
+        PidConfig {
+            kp: 0.15,
+            ki: 1.5,
+            kd: 0.0,
+            output_min: -100.0,
+            output_max: 100.0,
+            derivative_filter_s: 0.02,
+        },
+        COUNTS_PER_REV,
+    )
+    .unwrap();
+
+    let delay = esp_hal::delay::Delay::new();
+    let start = SystemClock.now_ms();
+    let mut last = start;
+    let mut last_print = start;
+
+    loop {
+        let now = SystemClock.now_ms();
+        let step = ((now - start) / STEP_MS) as usize % PROFILE.len();
+        speed_loop.set_setpoint(PROFILE[step]);
+
+        let dt_s = (now - last) as f32 / 1000.0;
+        last = now;
+        let duty = speed_loop.update(encoder.poll(), dt_s);
+        bridge.set_duty(duty);
+
+        if now - last_print >= PRINT_MS {
+            last_print = now;
+            let terms = speed_loop.pid().terms();
+            println!(
+                "target {:7.1} rpm | speed {:7.1} rpm | duty {:6.1}% | P {:6.1} I {:6.1} | {:.2} rev",
+                speed_loop.setpoint(),
+                speed_loop.speed_rpm(),
+                duty,
+                terms.proportional,
+                terms.integral,
+                encoder.revolutions(),
+            );
+        }
+
+        delay.delay_millis(LOOP_MS as u32);
+    }
+}
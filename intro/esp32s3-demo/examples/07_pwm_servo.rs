@@ -0,0 +1,102 @@
+// PWM - 舵机与电调：LEDC 定时器 50Hz、14-bit
+//
+// - GPIO7 接舵机：在 0° 和 180° 之间来回转动，最大转速 60°/s
+// - GPIO8 接电调：上电后先解锁（零油门保持 2 秒），然后油门在 0~30% 之间缓慢变化
+//
+// 舵机和电调共用一个 LEDC 定时器，主循环每 20ms（一个 PWM 周期）更新一次。
+
+#![no_std]
+#![no_main]
+
+use esp_backtrace as _;
+use esp32s3_demo::{
+    clock::{Clock, SystemClock},
+    servo::{Esc, EscConfig, PwmTiming, Servo, ServoCalibration, SERVO_FREQUENCY_HZ},
+};
+use esp_hal::{
+    ledc::{
+        self,
+        channel::{self, ChannelIFace},
+        timer::TimerIFace,
+        Ledc, LowSpeed,
+    },
+    main,
+    time::Rate,
+};
+use esp_println::println;
+
+esp_bootloader_esp_idf::esp_app_desc!();
+
+// 14-bit 分辨率：1000~2000us 之间有 819 级
+const RESOLUTION_BITS: u8 = 14;
+// 更新周期，与 PWM 周期相同
+const FRAME_MS: u32 = 20;
+
+#[main]
+fn main() -> ! {
+    // 获取外设工具箱
+    let peripherals = esp_hal::init(esp_hal::Config::default());
+
+    let mut ledc = Ledc::new(peripherals.LEDC);
+    ledc.set_global_slow_clock(ledc::LSGlobalClkSource::APBClk);
+
+    let mut timer = ledc.timer::<LowSpeed>(ledc::timer::Number::Timer0);
+    timer
+        .configure(ledc::timer::config::Config {
+            duty: ledc::timer::config::Duty::Duty14Bit, // 分辨率：14-bit
+            clock_source: ledc::timer::LSClockSource::APBClk,
+            frequency: Rate::from_hz(SERVO_FREQUENCY_HZ), // 频率：50Hz
+        })
+        .unwrap();
+    let timing = PwmTiming::new(SERVO_FREQUENCY_HZ, RESOLUTION_BITS).unwrap();
+
+    let channel_config = channel::config::Config {
+        timer: &timer,
+        duty_pct: 0,
+        pin_config: channel::config::PinConfig::PushPull,
+    };
+    let mut servo_channel = ledc.channel(channel::Number::Channel0, peripherals.GPIO7);
+    servo_channel.configure(channel_config).unwrap();
+    let mut esc_channel = ledc.channel(channel::Number::Channel1, peripherals.GPIO8);
+    esc_channel.configure(channel_config).unwrap();
+
+    // 舵机：实测的行程标定，很多 SG90 需要 500~2500us 才能转满 180°
+    let calibration = ServoCalibration::new(500, 2500, scaling::Range::new(0.0, 180.0));
+    let mut servo = Servo::new(servo_channel, timing, calibration, 90.0, Some(60.0)).unwrap();
+
+    // 电调：默认 1000~2000us，零油门保持 2 秒解锁
+    let mut esc = Esc::new(esc_channel, timing, EscConfig::default()).unwrap();
+    esc.arm(SystemClock.now_ms());
+    println!("ESC arming...");
+
+    let delay = esp_hal::delay::Delay::new();
+    let mut frame: u32 = 0;
+    let mut was_armed = false;
+
+    loop {
+        let now = SystemClock.now_ms();
+
+        // 到达一端后转向另一端
+        if servo.is_settled() {
+            let next = if servo.angle() < 90.0 { 180.0 } else { 0.0 };
+            servo.move_to(next);
+            println!("servo -> {}°", next);
+        }
+        servo.update(now).unwrap();
+
+        if esc.is_armed() {
+            if !was_armed {
+                println!("ESC armed");
+                was_armed = true;
+            }
+            // 10 秒一个来回的三角波：0 -> 30% -> 0
+            let t = (frame * FRAME_MS / 100) % 100;
+            let throttle = (if t < 50 { t } else { 100 - t }) * 30 / 50;
+            esc.set_throttle(throttle as u8);
+        }
+        esc.update(now).unwrap();
+
+        frame = frame.wrapping_add(1);
+        delay.delay_millis(FRAME_MS);
+    }
+}
@@ -1,53 +1,53 @@
-// 使用GPIO和延迟模拟PWM (软件PWM)
+// 使用定时器中断模拟PWM (软件PWM)：3 个引脚，占空比各自独立
 
 #![no_std]
 #![no_main]
 
-use embedded_hal::delay::DelayNs;
+use core::cell::RefCell;
+
+use critical_section::Mutex;
 use esp_backtrace as _;
+use esp32s3_demo::soft_pwm::{tick_period, DutyCycles, SoftPwm};
 use esp_hal::{
-    delay::Delay, gpio::{self, DriveMode, Level, Output, OutputConfig, Pull}, ledc::{self, channel::{self, ChannelIFace}, 
-    timer::TimerIFace, Ledc, LowSpeed}, main, time::Rate 
+    gpio::{DriveMode, Level, Output, OutputConfig, Pull},
+    handler, main,
+    time::Duration,
+    timer::timg::TimerGroup,
 };
+// 引入 Timer trait 以启用 load_value/enable_interrupt/clear_interrupt 等方法
+use esp_hal::timer::Timer as _;
 
 esp_bootloader_esp_idf::esp_app_desc!();
 
-// 软件PWM函数
-// - pin: 可变的GPIO输出引脚
-// - intensity: 亮度/占空比 (0-100)
-// - frequency_hz: PWM信号频率 (Hz)
-// - duration_ms: 该PWM信号持续的总时间 (ms)
-fn soft_pwm(
-    pin: &mut Output,
-    intensity: u8,
-    frequency_hz: u32,
-    duration_ms: u32,
-    delay: &mut Delay,
-) {
-    if frequency_hz == 0 {
-        return;
-    }
-    // 1. 计算周期和高/低电平时间 (单位: 微秒)
-    let period_us = 1_000_000 / frequency_hz;
-    let on_time_us = (period_us as u64 * intensity as u64 / 100) as u32;
-    let off_time_us = period_us - on_time_us;
+// 3 个通道
+const CHANNELS: usize = 3;
+// 500Hz, 避免闪烁（定时器中断频率为 500 × 100 = 50kHz）
+const PWM_FREQUENCY_HZ: u32 = 500;
+// 在编译期检查频率：超出 1..=10kHz 时编译失败
+const TICK_PERIOD: Duration = match tick_period(PWM_FREQUENCY_HZ) {
+    Some(period) => period,
+    None => panic!("PWM frequency must be 1..=10000 Hz"),
+};
+
+// 占空比：主循环随时修改，ISR 在每个 PWM 周期开始时读取
+static DUTIES: DutyCycles<CHANNELS> = DutyCycles::new();
 
-    // 2. 计算需要循环的次数
-    let cycles = duration_ms * 1000 / period_us;
+// ISR 需要访问的定时器和 PWM 输出
+static G_TIMER: Mutex<RefCell<Option<esp_hal::timer::timg::Timer>>> =
+    Mutex::new(RefCell::new(None));
+static G_PWM: Mutex<RefCell<Option<SoftPwm<'static, CHANNELS>>>> =
+    Mutex::new(RefCell::new(None));
 
-    // 3. 循环产生PWM波形
-    for _ in 0..cycles {
-        if on_time_us > 0 {
-            pin.set_high();
-            delay.delay_us(on_time_us);
+#[handler]
+fn pwm_isr() {
+    critical_section::with(|cs| {
+        if let Some(t) = G_TIMER.borrow_ref_mut(cs).as_mut() {
+            t.clear_interrupt();
         }
-        if off_time_us > 0 {
-            pin.set_low();
-            delay.delay_us(off_time_us);
+        if let Some(pwm) = G_PWM.borrow_ref_mut(cs).as_mut() {
+            pwm.tick();
         }
-    }
-    // 确保函数结束时引脚为低电平
-    pin.set_low();
+    });
 }
 
 #[main]
@@ -60,26 +60,40 @@ fn main() -> ! {
         .with_drive_mode(DriveMode::PushPull)
         .with_pull(Pull::None);
 
-    let mut led_pin = Output::new(peripherals.GPIO7, Level::Low, led_pin_conf);
+    let leds = [
+        Output::new(peripherals.GPIO7, Level::Low, led_pin_conf),
+        Output::new(peripherals.GPIO8, Level::Low, led_pin_conf),
+        Output::new(peripherals.GPIO9, Level::Low, led_pin_conf),
+    ];
+    critical_section::with(|cs| {
+        G_PWM.borrow_ref_mut(cs).replace(SoftPwm::new(leds, &DUTIES));
+    });
 
-    // 创建一个延时器实例，用于后续的延时操作
-    let mut delay = esp_hal::delay::Delay::new();
+    // 一个硬件定时器驱动所有通道
+    let timer_group = TimerGroup::new(peripherals.TIMG0);
+    let timer0 = timer_group.timer0;
+    timer0.enable_auto_reload(true);
+    timer0.load_value(TICK_PERIOD).unwrap();
+    timer0.set_interrupt_handler(pwm_isr);
+    timer0.enable_interrupt(true);
+    // 先放进全局变量再启动：否则第一次中断时 ISR 拿不到定时器、清除不了中断标志，
+    // 50kHz 的中断会在 main 存入定时器之前反复进入
+    critical_section::with(|cs| G_TIMER.borrow_ref_mut(cs).insert(timer0).start());
 
-    let max_intensity = 100_u8;
-    let min_intensity = 0_u8;
-    let pwm_frequency = 500; // 500Hz, 避免闪烁
-    
-    // 进入主循环
-    loop {
-        // 渐亮: 从 0% -> 100%
-        for i in min_intensity..max_intensity {
-            // 每次只输出一小段时间(20ms), 拼接成渐变效果
-            soft_pwm(&mut led_pin, i, pwm_frequency, 20, &mut delay);
-        }
+    // 创建一个延时器实例，只用来控制渐变速度，PWM 波形由中断产生
+    let delay = esp_hal::delay::Delay::new();
 
-        // 渐暗: 从 100% -> 0%
-        for i in (min_intensity..max_intensity).rev() {
-            soft_pwm(&mut led_pin, i, pwm_frequency, 20, &mut delay);
+    // 进入主循环：三个通道相位错开的呼吸灯
+    let mut step: u32 = 0;
+    loop {
+        for channel in 0..CHANNELS {
+            // 三角波 0 -> 100 -> 0，每个通道错开 1/3 周期
+            let t = (step + channel as u32 * 67) % 200;
+            let pct = if t < 100 { t } else { 200 - t };
+            // set_duty 立即返回，不会阻塞
+            DUTIES.set_duty(channel, pct as u8).unwrap();
         }
+        step = step.wrapping_add(1);
+        delay.delay_millis(20);
     }
 }
@@ -1,19 +1,23 @@
-// 修改文件标题和描述
 // 简化嵌入式Rust: ESP核心库版
 // 编程串行通信 - I2C MPU-6050 传感器示例
-// 此示例演示如何使用ESP32-S3的I2C接口与MPU-6050通信，初始化传感器并循环读取加速度和陀螺仪数据。
+// 此示例演示如何使用ESP32-S3的I2C接口与MPU-6050通信：初始化传感器、静止标定零偏，
+// 然后循环读取加速度（g）、角速度（°/s）和温度（°C）。
+//
+// 寄存器读写、量程换算、标定都在 `mpu6050` crate 中（阻塞和异步两种接口，可在主机上测试）。
+// 这里 I2C 总线整个交给了驱动；同一总线上再挂 RTC 等器件时见 08_i2c_shared_bus.rs。
 
 #![no_std]
 #![no_main]
 
 use esp_backtrace as _;
 use esp_hal::{
-    delay::Delay, i2c::master::{Config, I2c}, main, time::Rate,
+    delay::Delay,
+    i2c::master::{Config, I2c},
+    main,
+    time::Rate,
 };
 use esp_println::println;
-
-// MPU-6050的I2C从机地址（AD0接地为0x68）
-const MPU6050_ADDR: u8 = 0x68;
+use mpu6050::{blocking::Mpu6050, AccelRange, Dlpf, Gravity, GyroRange, ADDRESS_AD0_LOW};
 
 esp_bootloader_esp_idf::esp_app_desc!();
 
@@ -22,55 +26,58 @@ fn main() -> ! {
     // 初始化ESP外设，使用默认配置
     let peripherals = esp_hal::init(esp_hal::Config::default());
     // 创建延迟对象，用于时间延迟
-    let delay = Delay::new();
+    let mut delay = Delay::new();
 
-    // 创建I2C配置，默认配置并设置频率为100kHz（DS1307标准速率）
-    let i2c_config = Config::default().with_frequency(Rate::from_khz(100));
+    // 创建I2C配置，MPU-6050 支持 400kHz 快速模式
+    let i2c_config = Config::default().with_frequency(Rate::from_khz(400));
 
-    // 初始化I2C主设备，使用I2C0硬件，指定SDA (GPIO3) 和 SCL (GPIO2) 引脚
-    let mut ds1307 = I2c::new(peripherals.I2C0, i2c_config)
+    // 初始化I2C主设备，使用I2C0硬件，指定SDA (GPIO4) 和 SCL (GPIO5) 引脚
+    let i2c = I2c::new(peripherals.I2C0, i2c_config)
         .unwrap()
         .with_sda(peripherals.GPIO4)
         .with_scl(peripherals.GPIO5);
-        // .with_sda(peripherals.GPIO3)
-        // .with_scl(peripherals.GPIO2);
 
-    // 初始化 MPU-6050：写寄存器 0x6B (PWR_MGMT_1) 为 0x00 以唤醒
-    if let Err(e) = ds1307.write(MPU6050_ADDR, &[0x6B, 0x00]) {
-        println!("Failed to wake MPU-6050: {:?}", e);
-    } else {
-        println!("MPU-6050 initialized.");
-    }
+    // MPU-6050的I2C从机地址（AD0接地为0x68）
+    let mut imu = Mpu6050::new(i2c, ADDRESS_AD0_LOW);
 
-    // 主循环：每秒读取并打印时间
-    loop {
-        // 读取 14 字节数据：加速度 (6字节) + 温度 (2字节) + 陀螺仪 (6字节)，从 0x3B 开始
-        let mut data: [u8; 14] = [0; 14];
-        if let Err(e) = ds1307.write(MPU6050_ADDR, &[0x3B]) {
-            println!("I2C write error (Address): {:?}", e);
-            delay.delay_millis(1000_u32);
-            continue;
-        }
-        if let Err(e) = ds1307.read(MPU6050_ADDR, &mut data) {
-            println!("I2C read error: {:?}", e);
-            delay.delay_millis(1000_u32);
-            continue;
+    // 复位后唤醒：±500°/s、±4g、带宽 44Hz、采样率 1kHz / (1 + 9) = 100Hz
+    let config = mpu6050::Config {
+        gyro_range: GyroRange::Dps500,
+        accel_range: AccelRange::G4,
+        dlpf: Dlpf::Hz44,
+        sample_rate_divider: 9,
+    };
+    if let Err(e) = imu.reset(&mut delay).and_then(|_| imu.init(config)) {
+        println!("Failed to initialize MPU-6050: {:?}", e);
+        loop {
+            delay.delay_millis(1000);
         }
+    }
+    println!("MPU-6050 initialized, {} Hz", config.sample_rate_hz());
 
-        // 解析数据（16-bit 有符号整数，高字节先）
-        let accel_x = (data[0] as i16) << 8 | data[1] as i16;
-        let accel_y = (data[2] as i16) << 8 | data[3] as i16;
-        let accel_z = (data[4] as i16) << 8 | data[5] as i16;
-        let gyro_x = (data[8] as i16) << 8 | data[9] as i16;
-        let gyro_y = (data[10] as i16) << 8 | data[11] as i16;
-        let gyro_z = (data[12] as i16) << 8 | data[13] as i16;
+    // 标定：传感器水平静止放置（Z 轴朝上），平均 200 个样本（约 2 秒）
+    println!("Calibrating, keep the sensor still...");
+    match imu.calibrate(&mut delay, 200, Gravity::ZUp) {
+        Ok(offsets) => println!("Offsets: {:?}", offsets),
+        Err(e) => println!("Calibration failed: {:?}", e),
+    }
 
-        // 打印
-        println!(
-            "Accel: X={:5} Y={:5} Z={:5} | Gyro: X={:5} Y={:5} Z={:5}",
-            accel_x, accel_y, accel_z, gyro_x, gyro_y, gyro_z
-        );
+    // 主循环：每 500ms 读取并打印一次
+    loop {
+        match imu.read() {
+            Ok(m) => println!(
+                "Accel: X={:6.3} Y={:6.3} Z={:6.3} g | Gyro: X={:7.2} Y={:7.2} Z={:7.2} °/s | {:5.2} °C",
+                m.accel_g[0],
+                m.accel_g[1],
+                m.accel_g[2],
+                m.gyro_dps[0],
+                m.gyro_dps[1],
+                m.gyro_dps[2],
+                m.temperature_c,
+            ),
+            Err(e) => println!("I2C read error: {:?}", e),
+        }
 
-        delay.delay_millis(1000_u32);
+        delay.delay_millis(500);
     }
-}
\ No newline at end of file
+}
@@ -1,54 +1,124 @@
 // 简化嵌入式Rust: ESP核心库版
-// 编程串行通信 - I2C实时钟应用示例
-// 此示例演示如何使用ESP32-S3的I2C接口与DS1307 RTC芯片通信，设置初始时间并循环读取打印。
-// MPU6050 I2C 示例 (基于 esp-hal)
+// 编程串行通信 - I2C MPU-6050 异步示例：数据就绪中断 + FIFO 批量读取
+//
+// - MPU-6050 以 100Hz 采样，加速度和角速度写入内部 FIFO（每帧 12 字节）
+// - INT 引脚接 GPIO6，每个新样本拉高一次（锁存，读 INT_STATUS 清除）
+// - 主任务异步等待 INT 的上升沿，每攒够 10 个样本就从 FIFO 一次性读出并求平均
+//
+// I2C 使用 esp-hal 的异步驱动，`mpu6050::asynch::Mpu6050` 与阻塞版接口相同，只是方法需要 `.await`。
 
 #![no_std]
 #![no_main]
 
+use embassy_executor::Spawner;
 use esp_backtrace as _;
-use esp_hal::{delay::Delay, i2c::master::{Config, I2c}, main, time::Rate};
+use esp_hal::{
+    gpio::{Input, InputConfig, Pull},
+    i2c::master::{Config, I2c},
+    time::Rate,
+    timer::timg::TimerGroup,
+};
 use esp_println::println;
+use mpu6050::{
+    asynch::Mpu6050, Error, FifoConfig, Gravity, InterruptPinConfig, Interrupts, ADDRESS_AD0_HIGH,
+};
 
 esp_bootloader_esp_idf::esp_app_desc!();
 
-const MPU6050_ADDR: u8 = 0x69; // AD0 高电平
+// 每次从 FIFO 读取的样本数
+const BATCH: usize = 10;
 
-#[main]
-fn main() -> ! {
+#[esp_hal_embassy::main]
+async fn main(_spawner: Spawner) {
     let peripherals = esp_hal::init(esp_hal::Config::default());
-    let delay = Delay::new();
+    let timg0 = TimerGroup::new(peripherals.TIMG0);
+    esp_hal_embassy::init(timg0.timer0);
 
     let i2c_config = Config::default().with_frequency(Rate::from_khz(400)); // MPU6050 支持更高频率
-    let mut i2c = I2c::new(peripherals.I2C0, i2c_config)
+    let i2c = I2c::new(peripherals.I2C0, i2c_config)
         .unwrap()
         .with_sda(peripherals.GPIO4)
-        .with_scl(peripherals.GPIO5);
+        .with_scl(peripherals.GPIO5)
+        .into_async();
 
-    // 唤醒 MPU6050 (寄存器 0x6B, 值 0x00)
-    if let Err(e) = i2c.write(MPU6050_ADDR, &[0x6B, 0x00]) {
-        println!("I2C write error: {:?}", e);
-    }
+    // INT 引脚默认推挽、高电平有效
+    let mut int_pin = Input::new(
+        peripherals.GPIO6,
+        InputConfig::default().with_pull(Pull::Down),
+    );
+
+    let mut imu = Mpu6050::new(i2c, ADDRESS_AD0_HIGH); // AD0 高电平
+    let mut delay = embassy_time::Delay;
+    imu.reset(&mut delay).await.unwrap();
+    imu.init(mpu6050::Config::default()).await.unwrap();
+
+    println!("Calibrating, keep the sensor still...");
+    imu.calibrate(&mut delay, 100, Gravity::ZUp).await.unwrap();
+    let config = *imu.config();
+    let offsets = *imu.offsets();
+
+    let pin_config = InterruptPinConfig {
+        latch: true,
+        ..InterruptPinConfig::default()
+    };
+    imu.enable_interrupts(pin_config, Interrupts::DATA_READY)
+        .await
+        .unwrap();
+    let fifo = FifoConfig::ACCEL_GYRO;
+    imu.enable_fifo(fifo).await.unwrap();
 
+    let mut buf = [0u8; BATCH * 12];
+    let mut pending = 0;
     loop {
-        let mut data: [u8; 6] = [0; 6]; // 读取加速度 (寄存器 0x3B-0x40)
-        if let Err(e) = i2c.write(MPU6050_ADDR, &[0x3B]) {
-            println!("I2C write error: {:?}", e);
-            delay.delay_millis(1000);
+        // 等待数据就绪，读 INT_STATUS 清除锁存的中断
+        int_pin.wait_for_high().await;
+        let status = imu.interrupt_status().await.unwrap();
+        if !status.data_ready {
             continue;
         }
-        if let Err(e) = i2c.read(MPU6050_ADDR, &mut data) {
-            println!("I2C read error: {:?}", e);
-            delay.delay_millis(1000);
+        pending += 1;
+        if pending < BATCH {
             continue;
         }
+        pending = 0;
 
-        // 解析数据 (16位有符号整数)
-        let accel_x = ((data[0] as i16) << 8) | data[1] as i16;
-        let accel_y = ((data[2] as i16) << 8) | data[3] as i16;
-        let accel_z = ((data[4] as i16) << 8) | data[5] as i16;
+        // 一次 I2C 传输读出所有完整的帧
+        let len = match imu.read_fifo(&mut buf).await {
+            Ok(len) => len,
+            Err(Error::FifoOverflow) => {
+                println!("FIFO overflow, samples lost");
+                continue;
+            }
+            Err(e) => {
+                println!("I2C error: {:?}", e);
+                continue;
+            }
+        };
 
-        println!("Accel: X={}, Y={}, Z={}", accel_x, accel_y, accel_z);
-        delay.delay_millis(1000);
+        let mut accel = [0.0f32; 3];
+        let mut gyro = [0.0f32; 3];
+        let mut count = 0;
+        for raw in fifo.frames(&buf[..len]) {
+            let m = config.convert(&offsets.apply(&raw));
+            for axis in 0..3 {
+                accel[axis] += m.accel_g[axis];
+                gyro[axis] += m.gyro_dps[axis];
+            }
+            count += 1;
+        }
+        if count == 0 {
+            continue;
+        }
+        let n = count as f32;
+        println!(
+            "{} samples | Accel: X={:6.3} Y={:6.3} Z={:6.3} g | Gyro: X={:7.2} Y={:7.2} Z={:7.2} °/s",
+            count,
+            accel[0] / n,
+            accel[1] / n,
+            accel[2] / n,
+            gyro[0] / n,
+            gyro[1] / n,
+            gyro[2] / n,
+        );
     }
-}
\ No newline at end of file
+}
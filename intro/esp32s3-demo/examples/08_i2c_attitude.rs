@@ -0,0 +1,114 @@
+// 简化嵌入式Rust: ESP核心库版
+// 编程串行通信 - I2C MPU-6050 姿态解算示例
+// 在 08_i2c.rs 的基础上，把加速度和角速度融合成姿态：横滚（roll）、俯仰（pitch）、偏航（yaw）和四元数。
+//
+// - 陀螺仪积分短时准确但会漂移，加速度计长期准确但怕震动，滤波器取两者之长
+// - 同时运行互补滤波、Mahony、Madgwick 三种滤波器，方便对比
+// - 没有磁力计，偏航角只是陀螺仪积分，会缓慢漂移
+//
+// 滤波器在 `attitude` crate 中（no_std，支持 f32 和 Q24 定点数，在主机上用 IMU 记录数据测试）。
+// ESP32-S3 有 FPU，这里用 f32；没有 FPU 的芯片（如 ESP32-C3）可换成 `Mahony::<Q24>`。
+
+#![no_std]
+#![no_main]
+
+use attitude::{Complementary, Estimator, Madgwick, Mahony, Quaternion};
+use esp_backtrace as _;
+use esp_hal::{
+    delay::Delay,
+    i2c::master::{Config, I2c},
+    main,
+    time::{Duration, Instant, Rate},
+};
+use esp_println::println;
+use mpu6050::{blocking::Mpu6050, AccelRange, Dlpf, Gravity, GyroRange, ADDRESS_AD0_LOW};
+
+esp_bootloader_esp_idf::esp_app_desc!();
+
+// 每 10 个样本（100ms）打印一次
+const PRINT_EVERY: u32 = 10;
+
+#[main]
+fn main() -> ! {
+    let peripherals = esp_hal::init(esp_hal::Config::default());
+    let mut delay = Delay::new();
+
+    let i2c_config = Config::default().with_frequency(Rate::from_khz(400));
+    let i2c = I2c::new(peripherals.I2C0, i2c_config)
+        .unwrap()
+        .with_sda(peripherals.GPIO4)
+        .with_scl(peripherals.GPIO5);
+
+    // ±500°/s、±4g、带宽 44Hz、采样率 100Hz（与 08_i2c.rs 相同）
+    let mut imu = Mpu6050::new(i2c, ADDRESS_AD0_LOW);
+    let config = mpu6050::Config {
+        gyro_range: GyroRange::Dps500,
+        accel_range: AccelRange::G4,
+        dlpf: Dlpf::Hz44,
+        sample_rate_divider: 9,
+    };
+    imu.reset(&mut delay).unwrap();
+    imu.init(config).unwrap();
+
+    // 标定陀螺仪零偏，Madgwick 和互补滤波自身不估计零偏
+    println!("Calibrating, keep the sensor still and level...");
+    imu.calibrate(&mut delay, 200, Gravity::ZUp).unwrap();
+
+    // 时间常数 0.5s；kp=1.0、ki=0.05；beta=0.1
+    let mut complementary = Complementary::<f32>::new(0.5);
+    let mut mahony = Mahony::<f32>::new(1.0, 0.05);
+    let mut madgwick = Madgwick::<f32>::new(0.1);
+
+    // 用第一个样本的加速度直接设定初始倾角，省去收敛时间
+    let first = imu.read().unwrap();
+    let start = Quaternion::from_accel(first.accel_g);
+    complementary.set_quaternion(start);
+    mahony.set_quaternion(start);
+    madgwick.set_quaternion(start);
+
+    let period = Duration::from_micros(config.sample_period_us() as u64);
+    let mut last = Instant::now();
+    let mut count = 0u32;
+    loop {
+        // 等到下一个采样周期
+        while last.elapsed() < period {}
+        let now = Instant::now();
+        // 用实际间隔而不是标称周期，打印耽误的时间也能算进去
+        let dt = (now - last).as_micros() as f32 / 1_000_000.0;
+        last = now;
+
+        let m = match imu.read() {
+            Ok(m) => m,
+            Err(e) => {
+                println!("I2C read error: {:?}", e);
+                continue;
+            }
+        };
+        // 陀螺仪 °/s -> rad/s，加速度单位无所谓（只用方向）
+        let gyro = m.gyro_dps.map(|g| g.to_radians());
+        complementary.update(gyro, m.accel_g, dt);
+        mahony.update(gyro, m.accel_g, dt);
+        madgwick.update(gyro, m.accel_g, dt);
+
+        count += 1;
+        if count % PRINT_EVERY != 0 {
+            continue;
+        }
+        for (name, euler) in [
+            ("Complementary", complementary.euler()),
+            ("Mahony", mahony.euler()),
+            ("Madgwick", madgwick.euler()),
+        ] {
+            let e = euler.to_degrees();
+            println!(
+                "{:>13}: roll={:7.2} pitch={:7.2} yaw={:7.2} °",
+                name, e.roll, e.pitch, e.yaw
+            );
+        }
+        let q = mahony.quaternion();
+        println!(
+            "Quaternion (Mahony): w={:6.3} x={:6.3} y={:6.3} z={:6.3}\n",
+            q.w, q.x, q.y, q.z
+        );
+    }
+}
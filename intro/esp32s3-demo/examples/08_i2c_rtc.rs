@@ -0,0 +1,109 @@
+// 简化嵌入式Rust: ESP核心库版
+// 编程串行通信 - I2C实时钟应用示例
+// 此示例演示如何使用ESP32-S3的I2C接口与DS1307 RTC芯片通信：时钟停止时设置初始时间，然后每秒读取打印。
+//
+// BCD 编解码、12/24 小时制、闰年检查都在 `ds-rtc` crate 中（基于 nobcd，可在主机上用 embedded-hal-mock 测试）。
+// 另外用 DS1307 的 56 字节备份 RAM 记录上电次数，断电后只要有电池就不会丢失。
+
+#![no_std]
+#![no_main]
+
+use ds_rtc::{ds1307::Ds1307, DateTime, HourMode, SquareWave};
+use esp_backtrace as _;
+use esp_hal::{
+    delay::Delay,
+    i2c::master::{Config, I2c},
+    main,
+    time::Rate,
+};
+use esp_println::println;
+
+esp_bootloader_esp_idf::esp_app_desc!();
+
+// 上电计数在备份 RAM 中的位置：2 字节魔数 + 4 字节计数
+const RAM_MAGIC: [u8; 2] = [0x52, 0x54];
+const RAM_OFFSET: usize = 0;
+
+#[main]
+fn main() -> ! {
+    // 初始化ESP外设，使用默认配置
+    let peripherals = esp_hal::init(esp_hal::Config::default());
+    // 创建延迟对象，用于时间延迟
+    let delay = Delay::new();
+
+    // 创建I2C配置，设置频率为100kHz（DS1307标准速率）
+    let i2c_config = Config::default().with_frequency(Rate::from_khz(100));
+
+    // 初始化I2C主设备，使用I2C0硬件，指定SDA (GPIO4) 和 SCL (GPIO5) 引脚
+    let i2c = I2c::new(peripherals.I2C0, i2c_config)
+        .unwrap()
+        .with_sda(peripherals.GPIO4)
+        .with_scl(peripherals.GPIO5);
+
+    let mut rtc = Ds1307::new(i2c);
+
+    // 首次上电（或电池耗尽）时 CH 位为 1，时钟不走：设置初始时间（写入时间会同时清除 CH 位）
+    match rtc.is_running() {
+        Ok(true) => println!("Clock is running."),
+        Ok(false) => {
+            println!("Clock halted, setting the time...");
+            // 2015-05-15 00:00:00，星期由日期自动计算
+            let start = DateTime::new(2015, 5, 15, 0, 0, 0).unwrap();
+            if let Err(e) = rtc.set_datetime(&start) {
+                println!("Failed to set the time: {:?}", e);
+            }
+        }
+        Err(e) => println!("I2C error: {:?}", e),
+    }
+
+    // 切换为 12 小时制（芯片内部计数方式，读出的 DateTime 始终是 24 小时制）
+    rtc.set_hour_mode(HourMode::H12).unwrap();
+    // SQW/OUT 引脚输出 1Hz 方波，可接 LED 观察
+    rtc.set_square_wave(SquareWave::Hz1).unwrap();
+
+    // 上电计数：魔数不对说明 RAM 内容无效（首次上电），从 0 开始
+    let mut ram = [0u8; 6];
+    rtc.read_ram(RAM_OFFSET, &mut ram).unwrap();
+    let boots = if ram[..2] == RAM_MAGIC {
+        u32::from_le_bytes([ram[2], ram[3], ram[4], ram[5]]) + 1
+    } else {
+        1
+    };
+    ram[..2].copy_from_slice(&RAM_MAGIC);
+    ram[2..].copy_from_slice(&boots.to_le_bytes());
+    rtc.write_ram(RAM_OFFSET, &ram).unwrap();
+    println!("Boot #{}", boots);
+
+    // 主循环：每秒读取并打印时间
+    loop {
+        match rtc.datetime() {
+            Ok(dt) => {
+                let weekday = match dt.weekday().number() {
+                    1 => "Sunday",
+                    2 => "Monday",
+                    3 => "Tuesday",
+                    4 => "Wednesday",
+                    5 => "Thursday",
+                    6 => "Friday",
+                    _ => "Saturday",
+                };
+                // 按 12 小时制显示
+                let (hour, am_pm) = match dt.hour {
+                    0 => (12, "AM"),
+                    h @ 1..=11 => (h, "AM"),
+                    12 => (12, "PM"),
+                    h => (h - 12, "PM"),
+                };
+                println!(
+                    "{}, {}/{}/{}, {:02}:{:02}:{:02} {}",
+                    weekday, dt.day, dt.month, dt.year, hour, dt.minute, dt.second, am_pm
+                );
+            }
+            // 寄存器内容不是合法的 BCD 或日期（例如未初始化），打印错误后继续
+            Err(e) => println!("RTC read error: {:?}", e),
+        }
+
+        // 延迟1秒
+        delay.delay_millis(1000_u32);
+    }
+}
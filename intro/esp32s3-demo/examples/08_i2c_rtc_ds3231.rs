@@ -0,0 +1,90 @@
+// 简化嵌入式Rust: ESP核心库版
+// 编程串行通信 - I2C DS3231 闹钟示例
+//
+// - 闹钟 1 在每分钟的第 30 秒触发，闹钟 2 在每分钟开始（第 0 秒）触发
+// - INT/SQW 引脚（开漏，低电平有效）接 GPIO6，内部上拉；主任务异步等待下降沿
+// - 每次中断读取并清除闹钟标志，打印时间和芯片温度
+// - 掉电标志（OSF）为 1 时说明时间无效，重新设置
+//
+// DS3231 的驱动在 `ds-rtc` crate 中（阻塞 I2C，可在主机上用 embedded-hal-mock 测试）。
+
+#![no_std]
+#![no_main]
+
+use ds_rtc::{
+    ds3231::{Alarm1, Alarm2, AlarmFlags, Ds3231},
+    DateTime,
+};
+use embassy_executor::Spawner;
+use esp_backtrace as _;
+use esp_hal::{
+    gpio::{Input, InputConfig, Pull},
+    i2c::master::{Config, I2c},
+    time::Rate,
+    timer::timg::TimerGroup,
+};
+use esp_println::println;
+
+esp_bootloader_esp_idf::esp_app_desc!();
+
+#[esp_hal_embassy::main]
+async fn main(_spawner: Spawner) {
+    let peripherals = esp_hal::init(esp_hal::Config::default());
+    let timg0 = TimerGroup::new(peripherals.TIMG0);
+    esp_hal_embassy::init(timg0.timer0);
+
+    // DS3231 支持 400kHz
+    let i2c_config = Config::default().with_frequency(Rate::from_khz(400));
+    let i2c = I2c::new(peripherals.I2C0, i2c_config)
+        .unwrap()
+        .with_sda(peripherals.GPIO4)
+        .with_scl(peripherals.GPIO5);
+
+    // INT/SQW 为开漏输出，需要上拉
+    let mut int_pin = Input::new(
+        peripherals.GPIO6,
+        InputConfig::default().with_pull(Pull::Up),
+    );
+
+    let mut rtc = Ds3231::new(i2c);
+
+    if rtc.lost_power().unwrap() {
+        println!("Oscillator stopped, setting the time...");
+        let start = DateTime::new(2025, 1, 1, 0, 0, 0).unwrap();
+        rtc.set_datetime(&start).unwrap();
+    }
+
+    rtc.set_alarm1(Alarm1::Second { second: 30 }).unwrap();
+    rtc.set_alarm2(Alarm2::EveryMinute).unwrap();
+    // 清除旧的标志后再打开中断，否则 INT 会立即保持低电平
+    let both = AlarmFlags {
+        alarm1: true,
+        alarm2: true,
+    };
+    rtc.clear_alarm_flags(both).unwrap();
+    rtc.set_alarm_interrupts(both).unwrap();
+    println!("Waiting for alarms...");
+
+    loop {
+        int_pin.wait_for_falling_edge().await;
+
+        let flags = rtc.alarm_flags().unwrap();
+        // 清除标志，INT 引脚恢复高电平
+        rtc.clear_alarm_flags(flags).unwrap();
+
+        let dt = rtc.datetime().unwrap();
+        let temperature = rtc.temperature().unwrap();
+        println!(
+            "{}-{:02}-{:02} {:02}:{:02}:{:02} | alarm1={} alarm2={} | {:.2} °C",
+            dt.year,
+            dt.month,
+            dt.day,
+            dt.hour,
+            dt.minute,
+            dt.second,
+            flags.alarm1,
+            flags.alarm2,
+            temperature,
+        );
+    }
+}
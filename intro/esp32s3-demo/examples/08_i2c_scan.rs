@@ -0,0 +1,114 @@
+// 简化嵌入式Rust: ESP核心库版
+// 编程串行通信 - I2C 总线扫描与器件识别
+//
+// - 启动时先把 SDA (GPIO4) / SCL (GPIO5) 当作开漏 GPIO 检查总线：
+//   如果 MCU 在读操作中途复位，从机可能仍然拉低 SDA，此时最多输出 9 个 SCL 脉冲再发 STOP 释放总线
+// - 然后扫描 0x08..=0x77，对每个应答的地址读取 WHO_AM_I 等 ID 寄存器，打印器件名称
+// - 所有地址都报总线错误（而不是无应答）时，提示检查上拉电阻和接线
+//
+// 扫描、器件表和恢复逻辑在 `i2c-scan` crate 中（可在主机上用 embedded-hal-mock 测试）。
+
+#![no_std]
+#![no_main]
+
+use esp_backtrace as _;
+use esp_hal::{
+    delay::Delay,
+    gpio::{DriveMode, Flex, OutputConfig, Pull},
+    i2c::master::{Config, I2c},
+    main,
+    time::Rate,
+};
+use esp_println::println;
+use i2c_scan::{
+    candidates, identify,
+    recovery::{self, BusState},
+    scan, Confidence, Probe,
+};
+
+esp_bootloader_esp_idf::esp_app_desc!();
+
+#[main]
+fn main() -> ! {
+    let mut peripherals = esp_hal::init(esp_hal::Config::default());
+    let mut delay = Delay::new();
+
+    // 先借用引脚做 GPIO 检查，Flex 释放后再交给 I2C 外设
+    {
+        // 开漏输出 + 内部上拉（外部有上拉电阻时也无妨），同时打开输入以读回线电平
+        let od = OutputConfig::default()
+            .with_drive_mode(DriveMode::OpenDrain)
+            .with_pull(Pull::Up);
+        let mut sda = Flex::new(peripherals.GPIO4.reborrow());
+        let mut scl = Flex::new(peripherals.GPIO5.reborrow());
+        for pin in [&mut sda, &mut scl] {
+            pin.apply_output_config(&od);
+            pin.set_input_enable(true);
+            pin.set_output_enable(true);
+        }
+
+        match recovery::check_bus(&mut scl, &mut sda) {
+            Ok(BusState::Idle) => println!("Bus idle"),
+            Ok(BusState::SdaStuckLow) => {
+                println!("SDA held low, clocking SCL...");
+                match recovery::recover(&mut scl, &mut sda, &mut delay) {
+                    Ok(clocks) => println!("Bus released after {} clocks", clocks),
+                    Err(e) => println!("Recovery failed: {:?}", e),
+                }
+            }
+            // SCL 被拉低时时钟脉冲无济于事：检查上拉、短路或卡住的从机
+            Ok(BusState::SclStuckLow) => println!("SCL held low, check pull-ups and wiring"),
+            Err(e) => println!("Pin error: {:?}", e),
+        }
+    }
+
+    // 扫描用 100kHz，所有器件都支持
+    let i2c_config = Config::default().with_frequency(Rate::from_khz(100));
+    let mut i2c = I2c::new(peripherals.I2C0, i2c_config)
+        .unwrap()
+        .with_sda(peripherals.GPIO4)
+        .with_scl(peripherals.GPIO5);
+
+    loop {
+        // 与 i2cdetect 相同：EEPROM 地址段用读探测，其余用写探测
+        let found = scan(&mut i2c, Probe::Auto);
+        println!("{} device(s) found", found.count());
+
+        for address in found.addresses() {
+            // 0x70..=0x77 可能是 TCA9548A 多路复用器，写入寄存器地址会改变它打开的通道，
+            // 所以 identify() 在这些地址只按地址判断；确认总线上没有多路复用器时可以改用
+            // identify_with(&mut i2c, address, SharedAddress::Check) 读取 BME280 等的 ID 寄存器
+            match identify(&mut i2c, address) {
+                Some(id) => {
+                    let how = match id.confidence {
+                        Confidence::IdRegister => "ID register",
+                        Confidence::Heuristic => "register bits",
+                        Confidence::Address => "address only",
+                    };
+                    println!("  {:#04x}: {} ({})", address, id.part.name, how);
+                    // 仅凭地址判断时，把同一地址上的其它可能器件也列出来
+                    if id.confidence == Confidence::Address {
+                        for other in candidates(address).filter(|p| p.name != id.part.name) {
+                            println!("        or {}", other.name);
+                        }
+                    }
+                }
+                None => println!("  {:#04x}: unknown", address),
+            }
+        }
+
+        if found.bus_fault() {
+            println!(
+                "Every address failed with {:?}: missing pull-ups or SDA/SCL swapped?",
+                found.last_error()
+            );
+        } else if found.errors().count() > 0 {
+            println!("Bus errors at:");
+            for address in found.errors() {
+                println!("  {:#04x}", address);
+            }
+        }
+
+        delay.delay_millis(5000_u32);
+    }
+}
@@ -0,0 +1,79 @@
+// 简化嵌入式Rust: ESP核心库版
+// 编程串行通信 - 共享 I2C 总线：MPU-6050 和 DS3231 挂在同一个 I2C0 上
+//
+// - 两个驱动各拿一个 `RefCellDevice` 句柄，句柄在每次传输时借用 `RefCell` 里的总线
+// - 只在主循环中使用，不需要临界区；要在中断中访问总线见 08_i2c_shared_bus_isr.rs，
+//   embassy 多任务见 09_embassy_shared_i2c.rs（三种方式的对比见 src/shared_bus.rs）
+// - 地址冲突：DS3231 固定为 0x68，所以 MPU-6050 的 AD0 要接 3.3V（0x69）
+//
+// 主循环以 10Hz 读取 IMU，每秒打印一次 RTC 时间和最新的加速度。
+
+#![no_std]
+#![no_main]
+
+use core::cell::RefCell;
+
+use ds_rtc::{ds3231::Ds3231, DateTime};
+use embedded_hal_bus::i2c::RefCellDevice;
+use esp_backtrace as _;
+use esp_hal::{
+    delay::Delay,
+    i2c::master::{Config, I2c},
+    main,
+    time::Rate,
+};
+use esp_println::println;
+use mpu6050::{blocking::Mpu6050, ADDRESS_AD0_HIGH};
+
+esp_bootloader_esp_idf::esp_app_desc!();
+
+#[main]
+fn main() -> ! {
+    let peripherals = esp_hal::init(esp_hal::Config::default());
+    let mut delay = Delay::new();
+
+    // 两个器件都支持 400kHz
+    let i2c_config = Config::default().with_frequency(Rate::from_khz(400));
+    let i2c = I2c::new(peripherals.I2C0, i2c_config)
+        .unwrap()
+        .with_sda(peripherals.GPIO4)
+        .with_scl(peripherals.GPIO5);
+
+    // 总线放进 RefCell，驱动只拿到借用它的句柄
+    let bus = RefCell::new(i2c);
+    let mut imu = Mpu6050::new(RefCellDevice::new(&bus), ADDRESS_AD0_HIGH);
+    let mut rtc = Ds3231::new(RefCellDevice::new(&bus));
+
+    if let Err(e) = imu
+        .reset(&mut delay)
+        .and_then(|_| imu.init(mpu6050::Config::default()))
+    {
+        println!("Failed to initialize MPU-6050: {:?}", e);
+    }
+    match rtc.lost_power() {
+        Ok(true) => {
+            println!("Oscillator stopped, setting the time...");
+            let start = DateTime::new(2025, 1, 1, 0, 0, 0).unwrap();
+            rtc.set_datetime(&start).unwrap();
+        }
+        Ok(false) => {}
+        Err(e) => println!("Failed to read the DS3231: {:?}", e),
+    }
+
+    let mut tick = 0u32;
+    loop {
+        // 两个驱动交替使用总线，每次传输结束后借用就释放了
+        let accel = imu.read().map(|m| m.accel_g);
+        if tick % 10 == 0 {
+            match (rtc.datetime(), accel) {
+                (Ok(dt), Ok(a)) => println!(
+                    "{:02}:{:02}:{:02} | Accel: X={:6.3} Y={:6.3} Z={:6.3} g",
+                    dt.hour, dt.minute, dt.second, a[0], a[1], a[2]
+                ),
+                (rtc, imu) => println!("RTC: {:?} | IMU: {:?}", rtc.err(), imu.err()),
+            }
+        }
+        tick = tick.wrapping_add(1);
+        delay.delay_millis(100);
+    }
+}
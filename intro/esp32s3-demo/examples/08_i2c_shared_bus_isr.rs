@@ -0,0 +1,141 @@
+// 简化嵌入式Rust: ESP核心库版
+// 编程串行通信 - 共享 I2C 总线：主循环读 MPU-6050，中断里读 DS3231
+//
+// - DS3231 的 INT/SQW 输出 1Hz 方波，接 GPIO6（开漏，内部上拉），每个下降沿（秒开始时）触发 GPIO 中断
+// - 中断处理函数通过自己的 `CriticalSectionDevice` 句柄读取时间，放进事件队列交给主循环打印
+// - 主循环以 100Hz 读取 IMU；句柄在每次传输期间持有临界区，中断不会插进半个 I2C 传输中间
+//
+// 如果这里用 `RefCellDevice`，中断恰好发生在主循环的传输期间时会重复借用而 panic。
+// 代价是每次传输期间中断被推迟（400kHz 下读 14 字节约 0.4ms）。
+// 地址冲突：DS3231 固定为 0x68，所以 MPU-6050 的 AD0 要接 3.3V（0x69）
+
+#![no_std]
+#![no_main]
+
+use core::cell::RefCell;
+
+use critical_section::Mutex;
+use ds_rtc::{ds3231::Ds3231, DateTime, SquareWave};
+use embedded_hal_bus::i2c::CriticalSectionDevice;
+use esp32s3_demo::{
+    clock::{Clock, SystemClock},
+    event_queue::EventQueue,
+    shared_bus::{cs_bus, CsBus},
+};
+use esp_backtrace as _;
+use esp_hal::{
+    delay::Delay,
+    gpio::{Event, Input, InputConfig, Io, Pull},
+    handler,
+    i2c::master::{Config, I2c},
+    main,
+    time::Rate,
+    Blocking,
+};
+use esp_println::println;
+use mpu6050::{blocking::Mpu6050, ADDRESS_AD0_HIGH};
+use static_cell::StaticCell;
+
+esp_bootloader_esp_idf::esp_app_desc!();
+
+type Bus = I2c<'static, Blocking>;
+type Rtc = Ds3231<CriticalSectionDevice<'static, Bus>>;
+
+// 总线本身放在 StaticCell 中，句柄里保存的是 `&'static` 引用
+static BUS: StaticCell<CsBus<Bus>> = StaticCell::new();
+// 中断处理函数使用的 RTC 驱动和 SQW 引脚，在 main 中初始化
+static RTC: Mutex<RefCell<Option<Rtc>>> = Mutex::new(RefCell::new(None));
+static SQW_PIN: Mutex<RefCell<Option<Input>>> = Mutex::new(RefCell::new(None));
+// 中断读到的时间，交给主循环打印
+static TIMES: EventQueue<DateTime, 4> = EventQueue::new();
+
+#[handler]
+fn sqw_handler() {
+    critical_section::with(|cs| {
+        SQW_PIN
+            .borrow_ref_mut(cs)
+            .as_mut()
+            .unwrap()
+            .clear_interrupt();
+        // 读 7 个字节的时间寄存器，400kHz 下约 0.2ms
+        if let Some(rtc) = RTC.borrow_ref_mut(cs).as_mut() {
+            if let Ok(dt) = rtc.datetime() {
+                let _ = TIMES.push(SystemClock.now_ms(), dt);
+            }
+        }
+    });
+}
+
+#[main]
+fn main() -> ! {
+    let peripherals = esp_hal::init(esp_hal::Config::default());
+    let mut delay = Delay::new();
+
+    let i2c_config = Config::default().with_frequency(Rate::from_khz(400));
+    let i2c = I2c::new(peripherals.I2C0, i2c_config)
+        .unwrap()
+        .with_sda(peripherals.GPIO4)
+        .with_scl(peripherals.GPIO5);
+    let bus = cs_bus(&BUS, i2c);
+
+    // 每个驱动一个句柄
+    let mut imu = Mpu6050::new(CriticalSectionDevice::new(bus), ADDRESS_AD0_HIGH);
+    let mut rtc = Ds3231::new(CriticalSectionDevice::new(bus));
+
+    if let Err(e) = imu
+        .reset(&mut delay)
+        .and_then(|_| imu.init(mpu6050::Config::default()))
+    {
+        println!("Failed to initialize MPU-6050: {:?}", e);
+    }
+    if rtc.lost_power().unwrap() {
+        println!("Oscillator stopped, setting the time...");
+        let start = DateTime::new(2025, 1, 1, 0, 0, 0).unwrap();
+        rtc.set_datetime(&start).unwrap();
+    }
+    // SQW 每秒一个下降沿，与秒寄存器的更新对齐
+    rtc.set_square_wave(SquareWave::Hz1).unwrap();
+
+    let mut io = Io::new(peripherals.IO_MUX);
+    io.set_interrupt_handler(sqw_handler);
+    let mut sqw = Input::new(
+        peripherals.GPIO6,
+        InputConfig::default().with_pull(Pull::Up),
+    );
+    sqw.listen(Event::FallingEdge);
+    // 先放好 RTC 再放引脚：中断处理函数看到引脚时 RTC 一定已经就绪
+    critical_section::with(|cs| {
+        RTC.borrow_ref_mut(cs).replace(rtc);
+        SQW_PIN.borrow_ref_mut(cs).replace(sqw);
+    });
+
+    let mut samples = 0u32;
+    let mut sum_z = 0.0f32;
+    loop {
+        // 主循环和中断交替使用总线
+        match imu.read() {
+            Ok(m) => {
+                samples += 1;
+                sum_z += m.accel_g[2];
+            }
+            Err(e) => println!("IMU error: {:?}", e),
+        }
+
+        for stamped in TIMES.drain() {
+            let dt = stamped.event;
+            let mean_z = if samples > 0 {
+                sum_z / samples as f32
+            } else {
+                0.0
+            };
+            println!(
+                "{:02}:{:02}:{:02} (at {} ms) | {} IMU samples, mean Z={:6.3} g",
+                dt.hour, dt.minute, dt.second, stamped.timestamp_ms, samples, mean_z
+            );
+            samples = 0;
+            sum_z = 0.0;
+        }
+
+        delay.delay_millis(10);
+    }
+}
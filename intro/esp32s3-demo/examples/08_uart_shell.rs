@@ -0,0 +1,123 @@
+// 简化嵌入式Rust: ESP核心库版
+// 编程串行通信 - UART 交互式命令行
+//
+// 08_uart.rs 只会循环输出；这里在 UART0 上运行一个命令行（`shell` crate），用串口终端连接即可：
+//   picocom -b 115200 /dev/ttyUSB0    （或 minicom、PuTTY，需要 VT100 方向键）
+//
+// - 行编辑：退格、左右方向键、Home/End、Ctrl-C 放弃当前行、上下方向键翻历史、Tab 补全命令名
+// - 命令：help、history、gpio read/write、adc、reboot
+// - GPIO10..=GPIO12 可读可写（写之前是输入，第一次写时打开输出），ADC 只接了 GPIO1（11dB 衰减）
+//
+// 行编辑器、参数解析和内置命令都在 `shell` crate 中，可在主机上用字节脚本测试。
+// 改用 USB-Serial-JTAG：把 Uart 换成 `UsbSerialJtag::new(peripherals.USB_DEVICE)`，它同样实现了 embedded_io 的 Read + Write。
+
+#![no_std]
+#![no_main]
+
+use esp32s3_demo::adc::CalibratedAdc;
+use esp_backtrace as _;
+use esp_hal::{
+    analog::adc::Attenuation,
+    delay::Delay,
+    gpio::{Flex, Level, OutputConfig, Pin},
+    main,
+    peripherals::GPIO1,
+    uart::{Config, Uart},
+};
+use esp_println::println;
+use shell::{
+    builtins::{self, Adc, Gpio, Reboot},
+    Command, Shell,
+};
+
+esp_bootloader_esp_idf::esp_app_desc!();
+
+// 命令操作的硬件：按引脚号查找
+struct Board {
+    pins: [(u8, Flex<'static>); 3],
+    adc: CalibratedAdc<'static, GPIO1<'static>>,
+    delay: Delay,
+}
+
+impl Board {
+    fn pin(&mut self, pin: u8) -> Result<&mut Flex<'static>, &'static str> {
+        self.pins
+            .iter_mut()
+            .find(|(n, _)| *n == pin)
+            .map(|(_, flex)| flex)
+            .ok_or("pin not available, use 10, 11 or 12")
+    }
+}
+
+impl Gpio for Board {
+    fn read(&mut self, pin: u8) -> Result<bool, &'static str> {
+        Ok(self.pin(pin)?.is_high())
+    }
+
+    fn write(&mut self, pin: u8, high: bool) -> Result<(), &'static str> {
+        let flex = self.pin(pin)?;
+        flex.set_level(Level::from(high));
+        flex.set_output_enable(true);
+        Ok(())
+    }
+}
+
+impl Adc for Board {
+    fn read_mv(&mut self, pin: u8) -> Result<u16, &'static str> {
+        if pin != 1 {
+            return Err("only GPIO1 is set up as an ADC pin");
+        }
+        self.adc
+            .read_mv()
+            .map_err(|_| "outside the usable range of the 11dB attenuation")
+    }
+}
+
+impl Reboot for Board {
+    fn reboot(&mut self) -> ! {
+        // 等 UART 把 "Rebooting..." 发完
+        self.delay.delay_millis(20);
+        esp_hal::system::software_reset()
+    }
+}
+
+// 可读可写的 GPIO：同时打开输入，输出保持关闭直到第一次 `gpio write`
+fn io_pin(pin: impl Pin + 'static) -> Flex<'static> {
+    let mut flex = Flex::new(pin);
+    flex.apply_output_config(&OutputConfig::default());
+    flex.set_input_enable(true);
+    flex
+}
+
+#[main]
+fn main() -> ! {
+    let peripherals = esp_hal::init(esp_hal::Config::default());
+
+    // DevKitC 上 UART0 (GPIO43 TX / GPIO44 RX) 接在板载 USB 转串口芯片上，默认 115200 8N1
+    let mut uart = Uart::new(peripherals.UART0, Config::default())
+        .unwrap()
+        .with_tx(peripherals.GPIO43)
+        .with_rx(peripherals.GPIO44);
+
+    let mut board = Board {
+        pins: [
+            (10, io_pin(peripherals.GPIO10)),
+            (11, io_pin(peripherals.GPIO11)),
+            (12, io_pin(peripherals.GPIO12)),
+        ],
+        adc: CalibratedAdc::new(peripherals.ADC1, peripherals.GPIO1, Attenuation::_11dB),
+        delay: Delay::new(),
+    };
+
+    let commands: [Command<Board>; 3] = [builtins::gpio(), builtins::adc(), builtins::reboot()];
+    // 每行最多 64 字节，保留最近 8 条历史
+    let mut shell: Shell<Board> = Shell::new("esp32s3> ", &commands);
+
+    println!("Shell running on UART0 (GPIO43/GPIO44)");
+    loop {
+        // UART 读取永远不会返回 0，所以只有出错时 run 才会返回
+        if let Err(e) = shell.run(&mut board, &mut uart) {
+            println!("UART error: {:?}", e);
+        }
+    }
+}
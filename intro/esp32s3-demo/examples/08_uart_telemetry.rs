@@ -0,0 +1,146 @@
+// 简化嵌入式Rust: ESP核心库版
+// 编程串行通信 - UART 二进制遥测协议（设备端）
+//
+// 08_uart_shell.rs 面向人，这里面向程序：主机上的 `telemetry-host` 库通过 UART0 发请求、收遥测数据。
+// - 每帧：[类型][序号 u16][postcard 编码的消息][CRC-16]，整体 COBS 编码，以 0x00 结尾
+// - 请求：Ping、读 IMU、读电压（GPIO1）、开关 LED（GPIO7）、设置遥测频率
+// - 遥测：按设定频率发送 MPU-6050 读数（默认 10Hz），主机回 ACK；50ms 内没收到 ACK 就重发，最多 4 次
+// - 响应丢失时主机会重发同一序号的请求，设备直接重发缓存的响应，不会把请求执行两次
+//
+// 帧格式、重传和消息定义都在 `telemetry` crate 中，主机端与设备端的往返测试在 `telemetry-host` 中。
+// esp_println 在插着 USB-Serial-JTAG 口时输出到那里，否则输出到 UART0：
+// 混进帧流的日志只会让一帧 CRC 校验失败，随后被重发，但调试时最好把两个 USB 口都插上。
+
+#![no_std]
+#![no_main]
+
+use esp32s3_demo::{
+    adc::CalibratedAdc,
+    clock::{Clock, SystemClock},
+};
+use esp_backtrace as _;
+use esp_hal::{
+    analog::adc::Attenuation,
+    delay::Delay,
+    gpio::{Level, Output, OutputConfig},
+    i2c::master::{self, I2c},
+    main,
+    peripherals::GPIO1,
+    time::Rate,
+    uart::{Config, Uart},
+    Blocking,
+};
+use esp_println::println;
+use mpu6050::{blocking::Mpu6050, Measurement, ADDRESS_AD0_LOW};
+use telemetry::{Device, Failure, ImuSample, Request, Response, RetryPolicy, Telemetry};
+
+esp_bootloader_esp_idf::esp_app_desc!();
+
+// 115200 波特率下一帧 IMU 遥测约 40 字节（约 3.5ms），50Hz 时线路占用不到 20%
+const MAX_RATE_HZ: u8 = 50;
+
+// 请求处理需要的硬件
+struct Board {
+    imu: Mpu6050<I2c<'static, Blocking>>,
+    adc: CalibratedAdc<'static, GPIO1<'static>>,
+    led: Output<'static>,
+    rate_hz: u8,
+}
+
+fn sample(m: &Measurement) -> ImuSample {
+    ImuSample {
+        accel_g: m.accel_g,
+        gyro_dps: m.gyro_dps,
+        temperature_c: m.temperature_c,
+    }
+}
+
+impl Board {
+    fn handle(&mut self, now_ms: u64, request: Request) -> Response {
+        match request {
+            Request::Ping => Response::Pong { uptime_ms: now_ms },
+            Request::ReadImu => match self.imu.read() {
+                Ok(m) => Response::Imu(sample(&m)),
+                Err(_) => Response::Error(Failure::Sensor),
+            },
+            Request::ReadVoltage { pin: 1 } => match self.adc.read_mv() {
+                Ok(mv) => Response::Voltage { mv },
+                Err(_) => Response::Error(Failure::Sensor),
+            },
+            Request::ReadVoltage { .. } => Response::Error(Failure::Unsupported),
+            Request::SetLed { on } => {
+                self.led.set_level(Level::from(on));
+                Response::Ok
+            }
+            Request::SetTelemetryRate { hz } if hz <= MAX_RATE_HZ => {
+                self.rate_hz = hz;
+                Response::Ok
+            }
+            Request::SetTelemetryRate { .. } => Response::Error(Failure::OutOfRange),
+        }
+    }
+}
+
+#[main]
+fn main() -> ! {
+    let peripherals = esp_hal::init(esp_hal::Config::default());
+    let mut delay = Delay::new();
+
+    // DevKitC 上 UART0 (GPIO43 TX / GPIO44 RX) 接在板载 USB 转串口芯片上，默认 115200 8N1
+    let mut uart = Uart::new(peripherals.UART0, Config::default())
+        .unwrap()
+        .with_tx(peripherals.GPIO43)
+        .with_rx(peripherals.GPIO44);
+
+    let i2c_config = master::Config::default().with_frequency(Rate::from_khz(400));
+    let i2c = I2c::new(peripherals.I2C0, i2c_config)
+        .unwrap()
+        .with_sda(peripherals.GPIO4)
+        .with_scl(peripherals.GPIO5);
+    let mut imu = Mpu6050::new(i2c, ADDRESS_AD0_LOW);
+    if let Err(e) = imu
+        .reset(&mut delay)
+        .and_then(|_| imu.init(mpu6050::Config::default()))
+    {
+        println!("Failed to initialize MPU-6050: {:?}", e);
+    }
+
+    let mut board = Board {
+        imu,
+        adc: CalibratedAdc::new(peripherals.ADC1, peripherals.GPIO1, Attenuation::_11dB),
+        led: Output::new(peripherals.GPIO7, Level::Low, OutputConfig::default()),
+        rate_hz: 10,
+    };
+    let mut device = Device::new(RetryPolicy::default());
+    let mut next_sample_ms = 0;
+    let mut buf = [0u8; 32];
+
+    println!("Telemetry protocol running on UART0 (GPIO43/GPIO44)");
+    loop {
+        let now_ms = SystemClock.now_ms();
+
+        // 只取 FIFO 中已有的字节，不阻塞；溢出丢掉的字节由 CRC 和重发兜底
+        let n = uart.read_buffered(&mut buf).unwrap_or(0);
+        for &byte in &buf[..n] {
+            // 完整的请求会在这里处理并立即回复
+            let _ = device.on_byte(byte, &mut uart, |request| board.handle(now_ms, request));
+        }
+
+        // 超时未确认的遥测帧在这里重发
+        let _ = device.poll(now_ms, &mut uart);
+
+        if board.rate_hz > 0 && now_ms >= next_sample_ms {
+            next_sample_ms = now_ms + 1000 / board.rate_hz as u64;
+            if let Ok(m) = board.imu.read() {
+                let telemetry = Telemetry::Imu {
+                    uptime_ms: now_ms,
+                    sample: sample(&m),
+                };
+                // 上一帧还在等 ACK 时这个样本会被跳过
+                let _ = device.send_telemetry(&telemetry, now_ms, &mut uart);
+            }
+        }
+
+        delay.delay_millis(1);
+    }
+}
@@ -0,0 +1,84 @@
+// Embassy 渐变：在 async 任务中播放渐变序列，`run_until_idle().await` 在序列结束时返回
+
+#![no_std]
+#![no_main]
+
+use embassy_executor::Spawner;
+use embassy_time::{Duration, Timer};
+use esp_backtrace as _;
+use esp32s3_demo::{
+    clock::SystemClock,
+    fade::{Easing, FadeController, FadeStep, DEFAULT_GAMMA},
+};
+use esp_hal::{
+    ledc::{
+        self,
+        channel::{self, ChannelIFace},
+        timer::TimerIFace,
+        Ledc, LowSpeed,
+    },
+    time::Rate,
+    timer::timg::TimerGroup,
+};
+use esp_println::println;
+
+esp_bootloader_esp_idf::esp_app_desc!();
+
+#[esp_hal_embassy::main]
+async fn main(_spawner: Spawner) {
+    // 获取外设 & 配置系统时钟
+    let peripherals = esp_hal::init(esp_hal::Config::default());
+
+    let timg0 = TimerGroup::new(peripherals.TIMG0);
+    esp_hal_embassy::init(timg0.timer0);
+
+    let mut ledc = Ledc::new(peripherals.LEDC);
+    ledc.set_global_slow_clock(ledc::LSGlobalClkSource::APBClk);
+
+    let mut timer = ledc.timer::<LowSpeed>(ledc::timer::Number::Timer0);
+    timer
+        .configure(ledc::timer::config::Config {
+            duty: ledc::timer::config::Duty::Duty14Bit,
+            clock_source: ledc::timer::LSClockSource::APBClk,
+            frequency: Rate::from_khz(1),
+        })
+        .unwrap();
+
+    let channel_config = channel::config::Config {
+        timer: &timer,
+        duty_pct: 0,
+        pin_config: channel::config::PinConfig::PushPull,
+    };
+    let mut channels = [
+        ledc.channel(channel::Number::Channel0, peripherals.GPIO7),
+        ledc.channel(channel::Number::Channel1, peripherals.GPIO8),
+    ];
+    for channel in channels.iter_mut() {
+        channel.configure(channel_config).unwrap();
+    }
+
+    let mut fades = FadeController::<_, 2, 4>::new(channels, DEFAULT_GAMMA);
+
+    loop {
+        // 两个通道同时渐亮，曲线不同
+        fades.fader(0).enqueue_all(&[
+            FadeStep::new(100, 1500).with_easing(Easing::EaseInOut),
+            FadeStep::hold(100, 500),
+        ]);
+        fades.fader(1).enqueue_all(&[
+            FadeStep::hold(0, 500),
+            FadeStep::new(100, 1500).with_easing(Easing::Exponential),
+        ]);
+        fades.run_until_idle(&SystemClock, 10).await.unwrap();
+        println!("both channels on");
+
+        // 一起熄灭
+        for channel in 0..2 {
+            let _ = fades.enqueue(channel, FadeStep::new(0, 2000).with_easing(Easing::EaseOut));
+        }
+        fades.run_until_idle(&SystemClock, 10).await.unwrap();
+        println!("both channels off");
+
+        Timer::after(Duration::from_millis(1000)).await;
+    }
+}
@@ -0,0 +1,110 @@
+// 简化嵌入式Rust: ESP核心库版
+// Embassy - 共享 I2C 总线：IMU 任务和 RTC 任务同时使用 I2C0
+//
+// - 总线放在异步 Mutex 中（`esp32s3_demo::shared_bus::AsyncBus`），两个任务各拿一个 `&'static` 引用
+// - IMU 任务：`embassy_embedded_hal` 的 `I2cDevice` 句柄实现了异步 `I2c`，直接交给 `mpu6050::asynch::Mpu6050`，
+//   以 50Hz 读取，每次传输时锁住总线，传输期间 CPU 可以运行其它任务
+// - RTC 任务：ds-rtc 只有阻塞接口，用 `with_blocking` 锁住总线后在 `&mut I2c` 上运行 `Ds3231`，每秒读一次
+// - 地址冲突：DS3231 固定为 0x68，所以 MPU-6050 的 AD0 要接 3.3V（0x69）
+
+#![no_std]
+#![no_main]
+
+use ds_rtc::{ds3231::Ds3231, DateTime};
+use embassy_embedded_hal::shared_bus::asynch::i2c::I2cDevice;
+use embassy_executor::Spawner;
+use embassy_time::{Delay, Duration, Ticker};
+use esp32s3_demo::shared_bus::{async_bus, with_blocking, AsyncBus};
+use esp_backtrace as _;
+use esp_hal::{
+    i2c::master::{Config, I2c},
+    time::Rate,
+    timer::timg::TimerGroup,
+    Async,
+};
+use esp_println::println;
+use mpu6050::{asynch::Mpu6050, ADDRESS_AD0_HIGH};
+use static_cell::StaticCell;
+
+esp_bootloader_esp_idf::esp_app_desc!();
+
+type Bus = AsyncBus<I2c<'static, Async>>;
+
+static BUS: StaticCell<Bus> = StaticCell::new();
+
+#[embassy_executor::task]
+async fn imu_task(bus: &'static Bus) {
+    let mut imu = Mpu6050::new(I2cDevice::new(bus), ADDRESS_AD0_HIGH);
+    if let Err(e) = imu.reset(&mut Delay).await {
+        println!("Failed to reset MPU-6050: {:?}", e);
+        return;
+    }
+    imu.init(mpu6050::Config::default()).await.unwrap();
+
+    let mut ticker = Ticker::every(Duration::from_millis(20));
+    let mut count = 0u32;
+    loop {
+        ticker.next().await;
+        match imu.read().await {
+            Ok(m) => {
+                count += 1;
+                // 50 个样本打印一次，约每秒一次
+                if count % 50 == 0 {
+                    println!(
+                        "[imu] #{} Accel: X={:6.3} Y={:6.3} Z={:6.3} g",
+                        count, m.accel_g[0], m.accel_g[1], m.accel_g[2]
+                    );
+                }
+            }
+            Err(e) => println!("[imu] I2C error: {:?}", e),
+        }
+    }
+}
+
+#[embassy_executor::task]
+async fn rtc_task(bus: &'static Bus) {
+    // 驱动在闭包里临时创建，借用锁住的总线；闭包返回后锁就释放了
+    let lost_power = with_blocking(bus, |i2c| Ds3231::new(i2c).lost_power()).await;
+    if lost_power.unwrap_or(false) {
+        println!("[rtc] Oscillator stopped, setting the time...");
+        let start = DateTime::new(2025, 1, 1, 0, 0, 0).unwrap();
+        with_blocking(bus, |i2c| Ds3231::new(i2c).set_datetime(&start))
+            .await
+            .unwrap();
+    }
+
+    let mut ticker = Ticker::every(Duration::from_secs(1));
+    loop {
+        ticker.next().await;
+        let reading = with_blocking(bus, |i2c| -> Result<_, ds_rtc::Error<_>> {
+            let mut rtc = Ds3231::new(i2c);
+            Ok((rtc.datetime()?, rtc.temperature()?))
+        })
+        .await;
+        match reading {
+            Ok((dt, temperature)) => println!(
+                "[rtc] {}-{:02}-{:02} {:02}:{:02}:{:02} | {:.2} °C",
+                dt.year, dt.month, dt.day, dt.hour, dt.minute, dt.second, temperature
+            ),
+            Err(e) => println!("[rtc] error: {:?}", e),
+        }
+    }
+}
+
+#[esp_hal_embassy::main]
+async fn main(spawner: Spawner) {
+    let peripherals = esp_hal::init(esp_hal::Config::default());
+    let timg0 = TimerGroup::new(peripherals.TIMG0);
+    esp_hal_embassy::init(timg0.timer0);
+
+    let i2c_config = Config::default().with_frequency(Rate::from_khz(400));
+    let i2c = I2c::new(peripherals.I2C0, i2c_config)
+        .unwrap()
+        .with_sda(peripherals.GPIO4)
+        .with_scl(peripherals.GPIO5)
+        .into_async();
+    let bus = async_bus(&BUS, i2c);
+
+    spawner.spawn(imu_task(bus)).unwrap();
+    spawner.spawn(rtc_task(bus)).unwrap();
+}
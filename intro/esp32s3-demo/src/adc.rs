@@ -0,0 +1,77 @@
+// 经过校准的 ADC 电压读数
+//
+// 05_adc_dianyaqi.rs 中用 `sample * 3300 / 4095` 换算电压，这个公式有两个问题：
+// 1. 每一档衰减的量程都不一样，而且都不是 3.3V（11dB 档大约只能测到 3.1V）
+// 2. 每颗芯片的 ADC 都有偏差，出厂时会把校准数据烧写在 eFuse 中，这个公式完全没有用到
+//
+// `CalibratedAdc` 使用 esp-hal 的曲线校准方案 `AdcCalCurve`（读取 eFuse 校准数据，
+// 先做线性拟合再用多项式修正非线性误差），直接返回毫伏值，并检查读数是否在当前衰减档的可用范围内。
+//
+// 范围检查和无校准时的名义换算是纯函数，放在 `adc-range` crate 中并在主机上测试：
+// 读数高于量程上限或低于下限（接近 0V 时误差与读数本身一样大）都会返回错误。
+
+pub use adc_range::{AdcError, UsableRange, ADC_MAX_RAW};
+use esp_hal::{
+    analog::adc::{Adc, AdcCalCurve, AdcChannel, AdcConfig, AdcPin, Attenuation},
+    gpio::AnalogPin,
+    peripherals::ADC1,
+    Blocking,
+};
+
+// esp-hal 的衰减档 -> `adc-range` 中对应的衰减档
+pub fn range_attenuation(attenuation: Attenuation) -> adc_range::Attenuation {
+    match attenuation {
+        Attenuation::_0dB => adc_range::Attenuation::_0dB,
+        Attenuation::_2p5dB => adc_range::Attenuation::_2p5dB,
+        Attenuation::_6dB => adc_range::Attenuation::_6dB,
+        Attenuation::_11dB => adc_range::Attenuation::_11dB,
+    }
+}
+
+// 某一档衰减下的可用输入电压范围（毫伏）
+pub fn usable_range(attenuation: Attenuation) -> UsableRange {
+    UsableRange::for_attenuation(range_attenuation(attenuation))
+}
+
+// 没有校准数据时的名义换算：按该衰减档的可用上限线性换算
+// 只用于对比或没有 eFuse 校准数据的芯片，误差可能达到几十毫伏
+pub fn nominal_mv(raw: u16, attenuation: Attenuation) -> u16 {
+    adc_range::nominal_mv(raw, range_attenuation(attenuation))
+}
+
+// 使用 eFuse 曲线校准的 ADC1 单通道读数器
+pub struct CalibratedAdc<'d, PIN> {
+    adc: Adc<'d, ADC1<'d>, Blocking>,
+    pin: AdcPin<PIN, ADC1<'d>, AdcCalCurve<ADC1<'d>>>,
+    range: UsableRange,
+}
+
+impl<'d, PIN> CalibratedAdc<'d, PIN>
+where
+    PIN: AdcChannel + AnalogPin,
+{
+    pub fn new(adc1: ADC1<'d>, pin: PIN, attenuation: Attenuation) -> Self {
+        let mut config = AdcConfig::new();
+        let pin = config.enable_pin_with_cal::<_, AdcCalCurve<ADC1<'d>>>(pin, attenuation);
+        Self {
+            adc: Adc::new(adc1, config),
+            pin,
+            range: usable_range(attenuation),
+        }
+    }
+
+    pub fn range(&self) -> UsableRange {
+        self.range
+    }
+
+    // 读取一次，返回校准后的毫伏值（不检查范围）
+    pub fn read_mv_unchecked(&mut self) -> u16 {
+        nb::block!(self.adc.read_oneshot(&mut self.pin)).unwrap()
+    }
+
+    // 读取一次，返回校准后的毫伏值，超出可用范围时返回错误
+    pub fn read_mv(&mut self) -> Result<u16, AdcError> {
+        let mv = self.read_mv_unchecked();
+        self.range.check(mv)
+    }
+}
@@ -0,0 +1,391 @@
+// ADC 连续采样（DMA）
+//
+// 之前的 ADC 示例都用 `read_oneshot` 轮询：每读一次都要等一次转换完成，采样间隔不稳定，
+// 也达不到音频、振动信号需要的几 kHz ~ 几十 kHz 采样率。
+//
+// ESP32-S3 的 ADC 数字控制器（APB_SARADC）可以由内部定时器触发，按“扫描表”轮流转换多个通道，
+// 转换结果由 GDMA 直接写进内存：
+// - 缓冲区分成两半，两个 DMA 描述符首尾相连，DMA 不停地轮流写这两半（环形缓冲区）
+// - 每写满半个缓冲区产生一次中断，主循环用回调（`poll_half`）或 async（`next_half`）处理这一半，
+//   同时 DMA 继续写另一半
+// - 主循环只读 DMA 已经写完的那一半：先用 volatile 读复制出来，复制完再确认 DMA 还没有回到这一半，
+//   回调拿到的是这份完整的副本，而不是 DMA 可能正在写的内存
+// - 每个样本都带有单元号和通道号，扫描多个引脚时可以区分
+// - 主循环处理太慢、DMA 已经覆盖了还没处理完的数据时，记为一次溢出（overrun），这一半被丢弃
+//
+// 结果格式解析（`ResultFormat`）和半缓冲区的同步逻辑（`HalfBuffers`）与硬件无关，放在 `adc-dma` crate 中，
+// 在主机上用模拟的 DMA 写入顺序测试。
+// ESP32-C3 的 ADC 同样支持 DMA，GDMA 部分相同，但 APB_SARADC 寄存器和结果格式不同，
+// 这里只提供 C3 的结果格式解析（`ResultFormat::Esp32C3`），寄存器配置只实现了 S3。
+
+use core::{
+    future::poll_fn,
+    ptr::addr_of,
+    sync::atomic::{compiler_fence, AtomicU32, Ordering},
+    task::Poll,
+};
+
+use embassy_sync::waitqueue::AtomicWaker;
+use esp_hal::{
+    analog::adc::{Adc, AdcChannel, AdcConfig, Attenuation},
+    dma::{ChannelRx, DmaChannel, DmaDescriptor, Owner},
+    gpio::AnalogPin,
+    handler,
+    interrupt::{self, Priority},
+    peripherals::{Interrupt, ADC1, APB_SARADC, DMA, DMA_CH0, SENS},
+    Blocking,
+};
+use heapless::Vec;
+
+pub use adc_dma::{HalfBuffers, ReadyHalf, ResultFormat, Sample, Samples};
+
+// DMA 中每个转换结果占 4 字节
+pub const BYTES_PER_SAMPLE: usize = 4;
+
+// 扫描表最多 16 项（S3 的 sar1_patt_tab1..4，每个寄存器 4 项）
+pub const MAX_PATTERN_LEN: usize = 16;
+
+// 一个 DMA 描述符最多 4095 字节，半个缓冲区最多 1023 个样本
+pub const MAX_HALF_SAMPLES: usize = 4095 / BYTES_PER_SAMPLE;
+
+// S3 数字控制器支持的采样率范围
+pub const MIN_SAMPLE_RATE_HZ: u32 = 611;
+pub const MAX_SAMPLE_RATE_HZ: u32 = 83_333;
+
+// ADC 数字控制器使用的 GDMA 外设编号
+const DMA_PERIPHERAL_ADC: u8 = 8;
+
+// 数字控制器时钟：APB 80MHz / (15 + 1) = 5MHz
+const APB_CLK_HZ: u32 = 80_000_000;
+const CLKM_DIV_NUM: u8 = 15;
+
+// DMA 缓冲区：两个描述符 + 两半数据 + 主循环处理用的副本，必须放在内部 RAM 中（例如用 `StaticCell`）
+#[repr(C, align(4))]
+pub struct DmaBuffer<const HALF: usize> {
+    descriptors: [DmaDescriptor; 2],
+    data: [[u32; HALF]; 2],
+    copy: [u32; HALF],
+}
+
+impl<const HALF: usize> DmaBuffer<HALF> {
+    pub const fn new() -> Self {
+        assert!(
+            HALF > 0 && HALF <= MAX_HALF_SAMPLES,
+            "half buffer must hold 1..=1023 samples"
+        );
+        Self {
+            descriptors: [DmaDescriptor::EMPTY; 2],
+            data: [[0; HALF]; 2],
+            copy: [0; HALF],
+        }
+    }
+
+    // 两个描述符首尾相连，DMA 写满一个自动切换到另一个
+    fn link(&mut self) {
+        let first: *mut DmaDescriptor = &mut self.descriptors[0];
+        let second: *mut DmaDescriptor = &mut self.descriptors[1];
+        for (i, descriptor) in self.descriptors.iter_mut().enumerate() {
+            descriptor.set_size(HALF * BYTES_PER_SAMPLE);
+            descriptor.set_length(0);
+            descriptor.set_suc_eof(false);
+            descriptor.set_owner(Owner::Dma);
+            descriptor.buffer = self.data[i].as_mut_ptr().cast();
+            descriptor.next = if i == 0 { second } else { first };
+        }
+    }
+
+    fn descriptor_addr(&self, index: usize) -> u32 {
+        &self.descriptors[index] as *const DmaDescriptor as u32
+    }
+}
+
+impl<const HALF: usize> Default for DmaBuffer<HALF> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 配置错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigError {
+    // 扫描表为空
+    NoChannels,
+    // 扫描表已满
+    TooManyChannels,
+    // 采样率超出 611Hz ~ 83333Hz
+    SampleRate,
+}
+
+// 扫描配置：要采样的引脚（按顺序轮流转换）和总采样率
+pub struct ScanConfig<'d> {
+    adc: AdcConfig<ADC1<'d>>,
+    pattern: Vec<(u8, Attenuation), MAX_PATTERN_LEN>,
+    sample_rate_hz: u32,
+}
+
+impl<'d> ScanConfig<'d> {
+    // `sample_rate_hz`：所有通道合计的采样率，每个通道的采样率 = sample_rate_hz / 通道数
+    pub fn new(sample_rate_hz: u32) -> Self {
+        Self {
+            adc: AdcConfig::new(),
+            pattern: Vec::new(),
+            sample_rate_hz,
+        }
+    }
+
+    // 加入一个 ADC1 引脚，返回它的通道号（用于区分样本）
+    pub fn add_pin<PIN>(&mut self, pin: PIN, attenuation: Attenuation) -> Result<u8, ConfigError>
+    where
+        PIN: AdcChannel + AnalogPin,
+    {
+        let channel = pin.adc_channel();
+        if self.pattern.is_full() {
+            return Err(ConfigError::TooManyChannels);
+        }
+        let _ = self.pattern.push((channel, attenuation));
+        // 把引脚切换到模拟功能
+        let _ = self.adc.enable_pin(pin, attenuation);
+        Ok(channel)
+    }
+}
+
+// 定时器触发间隔：5MHz 时钟，每两个时钟周期计数一次
+pub const fn timer_target(sample_rate_hz: u32) -> u32 {
+    APB_CLK_HZ / (CLKM_DIV_NUM as u32 + 1) / 2 / sample_rate_hz
+}
+
+static HALVES: HalfBuffers = HalfBuffers::new();
+static WAKER: AtomicWaker = AtomicWaker::new();
+
+// 描述符 1 的地址，用于在中断中判断写满的是哪一半
+static SECOND_DESCRIPTOR: AtomicU32 = AtomicU32::new(0);
+
+#[handler(priority = Priority::Priority2)]
+fn dma_in_ch0() {
+    let ch = DMA::regs().ch(0);
+    let status = ch.in_int().st().read();
+    if status.in_suc_eof().bit_is_set() {
+        let addr = ch.in_suc_eof_des_addr().read().bits();
+        let index = if addr == SECOND_DESCRIPTOR.load(Ordering::Relaxed) {
+            1
+        } else {
+            0
+        };
+        HALVES.on_complete(index);
+        WAKER.wake();
+    }
+    ch.in_int()
+        .clr()
+        .write(|w| w.in_suc_eof().clear_bit_by_one());
+}
+
+// ADC1 + DMA_CH0 连续采样
+// 中断处理函数和同步状态是全局唯一的，同一时刻只能有一个 `AdcDma`
+pub struct AdcDma<'d, const HALF: usize> {
+    _adc: Adc<'d, ADC1<'d>, Blocking>,
+    _dma: ChannelRx<Blocking, <DMA_CH0<'d> as DmaChannel>::Rx>,
+    buffer: &'d mut DmaBuffer<HALF>,
+    pattern: Vec<(u8, Attenuation), MAX_PATTERN_LEN>,
+}
+
+impl<'d, const HALF: usize> AdcDma<'d, HALF> {
+    pub fn new(
+        adc1: ADC1<'d>,
+        dma: DMA_CH0<'d>,
+        config: ScanConfig<'d>,
+        buffer: &'d mut DmaBuffer<HALF>,
+    ) -> Result<Self, ConfigError> {
+        if config.pattern.is_empty() {
+            return Err(ConfigError::NoChannels);
+        }
+        if !(MIN_SAMPLE_RATE_HZ..=MAX_SAMPLE_RATE_HZ).contains(&config.sample_rate_hz) {
+            return Err(ConfigError::SampleRate);
+        }
+
+        // esp-hal 的 Adc 负责打开 APB_SARADC 时钟、给 ADC 上电、设置各引脚的衰减
+        let adc = Adc::new(adc1, config.adc);
+        // 同理，ChannelRx 负责打开 GDMA 时钟
+        let (rx, _tx) = dma.split();
+        let rx = ChannelRx::new(rx);
+
+        let mut this = Self {
+            _adc: adc,
+            _dma: rx,
+            buffer,
+            pattern: config.pattern,
+        };
+        this.configure_controller(config.sample_rate_hz);
+
+        unsafe { interrupt::bind_interrupt(Interrupt::DMA_IN_CH0, dma_in_ch0.handler()) };
+        interrupt::enable(Interrupt::DMA_IN_CH0, dma_in_ch0.priority()).unwrap();
+
+        Ok(this)
+    }
+
+    // 配置数字控制器：时钟、扫描表、定时器、DMA
+    fn configure_controller(&mut self, sample_rate_hz: u32) {
+        let saradc = APB_SARADC::regs();
+
+        // 由数字控制器（而不是 RTC 控制器）控制 ADC1
+        SENS::regs()
+            .sar_meas1_mux()
+            .modify(|_, w| w.sar1_dig_force().set_bit());
+
+        saradc.clkm_conf().modify(|_, w| unsafe {
+            w.clk_sel().bits(2); // APB 时钟
+            w.clkm_div_num().bits(CLKM_DIV_NUM);
+            w.clkm_div_b().bits(0);
+            w.clkm_div_a().bits(0);
+            w.clk_en().set_bit()
+        });
+
+        saradc.ctrl().modify(|_, w| unsafe {
+            w.sar_clk_gated().set_bit();
+            w.sar_clk_div().bits(1);
+            // 单 ADC1 模式
+            w.work_mode().bits(0);
+            w.sar_sel().clear_bit();
+            w.sar1_patt_len().bits(self.pattern.len() as u8 - 1);
+            w.sar_patt_p_clear().set_bit()
+        });
+        saradc
+            .ctrl()
+            .modify(|_, w| w.sar_patt_p_clear().clear_bit());
+
+        // 扫描表：每项 6 位（通道号 << 2 | 衰减），每个寄存器 4 项，第一项在最高位
+        let mut tables = [0xFF_FFFFu32; 4];
+        for (i, &(channel, attenuation)) in self.pattern.iter().enumerate() {
+            let item = ((channel as u32 & 0xF) << 2) | (attenuation as u32 & 0x3);
+            let shift = (3 - i % 4) * 6;
+            tables[i / 4] = (tables[i / 4] & !(0x3F << shift)) | (item << shift);
+        }
+        saradc
+            .sar1_patt_tab1()
+            .write(|w| unsafe { w.bits(tables[0]) });
+        saradc
+            .sar1_patt_tab2()
+            .write(|w| unsafe { w.bits(tables[1]) });
+        saradc
+            .sar1_patt_tab3()
+            .write(|w| unsafe { w.bits(tables[2]) });
+        saradc
+            .sar1_patt_tab4()
+            .write(|w| unsafe { w.bits(tables[3]) });
+
+        saradc.ctrl2().modify(|_, w| unsafe {
+            w.meas_num_limit().clear_bit();
+            w.sar1_inv().clear_bit();
+            w.timer_sel().set_bit();
+            w.timer_target().bits(timer_target(sample_rate_hz) as u16)
+        });
+
+        // 每 HALF 个样本产生一次 EOF，DMA 随即切换到下一个描述符
+        saradc
+            .dma_conf()
+            .modify(|_, w| unsafe { w.apb_adc_eof_num().bits(HALF as u16) });
+    }
+
+    // 开始连续采样
+    pub fn start(&mut self) {
+        let saradc = APB_SARADC::regs();
+        let ch = DMA::regs().ch(0);
+
+        self.buffer.link();
+        SECOND_DESCRIPTOR.store(self.buffer.descriptor_addr(1), Ordering::Relaxed);
+        HALVES.reset();
+
+        // 复位 ADC 的 DMA 状态机和 GDMA 通道
+        saradc
+            .dma_conf()
+            .modify(|_, w| w.apb_adc_reset_fsm().set_bit());
+        saradc
+            .dma_conf()
+            .modify(|_, w| w.apb_adc_reset_fsm().clear_bit());
+        ch.in_conf0().modify(|_, w| w.in_rst().set_bit());
+        ch.in_conf0().modify(|_, w| w.in_rst().clear_bit());
+
+        // 环形链表会反复使用同一组描述符，不检查 owner 位
+        ch.in_conf1().modify(|_, w| w.in_check_owner().clear_bit());
+        ch.in_peri_sel()
+            .modify(|_, w| unsafe { w.peri_in_sel().bits(DMA_PERIPHERAL_ADC) });
+        ch.in_int()
+            .clr()
+            .write(|w| w.in_suc_eof().clear_bit_by_one());
+        ch.in_int().ena().modify(|_, w| w.in_suc_eof().set_bit());
+        ch.in_link()
+            .modify(|_, w| unsafe { w.inlink_addr().bits(self.buffer.descriptor_addr(0)) });
+        compiler_fence(Ordering::SeqCst);
+        ch.in_link().modify(|_, w| w.inlink_start().set_bit());
+
+        // 打开 DMA 输出和触发定时器
+        saradc.dma_conf().modify(|_, w| w.apb_adc_trans().set_bit());
+        saradc.ctrl2().modify(|_, w| w.timer_en().set_bit());
+    }
+
+    // 停止采样
+    pub fn stop(&mut self) {
+        APB_SARADC::regs()
+            .ctrl2()
+            .modify(|_, w| w.timer_en().clear_bit());
+        APB_SARADC::regs()
+            .dma_conf()
+            .modify(|_, w| w.apb_adc_trans().clear_bit());
+        let ch = DMA::regs().ch(0);
+        ch.in_int().ena().modify(|_, w| w.in_suc_eof().clear_bit());
+        ch.in_link().modify(|_, w| w.inlink_stop().set_bit());
+    }
+
+    // 扫描表中的通道号，按转换顺序排列
+    pub fn channels(&self) -> impl Iterator<Item = u8> + '_ {
+        self.pattern.iter().map(|&(channel, _)| channel)
+    }
+
+    // 用 volatile 读把 DMA 已经写完的一半复制出来；复制期间 DMA 回到了这一半（副本不完整）时返回 None
+    fn copy_half(&mut self, half: ReadyHalf) -> Option<Samples<'_>> {
+        let buffer = &mut *self.buffer;
+        // 不创建指向 DMA 内存的引用，只取这一半的起始地址
+        let src = addr_of!(buffer.data[half.index]).cast::<u32>();
+        let intact = unsafe { HALVES.copy_half(half, src, &mut buffer.copy) };
+        intact.then(|| Samples::new(&buffer.copy, ResultFormat::Esp32S3))
+    }
+
+    // 回调方式：如果有完整的半区，用它的副本调用 `f` 并返回结果，否则立即返回 None
+    pub fn poll_half<R>(&mut self, f: impl FnOnce(Samples<'_>) -> R) -> Option<R> {
+        let half = HALVES.acquire()?;
+        self.copy_half(half).map(f)
+    }
+
+    // async 方式：等待下一个完整的半区
+    pub async fn next_half<R>(&mut self, f: impl FnOnce(Samples<'_>) -> R) -> R {
+        loop {
+            let half = poll_fn(|cx| {
+                WAKER.register(cx.waker());
+                match HALVES.acquire() {
+                    Some(half) => Poll::Ready(half),
+                    None => Poll::Pending,
+                }
+            })
+            .await;
+            // 副本不完整时已经计入溢出，继续等下一半
+            if let Some(samples) = self.copy_half(half) {
+                return f(samples);
+            }
+        }
+    }
+
+    // 自启动以来因处理不及时而丢失的半区数
+    pub fn overrun_count(&self) -> u32 {
+        HALVES.overrun_count()
+    }
+}
+
+impl<const HALF: usize> Drop for AdcDma<'_, HALF> {
+    fn drop(&mut self) {
+        self.stop();
+        interrupt::disable(esp_hal::system::Cpu::ProCpu, Interrupt::DMA_IN_CH0);
+        // 把 ADC1 交还给 RTC 控制器，之后仍然可以使用 read_oneshot
+        SENS::regs()
+            .sar_meas1_mux()
+            .modify(|_, w| w.sar1_dig_force().clear_bit());
+    }
+}
@@ -0,0 +1,65 @@
+// 带消抖的按键驱动：按下、松开、单击、双击、长按、连发
+//
+// 04_blinky_interrupte.rs 在主循环里用 `delay.delay_millis(50)` 消抖，会阻塞主循环。
+// 这里把按键逻辑拆成两层：
+// - `Gestures`：纯状态机，只接收“原始电平 + 时间戳”，输出按键事件，
+//   放在 `button-gestures` crate 中，用脚本化的边沿时间线在主机上测试
+// - `Button`：基于 `embedded_hal::digital::InputPin` 和 `Clock` 的阻塞轮询 API
+//
+// 在中断中使用时，ISR 只把带时间戳的边沿放进 `event_queue::EventQueue`，主循环按顺序把它们交给 `on_edge()`，
+// 再调用 `poll()` 取出事件（见 04_blinky_interrupte.rs）。状态机会先推进到每个边沿的时间戳，
+// 所以两次 `poll()` 之间发生的按下和松开也不会丢失。
+
+pub use button_gestures::{ButtonConfig, ButtonEvent, Gestures};
+use embedded_hal::digital::InputPin;
+
+use crate::clock::Clock;
+
+// 轮询方式的按键驱动
+pub struct Button<P, C> {
+    pin: P,
+    clock: C,
+    gestures: Gestures,
+}
+
+impl<P, C> Button<P, C>
+where
+    P: InputPin,
+    C: Clock,
+{
+    pub fn new(mut pin: P, clock: C, config: ButtonConfig) -> Self {
+        // 以创建时的电平作为初始状态：上电时已经按住的按键不产生任何事件（包括长按），
+        // 松开后再按才开始识别
+        let pressed = pin.is_high().is_ok_and(|high| high != config.active_low);
+        let gestures = Gestures::with_initial_state(config, pressed, clock.now_ms());
+        Self {
+            pin,
+            clock,
+            gestures,
+        }
+    }
+
+    // 读取一次引脚并返回可能产生的事件，需要在主循环里频繁调用（建议间隔 < 10ms）
+    pub fn poll(&mut self) -> Result<Option<ButtonEvent>, P::Error> {
+        let high = self.pin.is_high()?;
+        let pressed = self.gestures.is_pressed_level(high);
+        Ok(self.gestures.sample(pressed, self.clock.now_ms()))
+    }
+
+    // 阻塞直到产生下一个事件
+    pub fn wait(&mut self) -> Result<ButtonEvent, P::Error> {
+        loop {
+            if let Some(event) = self.poll()? {
+                return Ok(event);
+            }
+        }
+    }
+
+    pub fn is_pressed(&self) -> bool {
+        self.gestures.is_pressed()
+    }
+
+    pub fn release(self) -> P {
+        self.pin
+    }
+}
@@ -0,0 +1,102 @@
+// 无源蜂鸣器：音调与旋律播放（RTTTL 铃声）
+//
+// 无源蜂鸣器（压电片）需要外部提供方波，方波的频率就是音调，占空比 50% 时音量最大。
+// 用 LEDC 驱动时，每个音符都要修改 LEDC 定时器的频率，占空比保持不变。
+//
+// - `Melody`：播放进度的纯逻辑，按时间戳决定什么时候换音符、什么时候静音，不阻塞，不依赖硬件
+// - `Buzzer`：把 `Melody` 的输出写到 LEDC 上，主循环定期调用 `play()` 即可
+//
+// 旋律用 RTTTL 文本描述。解析器、音符频率表和 `Melody` 都在 `rtttl` crate 中（no_std），
+// 音符间隔、跳过错过的音符和结束时静音都在主机上测试。
+//
+// LEDC 的 `Channel` 在 `configure` 时借用了定时器，之后就不能再通过它修改频率了。
+// 因此 `Buzzer` 需要同一个定时器编号的第二个句柄（再调用一次 `ledc.timer(number)`），
+// 只用来修改频率；两个句柄的占空比分辨率必须相同。
+
+use esp_hal::{
+    ledc::{
+        channel::{self, Channel, ChannelIFace},
+        timer::{self, config::Duty, LSClockSource, TimerIFace},
+        LowSpeed,
+    },
+    time::Rate,
+};
+pub use rtttl::{Action, Melody, ParseError, Rtttl, Tone, DEFAULT_GAP_MS};
+
+// 蜂鸣器出错的原因
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BuzzerError {
+    // 频率超出 LEDC 定时器在当前分辨率下的范围
+    Timer(timer::Error),
+    Channel(channel::Error),
+    // 旋律中有无法解析的音符
+    Melody(ParseError),
+}
+
+// LEDC 驱动的无源蜂鸣器
+pub struct Buzzer<'d> {
+    // 与 `channel` 使用同一个定时器编号，只用来修改频率
+    timer: timer::Timer<'d, LowSpeed>,
+    channel: Channel<'d, LowSpeed>,
+    duty: Duty,
+    // 发声时的占空比（%），50% 音量最大
+    volume_pct: u8,
+}
+
+impl<'d> Buzzer<'d> {
+    // `channel` 已经用 `duty` 分辨率的定时器配置好，`timer` 是同一个定时器编号的另一个句柄
+    // 10-bit 分辨率下可用的频率范围约为 77Hz ~ 78kHz，足够覆盖 RTTTL 的 4~8 八度
+    pub fn new(
+        timer: timer::Timer<'d, LowSpeed>,
+        channel: Channel<'d, LowSpeed>,
+        duty: Duty,
+    ) -> Self {
+        Self {
+            timer,
+            channel,
+            duty,
+            volume_pct: 50,
+        }
+    }
+
+    // 音量（0-50%），从下一个音符开始生效
+    pub fn set_volume(&mut self, pct: u8) {
+        self.volume_pct = pct.min(50);
+    }
+
+    // 以 `frequency_hz` 发声，直到下一次调用 `tone`/`silence`
+    pub fn tone(&mut self, frequency_hz: u32) -> Result<(), BuzzerError> {
+        self.timer
+            .configure(timer::config::Config {
+                duty: self.duty,
+                clock_source: LSClockSource::APBClk,
+                frequency: Rate::from_hz(frequency_hz),
+            })
+            .map_err(BuzzerError::Timer)?;
+        self.channel
+            .set_duty(self.volume_pct)
+            .map_err(BuzzerError::Channel)
+    }
+
+    pub fn silence(&mut self) -> Result<(), BuzzerError> {
+        self.channel.set_duty(0).map_err(BuzzerError::Channel)
+    }
+
+    // 在主循环中定期调用（间隔 5~10ms 即可），返回旋律是否还在播放
+    pub fn play(&mut self, melody: &mut Melody<'_>, now_ms: u64) -> Result<bool, BuzzerError> {
+        match melody.poll(now_ms) {
+            Ok(Some(Action::Tone(frequency_hz))) => self.tone(frequency_hz)?,
+            Ok(Some(Action::Silence)) => self.silence()?,
+            Ok(None) => {}
+            Err(e) => {
+                self.silence()?;
+                return Err(BuzzerError::Melody(e));
+            }
+        }
+        Ok(!melody.is_finished())
+    }
+
+    pub fn release(self) -> (timer::Timer<'d, LowSpeed>, Channel<'d, LowSpeed>) {
+        (self.timer, self.channel)
+    }
+}
@@ -0,0 +1,28 @@
+// 时间源抽象
+//
+// 驱动和算法只通过 `Clock` 获取毫秒时间戳，而不是直接调用 `Instant::now()`，
+// 这样在主机上测试时可以换成一个手动推进的假时钟。
+
+use esp_hal::time::Instant;
+
+// 单调递增的毫秒时间源
+pub trait Clock {
+    fn now_ms(&self) -> u64;
+}
+
+// 基于 esp-hal `Instant` 的系统时钟（从启动开始计时）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_ms(&self) -> u64 {
+        Instant::now().duration_since_epoch().as_millis()
+    }
+}
+
+// 闭包也可以作为时间源，方便测试时注入假时间
+impl<F: Fn() -> u64> Clock for F {
+    fn now_ms(&self) -> u64 {
+        self()
+    }
+}
@@ -0,0 +1,13 @@
+// LED 灯效引擎：彩虹、呼吸、跑马（剧场追逐）、彗星、火焰
+//
+// 每个灯效都是一个“帧生成器”：给定节拍数 `tick`，把当前帧写入调用者提供的 `[RGB8; N]`。
+// 同一个 tick 总是生成同一帧（火焰效果使用固定种子的伪随机数）。
+// 输出前可以用 `Limits` 统一做伽马校正和全局亮度限制，防止灯带电流过大。
+//
+// 灯效本身与硬件无关，放在 `led-effects` crate 中，在主机上做了逐帧快照测试；
+// `smart_leds::RGB8` 与 `rgb::RGB8` 是同一个类型，生成的帧可以直接交给 `Ws2812::write`。
+
+pub use led_effects::{
+    gamma, heat_color, hsv, scale, Breathe, Comet, Effect, Fire, Limits, Rainbow, TheaterChase,
+    XorShift32, MAX_CATCH_UP_STEPS,
+};
@@ -0,0 +1,13 @@
+// 无锁 ISR -> main 事件队列（单生产者单消费者，SPSC）
+//
+// 之前的示例用一个 `AtomicBool` 标志通知主循环：如果两次轮询之间发生了多次中断，
+// 这些事件会被合并成一次，而且也不知道它们各自发生的时间。
+// `EventQueue` 是一个固定容量的环形缓冲区：
+// - ISR（唯一的生产者）调用 `push()`，每个事件都带有时间戳
+// - 主循环（唯一的消费者）调用 `pop()`，按发生顺序取出事件
+// - 队列满时新事件被丢弃，并记录溢出次数
+//
+// 队列本身放在 `event-queue` crate 中：在主机上用多线程的生产者/消费者测试，
+// 并用 loom 检查所有交错执行下的内存序（做法与 `isr-handshake` 相同）。
+
+pub use event_queue::{Drain, EventQueue, PushError, Stamped};
@@ -0,0 +1,107 @@
+// LEDC 渐变控制器：伽马校正 + 缓动曲线 + 多通道 + 渐变序列
+//
+// 07_pwm_advance.rs 使用 `channel.start_duty_fade(0, 100, 2000)` 做硬件渐变，有两个问题：
+// 1. 渐变对占空比是线性的，但人眼对亮度的感知是非线性的：低亮度时变化很快，高亮度时几乎看不出变化
+// 2. 主循环只能用 `delay` 等待渐变结束，期间不能做别的事情
+//
+// 这里改为由软件按固定间隔（例如每 10ms）计算占空比：
+// - 渐变在“感知亮度”上进行，输出前做伽马校正：duty = brightness ^ gamma
+// - 支持缓动曲线（`Easing`）：线性、缓入、缓出、缓入缓出、指数
+// - 每个通道有一个渐变序列队列，当前一步结束后自动开始下一步，可以设置为循环播放
+// - `FadeController::update()` 可以在定时器中断中调用，也可以用 `run_until_idle()` 在 async 任务中运行
+//
+// `Fader`（单通道的渐变计算）、缓动曲线和伽马校正不依赖硬件，放在 `led-fade` crate 中，
+// 在主机上测试（缓动、伽马、保持、循环序列）；
+// 这里的 `FadeController` 基于 `embedded_hal::pwm::SetDutyCycle`，LEDC 的 `Channel` 实现了这个 trait。
+
+use embedded_hal::pwm::SetDutyCycle;
+pub use led_fade::{duty_for, gamma_correct, Easing, FadeStep, FadeUpdate, Fader, DEFAULT_GAMMA};
+
+use crate::clock::Clock;
+
+// 多通道渐变控制器：CH 个 PWM 通道，每个通道的序列最多 Q 步
+pub struct FadeController<P, const CH: usize, const Q: usize> {
+    channels: [P; CH],
+    faders: [Fader<Q>; CH],
+    gamma: f32,
+}
+
+impl<P, const CH: usize, const Q: usize> FadeController<P, CH, Q>
+where
+    P: SetDutyCycle,
+{
+    // `gamma`：伽马值，1.0 表示不校正（占空比线性渐变）
+    pub fn new(channels: [P; CH], gamma: f32) -> Self {
+        Self {
+            channels,
+            faders: [const { Fader::new() }; CH],
+            gamma,
+        }
+    }
+
+    pub fn fader(&mut self, channel: usize) -> &mut Fader<Q> {
+        &mut self.faders[channel]
+    }
+
+    pub fn start(&mut self, channel: usize, step: FadeStep, now_ms: u64) {
+        self.faders[channel].start(step, now_ms);
+    }
+
+    pub fn enqueue(&mut self, channel: usize, step: FadeStep) -> Result<(), FadeStep> {
+        self.faders[channel].enqueue(step)
+    }
+
+    pub fn is_idle(&self, channel: usize) -> bool {
+        self.faders[channel].is_idle()
+    }
+
+    pub fn all_idle(&self) -> bool {
+        self.faders.iter().all(|f| f.is_idle())
+    }
+
+    // 更新所有通道的占空比，返回本次有步骤结束的通道（位图，第 n 位表示通道 n）
+    // 在定时器中断或主循环中以固定间隔调用，间隔 10~20ms 即可得到平滑的渐变
+    // 某个通道写入失败时，其余通道照常更新（各通道的渐变保持同步），最后返回第一个错误
+    pub fn update(&mut self, now_ms: u64) -> Result<u32, P::Error> {
+        let mut finished = 0;
+        let mut first_error = None;
+        for (i, (fader, channel)) in self
+            .faders
+            .iter_mut()
+            .zip(self.channels.iter_mut())
+            .enumerate()
+        {
+            let update = fader.update(now_ms);
+            let duty = duty_for(update.brightness, self.gamma, channel.max_duty_cycle());
+            if let Err(e) = channel.set_duty_cycle(duty) {
+                first_error.get_or_insert(e);
+            }
+            if update.step_finished {
+                finished |= 1 << i;
+            }
+        }
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(finished),
+        }
+    }
+
+    // async 方式：每隔 `period_ms` 更新一次，直到所有通道的序列都播放完毕
+    pub async fn run_until_idle(
+        &mut self,
+        clock: &impl Clock,
+        period_ms: u64,
+    ) -> Result<(), P::Error> {
+        loop {
+            self.update(clock.now_ms())?;
+            if self.all_idle() {
+                return Ok(());
+            }
+            embassy_time::Timer::after_millis(period_ms).await;
+        }
+    }
+
+    pub fn release(self) -> [P; CH] {
+        self.channels
+    }
+}
@@ -0,0 +1,21 @@
+// ADC 采样滤波流水线
+//
+// 示例中每次循环只用 `nb::block!(adc1.read_oneshot(..))` 读一个样本，读数噪声很大。
+// 这里提供一组可以组合的滤波器：
+// - `Oversample`：N 倍过采样，每 N 个样本输出一次平均值
+// - `MovingAverage`：滑动平均
+// - `Median`：N 点中值滤波，去掉偶发的尖峰
+// - `Ema`：指数平滑（一阶低通）
+// - `Hysteresis`：带回差的阈值判断，输出 bool，避免在阈值附近来回抖动
+//
+// 滤波器之间用 `then()` 串联，用 `SampleIter::filtered()` 套在任意样本迭代器上：
+//
+//     let samples = core::iter::from_fn(|| Some(nb::block!(adc1.read_oneshot(&mut pin)).unwrap()));
+//     let pipeline = Oversample::<4>::new().then(Median::<5>::new()).then(Ema::new(32));
+//     for value in samples.filtered(pipeline) { .. }
+//
+// 滤波器不依赖硬件，放在 `sample-filter` crate 中，在主机上用合成的带噪声信号（高斯噪声、尖峰、阶跃、慢速正弦）测试。
+
+pub use sample_filter::{
+    Chain, Ema, Filter, Filtered, Hysteresis, Median, MovingAverage, Oversample, SampleIter,
+};
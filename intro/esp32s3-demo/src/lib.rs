@@ -0,0 +1,22 @@
+// esp32s3-demo 公共库：examples 中可复用的驱动与算法都放在这里
+// 在示例中通过 `use esp32s3_demo::xxx;` 引用
+
+#![no_std]
+
+pub mod adc;
+pub mod adc_dma;
+pub mod button;
+pub mod buzzer;
+pub mod clock;
+pub mod effects;
+pub mod event_queue;
+pub mod fade;
+pub mod filter;
+pub mod motor;
+pub mod servo;
+pub mod shared_bus;
+pub mod soft_pwm;
+pub mod soft_timer;
+pub mod thermistor;
+pub mod watchdog;
+pub mod ws2812;
@@ -0,0 +1,319 @@
+// 直流有刷电机：MCPWM H 桥驱动 + PCNT 正交编码器 + PID 速度环
+//
+// H 桥由两个半桥组成，每个半桥的上管和下管由 MCPWM 一个 operator 的 A/B 两路输出驱动：
+// - A 路输出 PWM，B 路是它的互补信号（死区发生器 AHC 模式：Active High Complementary）
+// - 每次切换时上下管都先关断一段死区时间，防止上下管同时导通（直通短路）
+//
+// 采用符号-幅值调制：正转时左半桥输出 PWM、右半桥下管常开；反转时反过来。
+// 占空比为 0 时两个下管都导通，电机两端短接 → 制动（刹车）；`coast()` 关断全部四个管子 → 滑行。
+//
+// 编码器的 A/B 两相接到 PCNT 的一个单元，两个通道互为控制信号，四倍频计数（每个边沿计一次）。
+// PCNT 的计数器只有 16 位，`QuadratureCounter` 把两次读取之间的增量累加成 64 位位置。
+//
+// PID 控制器在 `pid` crate 中（no_std，在主机上用仿真电机测试过），`SpeedLoop` 把编码器增量换算成转速后交给它。
+//
+// MCPWM 的配置（外设时钟 40MHz，20kHz PWM）：
+//     McPwm::new(MCPWM0, PeripheralClockConfig::with_frequency(Rate::from_mhz(40)))
+//     timer_clock_with_frequency(PWM_PERIOD, PwmWorkingMode::Increase, Rate::from_khz(20))
+//     operator.with_linked_pins(高侧, PwmPinConfig::UP_ACTIVE_HIGH, 低侧, PwmPinConfig::EMPTY, DeadTimeCfg::new_ahc())
+
+use esp_hal::{
+    gpio::InputSignal,
+    mcpwm::{
+        operator::{DeadTimeCfg, LinkedPins, PwmActions, UpdateAction},
+        PwmPeripheral,
+    },
+    pcnt::{
+        channel::{CtrlMode, EdgeMode},
+        unit::{InvalidFilterThreshold, Unit},
+    },
+};
+pub use pid::{ConfigError, Pid, PidConfig, Terms};
+
+// 占空比（-100.0 ~ 100.0 %）对应的比较值
+// 定时器递增计数 0..=period，比较值为 period + 1 时一直是高电平（100%）
+pub fn compare_for_duty(period: u16, duty_pct: f32) -> u16 {
+    let steps = period as f32 + 1.0;
+    let duty = if duty_pct < 0.0 { -duty_pct } else { duty_pct };
+    let duty = duty.min(100.0);
+    (steps * duty / 100.0 + 0.5) as u16
+}
+
+// 死区时间（ns）换算成死区发生器的时钟周期数（时钟为 MCPWM 外设时钟，40MHz 时每个周期 25ns）
+pub fn dead_time_ticks(dead_time_ns: u32, pwm_clock_hz: u32) -> u16 {
+    let ticks = (dead_time_ns as u64 * pwm_clock_hz as u64).div_ceil(1_000_000_000);
+    ticks.min(u16::MAX as u64) as u16
+}
+
+// 每个周期开始时都拉低，输出一直是低电平
+fn always_low<const IS_A: bool>() -> PwmActions<IS_A> {
+    PwmActions::empty().on_up_counting_timer_equals_zero(UpdateAction::SetLow)
+}
+
+// 电机当前的驱动状态
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Drive {
+    // 占空比（%），正数正转，负数反转，0 为制动
+    Duty(f32),
+    // 四个管子全部关断，电机自由滑行
+    Coast,
+}
+
+// H 桥驱动：`OP_L`/`OP_R` 是左右两个半桥所用的 operator，两者必须连接到同一个定时器
+pub struct HBridge<'d, PWM, const OP_L: u8, const OP_R: u8> {
+    left: LinkedPins<'d, PWM, OP_L>,
+    right: LinkedPins<'d, PWM, OP_R>,
+    // 定时器的周期值（与 `timer_clock_with_frequency` 的 period 参数相同）
+    period: u16,
+    drive: Drive,
+}
+
+impl<'d, PWM: PwmPeripheral, const OP_L: u8, const OP_R: u8> HBridge<'d, PWM, OP_L, OP_R> {
+    // `dead_time`：死区的时钟周期数，见 `dead_time_ticks`
+    // 创建后处于制动状态
+    pub fn new(
+        left: LinkedPins<'d, PWM, OP_L>,
+        right: LinkedPins<'d, PWM, OP_R>,
+        period: u16,
+        dead_time: u16,
+    ) -> Self {
+        let mut bridge = Self {
+            left,
+            right,
+            period,
+            drive: Drive::Coast,
+        };
+        bridge.left.set_rising_edge_deadtime(dead_time);
+        bridge.left.set_falling_edge_deadtime(dead_time);
+        bridge.right.set_rising_edge_deadtime(dead_time);
+        bridge.right.set_falling_edge_deadtime(dead_time);
+        bridge.set_duty(0.0);
+        bridge
+    }
+
+    pub fn drive(&self) -> Drive {
+        self.drive
+    }
+
+    // 设置占空比（-100.0 ~ 100.0 %），正数正转，负数反转，0 为制动
+    // 新的比较值在下一个 PWM 周期开始时生效
+    pub fn set_duty(&mut self, duty_pct: f32) {
+        let duty_pct = if duty_pct.is_nan() {
+            0.0
+        } else {
+            duty_pct.clamp(-100.0, 100.0)
+        };
+        if self.drive == Drive::Coast {
+            self.complementary();
+        }
+        let compare = compare_for_duty(self.period, duty_pct);
+        // 注意：`LinkedPins::set_timestamp_b` 实际写的是 A 路，这里只使用 A 路
+        if duty_pct >= 0.0 {
+            self.right.set_timestamp_a(0);
+            self.left.set_timestamp_a(compare);
+        } else {
+            self.left.set_timestamp_a(0);
+            self.right.set_timestamp_a(compare);
+        }
+        self.drive = Drive::Duty(duty_pct);
+    }
+
+    // 制动：两个下管导通，电机两端短接
+    pub fn brake(&mut self) {
+        self.set_duty(0.0);
+    }
+
+    // 滑行：四个管子全部关断，再次调用 `set_duty` 时恢复互补输出
+    pub fn coast(&mut self) {
+        // 旁路死区发生器，A/B 两路都由各自的动作表控制，并且一直保持低电平
+        self.left.set_actions_a(always_low());
+        self.left.set_actions_b(always_low());
+        self.left.set_deadtime_cfg(DeadTimeCfg::new_bypass());
+        self.right.set_actions_a(always_low());
+        self.right.set_actions_b(always_low());
+        self.right.set_deadtime_cfg(DeadTimeCfg::new_bypass());
+        self.drive = Drive::Coast;
+    }
+
+    // 恢复 AHC 互补输出：B 路由死区发生器根据 A 路生成
+    fn complementary(&mut self) {
+        self.left.set_actions_b(PwmActions::empty());
+        self.left.set_actions_a(PwmActions::UP_ACTIVE_HIGH);
+        self.left.set_deadtime_cfg(DeadTimeCfg::new_ahc());
+        self.right.set_actions_b(PwmActions::empty());
+        self.right.set_actions_a(PwmActions::UP_ACTIVE_HIGH);
+        self.right.set_deadtime_cfg(DeadTimeCfg::new_ahc());
+    }
+
+    pub fn release(mut self) -> (LinkedPins<'d, PWM, OP_L>, LinkedPins<'d, PWM, OP_R>) {
+        self.coast();
+        (self.left, self.right)
+    }
+}
+
+// 16 位硬件计数值 -> 64 位累计位置
+// 两次 `update` 之间的计数变化不能超过 ±32767，否则无法区分方向
+#[derive(Debug, Clone, Copy, Default)]
+pub struct QuadratureCounter {
+    last: i16,
+    position: i64,
+}
+
+impl QuadratureCounter {
+    // `raw`：当前的硬件计数值
+    pub fn new(raw: i16) -> Self {
+        Self {
+            last: raw,
+            position: 0,
+        }
+    }
+
+    // 返回与上一次相比的增量，计数器溢出回绕也能得到正确的结果
+    pub fn update(&mut self, raw: i16) -> i32 {
+        let delta = raw.wrapping_sub(self.last) as i32;
+        self.last = raw;
+        self.position += delta as i64;
+        delta
+    }
+
+    pub fn position(&self) -> i64 {
+        self.position
+    }
+
+    pub fn set_position(&mut self, position: i64) {
+        self.position = position;
+    }
+}
+
+// PCNT 正交编码器，四倍频计数
+pub struct Encoder<'d, const NUM: usize> {
+    unit: Unit<'d, NUM>,
+    counter: QuadratureCounter,
+    // 输出轴转一圈的计数值（编码器线数 × 4 × 减速比）
+    counts_per_rev: u32,
+}
+
+impl<'d, const NUM: usize> Encoder<'d, NUM> {
+    // `filter`：毛刺滤波阈值（APB 时钟周期数，最大 1023），`None` 表示不滤波
+    // 80MHz APB 时钟下 `Some(800)` 会滤掉短于 10us 的脉冲
+    pub fn new(
+        unit: Unit<'d, NUM>,
+        a: InputSignal<'d>,
+        b: InputSignal<'d>,
+        counts_per_rev: u32,
+        filter: Option<u16>,
+    ) -> Result<Self, InvalidFilterThreshold> {
+        unit.set_filter(filter)?;
+        // 不设置上下限：计数器在 i16 范围内回绕，由 `QuadratureCounter` 处理
+        // 参数为 `None` 时不会出错
+        let _ = unit.set_low_limit(None);
+        let _ = unit.set_high_limit(None);
+        unit.clear();
+
+        // 通道 0：A 相为控制信号，B 相为计数信号
+        let ch0 = &unit.channel0;
+        ch0.set_ctrl_signal(a.clone());
+        ch0.set_edge_signal(b.clone());
+        ch0.set_ctrl_mode(CtrlMode::Reverse, CtrlMode::Keep);
+        ch0.set_input_mode(EdgeMode::Increment, EdgeMode::Decrement);
+
+        // 通道 1：B 相为控制信号，A 相为计数信号
+        let ch1 = &unit.channel1;
+        ch1.set_ctrl_signal(b);
+        ch1.set_edge_signal(a);
+        ch1.set_ctrl_mode(CtrlMode::Reverse, CtrlMode::Keep);
+        ch1.set_input_mode(EdgeMode::Decrement, EdgeMode::Increment);
+
+        unit.resume();
+        let counter = QuadratureCounter::new(unit.value());
+        Ok(Self {
+            unit,
+            counter,
+            counts_per_rev: counts_per_rev.max(1),
+        })
+    }
+
+    // 读取计数器，返回与上一次相比的增量
+    // 需要足够频繁地调用（两次之间不超过 32767 个计数）
+    pub fn poll(&mut self) -> i32 {
+        self.counter.update(self.unit.value())
+    }
+
+    // 累计位置（计数值）
+    pub fn position(&self) -> i64 {
+        self.counter.position()
+    }
+
+    // 累计转过的圈数
+    pub fn revolutions(&self) -> f32 {
+        self.counter.position() as f32 / self.counts_per_rev as f32
+    }
+
+    pub fn counts_per_rev(&self) -> u32 {
+        self.counts_per_rev
+    }
+
+    pub fn reset_position(&mut self) {
+        self.counter.set_position(0);
+    }
+
+    pub fn release(self) -> Unit<'d, NUM> {
+        self.unit.pause();
+        self.unit
+    }
+}
+
+// 速度环：编码器增量 -> 转速（rpm） -> PID -> 占空比（%）
+pub struct SpeedLoop {
+    pid: Pid,
+    counts_per_rev: u32,
+    setpoint_rpm: f32,
+    speed_rpm: f32,
+}
+
+impl SpeedLoop {
+    // PID 的输出范围就是占空比范围，一般为 ±100
+    pub fn new(config: PidConfig, counts_per_rev: u32) -> Result<Self, ConfigError> {
+        Ok(Self {
+            pid: Pid::new(config)?,
+            counts_per_rev: counts_per_rev.max(1),
+            setpoint_rpm: 0.0,
+            speed_rpm: 0.0,
+        })
+    }
+
+    pub fn set_setpoint(&mut self, rpm: f32) {
+        self.setpoint_rpm = rpm;
+    }
+
+    pub fn setpoint(&self) -> f32 {
+        self.setpoint_rpm
+    }
+
+    // 最近一次测得的转速
+    pub fn speed_rpm(&self) -> f32 {
+        self.speed_rpm
+    }
+
+    // 调参时查看各项的贡献，或者修改增益
+    pub fn pid(&self) -> &Pid {
+        &self.pid
+    }
+
+    pub fn pid_mut(&mut self) -> &mut Pid {
+        &mut self.pid
+    }
+
+    // 每个控制周期调用一次，`delta_counts` 是这个周期内编码器的增量，返回新的占空比
+    // 目标转速为 0 时清空 PID 的状态并返回 0（制动），避免电机停下后积分项继续累积
+    pub fn update(&mut self, delta_counts: i32, dt_s: f32) -> f32 {
+        if dt_s > 0.0 {
+            self.speed_rpm = delta_counts as f32 * 60.0 / (self.counts_per_rev as f32 * dt_s);
+        }
+        if self.setpoint_rpm == 0.0 {
+            self.pid.reset();
+            return 0.0;
+        }
+        self.pid.update(self.setpoint_rpm, self.speed_rpm, dt_s)
+    }
+}
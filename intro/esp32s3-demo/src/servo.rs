@@ -0,0 +1,210 @@
+// 舵机 / 电调（ESC）驱动：基于 LEDC 通道，50Hz、14-bit 占空比
+//
+// 舵机和电调都用脉冲宽度表示位置（油门）：每 20ms 一个脉冲，宽度一般为 1000~2000us。
+// LEDC 只能设置占空比，所以需要把脉宽换算成占空比，14-bit 分辨率下每一级约 1.22us。
+//
+// 脉宽的计算（`PwmTiming`、角度映射、限速、电调解锁流程）放在 `servo` crate 中，可以在主机上测试；
+// 这里只负责把计算结果写到实现了 `SetDutyCycle` 的通道上（LEDC 的 `Channel`）。
+//
+// LEDC 定时器的配置：
+//     frequency: Rate::from_hz(servo::SERVO_FREQUENCY_HZ), duty: Duty::Duty14Bit
+
+use embedded_hal::pwm::SetDutyCycle;
+pub use servo::{
+    ArmStep, Error as CalibrationError, EscConfig, EscState, PwmTiming, ServoCalibration,
+    SERVO_FREQUENCY_HZ,
+};
+use servo::{EscArming, Motion};
+
+// 驱动出错的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServoError<E> {
+    // 标定参数与 PWM 定时器不匹配
+    Calibration(CalibrationError),
+    // 通道的最大占空比与 `PwmTiming` 的分辨率不一致
+    Resolution,
+    // 写占空比失败
+    Pwm(E),
+}
+
+// 检查通道的分辨率是否与 `timing` 一致
+fn check_resolution<P: SetDutyCycle>(
+    channel: &P,
+    timing: &PwmTiming,
+) -> Result<(), ServoError<P::Error>> {
+    if channel.max_duty_cycle() as u32 != timing.full_duty() {
+        return Err(ServoError::Resolution);
+    }
+    Ok(())
+}
+
+fn write_pulse<P: SetDutyCycle>(
+    channel: &mut P,
+    timing: &PwmTiming,
+    pulse_us: u32,
+) -> Result<(), P::Error> {
+    // 16-bit 时 100% 占空比是 65536，`as u16` 会变成 0（完全没有脉冲），这里饱和到 65535
+    channel.set_duty_cycle(timing.duty_u16_for_pulse(pulse_us))
+}
+
+// 舵机：设置角度，或者以限定的速度转到目标角度
+pub struct Servo<P> {
+    channel: P,
+    timing: PwmTiming,
+    calibration: ServoCalibration,
+    motion: Motion,
+}
+
+impl<P: SetDutyCycle> Servo<P> {
+    // 创建后立即转到 `initial_deg`
+    // `max_speed_deg_per_s`：最大转速（度/秒），`None` 表示不限速
+    pub fn new(
+        mut channel: P,
+        timing: PwmTiming,
+        calibration: ServoCalibration,
+        initial_deg: f32,
+        max_speed_deg_per_s: Option<f32>,
+    ) -> Result<Self, ServoError<P::Error>> {
+        calibration
+            .validate(&timing)
+            .map_err(ServoError::Calibration)?;
+        check_resolution(&channel, &timing)?;
+        let initial_deg = calibration.clamp_angle(initial_deg);
+        write_pulse(
+            &mut channel,
+            &timing,
+            calibration.pulse_for_angle(initial_deg),
+        )
+        .map_err(ServoError::Pwm)?;
+        Ok(Self {
+            channel,
+            timing,
+            calibration,
+            motion: Motion::new(initial_deg, max_speed_deg_per_s),
+        })
+    }
+
+    pub fn angle(&self) -> f32 {
+        self.motion.current()
+    }
+
+    pub fn target(&self) -> f32 {
+        self.motion.target()
+    }
+
+    // 是否已经到达目标角度
+    pub fn is_settled(&self) -> bool {
+        self.motion.is_settled()
+    }
+
+    pub fn set_max_speed(&mut self, max_speed_deg_per_s: Option<f32>) {
+        self.motion.set_max_speed(max_speed_deg_per_s);
+    }
+
+    // 立即转到 `angle_deg`（不限速）
+    pub fn set_angle(&mut self, angle_deg: f32) -> Result<(), P::Error> {
+        let angle_deg = self.calibration.clamp_angle(angle_deg);
+        self.motion.jump_to(angle_deg);
+        self.write_angle(angle_deg)
+    }
+
+    // 设置目标角度，之后由 `update()` 按限定的速度转过去
+    pub fn move_to(&mut self, angle_deg: f32) {
+        self.motion
+            .set_target(self.calibration.clamp_angle(angle_deg));
+    }
+
+    // 定期调用（例如每 20ms 一次），返回当前角度
+    pub fn update(&mut self, now_ms: u64) -> Result<f32, P::Error> {
+        let angle = self.motion.update(now_ms);
+        self.write_angle(angle)?;
+        Ok(angle)
+    }
+
+    // 直接输出脉宽，用于标定舵机的行程
+    pub fn set_pulse_us(&mut self, pulse_us: u32) -> Result<(), P::Error> {
+        write_pulse(&mut self.channel, &self.timing, pulse_us)
+    }
+
+    // 停止输出脉冲（占空比为 0），大多数舵机会松开
+    pub fn detach(&mut self) -> Result<(), P::Error> {
+        self.channel.set_duty_cycle_fully_off()
+    }
+
+    pub fn release(self) -> P {
+        self.channel
+    }
+
+    fn write_angle(&mut self, angle_deg: f32) -> Result<(), P::Error> {
+        let pulse = self.calibration.pulse_for_angle(angle_deg);
+        write_pulse(&mut self.channel, &self.timing, pulse)
+    }
+}
+
+// 电调：先执行解锁流程，解锁后才接受油门
+pub struct Esc<'a, P> {
+    channel: P,
+    timing: PwmTiming,
+    arming: EscArming<'a>,
+}
+
+impl<'a, P: SetDutyCycle> Esc<'a, P> {
+    // 创建后输出零油门，调用 `arm()` 开始解锁
+    pub fn new(
+        mut channel: P,
+        timing: PwmTiming,
+        config: EscConfig<'a>,
+    ) -> Result<Self, ServoError<P::Error>> {
+        check_resolution(&channel, &timing)?;
+        if config.min_pulse_us >= config.max_pulse_us || config.max_pulse_us > timing.period_us() {
+            return Err(ServoError::Calibration(CalibrationError::PulseRange));
+        }
+        write_pulse(&mut channel, &timing, config.min_pulse_us).map_err(ServoError::Pwm)?;
+        Ok(Self {
+            channel,
+            timing,
+            arming: EscArming::new(config),
+        })
+    }
+
+    pub fn state(&self) -> EscState {
+        self.arming.state()
+    }
+
+    pub fn is_armed(&self) -> bool {
+        self.arming.is_armed()
+    }
+
+    // 从 `now_ms` 开始执行解锁流程
+    pub fn arm(&mut self, now_ms: u64) {
+        self.arming.arm(now_ms);
+    }
+
+    // 立即回到零油门并上锁
+    pub fn disarm(&mut self) -> Result<(), P::Error> {
+        self.arming.disarm();
+        let pulse = self.arming.pulse_us();
+        write_pulse(&mut self.channel, &self.timing, pulse)
+    }
+
+    // 设置油门（0-100%），未解锁时忽略
+    pub fn set_throttle(&mut self, pct: u8) {
+        self.arming.set_throttle_permille(pct.min(100) as u16 * 10);
+    }
+
+    // 设置油门（0-1000‰），未解锁时忽略
+    pub fn set_throttle_permille(&mut self, permille: u16) {
+        self.arming.set_throttle_permille(permille);
+    }
+
+    // 定期调用（例如每 20ms 一次），推进解锁流程并输出当前油门对应的脉宽
+    pub fn update(&mut self, now_ms: u64) -> Result<u32, P::Error> {
+        let pulse = self.arming.update(now_ms);
+        write_pulse(&mut self.channel, &self.timing, pulse)?;
+        Ok(pulse)
+    }
+
+    pub fn release(self) -> P {
+        self.channel
+    }
+}
@@ -0,0 +1,54 @@
+// 共享总线：一个 I2C / SPI 外设上挂多个驱动
+//
+// 驱动按值接收总线（`Mpu6050::new(i2c, ..)`、`Ds3231::new(i2c)`），总线交出去以后别的驱动就用不了了。
+// embedded-hal-bus 和 embassy-embedded-hal 提供“设备句柄”：句柄本身实现 `I2c` / `SpiDevice`，
+// 每次传输时锁住共享的总线，传输结束就释放。每个驱动拿一个句柄，驱动代码不需要任何修改。
+//
+// 按使用场景选择总线容器和句柄：
+//
+// | 场景              | 总线容器                            | I2C 句柄 / SPI 句柄                                          |
+// |-------------------|-------------------------------------|--------------------------------------------------------------|
+// | 只在主循环中使用  | `RefCell<BUS>`                      | `embedded_hal_bus::{i2c, spi}::RefCellDevice`                |
+// | 主循环 + 中断     | `CsBus<BUS>`（临界区 Mutex）        | `embedded_hal_bus::{i2c, spi}::CriticalSectionDevice`        |
+// | embassy 多任务    | `AsyncBus<BUS>`（异步 Mutex）       | `embassy_embedded_hal::shared_bus::asynch::{i2c::I2cDevice, spi::SpiDevice}` |
+//
+// - RefCell 最轻量，但同一总线在中断里使用会 panic（重复借用）
+// - 临界区版本在每次传输期间关中断，传输越长中断延迟越大（400kHz 下读 14 字节约 0.4ms）
+// - 异步版本锁住总线时其它任务 `.await` 等待，不关中断
+//
+// SPI 句柄还需要各自的片选引脚（和延迟），I2C 设备靠地址区分：
+// 同一总线上的地址不能冲突，例如 MPU-6050 的 AD0 接地时和 DS1307 / DS3231 都是 0x68。
+
+use core::cell::RefCell;
+
+use embassy_sync::{blocking_mutex::raw::NoopRawMutex, mutex::Mutex};
+use static_cell::StaticCell;
+
+// 主循环和中断共享的总线
+pub type CsBus<BUS> = critical_section::Mutex<RefCell<BUS>>;
+
+// embassy 任务之间共享的总线
+// 异步模式的 `I2c<'_, Async>` 不是 `Send`，只能在同一个执行器的任务之间共享，所以用 NoopRawMutex 就够了
+pub type AsyncBus<BUS> = Mutex<NoopRawMutex, BUS>;
+
+// 把总线放进 `'static` 存储，返回的引用可以传给中断处理函数中使用的句柄
+pub fn cs_bus<BUS>(cell: &'static StaticCell<CsBus<BUS>>, bus: BUS) -> &'static CsBus<BUS> {
+    cell.init(critical_section::Mutex::new(RefCell::new(bus)))
+}
+
+// 同上，返回的引用可以传给 `#[embassy_executor::task]`（任务参数必须是 `'static`）
+pub fn async_bus<BUS>(
+    cell: &'static StaticCell<AsyncBus<BUS>>,
+    bus: BUS,
+) -> &'static AsyncBus<BUS> {
+    cell.init(Mutex::new(bus))
+}
+
+// 在异步总线上运行阻塞驱动（例如 ds-rtc 只有阻塞接口）
+//
+// 锁住总线后把 `&mut BUS` 交给闭包：`&mut I2c` 本身也实现了 `I2c`，可以直接传给驱动的 `new`。
+// 闭包执行期间总线被占用，其它任务等待；阻塞传输不会让出 CPU，所以闭包里只做几次短传输。
+pub async fn with_blocking<BUS, R>(bus: &AsyncBus<BUS>, f: impl FnOnce(&mut BUS) -> R) -> R {
+    let mut guard = bus.lock().await;
+    f(&mut guard)
+}
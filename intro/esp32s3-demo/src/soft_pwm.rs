@@ -0,0 +1,174 @@
+// 定时器中断驱动的多通道软件 PWM
+//
+// 07_pwm_soft.rs 中的 `soft_pwm()` 用 `delay.delay_us` 拉高、拉低引脚，渐变期间 CPU 被完全占用，
+// 而且一次只能驱动一个引脚。这里改为由一个硬件定时器中断驱动：
+// - 每个 PWM 周期分成 100 步，定时器中断每一步调用一次 `SoftPwm::tick()`
+// - 最多 8 个 `Output`，每个通道的占空比独立
+// - 占空比保存在原子变量中（`DutyCycles`），主循环调用 `set_duty(channel, pct)` 立即返回，不需要关中断
+// - ISR 在每个周期开始时才读取新的占空比，周期中途修改不会产生异常的脉冲
+//
+// 适合 LEDC 通道不够用或者引脚无法连接到 LEDC 的场合。
+// 中断频率 = PWM 频率 × 100，例如 100Hz 的 PWM 需要 10kHz 的中断，PWM 频率不宜太高。
+
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use esp_hal::{gpio::Output, time::Duration};
+
+// 最多通道数
+pub const MAX_CHANNELS: usize = 8;
+
+// 每个 PWM 周期的步数，占空比分辨率为 1%
+pub const STEPS: u8 = 100;
+
+// 设置占空比失败的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PwmError {
+    // 通道号超出范围
+    InvalidChannel,
+    // 占空比大于 100
+    InvalidDuty,
+}
+
+// 各通道的占空比（0-100），主循环写、ISR 读，可以直接放在 `static` 中
+pub struct DutyCycles<const N: usize> {
+    duty: [AtomicU8; N],
+}
+
+impl<const N: usize> DutyCycles<N> {
+    pub const fn new() -> Self {
+        assert!(N > 0 && N <= MAX_CHANNELS, "soft PWM supports 1..=8 channels");
+        Self {
+            duty: [const { AtomicU8::new(0) }; N],
+        }
+    }
+
+    // 设置占空比，从下一个 PWM 周期开始生效
+    pub fn set_duty(&self, channel: usize, pct: u8) -> Result<(), PwmError> {
+        if pct > STEPS {
+            return Err(PwmError::InvalidDuty);
+        }
+        self.duty
+            .get(channel)
+            .ok_or(PwmError::InvalidChannel)?
+            .store(pct, Ordering::Relaxed);
+        Ok(())
+    }
+
+    pub fn duty(&self, channel: usize) -> Option<u8> {
+        self.duty.get(channel).map(|d| d.load(Ordering::Relaxed))
+    }
+
+    // 所有通道设置为同一个占空比
+    pub fn set_all(&self, pct: u8) -> Result<(), PwmError> {
+        (0..N).try_for_each(|channel| self.set_duty(channel, pct))
+    }
+}
+
+impl<const N: usize> Default for DutyCycles<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 纯逻辑部分：相位计数器，决定每一步各通道的电平
+pub struct PwmPhase<const N: usize> {
+    phase: u8,
+    // 本周期使用的占空比（周期开始时从 `DutyCycles` 复制）
+    latched: [u8; N],
+    // 各通道当前电平（位图），只在电平变化时才写 GPIO
+    levels: u8,
+}
+
+impl<const N: usize> PwmPhase<N> {
+    pub const fn new() -> Self {
+        Self {
+            phase: 0,
+            latched: [0; N],
+            levels: 0,
+        }
+    }
+
+    // 前进一步，对每个电平发生变化的通道调用 `set(channel, high)`
+    pub fn step(&mut self, duties: &DutyCycles<N>, mut set: impl FnMut(usize, bool)) {
+        if self.phase == 0 {
+            for (channel, latched) in self.latched.iter_mut().enumerate() {
+                *latched = duties.duty[channel].load(Ordering::Relaxed);
+            }
+        }
+        for (channel, &duty) in self.latched.iter().enumerate() {
+            let high = duty > self.phase;
+            let mask = 1 << channel;
+            if high != (self.levels & mask != 0) {
+                self.levels ^= mask;
+                set(channel, high);
+            }
+        }
+        self.phase = (self.phase + 1) % STEPS;
+    }
+}
+
+impl<const N: usize> Default for PwmPhase<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// 最高 PWM 频率：中断周期以微秒为单位，至少 1µs，即 1MHz / 100 步 = 10kHz
+pub const MAX_FREQUENCY_HZ: u32 = 1_000_000 / STEPS as u32;
+
+// 定时器中断周期：PWM 频率 × 100 步
+// 频率为 0 或高于 `MAX_FREQUENCY_HZ` 时返回 `None`（否则会除以 0 或得到 0µs 的周期）
+pub const fn tick_period(pwm_frequency_hz: u32) -> Option<Duration> {
+    if pwm_frequency_hz == 0 || pwm_frequency_hz > MAX_FREQUENCY_HZ {
+        return None;
+    }
+    Some(Duration::from_micros(
+        1_000_000 / (pwm_frequency_hz as u64 * STEPS as u64),
+    ))
+}
+
+// 软件 PWM 驱动，由定时器 ISR 调用 `tick()`
+// 一般放在 `Mutex<RefCell<Option<SoftPwm<..>>>>` 中与 ISR 共享，占空比通过 `DutyCycles` 修改
+pub struct SoftPwm<'d, const N: usize> {
+    outputs: [Output<'d>; N],
+    duties: &'d DutyCycles<N>,
+    phase: PwmPhase<N>,
+}
+
+impl<'d, const N: usize> SoftPwm<'d, N> {
+    pub fn new(mut outputs: [Output<'d>; N], duties: &'d DutyCycles<N>) -> Self {
+        for output in outputs.iter_mut() {
+            output.set_low();
+        }
+        Self {
+            outputs,
+            duties,
+            phase: PwmPhase::new(),
+        }
+    }
+
+    // 在定时器 ISR 中调用
+    pub fn tick(&mut self) {
+        let outputs = &mut self.outputs;
+        self.phase.step(self.duties, |channel, high| {
+            if high {
+                outputs[channel].set_high();
+            } else {
+                outputs[channel].set_low();
+            }
+        });
+    }
+
+    pub fn duties(&self) -> &'d DutyCycles<N> {
+        self.duties
+    }
+
+    // 停止输出，拉低所有引脚并交还
+    pub fn release(self) -> [Output<'d>; N] {
+        let mut outputs = self.outputs;
+        for output in outputs.iter_mut() {
+            output.set_low();
+        }
+        outputs
+    }
+}
@@ -0,0 +1,18 @@
+// 软件定时器服务：一个硬件定时器复用出任意多个一次性/周期软件定时器
+//
+// 06_timer_blinky_interrupte.rs 为了一个 500ms 的节拍就占用了整个 `TimerGroup` 的 timer0。
+// 这里用“时间轮”（hashed timing wheel）管理软件定时器：
+// - 硬件定时器以固定节拍（例如 10ms）触发中断，ISR 只负责通知主循环，不执行任何回调
+// - 主循环调用 `advance(now_ms)` 推进时间轮，取出到期的定时器，在主循环中执行回调
+// - 每个定时器都有一个句柄 `TimerHandle`，可以取消、重新计时、修改周期；
+//   句柄带有代数（generation），定时器被取消或结束后，旧句柄自动失效
+//
+// 时间轮有 SLOTS 个槽，定时器按到期节拍放进 `到期节拍 % SLOTS` 号槽中，
+// 每推进一个节拍只需要检查一个槽，启动、取消都是 O(1)。
+//
+// 启动定时器时的延时从最后一次 `advance` 传入的时间算起（可能在两个节拍之间），向上取整到节拍，
+// 所以定时器不会提前到期，最多晚一个节拍。
+//
+// 时间轮不读取时间，当前时间由调用者传入。它放在 `soft-timer` crate 中，在主机上用手动推进的假时钟测试。
+
+pub use soft_timer::{Callback, Fired, TimerError, TimerHandle, TimerWheel};
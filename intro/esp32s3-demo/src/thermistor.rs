@@ -0,0 +1,14 @@
+// NTC 热敏电阻：ADC 读数 -> 电阻 -> 温度
+//
+// 之前的示例把 Beta 公式（B = 3950、VMAX = 4095、`libm::log`）直接写在 main 里，
+// 并且默认热敏电阻接在分压电路的下端。这里拆成两步：
+// 1. `Divider`：根据分压电路的接法、串联电阻和 ADC 分辨率，把 ADC 原始读数换算成热敏电阻的阻值
+// 2. `Model`：把阻值换算成温度，支持 Beta 公式、三系数 Steinhart-Hart 公式和查表插值
+//
+// 温度既可以输出为 `f32` 摄氏度，也可以输出为定点数（0.01°C），方便在没有浮点格式化的地方使用。
+// 换算不依赖硬件，放在 `thermistor` crate 中，在主机上对照数据手册中的 R-T 表进行测试。
+
+pub use thermistor::{
+    ntc_10k_3950, Beta, CentiCelsius, Divider, Error, LookupTable, Model, SteinhartHart,
+    Thermistor, Topology, ZERO_CELSIUS_K,
+};
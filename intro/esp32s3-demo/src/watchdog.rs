@@ -0,0 +1,86 @@
+// 多级看门狗监控器（Watchdog Supervisor）
+//
+// 01_helloworld.rs 中只在一个主循环里 `wdt0.feed()`，只要主循环还在跑就会一直喂狗，
+// 即使某个逻辑任务已经卡死也发现不了。
+// 这里的做法是：
+// 1. 每个逻辑任务先注册，并给出自己的超时时间（deadline）
+// 2. 任务正常工作时定期 `check_in()` 报到
+// 3. 只有所有任务都在各自的 deadline 内报到过，才真正去喂 MWDT
+// 4. 一旦有任务超时，停止喂狗：MWDT Stage0 先触发警告中断，记录是哪一个任务卡住了；
+//    Stage1 再复位系统。记录保存在 RTC FAST 内存中，软复位后依然可以读出来。
+//
+// `Supervisor`、`Health` 和卡死记录的编码只和毫秒时间戳、u32 字打交道，不依赖硬件，
+// 放在 `watchdog-supervisor` crate 中并在主机上测试；这里只负责 MWDT 和 RTC 内存。
+
+use esp_hal::{
+    ram,
+    time::Duration,
+    timer::timg::{MwdtStage, MwdtStageAction, TimerGroupInstance, Wdt},
+};
+use watchdog_supervisor::STALL_WORDS;
+pub use watchdog_supervisor::{
+    Health, StallRecord, Supervisor, TaskEntry, TaskId, TooManyTasks, STALL_NAME_LEN,
+};
+
+// ---------------------------------------------------------------------------
+// MWDT 配置与喂狗
+// ---------------------------------------------------------------------------
+
+// 配置 MWDT 为两级：
+// - Stage0：超过 `warn_after` 没有喂狗，触发中断（在中断里记录卡死的任务）
+// - Stage1：再过 `reset_after` 仍没有喂狗，复位整个系统
+pub fn configure_mwdt<TG>(wdt: &mut Wdt<TG>, warn_after: Duration, reset_after: Duration)
+where
+    TG: TimerGroupInstance,
+{
+    wdt.set_timeout(MwdtStage::Stage0, warn_after);
+    wdt.set_stage_action(MwdtStage::Stage0, MwdtStageAction::Interrupt);
+    wdt.set_timeout(MwdtStage::Stage1, reset_after);
+    wdt.set_stage_action(MwdtStage::Stage1, MwdtStageAction::ResetSystem);
+    wdt.set_stage_action(MwdtStage::Stage2, MwdtStageAction::Off);
+    wdt.set_stage_action(MwdtStage::Stage3, MwdtStageAction::Off);
+    wdt.enable();
+}
+
+// 只有所有任务健康时才喂狗，返回本次检查结果
+pub fn feed_if_healthy<TG, const N: usize>(
+    wdt: &mut Wdt<TG>,
+    supervisor: &Supervisor<N>,
+    now_ms: u64,
+) -> Health
+where
+    TG: TimerGroupInstance,
+{
+    let health = supervisor.health(now_ms);
+    if health == Health::Healthy {
+        wdt.feed();
+    }
+    health
+}
+
+// ---------------------------------------------------------------------------
+// 卡死记录：保存在 RTC FAST 内存中，软复位/看门狗复位后仍然保留
+// ---------------------------------------------------------------------------
+
+// 布局见 `StallRecord::to_words`：[magic, task_index, overdue_ms, name_len, name(16 字节 = 4 个字)]
+#[ram(unstable(rtc_fast, persistent))]
+static mut STALL_STORE: [u32; STALL_WORDS] = [0; STALL_WORDS];
+
+// 记录卡死的任务（一般在 Stage0 中断里调用）
+pub fn record_stall(task: TaskId, name: &str, overdue_ms: u64) {
+    let words = StallRecord::new(task, name, overdue_ms).to_words();
+    critical_section::with(|_| unsafe {
+        core::ptr::addr_of_mut!(STALL_STORE).write_volatile(words);
+    });
+}
+
+// 读取并清除上一次的卡死记录
+pub fn take_last_stall() -> Option<StallRecord> {
+    let words = critical_section::with(|_| unsafe {
+        let ptr = core::ptr::addr_of_mut!(STALL_STORE);
+        let words = ptr.read_volatile();
+        ptr.write_volatile([0; STALL_WORDS]);
+        words
+    });
+    StallRecord::from_words(&words)
+}
@@ -0,0 +1,116 @@
+// WS2812 (NeoPixel) 驱动 - 基于 RMT 外设
+//
+// 之前的示例用空循环 `for _ in 0..60 {}` 控制每一位的高低电平时间，
+// 时序会随优化等级、CPU 频率变化，而且一旦被中断打断就会发错数据。
+// RMT（红外遥控收发器）可以按照预先编码好的脉冲序列由硬件输出波形，
+// CPU 只负责把颜色编码成脉冲码（pulse code），时序完全由硬件保证。
+//
+// 编码部分（`Timing`、`pulse_code`、`encode_*`）在 `ws2812` crate 中，不依赖 esp-hal，可以在主机上单元测试；
+// 这里只负责把编码好的脉冲码交给 RMT 通道发送。
+
+use esp_hal::{
+    gpio::{interconnect::PeripheralOutput, Level},
+    rmt::{Channel, Error as RmtError, Tx, TxChannelConfig, TxChannelCreator},
+    Blocking,
+};
+use smart_leds::{SmartLedsWrite, RGB8};
+pub use ws2812::{
+    buffer_size, encode_bit, encode_byte, encode_color, encode_frame, pulse_code, BufferTooSmall,
+    Timing, BITS_PER_LED, DEFAULT_RMT_CLOCK_MHZ, T0H_NS, T0L_NS, T1H_NS, T1L_NS,
+};
+
+// 驱动错误
+#[derive(Debug)]
+pub enum Ws2812Error {
+    // 缓冲区不足以容纳整帧
+    BufferTooSmall,
+    // RMT 外设报错
+    Rmt(RmtError),
+    // 之前的 `transmit` 出错时 esp-hal 已经把通道释放了，驱动无法再发送
+    ChannelLost,
+}
+
+impl From<BufferTooSmall> for Ws2812Error {
+    fn from(_: BufferTooSmall) -> Self {
+        Ws2812Error::BufferTooSmall
+    }
+}
+
+impl From<RmtError> for Ws2812Error {
+    fn from(e: RmtError) -> Self {
+        Ws2812Error::Rmt(e)
+    }
+}
+
+// 基于 RMT 的 WS2812 驱动
+// `buffer` 由调用者提供，长度用 `buffer_size(led 数量)` 计算，因此可以驱动任意长度的灯带
+pub struct Ws2812<'d> {
+    channel: Option<Channel<'d, Blocking, Tx>>,
+    buffer: &'d mut [u32],
+    timing: Timing,
+}
+
+impl<'d> Ws2812<'d> {
+    // 使用 RMT 通道和输出引脚创建驱动
+    // 注意：RMT 需要以 80MHz 创建（`Rmt::new(peripherals.RMT, Rate::from_mhz(80))`）
+    pub fn new<C>(
+        channel: C,
+        pin: impl PeripheralOutput<'d>,
+        buffer: &'d mut [u32],
+    ) -> Result<Self, Ws2812Error>
+    where
+        C: TxChannelCreator<'d, Blocking>,
+    {
+        let config = TxChannelConfig::default()
+            .with_clk_divider(1)
+            .with_idle_output_level(Level::Low)
+            .with_idle_output(true)
+            .with_carrier_modulation(false);
+        let channel = channel.configure_tx(pin, config)?;
+
+        Ok(Self {
+            channel: Some(channel),
+            buffer,
+            timing: Timing::default(),
+        })
+    }
+
+    // 自定义时序，比如使用了不同的 RMT 时钟或兼容芯片（SK6812 等）
+    pub fn with_timing(mut self, timing: Timing) -> Self {
+        self.timing = timing;
+        self
+    }
+}
+
+impl SmartLedsWrite for Ws2812<'_> {
+    type Error = Ws2812Error;
+    type Color = RGB8;
+
+    fn write<T, I>(&mut self, iterator: T) -> Result<(), Self::Error>
+    where
+        T: IntoIterator<Item = I>,
+        I: Into<Self::Color>,
+    {
+        let len = encode_frame(
+            iterator.into_iter().map(Into::into),
+            &self.timing,
+            self.buffer,
+        )?;
+
+        // transmit 会消耗通道，发送完成后再放回去
+        let channel = self.channel.take().ok_or(Ws2812Error::ChannelLost)?;
+        // transmit 只在数据为空或缺少结束标记时出错，encode_frame 保证了这两点；
+        // 万一出错，通道已被 esp-hal 丢弃，之后的 write 都返回 ChannelLost
+        let transaction = channel.transmit(&self.buffer[..len])?;
+        match transaction.wait() {
+            Ok(channel) => {
+                self.channel = Some(channel);
+                Ok(())
+            }
+            Err((e, channel)) => {
+                self.channel = Some(channel);
+                Err(Ws2812Error::Rmt(e))
+            }
+        }
+    }
+}
@@ -0,0 +1,238 @@
+//! Lock-free, fixed-capacity event queue from one interrupt handler to the
+//! main loop.
+//!
+//! An `AtomicBool` flag merges every interrupt between two polls of the main
+//! loop into one and loses when each of them happened. An [`EventQueue`]
+//! keeps them all, in order, with their timestamps:
+//!
+//! * the ISR, the only producer, calls [`EventQueue::push`];
+//! * the main loop, the only consumer, calls [`EventQueue::pop`] or
+//!   [`EventQueue::drain`];
+//! * when the queue is full, new events are dropped and counted.
+//!
+//! Only atomic loads and stores are used, no critical section. Each side
+//! also holds a busy flag, so a second producer (or consumer) running at the
+//! same time fails instead of corrupting a slot.
+//!
+//! ```rust
+//! use event_queue::{EventQueue, PushError};
+//!
+//! static EVENTS: EventQueue<u8, 2> = EventQueue::new();
+//!
+//! EVENTS.push(10, b'a').unwrap();
+//! EVENTS.push(12, b'b').unwrap();
+//! assert_eq!(EVENTS.push(15, b'c'), Err(PushError::Full(b'c')));
+//! let events: Vec<_> = EVENTS.drain().map(|e| (e.timestamp_ms, e.event)).collect();
+//! assert_eq!(events, [(10, b'a'), (12, b'b')]);
+//! assert_eq!(EVENTS.take_overflow_count(), 1);
+//! ```
+//!
+//! `tests/queue.rs` runs a producer and a consumer on two threads.
+//! `tests/loom.rs` checks the orderings under every interleaving and every
+//! weak-memory behaviour with [loom](https://docs.rs/loom):
+//!
+//! ```text
+//! RUSTFLAGS="--cfg loom" cargo test --release --test loom
+//! ```
+//!
+//! Without `--cfg loom` the crate is `no_std` and uses `core` atomics, so the
+//! same code runs on the chip.
+
+#![cfg_attr(not(loom), no_std)]
+
+use core::mem::MaybeUninit;
+
+use sync::{AtomicBool, AtomicU32, AtomicUsize, Ordering, UnsafeCell};
+
+#[cfg(not(loom))]
+mod sync {
+    pub use core::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+
+    /// `core::cell::UnsafeCell` behind loom's closure-based API.
+    pub struct UnsafeCell<T>(core::cell::UnsafeCell<T>);
+
+    impl<T> UnsafeCell<T> {
+        pub const fn new(value: T) -> Self {
+            Self(core::cell::UnsafeCell::new(value))
+        }
+
+        pub fn with_mut<R>(&self, f: impl FnOnce(*mut T) -> R) -> R {
+            f(self.0.get())
+        }
+    }
+}
+
+#[cfg(loom)]
+mod sync {
+    pub use loom::{
+        cell::UnsafeCell,
+        sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering},
+    };
+}
+
+/// An event and when it happened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stamped<T> {
+    pub timestamp_ms: u64,
+    pub event: T,
+}
+
+/// Why [`EventQueue::push`] failed; holds the event that was not queued.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PushError<T> {
+    /// The queue is full. The event was counted as an overflow.
+    Full(T),
+    /// Another producer is pushing right now.
+    Busy(T),
+}
+
+/// Single-producer single-consumer queue of `N` events, usable as a
+/// `static`.
+pub struct EventQueue<T, const N: usize> {
+    buffer: [UnsafeCell<MaybeUninit<Stamped<T>>>; N],
+    // Next slot to read, only advanced by the consumer
+    head: AtomicUsize,
+    // Next slot to write, only advanced by the producer
+    tail: AtomicUsize,
+    overflows: AtomicU32,
+    producing: AtomicBool,
+    consuming: AtomicBool,
+}
+
+// SAFETY: a slot is only ever accessed by one side at a time. The producer
+// only writes slots in [tail, head + N), the consumer only reads slots in
+// [head, tail), and the busy flags keep each side to one thread at a time.
+unsafe impl<T: Copy + Send, const N: usize> Sync for EventQueue<T, N> {}
+
+impl<T: Copy, const N: usize> EventQueue<T, N> {
+    #[cfg(not(loom))]
+    pub const fn new() -> Self {
+        assert!(N > 0, "EventQueue capacity must not be 0");
+        Self {
+            buffer: [const { UnsafeCell::new(MaybeUninit::uninit()) }; N],
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+            overflows: AtomicU32::new(0),
+            producing: AtomicBool::new(false),
+            consuming: AtomicBool::new(false),
+        }
+    }
+
+    // loom atomics cannot be created in a `const fn`
+    #[cfg(loom)]
+    pub fn new() -> Self {
+        assert!(N > 0, "EventQueue capacity must not be 0");
+        Self {
+            buffer: core::array::from_fn(|_| UnsafeCell::new(MaybeUninit::uninit())),
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+            overflows: AtomicU32::new(0),
+            producing: AtomicBool::new(false),
+            consuming: AtomicBool::new(false),
+        }
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Events in the queue right now.
+    pub fn len(&self) -> usize {
+        // head before tail, so that tail >= head
+        let head = self.head.load(Ordering::Acquire);
+        let tail = self.tail.load(Ordering::Acquire);
+        tail.wrapping_sub(head)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Producer side, usually called from the ISR.
+    pub fn push(&self, timestamp_ms: u64, event: T) -> Result<(), PushError<T>> {
+        if self.producing.swap(true, Ordering::Acquire) {
+            return Err(PushError::Busy(event));
+        }
+
+        let tail = self.tail.load(Ordering::Relaxed);
+        // Acquire: the consumer is done reading a slot before it is reused
+        let head = self.head.load(Ordering::Acquire);
+        let result = if tail.wrapping_sub(head) >= N {
+            self.overflows.fetch_add(1, Ordering::Relaxed);
+            Err(PushError::Full(event))
+        } else {
+            // SAFETY: the slot is outside [head, tail), the consumer does not
+            // touch it
+            self.buffer[tail % N].with_mut(|slot| unsafe {
+                (*slot).write(Stamped {
+                    timestamp_ms,
+                    event,
+                });
+            });
+            // Release: the slot is written before the new tail is published
+            self.tail.store(tail.wrapping_add(1), Ordering::Release);
+            Ok(())
+        };
+
+        self.producing.store(false, Ordering::Release);
+        result
+    }
+
+    /// Consumer side, in the main loop: the oldest event, if any.
+    pub fn pop(&self) -> Option<Stamped<T>> {
+        if self.consuming.swap(true, Ordering::Acquire) {
+            return None;
+        }
+
+        let head = self.head.load(Ordering::Relaxed);
+        // Acquire: pairs with the Release in `push`, the slot is written
+        let tail = self.tail.load(Ordering::Acquire);
+        let result = if head == tail {
+            None
+        } else {
+            // SAFETY: the slot is in [head, tail), written by the producer
+            // and not touched by it again until head moves past it
+            let item = self.buffer[head % N].with_mut(|slot| unsafe { (*slot).assume_init() });
+            // Release: the slot is read before it is handed back
+            self.head.store(head.wrapping_add(1), Ordering::Release);
+            Some(item)
+        };
+
+        self.consuming.store(false, Ordering::Release);
+        result
+    }
+
+    /// Pops until the queue is empty.
+    pub fn drain(&self) -> Drain<'_, T, N> {
+        Drain { queue: self }
+    }
+
+    /// Events dropped because the queue was full.
+    pub fn overflow_count(&self) -> u32 {
+        self.overflows.load(Ordering::Relaxed)
+    }
+
+    /// Returns the overflow count and resets it.
+    pub fn take_overflow_count(&self) -> u32 {
+        self.overflows.swap(0, Ordering::Relaxed)
+    }
+}
+
+impl<T: Copy, const N: usize> Default for EventQueue<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Iterator returned by [`EventQueue::drain`].
+pub struct Drain<'a, T: Copy, const N: usize> {
+    queue: &'a EventQueue<T, N>,
+}
+
+impl<T: Copy, const N: usize> Iterator for Drain<'_, T, N> {
+    type Item = Stamped<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.queue.pop()
+    }
+}
@@ -0,0 +1,178 @@
+#![cfg(not(loom))]
+
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+};
+
+use event_queue::{EventQueue, PushError, Stamped};
+
+#[test]
+fn events_come_out_in_order() {
+    let queue = EventQueue::<u32, 4>::new();
+    assert!(queue.is_empty());
+    for i in 0..3 {
+        queue.push(i as u64 * 10, i).unwrap();
+    }
+    assert_eq!(queue.len(), 3);
+    assert_eq!(
+        queue.pop(),
+        Some(Stamped {
+            timestamp_ms: 0,
+            event: 0
+        })
+    );
+    let rest: Vec<u32> = queue.drain().map(|e| e.event).collect();
+    assert_eq!(rest, [1, 2]);
+    assert_eq!(queue.pop(), None);
+}
+
+#[test]
+fn full_queue_drops_and_counts() {
+    let queue = EventQueue::<u8, 2>::new();
+    queue.push(0, 1).unwrap();
+    queue.push(0, 2).unwrap();
+    assert_eq!(queue.push(0, 3), Err(PushError::Full(3)));
+    assert_eq!(queue.push(0, 4), Err(PushError::Full(4)));
+    assert_eq!(queue.overflow_count(), 2);
+    assert_eq!(queue.take_overflow_count(), 2);
+    assert_eq!(queue.overflow_count(), 0);
+    // Room again after a pop; the dropped events are gone
+    assert_eq!(queue.pop().map(|e| e.event), Some(1));
+    queue.push(0, 5).unwrap();
+    let rest: Vec<u8> = queue.drain().map(|e| e.event).collect();
+    assert_eq!(rest, [2, 5]);
+}
+
+#[test]
+fn slots_are_reused_around_the_ring() {
+    let queue = EventQueue::<u64, 3>::new();
+    for i in 0..100 {
+        queue.push(i, i).unwrap();
+        queue.push(i, i + 1000).unwrap();
+        assert_eq!(queue.pop().map(|e| e.event), Some(i));
+        assert_eq!(queue.pop().map(|e| e.event), Some(i + 1000));
+        assert!(queue.is_empty());
+    }
+}
+
+/// Pushes `count` numbered events from one thread and pops them on another,
+/// retrying on `Full`. Every event must arrive exactly once and in order.
+fn run_spsc<const N: usize>(count: u64) {
+    let queue = EventQueue::<u64, N>::new();
+    thread::scope(|s| {
+        s.spawn(|| {
+            for i in 0..count {
+                let mut event = i;
+                loop {
+                    match queue.push(i, event) {
+                        Ok(()) => break,
+                        Err(PushError::Full(e)) => {
+                            event = e;
+                            thread::yield_now();
+                        }
+                        Err(PushError::Busy(_)) => panic!("only one producer"),
+                    }
+                }
+            }
+        });
+
+        let mut expected = 0;
+        while expected < count {
+            match queue.pop() {
+                Some(Stamped {
+                    timestamp_ms,
+                    event,
+                }) => {
+                    assert_eq!(event, expected);
+                    assert_eq!(timestamp_ms, expected);
+                    expected += 1;
+                }
+                None => thread::yield_now(),
+            }
+        }
+    });
+    assert!(queue.is_empty());
+}
+
+#[test]
+fn concurrent_producer_and_consumer_lose_nothing() {
+    run_spsc::<1>(20_000);
+    run_spsc::<7>(100_000);
+    run_spsc::<64>(100_000);
+}
+
+#[test]
+fn dropped_events_are_all_counted() {
+    // The producer never retries, like an ISR: everything it pushed is either
+    // popped or counted as an overflow
+    const COUNT: u64 = 100_000;
+    let queue = EventQueue::<u64, 8>::new();
+    let done = AtomicBool::new(false);
+    let (pushed, received) = thread::scope(|s| {
+        let producer = s.spawn(|| {
+            let mut pushed = 0u64;
+            for i in 0..COUNT {
+                if queue.push(0, i).is_ok() {
+                    pushed += 1;
+                }
+            }
+            done.store(true, Ordering::Release);
+            pushed
+        });
+
+        let mut received = Vec::new();
+        loop {
+            let finished = done.load(Ordering::Acquire);
+            received.extend(queue.drain().map(|e| e.event));
+            if finished {
+                break;
+            }
+        }
+        (producer.join().unwrap(), received)
+    });
+
+    assert_eq!(received.len() as u64, pushed);
+    assert_eq!(pushed + queue.overflow_count() as u64, COUNT);
+    // Gaps where events were dropped, but never out of order
+    assert!(received.windows(2).all(|w| w[0] < w[1]));
+}
+
+#[test]
+fn second_producer_is_turned_away_not_interleaved() {
+    // Events carry a checksum: a torn slot would not match it
+    let queue = EventQueue::<(u32, u32), 16>::new();
+    let stop = AtomicBool::new(false);
+    thread::scope(|s| {
+        for producer in 0..3u32 {
+            let queue = &queue;
+            let stop = &stop;
+            s.spawn(move || {
+                let mut i = 0u32;
+                while !stop.load(Ordering::Relaxed) {
+                    let value = producer << 24 | i;
+                    // Full and Busy are both fine here, only torn slots are not
+                    let _ = queue.push(value as u64, (value, !value));
+                    i = (i + 1) & 0xFF_FFFF;
+                    thread::yield_now();
+                }
+            });
+        }
+
+        let mut seen = 0;
+        while seen < 20_000 {
+            if let Some(Stamped {
+                timestamp_ms,
+                event: (value, check),
+            }) = queue.pop()
+            {
+                assert_eq!(check, !value, "torn event");
+                assert_eq!(timestamp_ms, value as u64, "event and stamp mismatch");
+                seen += 1;
+            } else {
+                thread::yield_now();
+            }
+        }
+        stop.store(true, Ordering::Relaxed);
+    });
+}
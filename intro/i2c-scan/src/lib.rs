@@ -0,0 +1,161 @@
+//! I2C bus diagnostics: address scan, part identification and stuck-bus
+//! recovery.
+//!
+//! When a driver only reports "I2C write error", the cause is usually one of
+//! three things, and each has a tool here:
+//!
+//! * Nothing answers at that address (wrong AD0 strap, wrong wiring):
+//!   [`scan`] lists every address in 0x08..=0x77 that acknowledges.
+//! * Something else answers there: [`identify`] reads the ID registers of
+//!   the parts in [`KNOWN_PARTS`] that can sit at an address and names the
+//!   one that matches. It writes nothing where a part that a register write
+//!   would disturb, such as a TCA9548A mux, may be instead.
+//! * The bus itself is wedged, typically because the MCU was reset in the
+//!   middle of a read and a slave still holds SDA low: [`recovery`] checks
+//!   the lines and clocks SCL until the slave lets go.
+//!
+//! ```rust
+//! use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+//! use i2c_scan::{identify, scan, Confidence, Probe};
+//!
+//! // An MPU-6050 with AD0 high answers at 0x69, not 0x68
+//! let mut script: Vec<_> = (0x08..=0x77u8)
+//!     .map(|address| {
+//!         let t = Transaction::write(address, vec![]);
+//!         if address == 0x69 {
+//!             t
+//!         } else {
+//!             t.with_error(embedded_hal::i2c::ErrorKind::NoAcknowledge(
+//!                 embedded_hal::i2c::NoAcknowledgeSource::Address,
+//!             ))
+//!         }
+//!     })
+//!     .collect();
+//! script.push(Transaction::write_read(0x69, vec![0x75], vec![0x68]));
+//! let mut i2c = Mock::new(&script);
+//!
+//! let found = scan(&mut i2c, Probe::Write);
+//! assert_eq!(found.addresses().collect::<Vec<_>>(), [0x69]);
+//! let id = identify(&mut i2c, 0x69).unwrap();
+//! assert_eq!(id.part.name, "MPU-6050");
+//! assert_eq!(id.confidence, Confidence::IdRegister);
+//! i2c.done();
+//! ```
+
+#![no_std]
+
+use core::ops::RangeInclusive;
+
+use embedded_hal::i2c::{Error as _, ErrorKind, I2c};
+
+mod parts;
+pub mod recovery;
+
+pub use parts::{
+    candidates, identify, identify_with, Check, Confidence, Identification, Part, SharedAddress,
+    KNOWN_PARTS,
+};
+
+/// The 7-bit addresses that are not reserved by the I2C specification.
+pub const SCAN_RANGE: RangeInclusive<u8> = 0x08..=0x77;
+
+/// How an address is probed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Probe {
+    /// Address-only write. Harmless for almost every part, but a few
+    /// write-only parts interpret it.
+    Write,
+    /// One-byte read. Reading can lock up write-only parts and clears the
+    /// status of some sensors.
+    Read,
+    /// What `i2cdetect` does: reads in 0x30..=0x37 and 0x50..=0x5F, where
+    /// EEPROMs could be corrupted by a write, and writes elsewhere.
+    #[default]
+    Auto,
+}
+
+impl Probe {
+    fn reads(self, address: u8) -> bool {
+        match self {
+            Self::Write => false,
+            Self::Read => true,
+            Self::Auto => matches!(address, 0x30..=0x37 | 0x50..=0x5f),
+        }
+    }
+}
+
+/// Addresses that acknowledged, and those where the transfer failed for
+/// another reason than a missing acknowledge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ScanResult {
+    probed: u128,
+    present: u128,
+    errors: u128,
+    last_error: Option<ErrorKind>,
+}
+
+impl ScanResult {
+    pub fn is_present(&self, address: u8) -> bool {
+        address < 128 && self.present & (1 << address) != 0
+    }
+
+    pub fn addresses(&self) -> impl Iterator<Item = u8> + '_ {
+        bits(self.present)
+    }
+
+    pub fn count(&self) -> u32 {
+        self.present.count_ones()
+    }
+
+    /// Addresses that neither acknowledged nor reported a missing
+    /// acknowledge: bus errors, lost arbitration, timeouts.
+    pub fn errors(&self) -> impl Iterator<Item = u8> + '_ {
+        bits(self.errors)
+    }
+
+    pub fn last_error(&self) -> Option<ErrorKind> {
+        self.last_error
+    }
+
+    /// Every probed address failed with something other than a missing
+    /// acknowledge, which points at the bus rather than at the devices:
+    /// SDA or SCL held low, missing pull-ups, or swapped lines.
+    pub fn bus_fault(&self) -> bool {
+        self.probed != 0 && self.errors == self.probed
+    }
+}
+
+fn bits(mask: u128) -> impl Iterator<Item = u8> {
+    (0..128u8).filter(move |a| mask & (1 << a) != 0)
+}
+
+/// Probes every address in [`SCAN_RANGE`].
+pub fn scan<I: I2c>(i2c: &mut I, probe: Probe) -> ScanResult {
+    scan_range(i2c, SCAN_RANGE, probe)
+}
+
+/// Probes `range`, clamped to 7-bit addresses. Errors are recorded in the
+/// result rather than aborting the scan.
+pub fn scan_range<I: I2c>(i2c: &mut I, range: RangeInclusive<u8>, probe: Probe) -> ScanResult {
+    let mut result = ScanResult::default();
+    let (start, end) = (*range.start(), (*range.end()).min(0x7f));
+    for address in start..=end {
+        result.probed |= 1 << address;
+        let outcome = if probe.reads(address) {
+            i2c.read(address, &mut [0])
+        } else {
+            i2c.write(address, &[])
+        };
+        match outcome {
+            Ok(()) => result.present |= 1 << address,
+            Err(e) => match e.kind() {
+                ErrorKind::NoAcknowledge(_) => {}
+                kind => {
+                    result.errors |= 1 << address;
+                    result.last_error = Some(kind);
+                }
+            },
+        }
+    }
+    result
+}
@@ -0,0 +1,248 @@
+use embedded_hal::i2c::I2c;
+
+/// How sure an identification is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Confidence {
+    /// Only the address fits.
+    Address,
+    /// Bits that are always zero on this part read as zero. Parts without
+    /// an ID register, e.g. RTCs, can only be recognised this way.
+    Heuristic,
+    /// A chip ID register holds the expected value.
+    IdRegister,
+}
+
+/// One register read of an identification: `read(register) & mask` must be
+/// one of `values`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Check {
+    pub register: u8,
+    pub mask: u8,
+    pub values: &'static [u8],
+}
+
+impl Check {
+    const fn id(register: u8, values: &'static [u8]) -> Self {
+        Self {
+            register,
+            mask: 0xff,
+            values,
+        }
+    }
+
+    const fn zero(register: u8, mask: u8) -> Self {
+        Self {
+            register,
+            mask,
+            values: &[0],
+        }
+    }
+}
+
+/// A part that can be recognised on the bus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Part {
+    pub name: &'static str,
+    pub addresses: &'static [u8],
+    /// All must pass; empty for parts without readable registers.
+    pub checks: &'static [Check],
+    /// What passing the checks proves.
+    pub confidence: Confidence,
+    /// Any write changes the part's state, including the register address
+    /// byte of another part's check: it would set a port expander's outputs,
+    /// a display's command or a mux's channels.
+    pub write_sensitive: bool,
+}
+
+const fn id(name: &'static str, addresses: &'static [u8], check: &'static [Check]) -> Part {
+    Part {
+        name,
+        addresses,
+        checks: check,
+        confidence: Confidence::IdRegister,
+        write_sensitive: false,
+    }
+}
+
+const fn heuristic(name: &'static str, addresses: &'static [u8], checks: &'static [Check]) -> Part {
+    Part {
+        name,
+        addresses,
+        checks,
+        confidence: Confidence::Heuristic,
+        write_sensitive: false,
+    }
+}
+
+const fn address(name: &'static str, addresses: &'static [u8]) -> Part {
+    Part {
+        name,
+        addresses,
+        checks: &[],
+        confidence: Confidence::Address,
+        write_sensitive: false,
+    }
+}
+
+const fn write_sensitive(name: &'static str, addresses: &'static [u8]) -> Part {
+    Part {
+        write_sensitive: true,
+        ..address(name, addresses)
+    }
+}
+
+const MPU: &[u8] = &[0x68, 0x69];
+
+/// Common breakout-board parts, most specific first.
+///
+/// Some parts have no register that can be read without side effects: a
+/// write to a PCF8574 sets its outputs, and a write to a TCA9548A selects
+/// its channels. They are marked [`Part::write_sensitive`], and [`identify`]
+/// runs no checks at their addresses. The Bosch sensors at 0x76/0x77 share
+/// those with the TCA9548A, so they are only checked by [`identify_with`]
+/// when the caller knows there is no mux there.
+pub static KNOWN_PARTS: &[Part] = &[
+    // IMUs at 0x68/0x69 all have WHO_AM_I-style registers
+    id("MPU-6050", MPU, &[Check::id(0x75, &[0x68])]),
+    id("MPU-6500", MPU, &[Check::id(0x75, &[0x70])]),
+    id("MPU-9250", MPU, &[Check::id(0x75, &[0x71, 0x73])]),
+    id("ICM-20948", MPU, &[Check::id(0x00, &[0xea])]),
+    id("BMI160", MPU, &[Check::id(0x00, &[0xd1])]),
+    id("BME280", &[0x76, 0x77], &[Check::id(0xd0, &[0x60])]),
+    id(
+        "BMP280",
+        &[0x76, 0x77],
+        &[Check::id(0xd0, &[0x56, 0x57, 0x58])],
+    ),
+    id("BME680", &[0x76, 0x77], &[Check::id(0xd0, &[0x61])]),
+    id("BMP180", &[0x77], &[Check::id(0xd0, &[0x55])]),
+    id("ADXL345", &[0x1d, 0x53], &[Check::id(0x00, &[0xe5])]),
+    id("LIS3DH", &[0x18, 0x19], &[Check::id(0x0f, &[0x33])]),
+    id("HMC5883L", &[0x1e], &[Check::id(0x0a, b"H")]),
+    id("QMC5883L", &[0x0d], &[Check::id(0x0d, &[0xff])]),
+    id("VL53L0X", &[0x29], &[Check::id(0xc0, &[0xee])]),
+    id("MAX30102", &[0x57], &[Check::id(0xff, &[0x15])]),
+    id("CCS811", &[0x5a, 0x5b], &[Check::id(0x20, &[0x81])]),
+    // RTCs have no ID register; both can sit next to an MPU at 0x68, so
+    // they come after the IMUs. The DS1307's RAM at 0x0F and 0x12 often
+    // reads zero, so a DS1307 can pass as a DS3231.
+    heuristic(
+        "DS3231",
+        &[0x68],
+        &[
+            // Hours bit 7, status bits 6..4, temperature LSB bits 5..0
+            Check::zero(0x02, 0x80),
+            Check::zero(0x0f, 0x70),
+            Check::zero(0x12, 0x3f),
+        ],
+    ),
+    heuristic(
+        "DS1307",
+        &[0x68],
+        &[
+            // Hours bit 7, control bits 6, 5, 3 and 2
+            Check::zero(0x02, 0x80),
+            Check::zero(0x07, 0x6c),
+        ],
+    ),
+    address("AK8963 (inside MPU-9250)", &[0x0c]),
+    write_sensitive("PCF8574", &[0x20, 0x21, 0x22, 0x23, 0x24, 0x25, 0x26, 0x27]),
+    address(
+        "MCP23017",
+        &[0x20, 0x21, 0x22, 0x23, 0x24, 0x25, 0x26, 0x27],
+    ),
+    address("BH1750", &[0x23, 0x5c]),
+    address("AHT10/AHT20", &[0x38]),
+    write_sensitive(
+        "PCF8574A",
+        &[0x38, 0x39, 0x3a, 0x3b, 0x3c, 0x3d, 0x3e, 0x3f],
+    ),
+    write_sensitive("SSD1306/SH1106 OLED", &[0x3c, 0x3d]),
+    address("HTU21D/SHT21/Si7021", &[0x40]),
+    address(
+        "INA219",
+        &[
+            0x40, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0x4a, 0x4b, 0x4c, 0x4d,
+            0x4e, 0x4f,
+        ],
+    ),
+    address("PCA9685", &[0x40]),
+    address("SHT3x", &[0x44, 0x45]),
+    address("ADS1115", &[0x48, 0x49, 0x4a, 0x4b]),
+    address(
+        "AT24Cxx EEPROM",
+        &[0x50, 0x51, 0x52, 0x53, 0x54, 0x55, 0x56, 0x57],
+    ),
+    address("MLX90614", &[0x5a]),
+    write_sensitive(
+        "TCA9548A",
+        &[0x70, 0x71, 0x72, 0x73, 0x74, 0x75, 0x76, 0x77],
+    ),
+];
+
+/// The best match for the part at `address`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Identification {
+    pub address: u8,
+    pub part: &'static Part,
+    pub confidence: Confidence,
+}
+
+/// Every part in [`KNOWN_PARTS`] that can sit at `address`.
+pub fn candidates(address: u8) -> impl Iterator<Item = &'static Part> {
+    KNOWN_PARTS
+        .iter()
+        .filter(move |part| part.addresses.contains(&address))
+}
+
+/// What [`identify_with`] does at an address a write-sensitive part can
+/// sit at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SharedAddress {
+    /// Run no checks, identify by the address only.
+    #[default]
+    Skip,
+    /// Run the checks anyway; the caller knows no write-sensitive part is
+    /// there.
+    Check,
+}
+
+/// [`identify_with`] that never writes to an address a write-sensitive part
+/// can sit at, such as a TCA9548A at 0x70..=0x77.
+pub fn identify<I: I2c>(i2c: &mut I, address: u8) -> Option<Identification> {
+    identify_with(i2c, address, SharedAddress::Skip)
+}
+
+/// Runs the checks of the candidates at `address` in table order and
+/// returns the first part whose checks all pass. If none does, falls back to
+/// the first candidate without checks, with [`Confidence::Address`].
+///
+/// Each check is a register read, i.e. a one-byte write of the register
+/// address followed by a read; a failed transfer counts as a mismatch. With
+/// [`SharedAddress::Skip`], no check runs where a write-sensitive part may
+/// be instead.
+pub fn identify_with<I: I2c>(
+    i2c: &mut I,
+    address: u8,
+    shared: SharedAddress,
+) -> Option<Identification> {
+    let found = |part: &'static Part| Identification {
+        address,
+        part,
+        confidence: part.confidence,
+    };
+    let checks_allowed =
+        shared == SharedAddress::Check || !candidates(address).any(|part| part.write_sensitive);
+    candidates(address)
+        .filter(|part| checks_allowed && !part.checks.is_empty())
+        .find(|part| part.checks.iter().all(|check| passes(i2c, address, check)))
+        .or_else(|| candidates(address).find(|part| part.checks.is_empty()))
+        .map(found)
+}
+
+fn passes<I: I2c>(i2c: &mut I, address: u8, check: &Check) -> bool {
+    let mut value = [0];
+    i2c.write_read(address, &[check.register], &mut value)
+        .is_ok()
+        && check.values.contains(&(value[0] & check.mask))
+}
@@ -0,0 +1,105 @@
+//! Freeing a bus that a slave holds low.
+//!
+//! If the master resets in the middle of a read, the slave is still driving
+//! a 0 bit on SDA and waits for the clock edges the master never sends. The
+//! I2C peripheral then sees a busy bus and every transfer fails. Up to nine
+//! SCL pulses (eight data bits and the acknowledge) let the slave finish its
+//! byte; it releases SDA, sees no acknowledge and the STOP that follows puts
+//! it back to idle.
+//!
+//! The functions take both lines as open-drain GPIOs that can be read back,
+//! i.e. before the pins are handed to the I2C peripheral. Driving a pin high
+//! releases it; the pull-up does the rest.
+
+use embedded_hal::{
+    delay::DelayNs,
+    digital::{ErrorKind, InputPin, OutputPin},
+};
+
+/// Half an SCL period: 100 kHz, the speed every part supports.
+const HALF_PERIOD_US: u32 = 5;
+
+/// Clock pulses that always get a slave through the rest of a byte.
+pub const MAX_CLOCKS: u8 = 9;
+
+/// Line levels with both lines released.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BusState {
+    Idle,
+    /// A slave is in the middle of a transfer; [`recover`] can fix this.
+    SdaStuckLow,
+    /// No pull-up on SCL, a short, or a slave stretching the clock forever.
+    /// Clocking cannot help.
+    SclStuckLow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// SCL stayed low after being released.
+    SclStuckLow,
+    /// SDA was still low after [`MAX_CLOCKS`] pulses.
+    SdaStuckLow,
+    Pin(ErrorKind),
+}
+
+fn pin<E: embedded_hal::digital::Error>(e: E) -> Error {
+    Error::Pin(e.kind())
+}
+
+/// Releases both lines and reports their levels.
+pub fn check_bus<SCL, SDA>(scl: &mut SCL, sda: &mut SDA) -> Result<BusState, Error>
+where
+    SCL: InputPin + OutputPin,
+    SDA: InputPin + OutputPin,
+{
+    sda.set_high().map_err(pin)?;
+    scl.set_high().map_err(pin)?;
+    if scl.is_low().map_err(pin)? {
+        Ok(BusState::SclStuckLow)
+    } else if sda.is_low().map_err(pin)? {
+        Ok(BusState::SdaStuckLow)
+    } else {
+        Ok(BusState::Idle)
+    }
+}
+
+/// Clocks SCL until the slave releases SDA, then sends a STOP.
+///
+/// Returns the number of clock pulses that were needed; 0 means the bus was
+/// already idle and only the STOP was sent.
+pub fn recover<SCL, SDA, D>(scl: &mut SCL, sda: &mut SDA, delay: &mut D) -> Result<u8, Error>
+where
+    SCL: InputPin + OutputPin,
+    SDA: InputPin + OutputPin,
+    D: DelayNs,
+{
+    sda.set_high().map_err(pin)?;
+    scl.set_high().map_err(pin)?;
+    delay.delay_us(HALF_PERIOD_US);
+    if scl.is_low().map_err(pin)? {
+        return Err(Error::SclStuckLow);
+    }
+
+    let mut clocks = 0;
+    while sda.is_low().map_err(pin)? {
+        if clocks == MAX_CLOCKS {
+            return Err(Error::SdaStuckLow);
+        }
+        scl.set_low().map_err(pin)?;
+        delay.delay_us(HALF_PERIOD_US);
+        scl.set_high().map_err(pin)?;
+        delay.delay_us(HALF_PERIOD_US);
+        clocks += 1;
+    }
+
+    // STOP: SDA rises while SCL is high
+    scl.set_low().map_err(pin)?;
+    delay.delay_us(HALF_PERIOD_US);
+    sda.set_low().map_err(pin)?;
+    delay.delay_us(HALF_PERIOD_US);
+    scl.set_high().map_err(pin)?;
+    delay.delay_us(HALF_PERIOD_US);
+    sda.set_high().map_err(pin)?;
+    delay.delay_us(HALF_PERIOD_US);
+    Ok(clocks)
+}
@@ -0,0 +1,103 @@
+//! Bus recovery against scripted pin levels.
+
+use embedded_hal_mock::eh1::{
+    delay::NoopDelay,
+    digital::{Mock as PinMock, State, Transaction},
+};
+use i2c_scan::recovery::{check_bus, recover, BusState, Error, MAX_CLOCKS};
+
+fn set(state: State) -> Transaction {
+    Transaction::set(state)
+}
+
+fn get(state: State) -> Transaction {
+    Transaction::get(state)
+}
+
+/// A clock pulse on SCL; also what either line does during the STOP.
+fn pulse() -> [Transaction; 2] {
+    [set(State::Low), set(State::High)]
+}
+
+#[test]
+fn check_reports_each_state() {
+    for (scl_level, sda_level, expected) in [
+        (State::High, State::High, BusState::Idle),
+        (State::High, State::Low, BusState::SdaStuckLow),
+    ] {
+        let mut scl = PinMock::new(&[set(State::High), get(scl_level)]);
+        let mut sda = PinMock::new(&[set(State::High), get(sda_level)]);
+        assert_eq!(check_bus(&mut scl, &mut sda), Ok(expected));
+        scl.done();
+        sda.done();
+    }
+
+    let mut scl = PinMock::new(&[set(State::High), get(State::Low)]);
+    let mut sda = PinMock::new(&[set(State::High)]);
+    assert_eq!(check_bus(&mut scl, &mut sda), Ok(BusState::SclStuckLow));
+    scl.done();
+    sda.done();
+}
+
+#[test]
+fn idle_bus_only_gets_a_stop() {
+    let mut scl: Vec<_> = vec![set(State::High), get(State::High)];
+    scl.extend(pulse());
+    let mut sda: Vec<_> = vec![set(State::High), get(State::High)];
+    sda.extend(pulse());
+    let (mut scl, mut sda) = (PinMock::new(&scl), PinMock::new(&sda));
+
+    assert_eq!(recover(&mut scl, &mut sda, &mut NoopDelay), Ok(0));
+    scl.done();
+    sda.done();
+}
+
+#[test]
+fn clocks_until_sda_is_released() {
+    // The slave still had three bits to send
+    let mut scl = vec![set(State::High), get(State::High)];
+    let mut sda = vec![set(State::High)];
+    for _ in 0..3 {
+        scl.extend(pulse());
+        sda.push(get(State::Low));
+    }
+    sda.push(get(State::High));
+    scl.extend(pulse());
+    sda.extend(pulse());
+    let (mut scl, mut sda) = (PinMock::new(&scl), PinMock::new(&sda));
+
+    assert_eq!(recover(&mut scl, &mut sda, &mut NoopDelay), Ok(3));
+    scl.done();
+    sda.done();
+}
+
+#[test]
+fn gives_up_after_nine_clocks() {
+    let mut scl = vec![set(State::High), get(State::High)];
+    let mut sda = vec![set(State::High)];
+    for _ in 0..MAX_CLOCKS {
+        scl.extend(pulse());
+        sda.push(get(State::Low));
+    }
+    sda.push(get(State::Low));
+    let (mut scl, mut sda) = (PinMock::new(&scl), PinMock::new(&sda));
+
+    assert_eq!(
+        recover(&mut scl, &mut sda, &mut NoopDelay),
+        Err(Error::SdaStuckLow)
+    );
+    scl.done();
+    sda.done();
+}
+
+#[test]
+fn scl_held_low_cannot_be_recovered() {
+    let mut scl = PinMock::new(&[set(State::High), get(State::Low)]);
+    let mut sda = PinMock::new(&[set(State::High)]);
+    assert_eq!(
+        recover(&mut scl, &mut sda, &mut NoopDelay),
+        Err(Error::SclStuckLow)
+    );
+    scl.done();
+    sda.done();
+}
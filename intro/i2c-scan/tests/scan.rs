@@ -0,0 +1,210 @@
+//! Scanning and identification against scripted I2C transactions.
+
+use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction};
+use i2c_scan::{
+    candidates, identify, identify_with, scan, scan_range, Confidence, Probe, SharedAddress,
+    KNOWN_PARTS,
+};
+
+const NACK: ErrorKind = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+
+fn probe(address: u8, read: bool, result: Result<(), ErrorKind>) -> Transaction {
+    let t = if read {
+        Transaction::read(address, vec![0])
+    } else {
+        Transaction::write(address, vec![])
+    };
+    match result {
+        Ok(()) => t,
+        Err(kind) => t.with_error(kind),
+    }
+}
+
+fn read(address: u8, reg: u8, value: u8) -> Transaction {
+    Transaction::write_read(address, vec![reg], vec![value])
+}
+
+fn read_fails(address: u8, reg: u8) -> Transaction {
+    Transaction::write_read(address, vec![reg], vec![0]).with_error(NACK)
+}
+
+#[test]
+fn auto_probe_reads_eeprom_ranges_and_writes_elsewhere() {
+    let present = [0x3c, 0x50, 0x68];
+    let script: Vec<_> = (0x08..=0x77u8)
+        .map(|a| {
+            let reads = matches!(a, 0x30..=0x37 | 0x50..=0x5f);
+            probe(
+                a,
+                reads,
+                if present.contains(&a) {
+                    Ok(())
+                } else {
+                    Err(NACK)
+                },
+            )
+        })
+        .collect();
+    let mut i2c = I2cMock::new(&script);
+
+    let result = scan(&mut i2c, Probe::Auto);
+    assert_eq!(result.addresses().collect::<Vec<_>>(), present);
+    assert_eq!(result.count(), 3);
+    assert!(result.is_present(0x50));
+    assert!(!result.is_present(0x51));
+    assert_eq!(result.errors().count(), 0);
+    assert!(!result.bus_fault());
+    i2c.done();
+}
+
+#[test]
+fn other_errors_are_recorded_and_do_not_count_as_present() {
+    let mut i2c = I2cMock::new(&[
+        probe(0x10, true, Ok(())),
+        probe(0x11, true, Err(ErrorKind::ArbitrationLoss)),
+        probe(0x12, true, Err(NACK)),
+    ]);
+    let result = scan_range(&mut i2c, 0x10..=0x12, Probe::Read);
+    assert_eq!(result.addresses().collect::<Vec<_>>(), [0x10]);
+    assert_eq!(result.errors().collect::<Vec<_>>(), [0x11]);
+    assert_eq!(result.last_error(), Some(ErrorKind::ArbitrationLoss));
+    assert!(!result.bus_fault());
+    i2c.done();
+}
+
+#[test]
+fn all_addresses_failing_is_a_bus_fault() {
+    let script: Vec<_> = (0x08..=0x0b)
+        .map(|a| probe(a, false, Err(ErrorKind::Bus)))
+        .collect();
+    let mut i2c = I2cMock::new(&script);
+    let result = scan_range(&mut i2c, 0x08..=0x0b, Probe::Write);
+    assert_eq!(result.count(), 0);
+    assert!(result.bus_fault());
+    i2c.done();
+}
+
+#[test]
+fn range_is_clamped_to_seven_bits() {
+    let mut i2c = I2cMock::new(&[probe(0x7f, false, Err(NACK))]);
+    let result = scan_range(&mut i2c, 0x7f..=0xff, Probe::Write);
+    assert_eq!(result.count(), 0);
+    i2c.done();
+}
+
+#[test]
+fn bme280_and_bmp280_share_an_address_but_not_an_id() {
+    // Checked only because the caller knows no TCA9548A is at 0x76
+    let mut i2c = I2cMock::new(&[read(0x76, 0xd0, 0x60)]);
+    let id = identify_with(&mut i2c, 0x76, SharedAddress::Check).unwrap();
+    assert_eq!(id.part.name, "BME280");
+    assert_eq!(id.address, 0x76);
+    assert_eq!(id.confidence, Confidence::IdRegister);
+    i2c.done();
+
+    let mut i2c = I2cMock::new(&[read(0x76, 0xd0, 0x58), read(0x76, 0xd0, 0x58)]);
+    let id = identify_with(&mut i2c, 0x76, SharedAddress::Check).unwrap();
+    assert_eq!(id.part.name, "BMP280");
+    i2c.done();
+}
+
+#[test]
+fn ds3231_next_to_no_imu() {
+    let mut i2c = I2cMock::new(&[
+        // MPU-6050, MPU-6500, MPU-9250
+        read(0x68, 0x75, 0x12),
+        read(0x68, 0x75, 0x12),
+        read(0x68, 0x75, 0x12),
+        // ICM-20948, BMI160
+        read(0x68, 0x00, 0x45),
+        read(0x68, 0x00, 0x45),
+        // DS3231: hours, status, temperature LSB
+        read(0x68, 0x02, 0x12),
+        read(0x68, 0x0f, 0x88),
+        read(0x68, 0x12, 0x40),
+    ]);
+    let id = identify(&mut i2c, 0x68).unwrap();
+    assert_eq!(id.part.name, "DS3231");
+    assert_eq!(id.confidence, Confidence::Heuristic);
+    i2c.done();
+}
+
+#[test]
+fn ds1307_fails_the_ds3231_checks() {
+    let mut i2c = I2cMock::new(&[
+        read(0x68, 0x75, 0x00),
+        read(0x68, 0x75, 0x00),
+        read(0x68, 0x75, 0x00),
+        read(0x68, 0x00, 0x15),
+        read(0x68, 0x00, 0x15),
+        // DS3231 checks stop at the first mismatch: RAM byte at 0x0F
+        read(0x68, 0x02, 0x08),
+        read(0x68, 0x0f, 0xa5),
+        // DS1307: hours, control with SQWE set
+        read(0x68, 0x02, 0x08),
+        read(0x68, 0x07, 0x10),
+    ]);
+    let id = identify(&mut i2c, 0x68).unwrap();
+    assert_eq!(id.part.name, "DS1307");
+    assert_eq!(id.confidence, Confidence::Heuristic);
+    i2c.done();
+}
+
+#[test]
+fn failed_reads_fall_back_to_the_address() {
+    let mut i2c = I2cMock::new(&[read_fails(0x57, 0xff)]);
+    let id = identify(&mut i2c, 0x57).unwrap();
+    assert_eq!(id.part.name, "AT24Cxx EEPROM");
+    assert_eq!(id.confidence, Confidence::Address);
+    i2c.done();
+}
+
+#[test]
+fn address_only_parts_are_not_touched() {
+    let mut i2c = I2cMock::new(&[]);
+    let id = identify(&mut i2c, 0x3c).unwrap();
+    assert_eq!(id.part.name, "PCF8574A");
+    assert_eq!(
+        candidates(0x3c).map(|p| p.name).collect::<Vec<_>>(),
+        ["PCF8574A", "SSD1306/SH1106 OLED"]
+    );
+    assert_eq!(identify(&mut i2c, 0x7a), None);
+    i2c.done();
+}
+
+#[test]
+fn bosch_sensors_are_not_checked_where_a_mux_may_be() {
+    // Writing the BME280 ID register address 0xD0 to a TCA9548A would
+    // enable its channels 4, 6 and 7
+    assert!(candidates(0x77).any(|p| p.name == "TCA9548A"));
+    assert!(candidates(0x77).any(|p| p.name == "BME280"));
+    let mut i2c = I2cMock::new(&[]);
+    let id = identify(&mut i2c, 0x77).unwrap();
+    assert_eq!(id.part.name, "TCA9548A");
+    assert_eq!(id.confidence, Confidence::Address);
+    i2c.done();
+
+    // BME280, BMP280, BME680, BMP180
+    let mut i2c = I2cMock::new(&[
+        read(0x77, 0xd0, 0x55),
+        read(0x77, 0xd0, 0x55),
+        read(0x77, 0xd0, 0x55),
+        read(0x77, 0xd0, 0x55),
+    ]);
+    let id = identify_with(&mut i2c, 0x77, SharedAddress::Check).unwrap();
+    assert_eq!(id.part.name, "BMP180");
+    i2c.done();
+}
+
+#[test]
+fn nothing_is_written_where_a_write_sensitive_part_may_be() {
+    for part in KNOWN_PARTS.iter().filter(|p| p.write_sensitive) {
+        for &address in part.addresses {
+            let mut i2c = I2cMock::new(&[]);
+            let id = identify(&mut i2c, address).unwrap();
+            assert_eq!(id.confidence, Confidence::Address, "{address:#04x}");
+            i2c.done();
+        }
+    }
+}
@@ -0,0 +1,172 @@
+//! The timer ISR / main loop handshake behind the `publish_before` feature of
+//! `esp32s3-demo`'s `06_timer_blinky_interrupte` example.
+//!
+//! The ISR bumps a data word (`COUNTER`) and raises a flag (`TICK`); the main
+//! loop takes the flag and then reads the data. Whether the main loop is
+//! guaranteed to see the new data depends on two things:
+//!
+//! * the order of the two writes in the ISR ([`Publish`]), and
+//! * the memory orderings of the flag store and the flag swap ([`Orderings`]).
+//!
+//! Only "write the data, then store the flag with `Release`" paired with
+//! "swap the flag with `Acquire`, then read the data" is guaranteed to work.
+//! The `tests/loom.rs` suite checks this claim, and that every other
+//! combination can fail, by exploring all interleavings and all weak-memory
+//! behaviours with [loom](https://docs.rs/loom):
+//!
+//! ```text
+//! RUSTFLAGS="--cfg loom" cargo test --release --test loom
+//! ```
+//!
+//! Without `--cfg loom` the crate is `no_std` and uses `core` atomics, so the
+//! same code runs on the chip; `tests/handshake.rs` exercises it with plain
+//! threads.
+
+#![cfg_attr(not(loom), no_std)]
+
+#[cfg(loom)]
+use loom::sync::atomic::{AtomicBool, AtomicU32};
+
+#[cfg(not(loom))]
+use core::sync::atomic::{AtomicBool, AtomicU32};
+
+pub use core::sync::atomic::Ordering;
+
+/// Where the ISR writes the data relative to raising the flag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Publish {
+    /// Bump the counter, then store the flag (the `publish_before` feature).
+    BeforeFlag,
+    /// Store the flag, then bump the counter (the default build).
+    AfterFlag,
+}
+
+/// The orderings a store accepts; `Acquire` and `AcqRel` would make
+/// `AtomicBool::store` panic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoreOrdering {
+    Relaxed,
+    Release,
+    SeqCst,
+}
+
+impl StoreOrdering {
+    pub const fn ordering(self) -> Ordering {
+        match self {
+            Self::Relaxed => Ordering::Relaxed,
+            Self::Release => Ordering::Release,
+            Self::SeqCst => Ordering::SeqCst,
+        }
+    }
+}
+
+/// Memory orderings used for the flag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Orderings {
+    /// Ordering of the flag store in the ISR.
+    pub publish: StoreOrdering,
+    /// Ordering of the flag swap in the main loop. A swap accepts every
+    /// ordering.
+    pub consume: Ordering,
+}
+
+impl Orderings {
+    /// `Release` store, `Acquire` swap: the data written before the store is
+    /// visible after the swap.
+    pub const RELEASE_ACQUIRE: Self = Self {
+        publish: StoreOrdering::Release,
+        consume: Ordering::Acquire,
+    };
+
+    /// `Relaxed` everywhere: the flag itself is delivered, but nothing is
+    /// ordered around it.
+    pub const RELAXED: Self = Self {
+        publish: StoreOrdering::Relaxed,
+        consume: Ordering::Relaxed,
+    };
+}
+
+/// Flag plus data word shared between the timer ISR and the main loop.
+pub struct Handshake {
+    tick: AtomicBool,
+    counter: AtomicU32,
+    publish: Publish,
+    orderings: Orderings,
+}
+
+impl Handshake {
+    /// A handshake using `Release`/`Acquire` for the flag.
+    #[cfg(not(loom))]
+    pub const fn new(publish: Publish) -> Self {
+        Self::with_orderings(publish, Orderings::RELEASE_ACQUIRE)
+    }
+
+    #[cfg(not(loom))]
+    pub const fn with_orderings(publish: Publish, orderings: Orderings) -> Self {
+        Self {
+            tick: AtomicBool::new(false),
+            counter: AtomicU32::new(0),
+            publish,
+            orderings,
+        }
+    }
+
+    /// A handshake using `Release`/`Acquire` for the flag.
+    #[cfg(loom)]
+    pub fn new(publish: Publish) -> Self {
+        Self::with_orderings(publish, Orderings::RELEASE_ACQUIRE)
+    }
+
+    // loom atomics cannot be created in a `const fn`
+    #[cfg(loom)]
+    pub fn with_orderings(publish: Publish, orderings: Orderings) -> Self {
+        Self {
+            tick: AtomicBool::new(false),
+            counter: AtomicU32::new(0),
+            publish,
+            orderings,
+        }
+    }
+
+    pub fn publish(&self) -> Publish {
+        self.publish
+    }
+
+    pub fn orderings(&self) -> Orderings {
+        self.orderings
+    }
+
+    /// ISR side: bump the counter and raise the flag.
+    pub fn isr_tick(&self) {
+        match self.publish {
+            Publish::BeforeFlag => {
+                self.counter.fetch_add(1, Ordering::Relaxed);
+                self.tick.store(true, self.orderings.publish.ordering());
+            }
+            Publish::AfterFlag => {
+                self.tick.store(true, self.orderings.publish.ordering());
+                self.counter.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Main loop side: if the flag was raised, take it and return the counter
+    /// as read right after.
+    pub fn main_poll(&self) -> Option<u32> {
+        if self.tick.swap(false, self.orderings.consume) {
+            Some(self.counter.load(Ordering::Relaxed))
+        } else {
+            None
+        }
+    }
+
+    /// Main loop side: add to the counter and return the new value.
+    /// Read-modify-write operations are never lost, even `Relaxed` ones.
+    pub fn add(&self, n: u32) -> u32 {
+        self.counter.fetch_add(n, Ordering::Relaxed).wrapping_add(n)
+    }
+
+    pub fn counter(&self) -> u32 {
+        self.counter.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,86 @@
+#![cfg(not(loom))]
+
+use std::{sync::Arc, thread};
+
+use isr_handshake::{Handshake, Ordering, Orderings, Publish, StoreOrdering};
+
+// Usable in a `static`, as in the timer example
+static SHARED: Handshake = Handshake::new(Publish::BeforeFlag);
+
+#[test]
+fn flag_is_taken_once_per_tick() {
+    assert_eq!(SHARED.main_poll(), None);
+    SHARED.isr_tick();
+    assert_eq!(SHARED.main_poll(), Some(1));
+    assert_eq!(SHARED.main_poll(), None);
+    // Two ticks between polls raise the flag once but count twice
+    SHARED.isr_tick();
+    SHARED.isr_tick();
+    assert_eq!(SHARED.main_poll(), Some(3));
+    assert_eq!(SHARED.main_poll(), None);
+}
+
+#[test]
+fn main_loop_adds_to_the_counter() {
+    let shared = Handshake::new(Publish::AfterFlag);
+    assert_eq!(shared.add(10), 10);
+    shared.isr_tick();
+    assert_eq!(shared.main_poll(), Some(11));
+    assert_eq!(shared.counter(), 11);
+    assert_eq!(shared.add(u32::MAX), 10, "the counter wraps");
+}
+
+#[test]
+fn every_store_ordering_is_accepted() {
+    for publish in [
+        StoreOrdering::Relaxed,
+        StoreOrdering::Release,
+        StoreOrdering::SeqCst,
+    ] {
+        for consume in [
+            Ordering::Relaxed,
+            Ordering::Acquire,
+            Ordering::Release,
+            Ordering::AcqRel,
+            Ordering::SeqCst,
+        ] {
+            let orderings = Orderings { publish, consume };
+            let shared = Handshake::with_orderings(Publish::BeforeFlag, orderings);
+            assert_eq!(shared.orderings(), orderings);
+            shared.isr_tick();
+            assert_eq!(shared.main_poll(), Some(1), "{orderings:?}");
+        }
+    }
+    assert_eq!(StoreOrdering::Release.ordering(), Ordering::Release);
+}
+
+#[test]
+fn release_acquire_delivers_every_tick_across_threads() {
+    const TICKS: u32 = 10_000;
+    let shared = Arc::new(Handshake::new(Publish::BeforeFlag));
+    let isr = {
+        let shared = shared.clone();
+        thread::spawn(move || {
+            for _ in 0..TICKS {
+                shared.isr_tick();
+                thread::yield_now();
+            }
+        })
+    };
+
+    // The counter seen after each flag never goes backwards, and the last
+    // one after the ISR is done is the full count
+    let mut last = 0;
+    while !isr.is_finished() {
+        if let Some(counter) = shared.main_poll() {
+            assert!(counter >= last && counter >= 1);
+            last = counter;
+        }
+        thread::yield_now();
+    }
+    isr.join().unwrap();
+    if let Some(counter) = shared.main_poll() {
+        last = counter;
+    }
+    assert_eq!(last, TICKS);
+}
@@ -0,0 +1,111 @@
+//! Model-checks the ISR/main handshake. Run with:
+//!
+//! ```text
+//! RUSTFLAGS="--cfg loom" cargo test --release --test loom
+//! ```
+//!
+//! Each test runs one ISR tick on one thread and one poll of the main loop on
+//! another. loom runs the pair under every possible interleaving and every
+//! value a weak memory model allows a load to return. A test marked
+//! `should_panic` passes when loom finds at least one execution in which the
+//! main loop sees the flag but not the counter update.
+
+#![cfg(loom)]
+
+use isr_handshake::{Handshake, Ordering, Orderings, Publish, StoreOrdering};
+use loom::{sync::Arc, thread};
+
+/// Runs the ISR and the main loop concurrently and asserts that, whenever the
+/// main loop sees the flag, it also sees the counter update.
+fn check(publish: Publish, orderings: Orderings) {
+    loom::model(move || {
+        let shared = Arc::new(Handshake::with_orderings(publish, orderings));
+
+        let isr = {
+            let shared = shared.clone();
+            thread::spawn(move || shared.isr_tick())
+        };
+
+        if let Some(counter) = shared.main_poll() {
+            assert_eq!(counter, 1, "main saw the flag but not the counter");
+        }
+
+        isr.join().unwrap();
+    });
+}
+
+#[test]
+fn publish_before_with_release_acquire_is_always_visible() {
+    check(Publish::BeforeFlag, Orderings::RELEASE_ACQUIRE);
+}
+
+#[test]
+fn publish_before_with_seq_cst_is_always_visible() {
+    check(
+        Publish::BeforeFlag,
+        Orderings {
+            publish: StoreOrdering::SeqCst,
+            consume: Ordering::SeqCst,
+        },
+    );
+}
+
+#[test]
+#[should_panic(expected = "main saw the flag but not the counter")]
+fn publish_after_can_be_missed_even_with_release_acquire() {
+    // Not a weak-memory effect: main can simply run between the two writes
+    check(Publish::AfterFlag, Orderings::RELEASE_ACQUIRE);
+}
+
+#[test]
+#[should_panic(expected = "main saw the flag but not the counter")]
+fn publish_before_with_relaxed_flag_can_be_missed() {
+    check(Publish::BeforeFlag, Orderings::RELAXED);
+}
+
+#[test]
+#[should_panic(expected = "main saw the flag but not the counter")]
+fn publish_before_without_acquire_can_be_missed() {
+    check(
+        Publish::BeforeFlag,
+        Orderings {
+            publish: StoreOrdering::Release,
+            consume: Ordering::Relaxed,
+        },
+    );
+}
+
+#[test]
+#[should_panic(expected = "main saw the flag but not the counter")]
+fn publish_before_without_release_can_be_missed() {
+    check(
+        Publish::BeforeFlag,
+        Orderings {
+            publish: StoreOrdering::Relaxed,
+            consume: Ordering::Acquire,
+        },
+    );
+}
+
+#[test]
+fn main_loop_update_is_never_lost() {
+    // Main's `fetch_add(10)` races with the ISR's `fetch_add(1)`; both are
+    // read-modify-write operations, so neither update is lost.
+    loom::model(|| {
+        let shared = Arc::new(Handshake::with_orderings(
+            Publish::AfterFlag,
+            Orderings::RELAXED,
+        ));
+
+        let isr = {
+            let shared = shared.clone();
+            thread::spawn(move || shared.isr_tick())
+        };
+
+        let after = shared.add(10);
+        assert!(after == 10 || after == 11);
+
+        isr.join().unwrap();
+        assert_eq!(shared.counter(), 11);
+    });
+}
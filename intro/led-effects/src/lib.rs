@@ -0,0 +1,380 @@
+//! Frame generators for addressable LED strips: rainbow, breathing,
+//! theater chase, comet and fire.
+//!
+//! Every effect implements [`Effect`]: given a tick count, it writes one
+//! frame into a caller-provided `[RGB8; N]`. The same tick always gives the
+//! same frame ([`Fire`] uses a seeded pseudo-random generator), so frames
+//! can be snapshot-tested on the host. [`Limits`] then applies gamma
+//! correction and a global brightness cap before the frame is sent, which
+//! keeps a long strip within what its power supply can deliver.
+//!
+//! ```rust
+//! use led_effects::{Comet, Effect, Limits};
+//! use rgb::RGB8;
+//!
+//! let mut comet = Comet::new(RGB8::new(255, 255, 255), 1);
+//! let mut frame = [RGB8::default(); 4];
+//! comet.render(1, &mut frame);
+//! assert_eq!(frame[1], RGB8::new(255, 255, 255));
+//! assert_eq!(frame[0], RGB8::new(128, 128, 128));
+//!
+//! Limits { brightness: 127, gamma: false }.apply(&mut frame);
+//! assert_eq!(frame[1], RGB8::new(127, 127, 127));
+//! ```
+//!
+//! The `esp32s3-demo` crate sends the frames to a WS2812 strip.
+
+#![no_std]
+
+use rgb::RGB8;
+
+/// Common interface of all effects.
+pub trait Effect {
+    /// Writes the frame for `tick` into `frame`.
+    fn render<const N: usize>(&mut self, tick: u32, frame: &mut [RGB8; N]);
+}
+
+/// HSV to RGB with all three components in 0..=255, the hue circle split
+/// into six 43-step segments (the same conversion as `smart_leds::hsv`).
+pub fn hsv(hue: u8, sat: u8, val: u8) -> RGB8 {
+    let v = val as u16;
+    let s = sat as u16;
+    // Position within the current segment, 0..=252
+    let f = (hue as u16 * 2 % 85) * 3;
+
+    let p = (v * (255 - s) / 255) as u8;
+    let q = (v * (255 - s * f / 255) / 255) as u8;
+    let t = (v * (255 - s * (255 - f) / 255) / 255) as u8;
+    let v = val;
+    match hue {
+        0..=42 => RGB8::new(v, t, p),
+        43..=84 => RGB8::new(q, v, p),
+        85..=127 => RGB8::new(p, v, t),
+        128..=169 => RGB8::new(p, q, v),
+        170..=212 => RGB8::new(t, p, v),
+        213..=254 => RGB8::new(v, p, q),
+        255 => RGB8::new(v, t, p),
+    }
+}
+
+/// Scales every channel by `(scale + 1) / 256`, so 255 leaves the colour
+/// unchanged and 0 turns it off.
+pub fn scale(color: RGB8, scale: u8) -> RGB8 {
+    let s = scale as u16 + 1;
+    RGB8::new(
+        ((color.r as u16 * s) >> 8) as u8,
+        ((color.g as u16 * s) >> 8) as u8,
+        ((color.b as u16 * s) >> 8) as u8,
+    )
+}
+
+/// Gamma 2.8 lookup table, `round((i / 255)^2.8 * 255)`.
+#[rustfmt::skip]
+const GAMMA8: [u8; 256] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1,
+    1, 1, 1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2, 2, 2, 2,
+    2, 3, 3, 3, 3, 3, 3, 3, 4, 4, 4, 4, 4, 5, 5, 5,
+    5, 6, 6, 6, 6, 7, 7, 7, 7, 8, 8, 8, 9, 9, 9, 10,
+    10, 10, 11, 11, 11, 12, 12, 13, 13, 13, 14, 14, 15, 15, 16, 16,
+    17, 17, 18, 18, 19, 19, 20, 20, 21, 21, 22, 22, 23, 24, 24, 25,
+    25, 26, 27, 27, 28, 29, 29, 30, 31, 32, 32, 33, 34, 35, 35, 36,
+    37, 38, 39, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50, 50,
+    51, 52, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64, 66, 67, 68,
+    69, 70, 72, 73, 74, 75, 77, 78, 79, 81, 82, 83, 85, 86, 87, 89,
+    90, 92, 93, 95, 96, 98, 99, 101, 102, 104, 105, 107, 109, 110, 112, 114,
+    115, 117, 119, 120, 122, 124, 126, 127, 129, 131, 133, 135, 137, 138, 140, 142,
+    144, 146, 148, 150, 152, 154, 156, 158, 160, 162, 164, 167, 169, 171, 173, 175,
+    177, 180, 182, 184, 186, 189, 191, 193, 196, 198, 200, 203, 205, 208, 210, 213,
+    215, 218, 220, 223, 225, 228, 231, 233, 236, 239, 241, 244, 247, 249, 252, 255,
+];
+
+/// Maps each channel through a gamma 2.8 curve, so that equal steps of the
+/// input look like equal steps of brightness.
+pub fn gamma(color: RGB8) -> RGB8 {
+    RGB8::new(
+        GAMMA8[color.r as usize],
+        GAMMA8[color.g as usize],
+        GAMMA8[color.b as usize],
+    )
+}
+
+/// Output limits applied to a whole frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// Global brightness cap, 0..=255.
+    pub brightness: u8,
+    /// Apply [`gamma`] before the brightness cap.
+    pub gamma: bool,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            brightness: 255,
+            gamma: true,
+        }
+    }
+}
+
+impl Limits {
+    /// Applies the limits in place.
+    pub fn apply<const N: usize>(&self, frame: &mut [RGB8; N]) {
+        for led in frame.iter_mut() {
+            let color = if self.gamma { gamma(*led) } else { *led };
+            *led = scale(color, self.brightness);
+        }
+    }
+}
+
+/// One full hue circle across the strip, rotating with the tick.
+#[derive(Debug, Clone, Copy)]
+pub struct Rainbow {
+    /// Hue advance per tick.
+    pub speed: u8,
+    pub sat: u8,
+    pub val: u8,
+}
+
+impl Default for Rainbow {
+    fn default() -> Self {
+        Self {
+            speed: 1,
+            sat: 255,
+            val: 255,
+        }
+    }
+}
+
+impl Effect for Rainbow {
+    fn render<const N: usize>(&mut self, tick: u32, frame: &mut [RGB8; N]) {
+        let base = tick.wrapping_mul(self.speed as u32) as u8;
+        for (i, led) in frame.iter_mut().enumerate() {
+            // 256 / N apart, so the strip holds exactly one circle
+            let offset = (i * 256 / N) as u8;
+            *led = hsv(base.wrapping_add(offset), self.sat, self.val);
+        }
+    }
+}
+
+/// The whole strip in one colour, fading in and out.
+#[derive(Debug, Clone, Copy)]
+pub struct Breathe {
+    pub color: RGB8,
+    /// Ticks per breath.
+    pub period: u32,
+}
+
+impl Breathe {
+    pub fn new(color: RGB8, period: u32) -> Self {
+        Self { color, period }
+    }
+
+    /// Brightness at `tick`: a squared triangle wave, which lingers in the
+    /// dark part of the cycle like a real breath.
+    pub fn level(&self, tick: u32) -> u8 {
+        let period = self.period.max(2) as u64;
+        let phase = tick as u64 % period;
+        let half = period / 2;
+        let tri = if phase < half {
+            phase * 255 / half
+        } else {
+            (period - phase) * 255 / (period - half)
+        };
+        (tri * tri / 255) as u8
+    }
+}
+
+impl Effect for Breathe {
+    fn render<const N: usize>(&mut self, tick: u32, frame: &mut [RGB8; N]) {
+        let color = scale(self.color, self.level(tick));
+        frame.fill(color);
+    }
+}
+
+/// Every `spacing`-th LED lit, the pattern moving one LED per `step` ticks.
+#[derive(Debug, Clone, Copy)]
+pub struct TheaterChase {
+    pub color: RGB8,
+    pub spacing: usize,
+    /// Ticks per move.
+    pub step: u32,
+}
+
+impl TheaterChase {
+    pub fn new(color: RGB8) -> Self {
+        Self {
+            color,
+            spacing: 3,
+            step: 1,
+        }
+    }
+}
+
+impl Effect for TheaterChase {
+    fn render<const N: usize>(&mut self, tick: u32, frame: &mut [RGB8; N]) {
+        let spacing = self.spacing.max(1);
+        let offset = (tick / self.step.max(1)) as usize % spacing;
+        for (i, led) in frame.iter_mut().enumerate() {
+            *led = if i % spacing == offset {
+                self.color
+            } else {
+                RGB8::default()
+            };
+        }
+    }
+}
+
+/// A bright head running along the strip with a fading tail behind it.
+#[derive(Debug, Clone, Copy)]
+pub struct Comet {
+    pub color: RGB8,
+    /// Tail length, head not included.
+    pub tail: usize,
+    /// Ticks per move.
+    pub step: u32,
+}
+
+impl Comet {
+    pub fn new(color: RGB8, tail: usize) -> Self {
+        Self {
+            color,
+            tail,
+            step: 1,
+        }
+    }
+}
+
+impl Effect for Comet {
+    fn render<const N: usize>(&mut self, tick: u32, frame: &mut [RGB8; N]) {
+        frame.fill(RGB8::default());
+        if N == 0 {
+            return;
+        }
+        let head = (tick / self.step.max(1)) as usize % N;
+        for d in 0..=self.tail.min(N - 1) {
+            // Full brightness at the head, dimmer further back
+            let level = 255 - (d * 255 / (self.tail + 1)) as u8;
+            let pos = (head + N - d) % N;
+            frame[pos] = scale(self.color, level);
+        }
+    }
+}
+
+/// Most simulation steps [`Fire`] runs in one render. A render after a long
+/// pause (or with a tick that went backwards) catches up this far and no
+/// further, instead of stalling the caller for up to 2^32 steps.
+pub const MAX_CATCH_UP_STEPS: u32 = 4;
+
+/// Fire2012: every LED holds a heat value. Each tick the cells cool down,
+/// heat drifts up the strip, new sparks ignite near the bottom, and the heat
+/// is mapped to a colour.
+pub struct Fire<const N: usize> {
+    heat: [u8; N],
+    rng: XorShift32,
+    /// Cooling rate; higher gives shorter flames.
+    pub cooling: u8,
+    /// Chance of a new spark per step, 0..=255; higher gives a livelier fire.
+    pub sparking: u8,
+    last_tick: Option<u32>,
+}
+
+impl<const N: usize> Fire<N> {
+    pub fn new(seed: u32) -> Self {
+        Self {
+            heat: [0; N],
+            rng: XorShift32::new(seed),
+            cooling: 55,
+            sparking: 120,
+            last_tick: None,
+        }
+    }
+
+    /// Current heat of every cell.
+    pub fn heat(&self) -> &[u8; N] {
+        &self.heat
+    }
+
+    fn step(&mut self) {
+        // 1. Every cell cools down a little; short strips cool faster
+        let max_cooling = ((self.cooling as usize * 10) / N.max(1) + 2).min(255) as u8;
+        for h in self.heat.iter_mut() {
+            *h = h.saturating_sub(self.rng.below(max_cooling));
+        }
+        // 2. Heat drifts up
+        for k in (2..N).rev() {
+            self.heat[k] = ((self.heat[k - 1] as u16 + self.heat[k - 2] as u16 * 2) / 3) as u8;
+        }
+        // 3. Random sparks near the bottom
+        if N > 0 && self.rng.next_u8() < self.sparking {
+            let y = self.rng.below(N.min(7) as u8) as usize;
+            self.heat[y] = self.heat[y].saturating_add(160 + self.rng.below(96));
+        }
+    }
+}
+
+/// Heat to colour: black, red, yellow, white.
+pub fn heat_color(temperature: u8) -> RGB8 {
+    // Scale 0..=255 down to 0..=191: three bands of 64 levels
+    let t192 = (temperature as u16 * 191 / 255) as u8;
+    let ramp = (t192 & 0x3F) << 2;
+    if t192 & 0x80 != 0 {
+        RGB8::new(255, 255, ramp)
+    } else if t192 & 0x40 != 0 {
+        RGB8::new(255, ramp, 0)
+    } else {
+        RGB8::new(ramp, 0, 0)
+    }
+}
+
+impl<const N: usize> Effect for Fire<N> {
+    fn render<const M: usize>(&mut self, tick: u32, frame: &mut [RGB8; M]) {
+        // One step per tick since the last render, so rendering the same
+        // tick twice does not change the fire
+        let steps = match self.last_tick {
+            Some(last) => tick.wrapping_sub(last).min(MAX_CATCH_UP_STEPS),
+            None => 1,
+        };
+        for _ in 0..steps {
+            self.step();
+        }
+        self.last_tick = Some(tick);
+
+        for (led, h) in frame.iter_mut().zip(self.heat.iter()) {
+            *led = heat_color(*h);
+        }
+    }
+}
+
+/// Xorshift pseudo-random generator: the same seed gives the same sequence.
+#[derive(Debug, Clone, Copy)]
+pub struct XorShift32(u32);
+
+impl XorShift32 {
+    /// A zero seed, which would only ever produce zeros, is replaced.
+    pub fn new(seed: u32) -> Self {
+        Self(if seed == 0 { 0x2545_F491 } else { seed })
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.0 = x;
+        x
+    }
+
+    pub fn next_u8(&mut self) -> u8 {
+        (self.next_u32() >> 24) as u8
+    }
+
+    /// A number in `0..n`, or 0 when `n` is 0.
+    pub fn below(&mut self, n: u8) -> u8 {
+        if n == 0 {
+            0
+        } else {
+            ((self.next_u8() as u16 * n as u16) >> 8) as u8
+        }
+    }
+}
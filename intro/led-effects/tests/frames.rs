@@ -0,0 +1,247 @@
+use led_effects::{
+    gamma, heat_color, hsv, scale, Breathe, Comet, Effect, Fire, Limits, Rainbow, TheaterChase,
+    XorShift32, MAX_CATCH_UP_STEPS,
+};
+use rgb::RGB8;
+
+const fn rgb(r: u8, g: u8, b: u8) -> RGB8 {
+    RGB8::new(r, g, b)
+}
+
+const OFF: RGB8 = rgb(0, 0, 0);
+
+fn render<E: Effect, const N: usize>(effect: &mut E, tick: u32) -> [RGB8; N] {
+    let mut frame = [rgb(1, 2, 3); N];
+    effect.render(tick, &mut frame);
+    frame
+}
+
+#[test]
+fn hsv_primaries() {
+    assert_eq!(hsv(0, 255, 255), rgb(255, 0, 0));
+    assert_eq!(hsv(85, 255, 255), rgb(0, 255, 0));
+    assert_eq!(hsv(170, 255, 255), rgb(0, 0, 255));
+    // No saturation is grey, no value is black
+    assert_eq!(hsv(123, 0, 200), rgb(200, 200, 200));
+    assert_eq!(hsv(123, 255, 0), OFF);
+}
+
+#[test]
+fn scale_and_gamma_keep_the_ends() {
+    let color = rgb(255, 128, 7);
+    assert_eq!(scale(color, 255), color);
+    assert_eq!(scale(color, 0), OFF);
+    assert_eq!(scale(color, 127), rgb(127, 64, 3));
+    assert_eq!(gamma(rgb(0, 255, 128)), rgb(0, 255, 37));
+}
+
+#[test]
+fn rainbow_snapshots() {
+    let mut rainbow = Rainbow::default();
+    assert_eq!(
+        render::<_, 6>(&mut rainbow, 0),
+        [
+            rgb(255, 0, 0),
+            rgb(255, 252, 0),
+            rgb(0, 255, 0),
+            rgb(0, 252, 255),
+            rgb(0, 0, 255),
+            rgb(255, 0, 252),
+        ]
+    );
+    assert_eq!(
+        render::<_, 6>(&mut rainbow, 100),
+        [
+            rgb(0, 255, 90),
+            rgb(0, 168, 255),
+            rgb(90, 0, 255),
+            rgb(255, 0, 162),
+            rgb(255, 84, 0),
+            rgb(168, 255, 0),
+        ]
+    );
+    // A full turn of the hue circle is the same frame
+    assert_eq!(
+        render::<_, 6>(&mut rainbow, 356),
+        render::<_, 6>(&mut rainbow, 100)
+    );
+}
+
+#[test]
+fn breathe_levels_over_one_period() {
+    let breathe = Breathe::new(rgb(0, 120, 255), 8);
+    let levels: Vec<u8> = (0..9).map(|tick| breathe.level(tick)).collect();
+    assert_eq!(levels, [0, 15, 63, 143, 255, 143, 63, 15, 0]);
+
+    let mut breathe = breathe;
+    assert_eq!(render::<_, 3>(&mut breathe, 3), [rgb(0, 67, 143); 3]);
+}
+
+#[test]
+fn breathe_with_a_long_period_does_not_overflow() {
+    // phase * 255 no longer fits in a u32 past 16.8M ticks
+    let breathe = Breathe::new(rgb(255, 255, 255), 4_000_000_000);
+    assert_eq!(breathe.level(0), 0);
+    assert_eq!(breathe.level(1_000_000_000), 63);
+    assert_eq!(breathe.level(2_000_000_000), 255);
+    assert_eq!(breathe.level(3_000_000_000), 63);
+    assert_eq!(breathe.level(3_999_999_999), 0);
+}
+
+#[test]
+fn theater_chase_snapshots() {
+    let mut chase = TheaterChase::new(rgb(255, 80, 0));
+    let on = rgb(255, 80, 0);
+    assert_eq!(render::<_, 6>(&mut chase, 0), [on, OFF, OFF, on, OFF, OFF]);
+    assert_eq!(render::<_, 6>(&mut chase, 4), [OFF, on, OFF, OFF, on, OFF]);
+
+    chase.step = 2;
+    chase.spacing = 2;
+    assert_eq!(render::<_, 4>(&mut chase, 1), [on, OFF, on, OFF]);
+    assert_eq!(render::<_, 4>(&mut chase, 2), [OFF, on, OFF, on]);
+}
+
+#[test]
+fn comet_snapshots() {
+    let mut comet = Comet::new(rgb(200, 100, 0), 3);
+    // Head at 1, tail wrapping around the end of the strip
+    assert_eq!(
+        render::<_, 6>(&mut comet, 1),
+        [
+            rgb(150, 75, 0),
+            rgb(200, 100, 0),
+            OFF,
+            OFF,
+            rgb(50, 25, 0),
+            rgb(100, 50, 0),
+        ]
+    );
+    // A tail longer than the strip lights every LED once
+    let mut long = Comet::new(rgb(255, 255, 255), 10);
+    let frame = render::<_, 3>(&mut long, 0);
+    assert_eq!(frame[0], rgb(255, 255, 255));
+    assert!(frame.iter().all(|&c| c != OFF));
+    // An empty strip is fine
+    assert_eq!(render::<_, 0>(&mut comet, 5), []);
+}
+
+#[test]
+fn heat_color_ramps_black_red_yellow_white() {
+    let colors: Vec<RGB8> = (0..=255u8).step_by(32).map(heat_color).collect();
+    assert_eq!(
+        colors,
+        [
+            rgb(0, 0, 0),
+            rgb(92, 0, 0),
+            rgb(188, 0, 0),
+            rgb(255, 28, 0),
+            rgb(255, 124, 0),
+            rgb(255, 220, 0),
+            rgb(255, 255, 60),
+            rgb(255, 255, 156),
+        ]
+    );
+}
+
+#[test]
+fn fire_snapshots() {
+    let mut fire = Fire::<6>::new(0x1234_5678);
+    let frames: Vec<[RGB8; 6]> = (0..4).map(|tick| render(&mut fire, tick)).collect();
+    assert_eq!(
+        frames,
+        [
+            [OFF; 6],
+            [OFF, OFF, OFF, rgb(255, 255, 240), OFF, OFF],
+            [OFF, OFF, OFF, OFF, rgb(255, 255, 252), rgb(255, 196, 0)],
+            [OFF, OFF, OFF, OFF, OFF, rgb(224, 0, 0)],
+        ]
+    );
+}
+
+#[test]
+fn fire_is_deterministic_per_tick() {
+    let mut a = Fire::<16>::new(7);
+    let mut b = Fire::<16>::new(7);
+    for tick in 0..50 {
+        assert_eq!(render::<_, 16>(&mut a, tick), render::<_, 16>(&mut b, tick));
+    }
+    // Rendering the same tick again does not advance the simulation
+    let heat = *a.heat();
+    render::<_, 16>(&mut a, 49);
+    assert_eq!(*a.heat(), heat);
+}
+
+#[test]
+fn fire_catch_up_is_capped() {
+    let mut stepped = Fire::<16>::new(99);
+    let mut jumped = Fire::<16>::new(99);
+    render::<_, 16>(&mut stepped, 0);
+    render::<_, 16>(&mut jumped, 0);
+    for tick in 1..=MAX_CATCH_UP_STEPS {
+        render::<_, 16>(&mut stepped, tick);
+    }
+    // Ten thousand ticks later only runs the capped number of steps
+    render::<_, 16>(&mut jumped, 10_000);
+    assert_eq!(jumped.heat(), stepped.heat());
+
+    // So does a tick that went backwards, instead of ~4 billion steps
+    let mut backwards = Fire::<16>::new(99);
+    let mut forwards = Fire::<16>::new(99);
+    render::<_, 16>(&mut backwards, 5);
+    render::<_, 16>(&mut forwards, 5);
+    render::<_, 16>(&mut backwards, 4);
+    render::<_, 16>(&mut forwards, 5 + MAX_CATCH_UP_STEPS);
+    assert_eq!(backwards.heat(), forwards.heat());
+}
+
+#[test]
+fn fire_cooling_is_clamped_on_short_strips() {
+    // cooling * 10 / 1 + 2 = 2552, which used to wrap to 248
+    let seed = 0xBEEF;
+    let mut fire = Fire::<1>::new(seed);
+    fire.cooling = 255;
+    fire.sparking = 255;
+    render::<_, 1>(&mut fire, 0);
+    let heat = fire.heat()[0];
+    assert!(heat > 0);
+
+    // Replay the random draws of the first step: cooling, spark chance,
+    // spark position and spark heat
+    let mut rng = XorShift32::new(seed);
+    rng.next_u32();
+    rng.next_u32();
+    rng.next_u32();
+    rng.next_u32();
+    let expected = heat.saturating_sub(rng.below(255));
+
+    fire.sparking = 0;
+    render::<_, 1>(&mut fire, 1);
+    assert_eq!(fire.heat()[0], expected);
+}
+
+#[test]
+fn limits_apply_gamma_then_brightness() {
+    let mut frame = [rgb(255, 128, 64), rgb(10, 0, 255)];
+    Limits {
+        brightness: 64,
+        gamma: true,
+    }
+    .apply(&mut frame);
+    assert_eq!(frame, [rgb(64, 9, 1), rgb(0, 0, 64)]);
+
+    let mut frame = [rgb(255, 128, 64)];
+    Limits {
+        brightness: 127,
+        gamma: false,
+    }
+    .apply(&mut frame);
+    assert_eq!(frame, [rgb(127, 64, 32)]);
+
+    let mut frame = [rgb(255, 128, 64)];
+    Limits {
+        brightness: 255,
+        gamma: false,
+    }
+    .apply(&mut frame);
+    assert_eq!(frame, [rgb(255, 128, 64)]);
+}
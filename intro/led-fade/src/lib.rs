@@ -0,0 +1,295 @@
+//! Software LED fades in perceived brightness, with easing curves and queued
+//! sequences.
+//!
+//! A hardware duty fade (LEDC's `start_duty_fade`) is linear in duty cycle,
+//! but the eye is not: the low end changes too fast and the high end barely
+//! at all. A [`Fader`] instead fades the *perceived* brightness, shaped by an
+//! [`Easing`] curve, and [`gamma_correct`] turns it into a duty cycle just
+//! before output.
+//!
+//! Each fader plays a queue of [`FadeStep`]s: a step fades from wherever the
+//! previous one ended, or with [`FadeStep::hold`] jumps to its level and
+//! stays there. Steps follow each other back to back, also when
+//! [`Fader::update`] is called late, and the whole sequence can loop; a loop
+//! that fell behind by more than its length skips the loops it missed.
+//!
+//! ```rust
+//! use led_fade::{FadeStep, Fader};
+//!
+//! let mut fader: Fader<4> = Fader::new();
+//! fader.enqueue_all(&[FadeStep::new(100, 1000), FadeStep::hold(20, 500)]);
+//! assert_eq!(fader.update(0).brightness, 0.0);
+//! assert_eq!(fader.update(500).brightness, 0.5);
+//! assert_eq!(fader.update(1200).brightness, 0.2);
+//! assert!(fader.update(1500).idle);
+//! ```
+//!
+//! The faders never read a clock, the caller passes the time. The
+//! `esp32s3-demo` crate drives them on LEDC channels from a timer interrupt
+//! or an async task.
+
+#![no_std]
+
+use heapless::Deque;
+use libm::{exp2f, powf};
+
+/// A common gamma for LEDs.
+pub const DEFAULT_GAMMA: f32 = 2.2;
+
+/// Maps time progress `t` in `0..=1` to fade progress in `0..=1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Easing {
+    #[default]
+    Linear,
+    /// Slow start (quadratic).
+    EaseIn,
+    /// Slow end (quadratic).
+    EaseOut,
+    /// Slow at both ends (cubic).
+    EaseInOut,
+    /// Barely moves at first, then rushes to the end.
+    Exponential,
+}
+
+impl Easing {
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => t * (2.0 - t),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    let u = 2.0 - 2.0 * t;
+                    1.0 - u * u * u / 2.0
+                }
+            }
+            Easing::Exponential => {
+                if t <= 0.0 {
+                    0.0
+                } else {
+                    exp2f(10.0 * (t - 1.0))
+                }
+            }
+        }
+    }
+}
+
+/// Perceived brightness to duty cycle, both in `0..=1`.
+pub fn gamma_correct(brightness: f32, gamma: f32) -> f32 {
+    powf(brightness.clamp(0.0, 1.0), gamma)
+}
+
+/// [`gamma_correct`] scaled to a PWM channel's `max_duty`, rounded.
+pub fn duty_for(brightness: f32, gamma: f32, max_duty: u16) -> u16 {
+    (gamma_correct(brightness, gamma) * max_duty as f32 + 0.5) as u16
+}
+
+/// One step of a sequence: fade to `target_pct` over `duration_ms`, or with
+/// `hold`, jump to `target_pct` and stay there for `duration_ms`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FadeStep {
+    /// Perceived brightness, 0-100.
+    pub target_pct: u8,
+    pub duration_ms: u32,
+    pub easing: Easing,
+    /// No fade: the whole step is at the target brightness.
+    pub hold: bool,
+}
+
+impl FadeStep {
+    pub const fn new(target_pct: u8, duration_ms: u32) -> Self {
+        Self {
+            target_pct,
+            duration_ms,
+            easing: Easing::Linear,
+            hold: false,
+        }
+    }
+
+    pub const fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// Jumps to `target_pct` and holds it for `duration_ms`, whatever level
+    /// the previous step ended at.
+    pub const fn hold(target_pct: u8, duration_ms: u32) -> Self {
+        Self {
+            hold: true,
+            ..Self::new(target_pct, duration_ms)
+        }
+    }
+
+    fn target(&self) -> f32 {
+        self.target_pct.min(100) as f32 / 100.0
+    }
+}
+
+// The step being played
+#[derive(Debug, Clone, Copy)]
+struct Running {
+    step: FadeStep,
+    from: f32,
+    start_ms: u64,
+}
+
+/// The result of one [`Fader::update`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FadeUpdate {
+    /// Perceived brightness, `0..=1`.
+    pub brightness: f32,
+    /// A step ended during this update.
+    pub step_finished: bool,
+    /// The whole sequence has been played.
+    pub idle: bool,
+}
+
+/// One channel: the current brightness and a sequence of up to `Q` steps.
+pub struct Fader<const Q: usize> {
+    brightness: f32,
+    running: Option<Running>,
+    queue: Deque<FadeStep, Q>,
+    // Each finished step goes back to the end of the queue
+    repeat: bool,
+}
+
+impl<const Q: usize> Fader<Q> {
+    pub const fn new() -> Self {
+        Self {
+            brightness: 0.0,
+            running: None,
+            queue: Deque::new(),
+            repeat: false,
+        }
+    }
+
+    pub fn brightness(&self) -> f32 {
+        self.brightness
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.running.is_none() && self.queue.is_empty()
+    }
+
+    /// Sets the brightness at once and clears the sequence.
+    pub fn set(&mut self, brightness_pct: u8) {
+        self.stop();
+        self.brightness = brightness_pct.min(100) as f32 / 100.0;
+    }
+
+    /// Clears the sequence, staying at the current brightness.
+    pub fn stop(&mut self) {
+        self.running = None;
+        self.queue.clear();
+        self.repeat = false;
+    }
+
+    /// Drops the sequence and starts `step` now, from the current
+    /// brightness.
+    pub fn start(&mut self, step: FadeStep, now_ms: u64) {
+        self.stop();
+        self.begin(step, now_ms);
+    }
+
+    /// Appends a step; hands it back if the queue is full.
+    pub fn enqueue(&mut self, step: FadeStep) -> Result<(), FadeStep> {
+        self.queue.push_back(step)
+    }
+
+    /// Appends steps until the queue is full; returns how many went in.
+    pub fn enqueue_all(&mut self, steps: &[FadeStep]) -> usize {
+        steps
+            .iter()
+            .take_while(|&&step| self.enqueue(step).is_ok())
+            .count()
+    }
+
+    /// Loops the sequence.
+    pub fn set_repeat(&mut self, repeat: bool) {
+        self.repeat = repeat;
+    }
+
+    fn begin(&mut self, step: FadeStep, now_ms: u64) {
+        // A hold starts at its target, so the level never changes
+        let from = if step.hold {
+            step.target()
+        } else {
+            self.brightness
+        };
+        self.running = Some(Running {
+            step,
+            from,
+            start_ms: now_ms,
+        });
+    }
+
+    // A looping sequence that fell more than a whole loop behind skips the
+    // loops it missed, so it comes back in phase in one update
+    fn skip_whole_loops(&mut self, now_ms: u64) {
+        let Some(running) = self.running.as_mut() else {
+            return;
+        };
+        if !self.repeat {
+            return;
+        }
+        let loop_ms: u64 = running.step.duration_ms as u64
+            + self.queue.iter().map(|s| s.duration_ms as u64).sum::<u64>();
+        let elapsed = now_ms.saturating_sub(running.start_ms);
+        if loop_ms > 0 && elapsed >= loop_ms {
+            running.start_ms += elapsed / loop_ms * loop_ms;
+        }
+    }
+
+    /// Brightness at `now_ms`, moving on through the sequence as steps end.
+    pub fn update(&mut self, now_ms: u64) -> FadeUpdate {
+        let mut step_finished = false;
+        self.skip_whole_loops(now_ms);
+
+        // A late update may finish several short steps
+        for _ in 0..=Q {
+            if self.running.is_none() {
+                match self.queue.pop_front() {
+                    Some(step) => self.begin(step, now_ms),
+                    None => break,
+                }
+            }
+
+            let running = self.running.unwrap();
+            let elapsed = now_ms.saturating_sub(running.start_ms);
+            let target = running.step.target();
+            if elapsed < running.step.duration_ms as u64 {
+                let t = elapsed as f32 / running.step.duration_ms as f32;
+                let progress = running.step.easing.apply(t);
+                self.brightness = running.from + (target - running.from) * progress;
+                break;
+            }
+
+            // The next step starts when this one ended, not now
+            self.brightness = target;
+            self.running = None;
+            step_finished = true;
+            if self.repeat {
+                let _ = self.queue.push_back(running.step);
+            }
+            let end_ms = running.start_ms + running.step.duration_ms as u64;
+            if let Some(next) = self.queue.pop_front() {
+                self.begin(next, end_ms);
+            }
+        }
+
+        FadeUpdate {
+            brightness: self.brightness,
+            step_finished,
+            idle: self.is_idle(),
+        }
+    }
+}
+
+impl<const Q: usize> Default for Fader<Q> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
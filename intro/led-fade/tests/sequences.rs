@@ -0,0 +1,196 @@
+use led_fade::{duty_for, gamma_correct, Easing, FadeStep, Fader, DEFAULT_GAMMA};
+
+const EASINGS: [Easing; 5] = [
+    Easing::Linear,
+    Easing::EaseIn,
+    Easing::EaseOut,
+    Easing::EaseInOut,
+    Easing::Exponential,
+];
+
+fn close(a: f32, b: f32) -> bool {
+    (a - b).abs() < 1e-4
+}
+
+fn levels<const Q: usize>(fader: &mut Fader<Q>, times: &[u64]) -> Vec<f32> {
+    times.iter().map(|&t| fader.update(t).brightness).collect()
+}
+
+#[test]
+fn easings_start_at_0_end_at_1_and_never_go_back() {
+    for easing in EASINGS {
+        assert_eq!(easing.apply(0.0), 0.0);
+        assert!(close(easing.apply(1.0), 1.0), "{easing:?}");
+        let mut last = 0.0;
+        for i in 0..=1000 {
+            let p = easing.apply(i as f32 / 1000.0);
+            assert!((0.0..=1.0).contains(&p) && p >= last, "{easing:?} at {i}");
+            last = p;
+        }
+        // Outside 0..=1 is clamped
+        assert_eq!(easing.apply(-1.0), easing.apply(0.0));
+        assert_eq!(easing.apply(2.0), easing.apply(1.0));
+    }
+}
+
+#[test]
+fn easing_shapes() {
+    assert!(close(Easing::Linear.apply(0.25), 0.25));
+    assert!(close(Easing::EaseIn.apply(0.5), 0.25));
+    assert!(close(Easing::EaseOut.apply(0.5), 0.75));
+    assert!(close(Easing::EaseInOut.apply(0.5), 0.5));
+    assert!(close(Easing::EaseInOut.apply(0.25), 0.0625));
+    assert!(close(Easing::EaseInOut.apply(0.75), 0.9375));
+    assert!(close(Easing::Exponential.apply(0.5), 1.0 / 32.0));
+    assert!(Easing::Exponential.apply(0.1) < 0.002);
+}
+
+#[test]
+fn gamma_darkens_the_middle_only() {
+    assert_eq!(gamma_correct(0.0, DEFAULT_GAMMA), 0.0);
+    assert_eq!(gamma_correct(1.0, DEFAULT_GAMMA), 1.0);
+    // Half perceived brightness is about a fifth of the duty cycle
+    assert!(close(gamma_correct(0.5, DEFAULT_GAMMA), 0.217_637_64));
+    assert!(close(gamma_correct(0.5, 1.0), 0.5));
+    assert_eq!(gamma_correct(1.5, 2.0), 1.0);
+    assert_eq!(gamma_correct(-0.5, 2.0), 0.0);
+}
+
+#[test]
+fn duty_scales_and_rounds() {
+    assert_eq!(duty_for(0.0, DEFAULT_GAMMA, 8191), 0);
+    assert_eq!(duty_for(1.0, DEFAULT_GAMMA, 8191), 8191);
+    assert_eq!(duty_for(0.5, 2.0, 100), 25);
+    assert_eq!(duty_for(0.5, 1.0, 255), 128);
+    // Monotonic at 13 bits
+    let duties: Vec<u16> = (0..=100)
+        .map(|p| duty_for(p as f32 / 100.0, DEFAULT_GAMMA, 8191))
+        .collect();
+    assert!(duties.windows(2).all(|w| w[0] <= w[1]));
+}
+
+#[test]
+fn fade_follows_its_easing() {
+    let mut fader: Fader<2> = Fader::new();
+    fader.start(FadeStep::new(100, 1000).with_easing(Easing::EaseIn), 0);
+    let got = levels(&mut fader, &[0, 250, 500, 1000]);
+    assert!(
+        close(got[1], 0.0625) && close(got[2], 0.25) && got[3] == 1.0,
+        "{got:?}"
+    );
+}
+
+#[test]
+fn fade_starts_from_the_current_brightness() {
+    let mut fader: Fader<2> = Fader::new();
+    fader.set(80);
+    fader.start(FadeStep::new(40, 400), 1000);
+    let got = levels(&mut fader, &[1000, 1100, 1200, 1400]);
+    let want = [0.8, 0.7, 0.6, 0.4];
+    assert!(got.iter().zip(want).all(|(&g, w)| close(g, w)), "{got:?}");
+}
+
+#[test]
+fn hold_jumps_to_its_level_and_stays_there() {
+    let mut fader: Fader<4> = Fader::new();
+    fader.enqueue_all(&[
+        FadeStep::new(100, 100),
+        FadeStep::hold(20, 100),
+        FadeStep::new(0, 100),
+    ]);
+    let got = levels(&mut fader, &[0, 50, 100, 101, 150, 199, 200, 250, 300]);
+    let want = [0.0, 0.5, 0.2, 0.2, 0.2, 0.2, 0.2, 0.1, 0.0];
+    assert!(got.iter().zip(want).all(|(&g, w)| close(g, w)), "{got:?}");
+    assert!(fader.is_idle());
+}
+
+#[test]
+fn hold_at_the_same_level_is_a_pause() {
+    let mut fader: Fader<4> = Fader::new();
+    fader.enqueue_all(&[
+        FadeStep::new(100, 100),
+        FadeStep::hold(100, 300),
+        FadeStep::new(0, 100),
+    ]);
+    let got = levels(&mut fader, &[0, 100, 250, 399, 450])[1..].to_vec();
+    assert_eq!(got[..3], [1.0, 1.0, 1.0]);
+    assert!(close(got[3], 0.5));
+}
+
+#[test]
+fn steps_report_their_end_and_idle() {
+    let mut fader: Fader<4> = Fader::new();
+    assert!(fader.update(0).idle);
+    fader.enqueue_all(&[FadeStep::new(50, 100), FadeStep::new(0, 100)]);
+    let a = fader.update(0);
+    assert!(!a.step_finished && !a.idle);
+    let b = fader.update(100);
+    assert!(b.step_finished && !b.idle);
+    assert!(!fader.update(150).step_finished);
+    let c = fader.update(200);
+    assert!(c.step_finished && c.idle);
+}
+
+#[test]
+fn late_update_keeps_the_sequence_on_schedule() {
+    let mut fader: Fader<4> = Fader::new();
+    fader.enqueue_all(&[
+        FadeStep::new(100, 100),
+        FadeStep::hold(100, 100),
+        FadeStep::new(0, 200),
+    ]);
+    fader.update(0);
+    // Two steps over and 100 ms into the third, as if updated on time
+    let update = fader.update(300);
+    assert!(update.step_finished);
+    assert!(close(update.brightness, 0.5));
+}
+
+#[test]
+fn repeat_loops_the_sequence_forever() {
+    let mut fader: Fader<4> = Fader::new();
+    fader.enqueue_all(&[
+        FadeStep::new(100, 100),
+        FadeStep::hold(100, 100),
+        FadeStep::new(0, 100),
+        FadeStep::hold(0, 100),
+    ]);
+    fader.set_repeat(true);
+    fader.update(0);
+    for cycle in 0..50u64 {
+        let base = cycle * 400;
+        let got = levels(&mut fader, &[base + 50, base + 150, base + 250, base + 350]);
+        let want = [0.5, 1.0, 0.5, 0.0];
+        assert!(
+            got.iter().zip(want).all(|(&g, w)| close(g, w)),
+            "cycle {cycle}: {got:?}"
+        );
+        assert!(!fader.is_idle());
+    }
+
+    // A long stall still comes back in phase
+    assert!(close(fader.update(100_000 + 50).brightness, 0.5));
+    assert!(close(fader.update(100_000 + 150).brightness, 1.0));
+}
+
+#[test]
+fn queue_limits_and_stop() {
+    let mut fader: Fader<2> = Fader::new();
+    let steps = [
+        FadeStep::new(10, 10),
+        FadeStep::new(20, 10),
+        FadeStep::new(30, 10),
+    ];
+    assert_eq!(fader.enqueue_all(&steps), 2);
+    assert_eq!(fader.enqueue(steps[2]), Err(steps[2]));
+
+    fader.update(0);
+    fader.update(5);
+    fader.stop();
+    assert!(fader.is_idle());
+    let level = fader.brightness();
+    assert_eq!(fader.update(1000).brightness, level);
+
+    fader.set(150);
+    assert_eq!(fader.brightness(), 1.0);
+}
@@ -0,0 +1,203 @@
+//! Driver over a blocking [`embedded_hal::i2c::I2c`] bus.
+
+use embedded_hal::{delay::DelayNs, i2c::I2c};
+
+use crate::{
+    bits, register, Calibrator, Config, Error, FifoConfig, Gravity, InterruptPinConfig, Interrupts,
+    Measurement, Offsets, RawSample, FIFO_SIZE, RESET_DELAY_MS, WHO_AM_I_VALUE,
+};
+
+/// MPU-6050 on a blocking I2C bus.
+pub struct Mpu6050<I2C> {
+    i2c: I2C,
+    address: u8,
+    config: Config,
+    offsets: Offsets,
+    fifo: FifoConfig,
+}
+
+impl<I2C: I2c> Mpu6050<I2C> {
+    /// Does not touch the bus; call [`Mpu6050::init`] next.
+    pub fn new(i2c: I2C, address: u8) -> Self {
+        Self {
+            i2c,
+            address,
+            config: Config::default(),
+            offsets: Offsets::default(),
+            fifo: FifoConfig::default(),
+        }
+    }
+
+    /// Checks WHO_AM_I, wakes the device up and applies `config`.
+    pub fn init(&mut self, config: Config) -> Result<(), Error<I2C::Error>> {
+        let id = self.who_am_i()?;
+        if id != WHO_AM_I_VALUE {
+            return Err(Error::WrongDevice(id));
+        }
+        self.write_register(register::PWR_MGMT_1, bits::CLOCK_PLL_X)?;
+        self.set_config(config)
+    }
+
+    /// Resets every register to its power-on value (the device is then
+    /// asleep) and waits until it is ready again.
+    pub fn reset(&mut self, delay: &mut impl DelayNs) -> Result<(), Error<I2C::Error>> {
+        self.write_register(register::PWR_MGMT_1, bits::DEVICE_RESET)?;
+        delay.delay_ms(RESET_DELAY_MS);
+        self.config = Config::default();
+        self.fifo = FifoConfig::default();
+        Ok(())
+    }
+
+    pub fn who_am_i(&mut self) -> Result<u8, Error<I2C::Error>> {
+        let mut id = [0];
+        self.read_registers(register::WHO_AM_I, &mut id)?;
+        Ok(id[0])
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: Config) -> Result<(), Error<I2C::Error>> {
+        for (reg, value) in config.register_writes() {
+            self.write_register(reg, value)?;
+        }
+        self.config = config;
+        Ok(())
+    }
+
+    pub fn offsets(&self) -> &Offsets {
+        &self.offsets
+    }
+
+    pub fn set_offsets(&mut self, offsets: Offsets) {
+        self.offsets = offsets;
+    }
+
+    /// Reads accelerometer, temperature and gyro in one burst, without
+    /// offsets.
+    pub fn read_raw(&mut self) -> Result<RawSample, Error<I2C::Error>> {
+        let mut bytes = [0; 14];
+        self.read_registers(register::ACCEL_XOUT_H, &mut bytes)?;
+        Ok(RawSample::from_bytes(&bytes))
+    }
+
+    /// Reads one sample, subtracts the offsets and converts it to physical
+    /// units.
+    pub fn read(&mut self) -> Result<Measurement, Error<I2C::Error>> {
+        let raw = self.read_raw()?;
+        Ok(self.config.convert(&self.offsets.apply(&raw)))
+    }
+
+    pub fn read_temperature(&mut self) -> Result<f32, Error<I2C::Error>> {
+        let mut bytes = [0; 2];
+        self.read_registers(register::TEMP_OUT_H, &mut bytes)?;
+        Ok(crate::temperature_c(i16::from_be_bytes(bytes)))
+    }
+
+    /// Averages `samples` readings, one per sample period, into new offsets
+    /// and starts using them. The sensor must be at rest with `gravity`
+    /// describing which way is up.
+    pub fn calibrate(
+        &mut self,
+        delay: &mut impl DelayNs,
+        samples: u16,
+        gravity: Gravity,
+    ) -> Result<Offsets, Error<I2C::Error>> {
+        let mut calibrator = Calibrator::new();
+        for _ in 0..samples.max(1) {
+            delay.delay_us(self.config.sample_period_us());
+            calibrator.add(&self.read_raw()?);
+        }
+        // At least one sample was added
+        let offsets = calibrator
+            .offsets(self.config.accel_range, gravity)
+            .unwrap_or_default();
+        self.offsets = offsets;
+        Ok(offsets)
+    }
+
+    /// Configures the INT pin and enables `interrupts`.
+    pub fn enable_interrupts(
+        &mut self,
+        pin: InterruptPinConfig,
+        interrupts: Interrupts,
+    ) -> Result<(), Error<I2C::Error>> {
+        self.write_register(register::INT_PIN_CFG, pin.bits())?;
+        self.write_register(register::INT_ENABLE, interrupts.bits())
+    }
+
+    /// Reads and clears the interrupt status.
+    pub fn interrupt_status(&mut self) -> Result<Interrupts, Error<I2C::Error>> {
+        let mut status = [0];
+        self.read_registers(register::INT_STATUS, &mut status)?;
+        Ok(Interrupts::from_bits(status[0]))
+    }
+
+    /// Resets the FIFO and starts storing the measurements in `fifo`.
+    pub fn enable_fifo(&mut self, fifo: FifoConfig) -> Result<(), Error<I2C::Error>> {
+        self.write_register(register::FIFO_EN, 0)?;
+        self.write_register(register::USER_CTRL, bits::USER_FIFO_RESET)?;
+        self.write_register(register::FIFO_EN, fifo.bits())?;
+        self.write_register(register::USER_CTRL, bits::USER_FIFO_EN)?;
+        self.fifo = fifo;
+        Ok(())
+    }
+
+    pub fn disable_fifo(&mut self) -> Result<(), Error<I2C::Error>> {
+        self.write_register(register::FIFO_EN, 0)?;
+        self.write_register(register::USER_CTRL, 0)?;
+        self.fifo = FifoConfig::default();
+        Ok(())
+    }
+
+    /// Number of bytes waiting in the FIFO.
+    pub fn fifo_count(&mut self) -> Result<u16, Error<I2C::Error>> {
+        let mut bytes = [0; 2];
+        self.read_registers(register::FIFO_COUNT_H, &mut bytes)?;
+        Ok(u16::from_be_bytes(bytes))
+    }
+
+    /// Reads as many whole frames as are waiting and fit in `buf`, in one
+    /// burst, and returns the number of bytes read. Decode them with
+    /// [`FifoConfig::frames`].
+    ///
+    /// A full FIFO has lost samples and may be misaligned, so it is reset
+    /// and [`Error::FifoOverflow`] is returned.
+    pub fn read_fifo(&mut self, buf: &mut [u8]) -> Result<usize, Error<I2C::Error>> {
+        let frame_len = self.fifo.frame_len();
+        if frame_len == 0 || buf.len() < frame_len {
+            return Err(Error::FifoDisabled);
+        }
+        let count = self.fifo_count()?;
+        if count >= FIFO_SIZE {
+            self.enable_fifo(self.fifo)?;
+            return Err(Error::FifoOverflow);
+        }
+        let len = (count as usize).min(buf.len()) / frame_len * frame_len;
+        if len > 0 {
+            self.read_registers(register::FIFO_R_W, &mut buf[..len])?;
+        }
+        Ok(len)
+    }
+
+    pub fn fifo_config(&self) -> &FifoConfig {
+        &self.fifo
+    }
+
+    pub fn release(self) -> I2C {
+        self.i2c
+    }
+
+    fn write_register(&mut self, reg: u8, value: u8) -> Result<(), Error<I2C::Error>> {
+        self.i2c
+            .write(self.address, &[reg, value])
+            .map_err(Error::I2c)
+    }
+
+    fn read_registers(&mut self, reg: u8, buf: &mut [u8]) -> Result<(), Error<I2C::Error>> {
+        self.i2c
+            .write_read(self.address, &[reg], buf)
+            .map_err(Error::I2c)
+    }
+}
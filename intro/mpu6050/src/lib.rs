@@ -0,0 +1,514 @@
+//! `no_std` driver for the InvenSense MPU-6050 6-axis IMU.
+//!
+//! The driver comes in two flavours with the same methods:
+//! [`blocking::Mpu6050`] over [`embedded_hal::i2c::I2c`] and
+//! [`asynch::Mpu6050`] over [`embedded_hal_async::i2c::I2c`]. Everything
+//! that does not touch the bus — register encoding, unit conversion,
+//! calibration and FIFO frame parsing — lives in this module and is shared by
+//! both.
+//!
+//! * Gyro and accelerometer full-scale ranges, the digital low-pass filter
+//!   and the sample rate are set through [`Config`].
+//! * [`Measurement`] is in g, degrees per second and degrees Celsius;
+//!   [`RawSample`] keeps the raw ADC counts.
+//! * [`Calibrator`] averages samples taken at rest into software
+//!   [`Offsets`], which the driver subtracts from every reading.
+//! * The FIFO is read in bursts of whole frames; [`FifoConfig`] selects
+//!   which sensors are stored and [`FifoConfig::parse`] decodes a frame.
+//! * The INT pin can signal data-ready and FIFO overflow
+//!   ([`InterruptPinConfig`], [`Interrupts`]).
+//!
+//! ```rust
+//! use mpu6050::{AccelRange, Config, RawSample};
+//!
+//! let config = Config {
+//!     accel_range: AccelRange::G4,
+//!     ..Config::default()
+//! };
+//! // +1 g on Z at ±4 g is 8192 counts; 0 counts of temperature is 36.53 °C
+//! let raw = RawSample {
+//!     accel: [0, 0, 8192],
+//!     temperature: 0,
+//!     gyro: [131, 0, 0],
+//! };
+//! let m = config.convert(&raw);
+//! assert_eq!(m.accel_g, [0.0, 0.0, 1.0]);
+//! assert_eq!(m.gyro_dps, [1.0, 0.0, 0.0]);
+//! assert!((m.temperature_c - 36.53).abs() < 1e-3);
+//! ```
+
+#![no_std]
+
+pub mod asynch;
+pub mod blocking;
+
+/// I2C address with AD0 tied low.
+pub const ADDRESS_AD0_LOW: u8 = 0x68;
+/// I2C address with AD0 tied high.
+pub const ADDRESS_AD0_HIGH: u8 = 0x69;
+
+/// Value of the WHO_AM_I register (independent of AD0).
+pub const WHO_AM_I_VALUE: u8 = 0x68;
+
+/// Size of the hardware FIFO in bytes.
+pub const FIFO_SIZE: u16 = 1024;
+
+/// Time the device needs after a reset before it answers again.
+pub const RESET_DELAY_MS: u32 = 100;
+
+/// Register addresses, from the MPU-6000/6050 register map (RM-MPU-6000A).
+pub mod register {
+    pub const SMPLRT_DIV: u8 = 0x19;
+    pub const CONFIG: u8 = 0x1A;
+    pub const GYRO_CONFIG: u8 = 0x1B;
+    pub const ACCEL_CONFIG: u8 = 0x1C;
+    pub const FIFO_EN: u8 = 0x23;
+    pub const INT_PIN_CFG: u8 = 0x37;
+    pub const INT_ENABLE: u8 = 0x38;
+    pub const INT_STATUS: u8 = 0x3A;
+    pub const ACCEL_XOUT_H: u8 = 0x3B;
+    pub const TEMP_OUT_H: u8 = 0x41;
+    pub const GYRO_XOUT_H: u8 = 0x43;
+    pub const USER_CTRL: u8 = 0x6A;
+    pub const PWR_MGMT_1: u8 = 0x6B;
+    pub const FIFO_COUNT_H: u8 = 0x72;
+    pub const FIFO_R_W: u8 = 0x74;
+    pub const WHO_AM_I: u8 = 0x75;
+}
+
+pub(crate) mod bits {
+    pub const DEVICE_RESET: u8 = 0x80;
+    /// Awake, clocked from the X gyro PLL (recommended over the internal
+    /// oscillator).
+    pub const CLOCK_PLL_X: u8 = 0x01;
+    pub const USER_FIFO_EN: u8 = 0x40;
+    pub const USER_FIFO_RESET: u8 = 0x04;
+}
+
+/// Gyroscope full-scale range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GyroRange {
+    /// ±250 °/s
+    #[default]
+    Dps250,
+    /// ±500 °/s
+    Dps500,
+    /// ±1000 °/s
+    Dps1000,
+    /// ±2000 °/s
+    Dps2000,
+}
+
+impl GyroRange {
+    /// FS_SEL, as written to GYRO_CONFIG.
+    pub fn bits(self) -> u8 {
+        (self as u8) << 3
+    }
+
+    /// Counts per degree per second.
+    pub fn sensitivity(self) -> f32 {
+        match self {
+            Self::Dps250 => 131.0,
+            Self::Dps500 => 65.5,
+            Self::Dps1000 => 32.8,
+            Self::Dps2000 => 16.4,
+        }
+    }
+}
+
+/// Accelerometer full-scale range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AccelRange {
+    /// ±2 g
+    #[default]
+    G2,
+    /// ±4 g
+    G4,
+    /// ±8 g
+    G8,
+    /// ±16 g
+    G16,
+}
+
+impl AccelRange {
+    /// AFS_SEL, as written to ACCEL_CONFIG.
+    pub fn bits(self) -> u8 {
+        (self as u8) << 3
+    }
+
+    /// Counts per g.
+    pub fn sensitivity(self) -> f32 {
+        (16384 >> self as u8) as f32
+    }
+}
+
+/// Digital low-pass filter bandwidth (accelerometer / gyroscope).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dlpf {
+    /// 260 / 256 Hz, no filtering; the gyro is sampled at 8 kHz.
+    Hz260,
+    /// 184 / 188 Hz
+    Hz184,
+    /// 94 / 98 Hz
+    Hz94,
+    /// 44 / 42 Hz
+    #[default]
+    Hz44,
+    /// 21 / 20 Hz
+    Hz21,
+    /// 10 Hz
+    Hz10,
+    /// 5 Hz
+    Hz5,
+}
+
+impl Dlpf {
+    /// DLPF_CFG, as written to CONFIG.
+    pub fn bits(self) -> u8 {
+        self as u8
+    }
+
+    /// Gyro output rate that the sample rate divider divides.
+    pub fn gyro_output_rate_hz(self) -> u32 {
+        match self {
+            Self::Hz260 => 8000,
+            _ => 1000,
+        }
+    }
+}
+
+/// Measurement configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    pub gyro_range: GyroRange,
+    pub accel_range: AccelRange,
+    pub dlpf: Dlpf,
+    /// Sample rate = gyro output rate / (1 + divider).
+    pub sample_rate_divider: u8,
+}
+
+impl Default for Config {
+    /// ±250 °/s, ±2 g, 44 Hz bandwidth, 100 Hz sample rate.
+    fn default() -> Self {
+        Self {
+            gyro_range: GyroRange::default(),
+            accel_range: AccelRange::default(),
+            dlpf: Dlpf::default(),
+            sample_rate_divider: 9,
+        }
+    }
+}
+
+impl Config {
+    /// Rate at which the data registers and the FIFO are updated.
+    pub fn sample_rate_hz(&self) -> u32 {
+        self.dlpf.gyro_output_rate_hz() / (1 + self.sample_rate_divider as u32)
+    }
+
+    /// Time between two samples, rounded up.
+    pub fn sample_period_us(&self) -> u32 {
+        1_000_000u32.div_ceil(self.sample_rate_hz())
+    }
+
+    /// (register, value) pairs that apply this configuration.
+    pub fn register_writes(&self) -> [(u8, u8); 4] {
+        [
+            (register::SMPLRT_DIV, self.sample_rate_divider),
+            (register::CONFIG, self.dlpf.bits()),
+            (register::GYRO_CONFIG, self.gyro_range.bits()),
+            (register::ACCEL_CONFIG, self.accel_range.bits()),
+        ]
+    }
+
+    /// Converts raw counts to physical units.
+    pub fn convert(&self, raw: &RawSample) -> Measurement {
+        let accel = self.accel_range.sensitivity();
+        let gyro = self.gyro_range.sensitivity();
+        Measurement {
+            accel_g: raw.accel.map(|v| v as f32 / accel),
+            gyro_dps: raw.gyro.map(|v| v as f32 / gyro),
+            temperature_c: temperature_c(raw.temperature),
+        }
+    }
+}
+
+/// Converts a TEMP_OUT reading to degrees Celsius.
+pub fn temperature_c(raw: i16) -> f32 {
+    raw as f32 / 340.0 + 36.53
+}
+
+/// One sample in raw ADC counts, X/Y/Z order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RawSample {
+    pub accel: [i16; 3],
+    pub temperature: i16,
+    pub gyro: [i16; 3],
+}
+
+impl RawSample {
+    /// Decodes the 14 bytes starting at ACCEL_XOUT_H.
+    pub fn from_bytes(bytes: &[u8; 14]) -> Self {
+        let word = |i: usize| i16::from_be_bytes([bytes[i], bytes[i + 1]]);
+        Self {
+            accel: [word(0), word(2), word(4)],
+            temperature: word(6),
+            gyro: [word(8), word(10), word(12)],
+        }
+    }
+}
+
+/// One sample in physical units.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Measurement {
+    pub accel_g: [f32; 3],
+    pub gyro_dps: [f32; 3],
+    pub temperature_c: f32,
+}
+
+/// Zero-rate and zero-g offsets in raw counts, subtracted from every
+/// reading. They are only valid for the ranges they were measured with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Offsets {
+    pub accel: [i16; 3],
+    pub gyro: [i16; 3],
+}
+
+impl Offsets {
+    pub fn apply(&self, raw: &RawSample) -> RawSample {
+        let sub = |v: [i16; 3], o: [i16; 3]| {
+            [
+                v[0].saturating_sub(o[0]),
+                v[1].saturating_sub(o[1]),
+                v[2].saturating_sub(o[2]),
+            ]
+        };
+        RawSample {
+            accel: sub(raw.accel, self.accel),
+            temperature: raw.temperature,
+            gyro: sub(raw.gyro, self.gyro),
+        }
+    }
+}
+
+/// Which sensor axis points up (reads +1 g) while calibrating.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Gravity {
+    XUp,
+    XDown,
+    YUp,
+    YDown,
+    #[default]
+    ZUp,
+    ZDown,
+}
+
+impl Gravity {
+    /// Expected accelerometer reading in g.
+    pub fn expected_g(self) -> [f32; 3] {
+        match self {
+            Self::XUp => [1.0, 0.0, 0.0],
+            Self::XDown => [-1.0, 0.0, 0.0],
+            Self::YUp => [0.0, 1.0, 0.0],
+            Self::YDown => [0.0, -1.0, 0.0],
+            Self::ZUp => [0.0, 0.0, 1.0],
+            Self::ZDown => [0.0, 0.0, -1.0],
+        }
+    }
+}
+
+/// Averages samples taken while the sensor is at rest.
+///
+/// The gyro offset is the mean reading; the accelerometer offset is the mean
+/// minus the 1 g that gravity contributes along the axis given to
+/// [`Calibrator::offsets`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Calibrator {
+    accel: [i64; 3],
+    gyro: [i64; 3],
+    count: u32,
+}
+
+impl Calibrator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, raw: &RawSample) {
+        for axis in 0..3 {
+            self.accel[axis] += raw.accel[axis] as i64;
+            self.gyro[axis] += raw.gyro[axis] as i64;
+        }
+        self.count += 1;
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Offsets for `range`, or `None` if no samples were added.
+    pub fn offsets(&self, range: AccelRange, gravity: Gravity) -> Option<Offsets> {
+        if self.count == 0 {
+            return None;
+        }
+        let n = self.count as i64;
+        // Rounded to the nearest count, halves away from zero
+        let mean = |sum: i64| {
+            let half = if sum < 0 { -n / 2 } else { n / 2 };
+            ((sum + half) / n).clamp(i16::MIN as i64, i16::MAX as i64) as i16
+        };
+        let one_g = range.sensitivity();
+        let expected = gravity.expected_g();
+        Some(Offsets {
+            accel: [0, 1, 2]
+                .map(|axis| mean(self.accel[axis]).saturating_sub((expected[axis] * one_g) as i16)),
+            gyro: self.gyro.map(mean),
+        })
+    }
+}
+
+/// Which measurements are written to the FIFO, in register order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FifoConfig {
+    pub accel: bool,
+    pub temperature: bool,
+    pub gyro: bool,
+}
+
+impl FifoConfig {
+    /// Accelerometer and gyro, 12 bytes per frame.
+    pub const ACCEL_GYRO: Self = Self {
+        accel: true,
+        temperature: false,
+        gyro: true,
+    };
+
+    /// Value of the FIFO_EN register.
+    pub fn bits(&self) -> u8 {
+        let mut bits = 0;
+        if self.temperature {
+            bits |= 0x80;
+        }
+        if self.gyro {
+            // XG, YG, ZG
+            bits |= 0x70;
+        }
+        if self.accel {
+            bits |= 0x08;
+        }
+        bits
+    }
+
+    /// Bytes per FIFO frame (0 if nothing is enabled).
+    pub fn frame_len(&self) -> usize {
+        6 * self.accel as usize + 2 * self.temperature as usize + 6 * self.gyro as usize
+    }
+
+    /// Decodes one frame; measurements that are not in the FIFO are 0.
+    /// Returns `None` if `frame` is shorter than [`FifoConfig::frame_len`].
+    pub fn parse(&self, frame: &[u8]) -> Option<RawSample> {
+        if frame.len() < self.frame_len() {
+            return None;
+        }
+        let mut words = frame
+            .as_chunks::<2>()
+            .0
+            .iter()
+            .map(|&w| i16::from_be_bytes(w));
+        let mut next = || words.next().unwrap_or(0);
+        let mut sample = RawSample::default();
+        if self.accel {
+            sample.accel = [next(), next(), next()];
+        }
+        if self.temperature {
+            sample.temperature = next();
+        }
+        if self.gyro {
+            sample.gyro = [next(), next(), next()];
+        }
+        Some(sample)
+    }
+
+    /// Iterates over the complete frames in `bytes`.
+    pub fn frames<'a>(&self, bytes: &'a [u8]) -> FifoFrames<'a> {
+        FifoFrames {
+            config: *self,
+            chunks: bytes.chunks_exact(self.frame_len().max(1)),
+        }
+    }
+}
+
+/// Iterator returned by [`FifoConfig::frames`].
+pub struct FifoFrames<'a> {
+    config: FifoConfig,
+    chunks: core::slice::ChunksExact<'a, u8>,
+}
+
+impl Iterator for FifoFrames<'_> {
+    type Item = RawSample;
+
+    fn next(&mut self) -> Option<RawSample> {
+        if self.config.frame_len() == 0 {
+            return None;
+        }
+        self.config.parse(self.chunks.next()?)
+    }
+}
+
+/// Electrical behaviour of the INT pin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct InterruptPinConfig {
+    pub active_low: bool,
+    pub open_drain: bool,
+    /// Hold the pin active until the interrupt is cleared, instead of a 50 µs
+    /// pulse.
+    pub latch: bool,
+    /// Clear the interrupt on any register read, not only INT_STATUS.
+    pub clear_on_any_read: bool,
+}
+
+impl InterruptPinConfig {
+    /// Value of the INT_PIN_CFG register.
+    pub fn bits(&self) -> u8 {
+        (self.active_low as u8) << 7
+            | (self.open_drain as u8) << 6
+            | (self.latch as u8) << 5
+            | (self.clear_on_any_read as u8) << 4
+    }
+}
+
+/// Interrupt sources, for INT_ENABLE and INT_STATUS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Interrupts {
+    pub data_ready: bool,
+    pub fifo_overflow: bool,
+}
+
+impl Interrupts {
+    pub const DATA_READY: Self = Self {
+        data_ready: true,
+        fifo_overflow: false,
+    };
+
+    pub fn bits(&self) -> u8 {
+        (self.fifo_overflow as u8) << 4 | self.data_ready as u8
+    }
+
+    pub fn from_bits(bits: u8) -> Self {
+        Self {
+            data_ready: bits & 0x01 != 0,
+            fifo_overflow: bits & 0x10 != 0,
+        }
+    }
+}
+
+/// Driver error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error<E> {
+    I2c(E),
+    /// WHO_AM_I returned this value instead of [`WHO_AM_I_VALUE`].
+    WrongDevice(u8),
+    /// The FIFO filled up and samples were lost; it has been reset.
+    FifoOverflow,
+    /// The FIFO is not enabled, or the buffer cannot hold one frame.
+    FifoDisabled,
+}
@@ -0,0 +1,382 @@
+//! Drives both flavours of the driver against scripted I2C transactions.
+
+use embassy_futures::block_on;
+use embedded_hal::i2c::ErrorKind;
+use embedded_hal_mock::eh1::{
+    delay::{CheckedDelay, NoopDelay, Transaction as DelayTransaction},
+    i2c::{Mock as I2cMock, Transaction},
+};
+use mpu6050::{
+    asynch, blocking, register, AccelRange, Config, Dlpf, Error, FifoConfig, Gravity, GyroRange,
+    InterruptPinConfig, Interrupts, Offsets, RawSample, ADDRESS_AD0_HIGH, ADDRESS_AD0_LOW,
+};
+
+const ADDR: u8 = ADDRESS_AD0_LOW;
+
+fn write(reg: u8, value: u8) -> Transaction {
+    Transaction::write(ADDR, vec![reg, value])
+}
+
+fn read(reg: u8, response: &[u8]) -> Transaction {
+    Transaction::write_read(ADDR, vec![reg], response.to_vec())
+}
+
+/// Encodes a sample the way the data registers (and the FIFO) hold it.
+fn sample_bytes(accel: [i16; 3], temperature: i16, gyro: [i16; 3]) -> Vec<u8> {
+    accel
+        .iter()
+        .chain([temperature].iter())
+        .chain(gyro.iter())
+        .flat_map(|v| v.to_be_bytes())
+        .collect()
+}
+
+fn init_script(config: &Config) -> Vec<Transaction> {
+    let mut script = vec![
+        read(register::WHO_AM_I, &[0x68]),
+        write(register::PWR_MGMT_1, 0x01),
+    ];
+    script.extend(
+        config
+            .register_writes()
+            .iter()
+            .map(|&(reg, value)| write(reg, value)),
+    );
+    script
+}
+
+#[test]
+fn init_wakes_up_and_applies_the_configuration() {
+    let config = Config {
+        gyro_range: GyroRange::Dps1000,
+        accel_range: AccelRange::G8,
+        dlpf: Dlpf::Hz21,
+        sample_rate_divider: 4,
+    };
+    let script = [
+        read(register::WHO_AM_I, &[0x68]),
+        write(register::PWR_MGMT_1, 0x01),
+        write(register::SMPLRT_DIV, 4),
+        write(register::CONFIG, 0x04),
+        write(register::GYRO_CONFIG, 0x10),
+        write(register::ACCEL_CONFIG, 0x10),
+    ];
+    let mut imu = blocking::Mpu6050::new(I2cMock::new(&script), ADDR);
+    imu.init(config).unwrap();
+    assert_eq!(imu.config(), &config);
+    assert_eq!(config.sample_rate_hz(), 200);
+    imu.release().done();
+}
+
+#[test]
+fn init_rejects_other_devices() {
+    let script = [Transaction::write_read(
+        ADDRESS_AD0_HIGH,
+        vec![register::WHO_AM_I],
+        vec![0x70],
+    )];
+    let mut imu = blocking::Mpu6050::new(I2cMock::new(&script), ADDRESS_AD0_HIGH);
+    assert_eq!(
+        imu.init(Config::default()).unwrap_err(),
+        Error::WrongDevice(0x70)
+    );
+    imu.release().done();
+}
+
+#[test]
+fn bus_errors_are_passed_through() {
+    let script = [read(register::WHO_AM_I, &[0]).with_error(ErrorKind::Other)];
+    let mut imu = blocking::Mpu6050::new(I2cMock::new(&script), ADDR);
+    assert_eq!(imu.who_am_i().unwrap_err(), Error::I2c(ErrorKind::Other));
+    imu.release().done();
+}
+
+#[test]
+fn reset_waits_for_the_device() {
+    let script = [write(register::PWR_MGMT_1, 0x80)];
+    let mut delay = CheckedDelay::new(&[DelayTransaction::blocking_delay_ms(100)]);
+    let mut imu = blocking::Mpu6050::new(I2cMock::new(&script), ADDR);
+    imu.reset(&mut delay).unwrap();
+    delay.done();
+    imu.release().done();
+}
+
+#[test]
+fn converts_to_physical_units_and_subtracts_offsets() {
+    let config = Config {
+        gyro_range: GyroRange::Dps500,
+        accel_range: AccelRange::G4,
+        ..Config::default()
+    };
+    let mut script = init_script(&config);
+    // 0.5 g, -1 g, 2 g; 340 counts above 36.53 °C; 10, -20, 0.5 °/s at 65.5 counts per °/s
+    let bytes = sample_bytes([4096 + 10, -8192 + 10, 16384 + 10], 340, [655, -1310, 33]);
+    script.push(read(register::ACCEL_XOUT_H, &bytes));
+    script.push(read(register::TEMP_OUT_H, &[0xFF, 0xAC]));
+
+    let mut imu = blocking::Mpu6050::new(I2cMock::new(&script), ADDR);
+    imu.init(config).unwrap();
+    imu.set_offsets(Offsets {
+        accel: [10, 10, 10],
+        gyro: [0, 0, 0],
+    });
+    let m = imu.read().unwrap();
+    assert_eq!(m.accel_g, [0.5, -1.0, 2.0]);
+    assert!((m.gyro_dps[0] - 10.0).abs() < 1e-4);
+    assert!((m.gyro_dps[1] + 20.0).abs() < 1e-4);
+    assert!((m.gyro_dps[2] - 0.5038).abs() < 1e-3);
+    assert!((m.temperature_c - 37.53).abs() < 1e-3);
+
+    // -84 counts is 36.28 °C
+    let t = imu.read_temperature().unwrap();
+    assert!((t - 36.283).abs() < 1e-3, "{t}");
+    imu.release().done();
+}
+
+#[test]
+fn calibration_averages_samples_at_rest() {
+    let config = Config::default();
+    let mut script = init_script(&config);
+    // Z up at ±2 g reads about 16384 + bias
+    let samples = [
+        ([100, -50, 16384 + 200], [12, -7, 3]),
+        ([102, -52, 16384 + 198], [14, -9, 3]),
+        ([98, -48, 16384 + 202], [10, -5, 2]),
+        ([100, -50, 16384 + 200], [12, -7, 4]),
+    ];
+    for (accel, gyro) in samples {
+        script.push(read(register::ACCEL_XOUT_H, &sample_bytes(accel, 0, gyro)));
+    }
+    let mut imu = blocking::Mpu6050::new(I2cMock::new(&script), ADDR);
+    imu.init(config).unwrap();
+    let offsets = imu.calibrate(&mut NoopDelay, 4, Gravity::ZUp).unwrap();
+    assert_eq!(
+        offsets,
+        Offsets {
+            accel: [100, -50, 200],
+            gyro: [12, -7, 3],
+        }
+    );
+    assert_eq!(imu.offsets(), &offsets);
+    imu.release().done();
+}
+
+#[test]
+fn calibrator_handles_other_orientations_and_rounding() {
+    let mut calibrator = mpu6050::Calibrator::new();
+    assert_eq!(
+        calibrator.offsets(AccelRange::G2, Gravity::ZUp),
+        None,
+        "no samples yet"
+    );
+    // X pointing down at ±8 g: -4096 counts of gravity
+    for gyro_x in [-1, -2] {
+        calibrator.add(&RawSample {
+            accel: [-4096 + 5, 0, 0],
+            temperature: 0,
+            gyro: [gyro_x, 0, 0],
+        });
+    }
+    let offsets = calibrator.offsets(AccelRange::G8, Gravity::XDown).unwrap();
+    assert_eq!(offsets.accel, [5, 0, 0]);
+    // -1.5 rounds away from zero
+    assert_eq!(offsets.gyro, [-2, 0, 0]);
+}
+
+#[test]
+fn fifo_is_read_in_whole_frames() {
+    let fifo = FifoConfig::ACCEL_GYRO;
+    assert_eq!(fifo.bits(), 0x78);
+    assert_eq!(fifo.frame_len(), 12);
+
+    let frame = |n: i16| {
+        let mut bytes = sample_bytes([n, n + 1, n + 2], 0, [n + 3, n + 4, n + 5]);
+        // Temperature is not in this FIFO
+        bytes.drain(6..8);
+        bytes
+    };
+    let burst: Vec<u8> = frame(10).into_iter().chain(frame(20)).collect();
+    let script = [
+        write(register::FIFO_EN, 0),
+        write(register::USER_CTRL, 0x04),
+        write(register::FIFO_EN, 0x78),
+        write(register::USER_CTRL, 0x40),
+        // 30 bytes waiting: two whole frames and part of a third
+        read(register::FIFO_COUNT_H, &[0, 30]),
+        read(register::FIFO_R_W, &burst),
+    ];
+    let mut imu = blocking::Mpu6050::new(I2cMock::new(&script), ADDR);
+    imu.enable_fifo(fifo).unwrap();
+    let mut buf = [0; 64];
+    let len = imu.read_fifo(&mut buf).unwrap();
+    assert_eq!(len, 24);
+    let samples: Vec<RawSample> = fifo.frames(&buf[..len]).collect();
+    assert_eq!(
+        samples,
+        vec![
+            RawSample {
+                accel: [10, 11, 12],
+                temperature: 0,
+                gyro: [13, 14, 15],
+            },
+            RawSample {
+                accel: [20, 21, 22],
+                temperature: 0,
+                gyro: [23, 24, 25],
+            },
+        ]
+    );
+    imu.release().done();
+}
+
+#[test]
+fn fifo_read_is_limited_by_the_buffer() {
+    let fifo = FifoConfig {
+        accel: false,
+        temperature: true,
+        gyro: true,
+    };
+    assert_eq!(fifo.bits(), 0xF0);
+    let frame = [0x01, 0x54, 0, 1, 0, 2, 0, 3];
+    let script = [
+        write(register::FIFO_EN, 0),
+        write(register::USER_CTRL, 0x04),
+        write(register::FIFO_EN, 0xF0),
+        write(register::USER_CTRL, 0x40),
+        read(register::FIFO_COUNT_H, &[0x01, 0x00]),
+        read(register::FIFO_R_W, &frame),
+    ];
+    let mut imu = blocking::Mpu6050::new(I2cMock::new(&script), ADDR);
+    imu.enable_fifo(fifo).unwrap();
+    // Room for one 8-byte frame only
+    let mut buf = [0; 12];
+    assert_eq!(imu.read_fifo(&mut buf).unwrap(), 8);
+    let sample = fifo.parse(&buf[..8]).unwrap();
+    assert_eq!(sample.temperature, 340);
+    assert_eq!(sample.gyro, [1, 2, 3]);
+    assert_eq!(sample.accel, [0, 0, 0]);
+    imu.release().done();
+}
+
+#[test]
+fn full_fifo_is_reset_and_reported() {
+    let fifo = FifoConfig::ACCEL_GYRO;
+    let enable = [
+        write(register::FIFO_EN, 0),
+        write(register::USER_CTRL, 0x04),
+        write(register::FIFO_EN, 0x78),
+        write(register::USER_CTRL, 0x40),
+    ];
+    let mut script = enable.to_vec();
+    script.push(read(register::FIFO_COUNT_H, &[0x04, 0x00]));
+    script.extend(enable);
+    let mut imu = blocking::Mpu6050::new(I2cMock::new(&script), ADDR);
+    imu.enable_fifo(fifo).unwrap();
+    let mut buf = [0; 48];
+    assert_eq!(imu.read_fifo(&mut buf).unwrap_err(), Error::FifoOverflow);
+    assert_eq!(imu.fifo_config(), &fifo);
+    imu.release().done();
+}
+
+#[test]
+fn fifo_must_be_enabled() {
+    let mut imu = blocking::Mpu6050::new(I2cMock::new(&[]), ADDR);
+    let mut buf = [0; 48];
+    assert_eq!(imu.read_fifo(&mut buf).unwrap_err(), Error::FifoDisabled);
+    imu.release().done();
+}
+
+#[test]
+fn data_ready_interrupt() {
+    let pin = InterruptPinConfig {
+        active_low: false,
+        open_drain: false,
+        latch: true,
+        clear_on_any_read: true,
+    };
+    let script = [
+        write(register::INT_PIN_CFG, 0x30),
+        write(register::INT_ENABLE, 0x01),
+        read(register::INT_STATUS, &[0x11]),
+        read(register::INT_STATUS, &[0x00]),
+    ];
+    let mut imu = blocking::Mpu6050::new(I2cMock::new(&script), ADDR);
+    imu.enable_interrupts(pin, Interrupts::DATA_READY).unwrap();
+    assert_eq!(
+        imu.interrupt_status().unwrap(),
+        Interrupts {
+            data_ready: true,
+            fifo_overflow: true,
+        }
+    );
+    assert_eq!(imu.interrupt_status().unwrap(), Interrupts::default());
+    imu.release().done();
+}
+
+#[test]
+fn async_driver_runs_the_same_transactions() {
+    let config = Config::default();
+    let mut script = init_script(&config);
+    script.push(write(register::INT_PIN_CFG, 0x80));
+    script.push(write(register::INT_ENABLE, 0x01));
+    script.push(read(
+        register::ACCEL_XOUT_H,
+        &sample_bytes([0, 0, 16384], 0, [131, 0, 0]),
+    ));
+    script.extend([
+        write(register::FIFO_EN, 0),
+        write(register::USER_CTRL, 0x04),
+        write(register::FIFO_EN, 0x78),
+        write(register::USER_CTRL, 0x40),
+        read(register::FIFO_COUNT_H, &[0, 12]),
+        read(register::FIFO_R_W, &[0; 12]),
+    ]);
+
+    let mut imu = asynch::Mpu6050::new(I2cMock::new(&script), ADDR);
+    block_on(async {
+        imu.init(config).await.unwrap();
+        let pin = InterruptPinConfig {
+            active_low: true,
+            ..InterruptPinConfig::default()
+        };
+        imu.enable_interrupts(pin, Interrupts::DATA_READY)
+            .await
+            .unwrap();
+        let m = imu.read().await.unwrap();
+        assert_eq!(m.accel_g, [0.0, 0.0, 1.0]);
+        assert_eq!(m.gyro_dps, [1.0, 0.0, 0.0]);
+        imu.enable_fifo(FifoConfig::ACCEL_GYRO).await.unwrap();
+        let mut buf = [0; 24];
+        assert_eq!(imu.read_fifo(&mut buf).await.unwrap(), 12);
+    });
+    imu.release().done();
+}
+
+#[test]
+fn async_reset_and_calibration() {
+    let config = Config::default();
+    let mut script = vec![write(register::PWR_MGMT_1, 0x80)];
+    script.extend(init_script(&config));
+    for _ in 0..2 {
+        script.push(read(
+            register::ACCEL_XOUT_H,
+            &sample_bytes([0, 0, 16384 - 16], 0, [-3, 0, 0]),
+        ));
+    }
+    // 100 Hz: one sample every 10 ms
+    let mut delay = CheckedDelay::new(&[
+        DelayTransaction::async_delay_ms(100),
+        DelayTransaction::async_delay_us(10_000),
+        DelayTransaction::async_delay_us(10_000),
+    ]);
+    let mut imu = asynch::Mpu6050::new(I2cMock::new(&script), ADDR);
+    block_on(async {
+        imu.reset(&mut delay).await.unwrap();
+        imu.init(config).await.unwrap();
+        let offsets = imu.calibrate(&mut delay, 2, Gravity::ZUp).await.unwrap();
+        assert_eq!(offsets.accel, [0, 0, -16]);
+        assert_eq!(offsets.gyro, [-3, 0, 0]);
+    });
+    delay.done();
+    imu.release().done();
+}
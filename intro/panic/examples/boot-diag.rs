@@ -0,0 +1,30 @@
+#![no_std]
+#![no_main]
+
+use esp_hal::{delay::Delay, main, system::software_reset};
+use esp_println::println;
+
+esp_bootloader_esp_idf::esp_app_desc!();
+
+// Record a summary of the panic in RTC memory, then reset. The summary is
+// reported by `boot_diag::init()` after the reboot.
+#[panic_handler]
+fn panic(info: &core::panic::PanicInfo) -> ! {
+    println!("PANIC: {}", info);
+    boot_diag::record_panic(info);
+    software_reset()
+}
+
+#[main]
+fn main() -> ! {
+    esp_hal::init(esp_hal::Config::default());
+
+    // Why did we boot?
+    let report = boot_diag::init();
+    report.print();
+
+    let delay = Delay::new();
+    delay.delay_millis(5000);
+
+    panic!("boot #{} panicked on purpose", report.boot_count);
+}
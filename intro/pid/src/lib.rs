@@ -0,0 +1,225 @@
+//! A `no_std` PID controller for closed-loop speed and position control.
+//!
+//! The controller is the textbook parallel form
+//!
+//! ```text
+//! u = kp·e + ki·∫e dt − kd·d(measurement)/dt + feed_forward
+//! ```
+//!
+//! with the refinements a motor loop needs in practice:
+//!
+//! * the derivative acts on the measurement rather than the error, so a
+//!   setpoint step does not produce a "derivative kick";
+//! * the derivative is low-pass filtered, because encoder speed estimates
+//!   are noisy;
+//! * the output is clamped to `[output_min, output_max]` (e.g. ±100 % duty),
+//!   and the integral stops growing while the output is saturated
+//!   (conditional integration), so the loop does not overshoot after a long
+//!   saturation ("integral windup").
+//!
+//! The controller does not read a clock: pass the time since the previous
+//! update to [`Pid::update`]. The `tests/plant.rs` suite closes the loop
+//! around a simulated DC motor on the host.
+//!
+//! ```rust
+//! use pid::{Pid, PidConfig};
+//!
+//! let mut pid = Pid::new(PidConfig {
+//!     kp: 0.5,
+//!     ki: 2.0,
+//!     ..PidConfig::default()
+//! })
+//! .unwrap();
+//! let duty = pid.update(100.0, 80.0, 0.01);
+//! assert!(duty > 0.0);
+//! ```
+
+#![no_std]
+
+/// Controller gains and limits.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PidConfig {
+    pub kp: f32,
+    /// Integral gain, per second.
+    pub ki: f32,
+    /// Derivative gain, in seconds.
+    pub kd: f32,
+    pub output_min: f32,
+    pub output_max: f32,
+    /// Time constant of the derivative low-pass filter in seconds; 0
+    /// disables the filter.
+    pub derivative_filter_s: f32,
+}
+
+impl Default for PidConfig {
+    /// All gains zero, output limited to ±100.
+    fn default() -> Self {
+        Self {
+            kp: 0.0,
+            ki: 0.0,
+            kd: 0.0,
+            output_min: -100.0,
+            output_max: 100.0,
+            derivative_filter_s: 0.0,
+        }
+    }
+}
+
+/// Why a configuration was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigError {
+    /// A gain or time constant is negative or not finite.
+    InvalidGain,
+    /// `output_min` is not below `output_max`.
+    OutputRange,
+}
+
+impl PidConfig {
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let valid = |x: f32| x.is_finite() && x >= 0.0;
+        if !(valid(self.kp) && valid(self.ki) && valid(self.kd) && valid(self.derivative_filter_s))
+        {
+            return Err(ConfigError::InvalidGain);
+        }
+        if self.output_min >= self.output_max
+            || self.output_min.is_nan()
+            || self.output_max.is_nan()
+        {
+            return Err(ConfigError::OutputRange);
+        }
+        Ok(())
+    }
+}
+
+/// The contribution of each term to the last output, for tuning.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Terms {
+    pub proportional: f32,
+    pub integral: f32,
+    pub derivative: f32,
+    pub feed_forward: f32,
+}
+
+/// PID controller state.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pid {
+    config: PidConfig,
+    /// Integral term (already multiplied by `ki`), so gain changes do not
+    /// make the output jump.
+    integral: f32,
+    last_measurement: Option<f32>,
+    /// Filtered derivative of the measurement.
+    derivative: f32,
+    feed_forward: f32,
+    terms: Terms,
+    output: f32,
+}
+
+impl Pid {
+    pub fn new(config: PidConfig) -> Result<Self, ConfigError> {
+        config.validate()?;
+        Ok(Self {
+            config,
+            integral: 0.0,
+            last_measurement: None,
+            derivative: 0.0,
+            feed_forward: 0.0,
+            terms: Terms::default(),
+            output: 0.0,
+        })
+    }
+
+    pub fn config(&self) -> &PidConfig {
+        &self.config
+    }
+
+    /// Changes gains and limits without resetting the state. The integral
+    /// is clamped to the new output range.
+    pub fn set_config(&mut self, config: PidConfig) -> Result<(), ConfigError> {
+        config.validate()?;
+        self.config = config;
+        self.integral = self.clamp(self.integral);
+        Ok(())
+    }
+
+    /// An output added to every update, e.g. the duty that a model says the
+    /// setpoint needs. The PID terms then only correct the model's error.
+    pub fn set_feed_forward(&mut self, feed_forward: f32) {
+        self.feed_forward = feed_forward;
+    }
+
+    /// Forgets the integral and the derivative history, e.g. after the motor
+    /// was disabled.
+    pub fn reset(&mut self) {
+        self.integral = 0.0;
+        self.last_measurement = None;
+        self.derivative = 0.0;
+        self.terms = Terms::default();
+        self.output = 0.0;
+    }
+
+    /// Starts from `output` instead of 0 (bumpless transfer from manual
+    /// control): the integral is preloaded so the next update continues
+    /// near `output`.
+    pub fn preload(&mut self, output: f32) {
+        self.reset();
+        self.integral = self.clamp(output - self.feed_forward);
+        self.output = self.clamp(output);
+    }
+
+    /// Last output.
+    pub fn output(&self) -> f32 {
+        self.output
+    }
+
+    /// Contribution of each term to the last output.
+    pub fn terms(&self) -> Terms {
+        self.terms
+    }
+
+    /// Runs one control step and returns the new output. `dt_s` is the time
+    /// since the previous update; a non-positive or non-finite `dt_s`
+    /// returns the previous output unchanged.
+    pub fn update(&mut self, setpoint: f32, measurement: f32, dt_s: f32) -> f32 {
+        if !(dt_s > 0.0 && dt_s.is_finite()) {
+            return self.output;
+        }
+        let config = self.config;
+        let error = setpoint - measurement;
+
+        // Derivative on measurement, low-pass filtered
+        if let Some(last) = self.last_measurement {
+            let raw = (measurement - last) / dt_s;
+            let alpha = dt_s / (config.derivative_filter_s + dt_s);
+            self.derivative += alpha * (raw - self.derivative);
+        }
+        self.last_measurement = Some(measurement);
+
+        let proportional = config.kp * error;
+        let derivative = -config.kd * self.derivative;
+        let unclamped_without_integral = proportional + derivative + self.feed_forward;
+
+        // Conditional integration: only integrate if that does not push an
+        // already saturated output further into saturation
+        let candidate = self.integral + config.ki * error * dt_s;
+        let output = unclamped_without_integral + candidate;
+        let pushing_up = output > config.output_max && error > 0.0;
+        let pushing_down = output < config.output_min && error < 0.0;
+        if !(pushing_up || pushing_down) {
+            self.integral = self.clamp(candidate);
+        }
+
+        self.terms = Terms {
+            proportional,
+            integral: self.integral,
+            derivative,
+            feed_forward: self.feed_forward,
+        };
+        self.output = self.clamp(unclamped_without_integral + self.integral);
+        self.output
+    }
+
+    fn clamp(&self, value: f32) -> f32 {
+        value.clamp(self.config.output_min, self.config.output_max)
+    }
+}
@@ -0,0 +1,251 @@
+//! Closes the loop around a simulated brushed DC motor.
+//!
+//! The motor is modelled as a first-order system: at duty `u` (in %) its
+//! speed approaches `GAIN·u − load` rpm with time constant `TAU_S`. The
+//! controller runs every `DT_S`; the plant is integrated in finer steps in
+//! between, like a real motor running between two control interrupts.
+
+use pid::{ConfigError, Pid, PidConfig};
+
+const GAIN_RPM_PER_PCT: f32 = 30.0;
+const TAU_S: f32 = 0.1;
+const DT_S: f32 = 0.01;
+const PLANT_SUBSTEPS: u32 = 10;
+
+struct Motor {
+    speed_rpm: f32,
+    load_rpm: f32,
+}
+
+impl Motor {
+    fn new() -> Self {
+        Self {
+            speed_rpm: 0.0,
+            load_rpm: 0.0,
+        }
+    }
+
+    fn step(&mut self, duty_pct: f32) {
+        let h = DT_S / PLANT_SUBSTEPS as f32;
+        for _ in 0..PLANT_SUBSTEPS {
+            let target = GAIN_RPM_PER_PCT * duty_pct - self.load_rpm;
+            self.speed_rpm += (target - self.speed_rpm) * h / TAU_S;
+        }
+    }
+}
+
+fn speed_loop() -> PidConfig {
+    PidConfig {
+        kp: 0.02,
+        ki: 0.4,
+        kd: 0.0,
+        ..PidConfig::default()
+    }
+}
+
+/// Runs the loop for `seconds` and returns every (speed, duty) sample.
+fn run(pid: &mut Pid, motor: &mut Motor, setpoint: f32, seconds: f32) -> Vec<(f32, f32)> {
+    let steps = (seconds / DT_S).round() as usize;
+    (0..steps)
+        .map(|_| {
+            let duty = pid.update(setpoint, motor.speed_rpm, DT_S);
+            motor.step(duty);
+            (motor.speed_rpm, duty)
+        })
+        .collect()
+}
+
+#[test]
+fn rejects_invalid_config() {
+    let bad_gain = PidConfig {
+        kp: -1.0,
+        ..PidConfig::default()
+    };
+    assert_eq!(Pid::new(bad_gain).unwrap_err(), ConfigError::InvalidGain);
+    let nan = PidConfig {
+        ki: f32::NAN,
+        ..PidConfig::default()
+    };
+    assert_eq!(Pid::new(nan).unwrap_err(), ConfigError::InvalidGain);
+    let bad_range = PidConfig {
+        output_min: 10.0,
+        output_max: 10.0,
+        ..PidConfig::default()
+    };
+    assert_eq!(Pid::new(bad_range).unwrap_err(), ConfigError::OutputRange);
+}
+
+#[test]
+fn tracks_speed_setpoint_without_steady_state_error() {
+    let mut pid = Pid::new(speed_loop()).unwrap();
+    let mut motor = Motor::new();
+    let trace = run(&mut pid, &mut motor, 1500.0, 2.0);
+
+    let (speed, duty) = *trace.last().unwrap();
+    assert!((speed - 1500.0).abs() < 1.0, "settled at {speed} rpm");
+    assert!((duty - 50.0).abs() < 0.1, "settled at {duty} %");
+
+    // Reaches 90 % of the step within half a second
+    let rise = trace.iter().position(|&(s, _)| s >= 1350.0).unwrap();
+    assert!(
+        rise as f32 * DT_S < 0.5,
+        "rise time {} s",
+        rise as f32 * DT_S
+    );
+
+    // Overshoot stays below 10 %
+    let peak = trace.iter().map(|&(s, _)| s).fold(0.0, f32::max);
+    assert!(peak < 1650.0, "peak {peak} rpm");
+}
+
+#[test]
+fn proportional_only_leaves_steady_state_error() {
+    let mut pid = Pid::new(PidConfig {
+        ki: 0.0,
+        ..speed_loop()
+    })
+    .unwrap();
+    let mut motor = Motor::new();
+    let trace = run(&mut pid, &mut motor, 1500.0, 2.0);
+    let (speed, _) = *trace.last().unwrap();
+    // kp·GAIN = 0.6, so the loop settles at 0.6 / 1.6 of the setpoint
+    assert!((speed - 562.5).abs() < 1.0, "settled at {speed} rpm");
+}
+
+#[test]
+fn rejects_load_disturbance() {
+    let mut pid = Pid::new(speed_loop()).unwrap();
+    let mut motor = Motor::new();
+    run(&mut pid, &mut motor, 1000.0, 2.0);
+
+    motor.load_rpm = 600.0;
+    let trace = run(&mut pid, &mut motor, 1000.0, 2.0);
+    let dip = trace.iter().map(|&(s, _)| s).fold(f32::MAX, f32::min);
+    assert!(dip < 1000.0 && dip > 700.0, "dip to {dip} rpm");
+    let (speed, duty) = *trace.last().unwrap();
+    assert!((speed - 1000.0).abs() < 1.0, "recovered to {speed} rpm");
+    // The extra 600 rpm of load needs 20 % more duty
+    assert!((duty - 53.33).abs() < 0.1, "duty {duty} %");
+}
+
+#[test]
+fn output_stays_within_limits() {
+    let mut pid = Pid::new(PidConfig {
+        kp: 1.0,
+        ki: 10.0,
+        output_min: -40.0,
+        output_max: 40.0,
+        ..PidConfig::default()
+    })
+    .unwrap();
+    let mut motor = Motor::new();
+    // 3000 rpm needs 100 % duty, which is out of reach
+    for (_, duty) in run(&mut pid, &mut motor, 3000.0, 1.0) {
+        assert!((-40.0..=40.0).contains(&duty));
+    }
+    assert_eq!(pid.output(), 40.0);
+    for (_, duty) in run(&mut pid, &mut motor, -3000.0, 1.0) {
+        assert!((-40.0..=40.0).contains(&duty));
+    }
+    assert_eq!(pid.output(), -40.0);
+}
+
+#[test]
+fn integral_does_not_wind_up_during_saturation() {
+    let config = PidConfig {
+        output_max: 60.0,
+        ..speed_loop()
+    };
+    let mut pid = Pid::new(config).unwrap();
+    let mut motor = Motor::new();
+    // Unreachable setpoint for a long time: 60 % gives at most 1800 rpm
+    run(&mut pid, &mut motor, 2500.0, 5.0);
+    assert!(pid.terms().integral <= 60.0);
+
+    // Dropping to a reachable setpoint must not overshoot far below it
+    // while a wound-up integral unwinds
+    let trace = run(&mut pid, &mut motor, 900.0, 2.0);
+    let undershoot = trace.iter().map(|&(s, _)| s).fold(f32::MAX, f32::min);
+    assert!(undershoot > 800.0, "undershoot to {undershoot} rpm");
+    let (speed, _) = *trace.last().unwrap();
+    assert!((speed - 900.0).abs() < 1.0);
+}
+
+#[test]
+fn derivative_acts_on_measurement_not_setpoint() {
+    let mut pid = Pid::new(PidConfig {
+        kp: 0.0,
+        ki: 0.0,
+        kd: 1.0,
+        ..PidConfig::default()
+    })
+    .unwrap();
+    pid.update(0.0, 10.0, DT_S);
+    // A setpoint step with a constant measurement gives no derivative kick
+    assert_eq!(pid.update(1000.0, 10.0, DT_S), 0.0);
+    // A rising measurement is opposed
+    assert!(pid.update(1000.0, 10.5, DT_S) < 0.0);
+}
+
+#[test]
+fn derivative_filter_smooths_noise() {
+    let noisy = |filter_s| {
+        let mut pid = Pid::new(PidConfig {
+            kd: 0.01,
+            derivative_filter_s: filter_s,
+            ..PidConfig::default()
+        })
+        .unwrap();
+        (0..200)
+            .map(|i| {
+                // ±5 rpm of quantisation noise around a constant speed
+                let measurement = 1000.0 + if i % 2 == 0 { 5.0 } else { -5.0 };
+                pid.update(1000.0, measurement, DT_S).abs()
+            })
+            .skip(100)
+            .fold(0.0, f32::max)
+    };
+    let raw = noisy(0.0);
+    let filtered = noisy(0.05);
+    assert!(filtered < raw / 5.0, "raw {raw}, filtered {filtered}");
+}
+
+#[test]
+fn feed_forward_speeds_up_the_response() {
+    let settle_time = |feed_forward| {
+        let mut pid = Pid::new(speed_loop()).unwrap();
+        pid.set_feed_forward(feed_forward);
+        let mut motor = Motor::new();
+        let trace = run(&mut pid, &mut motor, 1500.0, 2.0);
+        trace
+            .iter()
+            .rposition(|&(s, _)| (s - 1500.0).abs() > 15.0)
+            .unwrap_or(0)
+    };
+    // The model says 1500 rpm needs 50 % duty
+    assert!(settle_time(50.0) < settle_time(0.0));
+}
+
+#[test]
+fn ignores_invalid_time_steps() {
+    let mut pid = Pid::new(speed_loop()).unwrap();
+    let out = pid.update(100.0, 0.0, DT_S);
+    assert_eq!(pid.update(100.0, 0.0, 0.0), out);
+    assert_eq!(pid.update(100.0, 0.0, -1.0), out);
+    assert_eq!(pid.update(100.0, 0.0, f32::NAN), out);
+}
+
+#[test]
+fn reset_and_preload() {
+    let mut pid = Pid::new(speed_loop()).unwrap();
+    let mut motor = Motor::new();
+    run(&mut pid, &mut motor, 1500.0, 1.0);
+    pid.reset();
+    assert_eq!(pid.output(), 0.0);
+    assert_eq!(pid.terms().integral, 0.0);
+
+    // Bumpless transfer: continue from 50 % duty at the settled speed
+    pid.preload(50.0);
+    let duty = pid.update(1500.0, 1500.0, DT_S);
+    assert!((duty - 50.0).abs() < 1e-3);
+}
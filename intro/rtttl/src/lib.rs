@@ -0,0 +1,424 @@
+//! `no_std` parser for RTTTL ringtones and an equal-temperament note table.
+//!
+//! RTTTL (Ring Tone Text Transfer Language) describes a monophonic melody as
+//! three `:`-separated sections:
+//!
+//! ```text
+//! Indiana:d=4,o=5,b=250:e,8p,8f,8g,8p,1c6,8p.,d,8p,8e,1f,p.
+//! ```
+//!
+//! * a name;
+//! * defaults: `d` (duration, as a fraction of a whole note), `o` (octave)
+//!   and `b` (tempo in quarter-note beats per minute), any of which may be
+//!   omitted (`d=4`, `o=6`, `b=63`);
+//! * comma-separated notes: `[duration]note[#][.][octave][.]`, where the note
+//!   is `a`–`g` (`h` is accepted as `b`) or `p` for a pause, and a dot
+//!   makes the note half as long again.
+//!
+//! [`Rtttl::parse`] checks the name and defaults and borrows the note
+//! section; [`Rtttl::notes`] then decodes one note at a time without
+//! allocating, so a player can start before the whole tune is parsed.
+//!
+//! ```rust
+//! use rtttl::{Pitch, Rtttl};
+//!
+//! let tune = Rtttl::parse("Beep:d=8,o=5,b=120:a,p,a6.").unwrap();
+//! let notes: Result<Vec<_>, _> = tune.notes().collect();
+//! let notes = notes.unwrap();
+//! assert_eq!(notes[0].tone.unwrap().pitch, Pitch::A);
+//! assert_eq!(notes[0].tone.unwrap().frequency_hz(), 880);
+//! assert_eq!(notes[0].duration_ms, 250);
+//! assert_eq!(notes[1].tone, None);
+//! assert_eq!(notes[2].duration_ms, 375);
+//! ```
+//!
+//! [`Melody`] plays those notes against timestamps: polled from a main loop,
+//! it says when to start a tone and when to go silent, leaving a short gap
+//! between notes. The `esp32s3-demo` crate drives a passive buzzer on LEDC
+//! with it.
+
+#![no_std]
+
+mod melody;
+
+pub use melody::{Action, Melody, DEFAULT_GAP_MS};
+
+/// Duration used when the defaults section has no `d`.
+pub const DEFAULT_DURATION: u8 = 4;
+/// Octave used when the defaults section has no `o`.
+pub const DEFAULT_OCTAVE: u8 = 6;
+/// Tempo used when the defaults section has no `b`.
+pub const DEFAULT_BPM: u16 = 63;
+
+/// Highest accepted octave. Standard RTTTL only uses 4–7.
+pub const MAX_OCTAVE: u8 = 8;
+/// Highest accepted tempo.
+pub const MAX_BPM: u16 = 900;
+
+/// The twelve semitones of an octave.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Pitch {
+    C,
+    CSharp,
+    D,
+    DSharp,
+    E,
+    F,
+    FSharp,
+    G,
+    GSharp,
+    A,
+    ASharp,
+    B,
+}
+
+impl Pitch {
+    /// Parses a note letter (`a`–`h`, either case) and an optional sharp.
+    /// `e#` and `b#` wrap to the next natural note's pitch class; use
+    /// [`Tone::new`] with the next octave if that matters.
+    pub fn from_letter(letter: u8, sharp: bool) -> Option<Self> {
+        let natural = match letter.to_ascii_lowercase() {
+            b'c' => 0,
+            b'd' => 2,
+            b'e' => 4,
+            b'f' => 5,
+            b'g' => 7,
+            b'a' => 9,
+            b'b' | b'h' => 11,
+            _ => return None,
+        };
+        Some(Self::from_semitone((natural + sharp as u8) % 12))
+    }
+
+    /// The pitch `semitone` steps above C (modulo 12).
+    pub const fn from_semitone(semitone: u8) -> Self {
+        match semitone % 12 {
+            0 => Pitch::C,
+            1 => Pitch::CSharp,
+            2 => Pitch::D,
+            3 => Pitch::DSharp,
+            4 => Pitch::E,
+            5 => Pitch::F,
+            6 => Pitch::FSharp,
+            7 => Pitch::G,
+            8 => Pitch::GSharp,
+            9 => Pitch::A,
+            10 => Pitch::ASharp,
+            _ => Pitch::B,
+        }
+    }
+
+    /// Number of semitones above C.
+    pub const fn semitone(self) -> u8 {
+        self as u8
+    }
+}
+
+/// Frequencies of octave 4 in millihertz (A4 = 440 Hz).
+const OCTAVE_4_MILLIHERTZ: [u32; 12] = [
+    261_626, 277_183, 293_665, 311_127, 329_628, 349_228, 369_994, 391_995, 415_305, 440_000,
+    466_164, 493_883,
+];
+
+/// A pitch in a given octave.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Tone {
+    pub octave: u8,
+    pub pitch: Pitch,
+}
+
+impl Tone {
+    pub const fn new(pitch: Pitch, octave: u8) -> Self {
+        Self { octave, pitch }
+    }
+
+    /// Equal-temperament frequency in millihertz.
+    pub const fn frequency_millihertz(&self) -> u32 {
+        let base = OCTAVE_4_MILLIHERTZ[self.pitch as usize] as u64;
+        let scaled = if self.octave >= 4 {
+            base << (self.octave - 4)
+        } else {
+            let divisor = 1u64 << (4 - self.octave);
+            (base + divisor / 2) / divisor
+        };
+        scaled as u32
+    }
+
+    /// Equal-temperament frequency rounded to whole hertz, the resolution
+    /// of an LEDC timer.
+    pub const fn frequency_hz(&self) -> u32 {
+        (self.frequency_millihertz() + 500) / 1000
+    }
+
+    /// MIDI note number (C4 = 60, A4 = 69).
+    pub const fn midi_number(&self) -> u8 {
+        (self.octave + 1) * 12 + self.pitch.semitone()
+    }
+}
+
+/// One note of a melody.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Note {
+    /// `None` for a pause.
+    pub tone: Option<Tone>,
+    pub duration_ms: u32,
+}
+
+/// What is wrong with a ringtone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// The text does not have three `:`-separated sections.
+    MissingSection,
+    /// A defaults entry is not `d=`, `o=` or `b=` followed by a number.
+    InvalidDefault,
+    /// A duration is not 1, 2, 4, 8, 16 or 32.
+    InvalidDuration,
+    /// An octave is above [`MAX_OCTAVE`].
+    InvalidOctave,
+    /// The tempo is 0 or above [`MAX_BPM`].
+    InvalidBpm,
+    /// A note is not `a`–`h` or `p`, or has trailing characters.
+    InvalidNote,
+}
+
+/// A parse error and the byte offset in the ringtone text where it occurred.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseError {
+    pub kind: ErrorKind,
+    pub position: usize,
+}
+
+impl ParseError {
+    const fn new(kind: ErrorKind, position: usize) -> Self {
+        Self { kind, position }
+    }
+}
+
+/// The values of the defaults section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Defaults {
+    pub duration: u8,
+    pub octave: u8,
+    pub bpm: u16,
+}
+
+impl Default for Defaults {
+    fn default() -> Self {
+        Self {
+            duration: DEFAULT_DURATION,
+            octave: DEFAULT_OCTAVE,
+            bpm: DEFAULT_BPM,
+        }
+    }
+}
+
+impl Defaults {
+    /// Length of a note of the given duration (1 = whole note, 4 = quarter
+    /// note) at this tempo, optionally dotted.
+    pub const fn note_ms(&self, duration: u8, dotted: bool) -> u32 {
+        // A whole note is four beats of 60 000 / bpm ms
+        let whole = 240_000 * if dotted { 3 } else { 2 };
+        whole / (self.bpm as u32 * duration as u32 * 2)
+    }
+}
+
+fn valid_duration(duration: u32) -> bool {
+    matches!(duration, 1 | 2 | 4 | 8 | 16 | 32)
+}
+
+/// A parsed ringtone. The notes are decoded lazily by [`Rtttl::notes`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rtttl<'a> {
+    name: &'a str,
+    defaults: Defaults,
+    notes: &'a str,
+    notes_offset: usize,
+}
+
+impl<'a> Rtttl<'a> {
+    /// Parses the name and defaults sections. Notes are checked when they
+    /// are decoded; call [`Rtttl::validate`] to check them all up front.
+    pub fn parse(text: &'a str) -> Result<Self, ParseError> {
+        let mut sections = text.splitn(3, ':');
+        let name = sections.next().unwrap_or_default();
+        let defaults_text = sections
+            .next()
+            .ok_or(ParseError::new(ErrorKind::MissingSection, text.len()))?;
+        let notes = sections
+            .next()
+            .ok_or(ParseError::new(ErrorKind::MissingSection, text.len()))?;
+
+        let defaults_offset = name.len() + 1;
+        let defaults = parse_defaults(defaults_text, defaults_offset)?;
+        Ok(Self {
+            name: name.trim(),
+            defaults,
+            notes,
+            notes_offset: defaults_offset + defaults_text.len() + 1,
+        })
+    }
+
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    pub fn defaults(&self) -> Defaults {
+        self.defaults
+    }
+
+    /// Decodes the notes one at a time. Stops after the first error.
+    pub fn notes(&self) -> Notes<'a> {
+        Notes {
+            defaults: self.defaults,
+            rest: self.notes,
+            offset: self.notes_offset,
+            failed: false,
+        }
+    }
+
+    /// Checks every note and returns how many there are.
+    pub fn validate(&self) -> Result<usize, ParseError> {
+        self.notes()
+            .try_fold(0, |count, note| note.map(|_| count + 1))
+    }
+
+    /// Total length of the melody.
+    pub fn duration_ms(&self) -> Result<u32, ParseError> {
+        self.notes()
+            .try_fold(0u32, |total, note| note.map(|n| total + n.duration_ms))
+    }
+}
+
+fn parse_defaults(text: &str, offset: usize) -> Result<Defaults, ParseError> {
+    let mut defaults = Defaults::default();
+    let mut position = offset;
+    for entry in text.split(',') {
+        let entry_position = position;
+        position += entry.len() + 1;
+        let trimmed = entry.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let error = |kind| ParseError::new(kind, entry_position);
+        let (key, value) = trimmed
+            .split_once('=')
+            .ok_or(error(ErrorKind::InvalidDefault))?;
+        let value: u32 = value
+            .trim()
+            .parse()
+            .map_err(|_| error(ErrorKind::InvalidDefault))?;
+        match key.trim() {
+            "d" | "D" if valid_duration(value) => defaults.duration = value as u8,
+            "d" | "D" => return Err(error(ErrorKind::InvalidDuration)),
+            "o" | "O" if value <= MAX_OCTAVE as u32 => defaults.octave = value as u8,
+            "o" | "O" => return Err(error(ErrorKind::InvalidOctave)),
+            "b" | "B" if value > 0 && value <= MAX_BPM as u32 => defaults.bpm = value as u16,
+            "b" | "B" => return Err(error(ErrorKind::InvalidBpm)),
+            _ => return Err(error(ErrorKind::InvalidDefault)),
+        }
+    }
+    Ok(defaults)
+}
+
+/// Iterator over the notes of a ringtone.
+#[derive(Debug, Clone)]
+pub struct Notes<'a> {
+    defaults: Defaults,
+    rest: &'a str,
+    offset: usize,
+    failed: bool,
+}
+
+impl Iterator for Notes<'_> {
+    type Item = Result<Note, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.failed && !self.rest.is_empty() {
+            let (entry, rest) = match self.rest.split_once(',') {
+                Some((entry, rest)) => (entry, Some(rest)),
+                None => (self.rest, None),
+            };
+            let position = self.offset;
+            self.offset += entry.len() + 1;
+            self.rest = rest.unwrap_or_default();
+
+            let leading = entry.len() - entry.trim_start().len();
+            let entry = entry.trim();
+            if entry.is_empty() {
+                // Tolerate a trailing comma or blank entries
+                continue;
+            }
+            let result = parse_note(entry, &self.defaults, position + leading);
+            self.failed = result.is_err();
+            return Some(result);
+        }
+        None
+    }
+}
+
+fn parse_note(entry: &str, defaults: &Defaults, position: usize) -> Result<Note, ParseError> {
+    let bytes = entry.as_bytes();
+    let mut i = 0;
+    let error = |kind, at| ParseError::new(kind, position + at);
+
+    // Duration
+    let mut duration = 0u32;
+    while i < bytes.len() && bytes[i].is_ascii_digit() {
+        duration = duration * 10 + (bytes[i] - b'0') as u32;
+        if duration > 32 {
+            return Err(error(ErrorKind::InvalidDuration, 0));
+        }
+        i += 1;
+    }
+    let duration = if i == 0 {
+        defaults.duration
+    } else if valid_duration(duration) {
+        duration as u8
+    } else {
+        return Err(error(ErrorKind::InvalidDuration, 0));
+    };
+
+    // Note letter and sharp
+    let letter_at = i;
+    let letter = *bytes
+        .get(i)
+        .ok_or(error(ErrorKind::InvalidNote, letter_at))?;
+    i += 1;
+    let sharp = bytes.get(i) == Some(&b'#');
+    if sharp {
+        i += 1;
+    }
+    let pause = letter.eq_ignore_ascii_case(&b'p');
+    let pitch = if pause {
+        None
+    } else {
+        Some(Pitch::from_letter(letter, sharp).ok_or(error(ErrorKind::InvalidNote, letter_at))?)
+    };
+
+    // Dot before or after the octave, octave
+    let mut dotted = false;
+    if bytes.get(i) == Some(&b'.') {
+        dotted = true;
+        i += 1;
+    }
+    let mut octave = defaults.octave;
+    if let Some(&digit) = bytes.get(i).filter(|b| b.is_ascii_digit()) {
+        octave = digit - b'0';
+        if octave > MAX_OCTAVE {
+            return Err(error(ErrorKind::InvalidOctave, i));
+        }
+        i += 1;
+    }
+    if !dotted && bytes.get(i) == Some(&b'.') {
+        dotted = true;
+        i += 1;
+    }
+    if i != bytes.len() {
+        return Err(error(ErrorKind::InvalidNote, i));
+    }
+
+    Ok(Note {
+        tone: pitch.map(|pitch| Tone::new(pitch, octave)),
+        duration_ms: defaults.note_ms(duration, dotted),
+    })
+}
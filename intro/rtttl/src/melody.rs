@@ -0,0 +1,141 @@
+use crate::{Notes, ParseError, Rtttl, Tone};
+
+/// Default silence between two notes, so that two equal notes do not merge
+/// into one long one.
+pub const DEFAULT_GAP_MS: u32 = 15;
+
+/// What [`Melody::poll`] asks the output to do when it has to change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Sound this frequency in hertz.
+    Tone(u32),
+    Silence,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Current {
+    tone: Option<Tone>,
+    // End of the tone, followed by the gap
+    tone_end: u64,
+    note_end: u64,
+}
+
+/// Playback position in a melody, driven by timestamps.
+///
+/// It never blocks and knows nothing about the hardware: the caller polls
+/// it with the current time and applies the returned [`Action`] to a
+/// buzzer.
+#[derive(Debug, Clone)]
+pub struct Melody<'a> {
+    notes: Notes<'a>,
+    gap_ms: u32,
+    start_ms: u64,
+    current: Option<Current>,
+    // What the output is doing now
+    output: Option<Tone>,
+    finished: bool,
+}
+
+impl<'a> Melody<'a> {
+    /// Plays `tune` from `now_ms` on.
+    pub fn new(tune: &Rtttl<'a>, now_ms: u64) -> Self {
+        Self {
+            notes: tune.notes(),
+            gap_ms: DEFAULT_GAP_MS,
+            start_ms: now_ms,
+            current: None,
+            output: None,
+            finished: false,
+        }
+    }
+
+    /// Sets the silence between notes; 0 plays legato. The gap never takes
+    /// more than half of a note.
+    pub fn with_gap_ms(mut self, gap_ms: u32) -> Self {
+        self.gap_ms = gap_ms;
+        self
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// The tone being sounded, if any.
+    pub fn current_tone(&self) -> Option<Tone> {
+        self.output
+    }
+
+    /// When the output may change next; the main loop can sleep until then.
+    pub fn next_change_ms(&self) -> Option<u64> {
+        if self.finished {
+            return None;
+        }
+        match self.current {
+            Some(c) if self.output.is_some() => Some(c.tone_end),
+            Some(c) => Some(c.note_end),
+            None => Some(self.start_ms),
+        }
+    }
+
+    /// Advances to `now_ms` and returns what the output has to do, if it
+    /// has to change. Notes that ended before `now_ms` are skipped.
+    ///
+    /// A note that does not parse ends the melody and is returned as the
+    /// error; the caller should silence the output.
+    pub fn poll(&mut self, now_ms: u64) -> Result<Option<Action>, ParseError> {
+        if self.finished || (self.current.is_none() && now_ms < self.start_ms) {
+            return Ok(None);
+        }
+
+        // Start of the next note; `None` while the current one is playing
+        let mut next_start = match self.current {
+            Some(c) if now_ms < c.note_end => None,
+            Some(c) => Some(c.note_end),
+            None => Some(self.start_ms),
+        };
+        while let Some(start) = next_start {
+            match self.notes.next() {
+                None => {
+                    self.finished = true;
+                    self.current = None;
+                    return Ok(self.set_output(None));
+                }
+                Some(Err(e)) => {
+                    self.finished = true;
+                    self.current = None;
+                    self.output = None;
+                    return Err(e);
+                }
+                Some(Ok(note)) => {
+                    let note_end = start + note.duration_ms as u64;
+                    let gap = self.gap_ms.min(note.duration_ms / 2) as u64;
+                    self.current = Some(Current {
+                        tone: note.tone,
+                        tone_end: note_end - gap,
+                        note_end,
+                    });
+                    next_start = (now_ms >= note_end).then_some(note_end);
+                }
+            }
+        }
+
+        let current = self.current.unwrap();
+        let wanted = if now_ms < current.tone_end {
+            current.tone
+        } else {
+            None
+        };
+        Ok(self.set_output(wanted))
+    }
+
+    fn set_output(&mut self, wanted: Option<Tone>) -> Option<Action> {
+        if wanted == self.output {
+            return None;
+        }
+        self.output = wanted;
+        Some(match wanted {
+            Some(tone) => Action::Tone(tone.frequency_hz()),
+            None => Action::Silence,
+        })
+    }
+}
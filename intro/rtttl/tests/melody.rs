@@ -0,0 +1,112 @@
+use rtttl::{Action, ErrorKind, Melody, Rtttl, DEFAULT_GAP_MS};
+
+// At 60 bpm a quarter note lasts one second
+const C5: u32 = 523;
+const E5: u32 = 659;
+
+fn tune(text: &str) -> Rtttl<'_> {
+    Rtttl::parse(text).unwrap()
+}
+
+#[test]
+fn notes_are_separated_by_the_gap() {
+    let tune = tune("t:d=4,o=5,b=60:c,8p,e");
+    let mut melody = Melody::new(&tune, 100);
+    assert_eq!(melody.poll(99), Ok(None), "not started yet");
+    assert_eq!(melody.next_change_ms(), Some(100));
+
+    assert_eq!(melody.poll(100), Ok(Some(Action::Tone(C5))));
+    assert_eq!(melody.current_tone().unwrap().frequency_hz(), C5);
+    let silence_at = 1_100 - DEFAULT_GAP_MS as u64;
+    assert_eq!(melody.next_change_ms(), Some(silence_at));
+    assert_eq!(melody.poll(silence_at - 1), Ok(None));
+    assert_eq!(melody.poll(silence_at), Ok(Some(Action::Silence)));
+    assert_eq!(melody.next_change_ms(), Some(1_100));
+
+    // The pause keeps the output silent
+    assert_eq!(melody.poll(1_100), Ok(None));
+    assert_eq!(melody.next_change_ms(), Some(1_600));
+    assert_eq!(melody.poll(1_600), Ok(Some(Action::Tone(E5))));
+    assert_eq!(
+        melody.poll(2_600 - DEFAULT_GAP_MS as u64),
+        Ok(Some(Action::Silence))
+    );
+    assert!(!melody.is_finished());
+    assert_eq!(melody.poll(2_600), Ok(None));
+    assert!(melody.is_finished());
+}
+
+#[test]
+fn equal_notes_are_split_by_the_gap_but_not_when_legato() {
+    let tune = tune("t:d=4,o=5,b=60:c,c");
+    let mut melody = Melody::new(&tune, 0);
+    assert_eq!(melody.poll(0), Ok(Some(Action::Tone(C5))));
+    assert_eq!(melody.poll(985), Ok(Some(Action::Silence)));
+    assert_eq!(melody.poll(1_000), Ok(Some(Action::Tone(C5))));
+
+    let mut legato = Melody::new(&tune, 0).with_gap_ms(0);
+    assert_eq!(legato.poll(0), Ok(Some(Action::Tone(C5))));
+    for now in [985, 1_000, 1_999] {
+        assert_eq!(legato.poll(now), Ok(None), "{now}");
+    }
+    assert_eq!(legato.poll(2_000), Ok(Some(Action::Silence)));
+    assert!(legato.is_finished());
+}
+
+#[test]
+fn gap_takes_at_most_half_a_note() {
+    let tune = tune("t:d=4,o=5,b=60:c");
+    let mut melody = Melody::new(&tune, 0).with_gap_ms(800);
+    assert_eq!(melody.poll(0), Ok(Some(Action::Tone(C5))));
+    assert_eq!(melody.poll(499), Ok(None));
+    assert_eq!(melody.poll(500), Ok(Some(Action::Silence)));
+}
+
+#[test]
+fn late_poll_skips_the_notes_that_already_ended() {
+    let tune = tune("t:d=4,o=5,b=60:c,d,e");
+    let mut melody = Melody::new(&tune, 0);
+    assert_eq!(melody.poll(0), Ok(Some(Action::Tone(C5))));
+    // D played from 1000 to 2000 while the main loop was busy
+    assert_eq!(melody.poll(2_500), Ok(Some(Action::Tone(E5))));
+    assert_eq!(melody.next_change_ms(), Some(2_985));
+
+    // Landing in the gap of the next note stays silent
+    let mut melody = Melody::new(&tune, 0);
+    assert_eq!(melody.poll(0), Ok(Some(Action::Tone(C5))));
+    assert_eq!(melody.poll(1_990), Ok(Some(Action::Silence)));
+    assert_eq!(melody.poll(2_000), Ok(Some(Action::Tone(E5))));
+}
+
+#[test]
+fn end_of_tune_silences_and_stays_finished() {
+    let tune = tune("t:d=4,o=5,b=60:c");
+    let mut melody = Melody::new(&tune, 0).with_gap_ms(0);
+    assert_eq!(melody.poll(0), Ok(Some(Action::Tone(C5))));
+    // Polled long after the end while the tone is still on
+    assert_eq!(melody.poll(5_000), Ok(Some(Action::Silence)));
+    assert!(melody.is_finished());
+    assert_eq!(melody.current_tone(), None);
+    assert_eq!(melody.next_change_ms(), None);
+    assert_eq!(melody.poll(6_000), Ok(None));
+}
+
+#[test]
+fn empty_tune_finishes_at_once() {
+    let tune = tune("t:d=4:");
+    let mut melody = Melody::new(&tune, 0);
+    assert_eq!(melody.poll(0), Ok(None));
+    assert!(melody.is_finished());
+}
+
+#[test]
+fn bad_note_ends_the_melody_with_its_error() {
+    let tune = tune("t:d=4,o=5,b=60:c,x");
+    let mut melody = Melody::new(&tune, 0);
+    assert_eq!(melody.poll(0), Ok(Some(Action::Tone(C5))));
+    let error = melody.poll(1_000).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidNote);
+    assert!(melody.is_finished());
+    assert_eq!(melody.current_tone(), None);
+    assert_eq!(melody.poll(2_000), Ok(None));
+}
@@ -0,0 +1,211 @@
+use rtttl::{Defaults, ErrorKind, Note, ParseError, Pitch, Rtttl, Tone};
+
+fn notes(text: &str) -> Vec<Note> {
+    Rtttl::parse(text)
+        .unwrap()
+        .notes()
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+fn tone(pitch: Pitch, octave: u8) -> Option<Tone> {
+    Some(Tone::new(pitch, octave))
+}
+
+#[test]
+fn note_table_matches_equal_temperament() {
+    assert_eq!(Tone::new(Pitch::A, 4).frequency_millihertz(), 440_000);
+    assert_eq!(Tone::new(Pitch::A, 5).frequency_hz(), 880);
+    assert_eq!(Tone::new(Pitch::A, 3).frequency_hz(), 220);
+    assert_eq!(Tone::new(Pitch::C, 4).frequency_hz(), 262);
+    assert_eq!(Tone::new(Pitch::C, 8).frequency_hz(), 4186);
+    assert_eq!(Tone::new(Pitch::C, 0).frequency_hz(), 16);
+
+    // Each semitone is 2^(1/12) above the previous one (to within the
+    // millihertz rounding, which matters most in octave 0)
+    for octave in 0..=8 {
+        for semitone in 0..12 {
+            let t = Tone::new(Pitch::from_semitone(semitone), octave);
+            let midi = t.midi_number() as f64;
+            let exact = 440.0 * 2f64.powf((midi - 69.0) / 12.0);
+            let mhz = t.frequency_millihertz() as f64 / 1000.0;
+            assert!(
+                (mhz - exact).abs() / exact < 1e-4,
+                "{t:?}: {mhz} Hz, expected {exact} Hz"
+            );
+        }
+    }
+}
+
+#[test]
+fn midi_numbers() {
+    assert_eq!(Tone::new(Pitch::C, 4).midi_number(), 60);
+    assert_eq!(Tone::new(Pitch::A, 4).midi_number(), 69);
+    assert_eq!(Tone::new(Pitch::B, 3).midi_number(), 59);
+}
+
+#[test]
+fn parses_name_and_defaults() {
+    let tune = Rtttl::parse("Indiana:d=4,o=5,b=250:e,8p").unwrap();
+    assert_eq!(tune.name(), "Indiana");
+    assert_eq!(
+        tune.defaults(),
+        Defaults {
+            duration: 4,
+            octave: 5,
+            bpm: 250
+        }
+    );
+}
+
+#[test]
+fn missing_defaults_use_the_standard_values() {
+    let tune = Rtttl::parse("x::c").unwrap();
+    assert_eq!(tune.defaults(), Defaults::default());
+    let tune = Rtttl::parse("x: b = 100 :c").unwrap();
+    assert_eq!(tune.defaults().bpm, 100);
+    assert_eq!(tune.defaults().octave, 6);
+}
+
+#[test]
+fn decodes_durations_dots_sharps_and_octaves() {
+    // Quarter note = 500 ms at 120 bpm
+    let n = notes("t:d=4,o=5,b=120:c,8d#,2e.,16f#6,g.6,a6.,32h,1p,b4");
+    assert_eq!(
+        n,
+        vec![
+            Note {
+                tone: tone(Pitch::C, 5),
+                duration_ms: 500
+            },
+            Note {
+                tone: tone(Pitch::DSharp, 5),
+                duration_ms: 250
+            },
+            Note {
+                tone: tone(Pitch::E, 5),
+                duration_ms: 1500
+            },
+            Note {
+                tone: tone(Pitch::FSharp, 6),
+                duration_ms: 125
+            },
+            Note {
+                tone: tone(Pitch::G, 6),
+                duration_ms: 750
+            },
+            Note {
+                tone: tone(Pitch::A, 6),
+                duration_ms: 750
+            },
+            Note {
+                tone: tone(Pitch::B, 5),
+                duration_ms: 62
+            },
+            Note {
+                tone: None,
+                duration_ms: 2000
+            },
+            Note {
+                tone: tone(Pitch::B, 4),
+                duration_ms: 500
+            },
+        ]
+    );
+}
+
+#[test]
+fn tolerates_whitespace_case_and_trailing_comma() {
+    let n = notes("Tune : d=8, o=4, b=60 : C, 4P , A#5 ,");
+    assert_eq!(n.len(), 3);
+    assert_eq!(n[0].tone, tone(Pitch::C, 4));
+    assert_eq!(n[0].duration_ms, 500);
+    assert_eq!(n[1].tone, None);
+    assert_eq!(n[1].duration_ms, 1000);
+    assert_eq!(n[2].tone, tone(Pitch::ASharp, 5));
+}
+
+#[test]
+fn empty_melody_has_no_notes() {
+    let tune = Rtttl::parse("silence:d=4,o=5,b=100:").unwrap();
+    assert_eq!(tune.validate(), Ok(0));
+    assert_eq!(tune.duration_ms(), Ok(0));
+}
+
+#[test]
+fn total_duration() {
+    let tune = Rtttl::parse("t:d=4,o=5,b=60:c,d,2e,p").unwrap();
+    assert_eq!(tune.validate(), Ok(4));
+    assert_eq!(tune.duration_ms(), Ok(5000));
+}
+
+#[test]
+fn reports_missing_sections() {
+    assert_eq!(
+        Rtttl::parse("no sections").unwrap_err().kind,
+        ErrorKind::MissingSection
+    );
+    assert_eq!(
+        Rtttl::parse("name:d=4").unwrap_err().kind,
+        ErrorKind::MissingSection
+    );
+}
+
+#[test]
+fn reports_invalid_defaults_with_position() {
+    let err = |text| Rtttl::parse(text).unwrap_err();
+    assert_eq!(
+        err("t:d=3:c"),
+        ParseError {
+            kind: ErrorKind::InvalidDuration,
+            position: 2
+        }
+    );
+    assert_eq!(
+        err("t:d=4,o=9:c"),
+        ParseError {
+            kind: ErrorKind::InvalidOctave,
+            position: 6
+        }
+    );
+    assert_eq!(err("t:b=0:c").kind, ErrorKind::InvalidBpm);
+    assert_eq!(err("t:b=901:c").kind, ErrorKind::InvalidBpm);
+    assert_eq!(err("t:x=1:c").kind, ErrorKind::InvalidDefault);
+    assert_eq!(err("t:d4:c").kind, ErrorKind::InvalidDefault);
+    assert_eq!(err("t:d=four:c").kind, ErrorKind::InvalidDefault);
+}
+
+#[test]
+fn reports_invalid_notes_with_position_and_stops() {
+    let tune = Rtttl::parse("t:d=4,o=5,b=100:c,x,d").unwrap();
+    let mut iter = tune.notes();
+    assert!(iter.next().unwrap().is_ok());
+    assert_eq!(
+        iter.next(),
+        Some(Err(ParseError {
+            kind: ErrorKind::InvalidNote,
+            position: 18
+        }))
+    );
+    assert_eq!(iter.next(), None);
+    assert_eq!(tune.validate().unwrap_err().position, 18);
+
+    let first_error = |text| Rtttl::parse(text).unwrap().validate().unwrap_err();
+    assert_eq!(first_error("t::3c").kind, ErrorKind::InvalidDuration);
+    assert_eq!(first_error("t::64c").kind, ErrorKind::InvalidDuration);
+    assert_eq!(first_error("t::c9").kind, ErrorKind::InvalidOctave);
+    assert_eq!(first_error("t::8").kind, ErrorKind::InvalidNote);
+    assert_eq!(first_error("t::c5x").kind, ErrorKind::InvalidNote);
+    assert_eq!(first_error("t::c..").kind, ErrorKind::InvalidNote);
+}
+
+#[test]
+fn parses_a_real_ringtone() {
+    let text = "TakeOnMe:d=4,o=4,b=160:8f#5,8f#5,8f#5,8d5,8p,8b,8p,8e5,8p,8e5,8p,8e5,8g#5,8g#5,8a5,8b5,8a5,8a5,8a5,8e5,8p,8d5,8p,8f#5,8p,8f#5,8p,8f#5,8e5,8e5,8f#5,8e5";
+    let tune = Rtttl::parse(text).unwrap();
+    assert_eq!(tune.name(), "TakeOnMe");
+    assert_eq!(tune.validate(), Ok(32));
+    let first = tune.notes().next().unwrap().unwrap();
+    assert_eq!(first.tone.unwrap().frequency_hz(), 740);
+    assert_eq!(first.duration_ms, 187);
+}
@@ -0,0 +1,355 @@
+//! Composable integer filters for ADC samples.
+//!
+//! A single one-shot ADC reading is noisy. The stages here can be chained
+//! with [`Filter::then`] and run over any iterator of samples with
+//! [`SampleIter::filtered`]:
+//!
+//! * [`Oversample`] averages every `N` samples into one;
+//! * [`MovingAverage`] averages the last `N` samples;
+//! * [`Median`] removes isolated spikes;
+//! * [`Ema`] is a first-order low-pass;
+//! * [`Hysteresis`] turns the result into a stable on/off state.
+//!
+//! ```rust
+//! use sample_filter::{Filter, Hysteresis, Median, SampleIter};
+//!
+//! // A glitch on a quiet input
+//! let samples = [100u16, 101, 4000, 99, 100, 102];
+//! let mut alarm = samples.into_iter().filtered(Hysteresis::new(1000, 2000));
+//! assert!(alarm.any(|on| on));
+//!
+//! // The median votes it out before it reaches the threshold
+//! let pipeline = Median::<3>::new().then(Hysteresis::new(1000, 2000));
+//! let mut alarm = samples.into_iter().filtered(pipeline);
+//! assert!(!alarm.any(|on| on));
+//! ```
+//!
+//! The `esp32s3-demo` crate feeds them with one-shot ADC readings.
+
+#![no_std]
+
+/// One stage of a pipeline: takes a sample and may produce an output.
+/// Decimating stages such as [`Oversample`] only produce one every few inputs.
+pub trait Filter<In> {
+    type Output;
+
+    fn update(&mut self, input: In) -> Option<Self::Output>;
+
+    /// Forgets every sample seen so far.
+    fn reset(&mut self);
+
+    /// Feeds this stage's outputs into `next`.
+    fn then<F>(self, next: F) -> Chain<Self, F>
+    where
+        Self: Sized,
+        F: Filter<Self::Output>,
+    {
+        Chain {
+            first: self,
+            second: next,
+        }
+    }
+}
+
+/// Two stages in series, built by [`Filter::then`].
+#[derive(Debug, Clone)]
+pub struct Chain<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<In, A, B> Filter<In> for Chain<A, B>
+where
+    A: Filter<In>,
+    B: Filter<A::Output>,
+{
+    type Output = B::Output;
+
+    fn update(&mut self, input: In) -> Option<Self::Output> {
+        self.first.update(input).and_then(|x| self.second.update(x))
+    }
+
+    fn reset(&mut self) {
+        self.first.reset();
+        self.second.reset();
+    }
+}
+
+/// Averages every `N` samples into one output, rounded to nearest.
+#[derive(Debug, Clone)]
+pub struct Oversample<const N: usize> {
+    sum: u32,
+    count: usize,
+}
+
+impl<const N: usize> Oversample<N> {
+    pub const fn new() -> Self {
+        Self { sum: 0, count: 0 }
+    }
+}
+
+impl<const N: usize> Default for Oversample<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Filter<u16> for Oversample<N> {
+    type Output = u16;
+
+    fn update(&mut self, input: u16) -> Option<u16> {
+        self.sum += input as u32;
+        self.count += 1;
+        if self.count < N.max(1) {
+            return None;
+        }
+        let n = self.count as u32;
+        let avg = (self.sum + n / 2) / n;
+        self.reset();
+        Some(avg as u16)
+    }
+
+    fn reset(&mut self) {
+        self.sum = 0;
+        self.count = 0;
+    }
+}
+
+/// Average of the last `N` samples, or of all samples while fewer than `N`
+/// have arrived.
+#[derive(Debug, Clone)]
+pub struct MovingAverage<const N: usize> {
+    window: [u16; N],
+    pos: usize,
+    len: usize,
+    sum: u32,
+}
+
+impl<const N: usize> MovingAverage<N> {
+    pub const fn new() -> Self {
+        Self {
+            window: [0; N],
+            pos: 0,
+            len: 0,
+            sum: 0,
+        }
+    }
+}
+
+impl<const N: usize> Default for MovingAverage<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Filter<u16> for MovingAverage<N> {
+    type Output = u16;
+
+    fn update(&mut self, input: u16) -> Option<u16> {
+        if N == 0 {
+            return Some(input);
+        }
+        if self.len == N {
+            self.sum -= self.window[self.pos] as u32;
+        } else {
+            self.len += 1;
+        }
+        self.window[self.pos] = input;
+        self.sum += input as u32;
+        self.pos = (self.pos + 1) % N;
+
+        let n = self.len as u32;
+        Some(((self.sum + n / 2) / n) as u16)
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// Median of the last `N` samples, which removes isolated spikes entirely.
+/// Works best with an odd `N`.
+#[derive(Debug, Clone)]
+pub struct Median<const N: usize> {
+    window: [u16; N],
+    pos: usize,
+    len: usize,
+}
+
+impl<const N: usize> Median<N> {
+    pub const fn new() -> Self {
+        Self {
+            window: [0; N],
+            pos: 0,
+            len: 0,
+        }
+    }
+}
+
+impl<const N: usize> Default for Median<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Filter<u16> for Median<N> {
+    type Output = u16;
+
+    fn update(&mut self, input: u16) -> Option<u16> {
+        if N == 0 {
+            return Some(input);
+        }
+        self.window[self.pos] = input;
+        self.pos = (self.pos + 1) % N;
+        self.len = (self.len + 1).min(N);
+
+        // N is small: insertion sort on a copy
+        let mut sorted = self.window;
+        let sorted = &mut sorted[..self.len];
+        for i in 1..sorted.len() {
+            let mut j = i;
+            while j > 0 && sorted[j - 1] > sorted[j] {
+                sorted.swap(j - 1, j);
+                j -= 1;
+            }
+        }
+        Some(sorted[sorted.len() / 2])
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// Exponential smoothing, `y += alpha * (x - y)` with `alpha = alpha_256 / 256`.
+/// A smaller alpha smooths more and follows changes more slowly.
+#[derive(Debug, Clone)]
+pub struct Ema {
+    alpha_256: u16,
+    // Scaled by 256 to keep the fractional part
+    state: Option<i32>,
+}
+
+impl Ema {
+    /// `alpha_256` is clamped to 1..=256; 256 passes samples through.
+    pub const fn new(alpha_256: u16) -> Self {
+        let alpha_256 = if alpha_256 == 0 {
+            1
+        } else if alpha_256 > 256 {
+            256
+        } else {
+            alpha_256
+        };
+        Self {
+            alpha_256,
+            state: None,
+        }
+    }
+}
+
+impl Filter<u16> for Ema {
+    type Output = u16;
+
+    fn update(&mut self, input: u16) -> Option<u16> {
+        let x = (input as i32) << 8;
+        let y = match self.state {
+            // Start at the first sample instead of creeping up from 0
+            None => x,
+            Some(y) => y + (((x - y) as i64 * self.alpha_256 as i64) >> 8) as i32,
+        };
+        self.state = Some(y);
+        Some(((y + 128) >> 8) as u16)
+    }
+
+    fn reset(&mut self) {
+        self.state = None;
+    }
+}
+
+/// Threshold with hysteresis: turns true at or above `high`, false at or
+/// below `low`, and holds its state in between, so a noisy signal near the
+/// threshold does not chatter.
+#[derive(Debug, Clone)]
+pub struct Hysteresis {
+    pub low: u16,
+    pub high: u16,
+    state: bool,
+}
+
+impl Hysteresis {
+    pub const fn new(low: u16, high: u16) -> Self {
+        Self {
+            low,
+            high,
+            state: false,
+        }
+    }
+
+    pub fn state(&self) -> bool {
+        self.state
+    }
+}
+
+impl Filter<u16> for Hysteresis {
+    type Output = bool;
+
+    fn update(&mut self, input: u16) -> Option<bool> {
+        if input >= self.high {
+            self.state = true;
+        } else if input <= self.low {
+            self.state = false;
+        }
+        Some(self.state)
+    }
+
+    fn reset(&mut self) {
+        self.state = false;
+    }
+}
+
+/// Runs any iterator of samples through a filter.
+pub trait SampleIter: Iterator + Sized {
+    fn filtered<F>(self, filter: F) -> Filtered<Self, F>
+    where
+        F: Filter<Self::Item>,
+    {
+        Filtered { iter: self, filter }
+    }
+}
+
+impl<I: Iterator> SampleIter for I {}
+
+/// Iterator returned by [`SampleIter::filtered`].
+#[derive(Debug, Clone)]
+pub struct Filtered<I, F> {
+    iter: I,
+    filter: F,
+}
+
+impl<I, F> Filtered<I, F> {
+    pub fn filter(&self) -> &F {
+        &self.filter
+    }
+
+    pub fn into_inner(self) -> (I, F) {
+        (self.iter, self.filter)
+    }
+}
+
+impl<I, F> Iterator for Filtered<I, F>
+where
+    I: Iterator,
+    F: Filter<I::Item>,
+{
+    type Item = F::Output;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Decimating filters need several inputs per output
+        for input in self.iter.by_ref() {
+            if let Some(output) = self.filter.update(input) {
+                return Some(output);
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,255 @@
+use sample_filter::{Ema, Filter, Hysteresis, Median, MovingAverage, Oversample, SampleIter};
+
+/// Deterministic noise: xorshift32 with a fixed seed.
+struct Noise(u32);
+
+impl Noise {
+    fn new() -> Self {
+        Self(0x1234_5678)
+    }
+
+    fn uniform(&mut self) -> f64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.0 = x;
+        x as f64 / u32::MAX as f64
+    }
+
+    /// Roughly Gaussian with standard deviation `sigma` (Irwin-Hall, 12 terms).
+    fn gaussian(&mut self, sigma: f64) -> f64 {
+        let sum: f64 = (0..12).map(|_| self.uniform()).sum();
+        (sum - 6.0) * sigma
+    }
+}
+
+fn adc(value: f64) -> u16 {
+    value.round().clamp(0.0, 4095.0) as u16
+}
+
+/// `len` samples of `level` with Gaussian noise.
+fn noisy(level: f64, sigma: f64, len: usize) -> Vec<u16> {
+    let mut noise = Noise::new();
+    (0..len)
+        .map(|_| adc(level + noise.gaussian(sigma)))
+        .collect()
+}
+
+fn std_dev(values: &[u16], mean: f64) -> f64 {
+    let var = values
+        .iter()
+        .map(|&v| (v as f64 - mean).powi(2))
+        .sum::<f64>()
+        / values.len() as f64;
+    var.sqrt()
+}
+
+fn run<F: Filter<u16>>(filter: F, samples: &[u16]) -> Vec<F::Output> {
+    samples.iter().copied().filtered(filter).collect()
+}
+
+#[test]
+fn synthetic_noise_has_the_requested_spread() {
+    let samples = noisy(2000.0, 40.0, 20_000);
+    let sd = std_dev(&samples, 2000.0);
+    assert!((sd - 40.0).abs() < 2.0, "{sd}");
+}
+
+#[test]
+fn oversampling_divides_noise_by_sqrt_n() {
+    let samples = noisy(2000.0, 40.0, 16 * 2_000);
+    let out = run(Oversample::<16>::new(), &samples);
+    assert_eq!(out.len(), 2_000);
+    let sd = std_dev(&out, 2000.0);
+    // 40 / sqrt(16) = 10, plus rounding
+    assert!((8.0..12.0).contains(&sd), "{sd}");
+}
+
+#[test]
+fn oversampling_rounds_to_nearest() {
+    assert_eq!(run(Oversample::<2>::new(), &[1, 2, 2, 2, 0, 1]), [2, 2, 1]);
+    assert_eq!(run(Oversample::<4>::new(), &[1, 1, 1, 2, 9]), [1]);
+    // N = 0 behaves like N = 1
+    assert_eq!(run(Oversample::<0>::new(), &[5, 6]), [5, 6]);
+}
+
+#[test]
+fn moving_average_smooths_and_settles_after_n_samples() {
+    let samples = noisy(1000.0, 40.0, 10_000);
+    let out = run(MovingAverage::<16>::new(), &samples);
+    assert_eq!(out.len(), samples.len());
+    let sd = std_dev(&out[16..], 1000.0);
+    assert!((8.0..12.0).contains(&sd), "{sd}");
+
+    // A clean step is fully followed after exactly N samples
+    let mut step = vec![1000u16; 8];
+    step.extend([3000; 8]);
+    let out = run(MovingAverage::<4>::new(), &step);
+    assert_eq!(&out[..8], [1000; 8]);
+    assert_eq!(&out[8..12], [1500, 2000, 2500, 3000]);
+    assert_eq!(&out[12..], [3000; 4]);
+}
+
+#[test]
+fn moving_average_of_a_partial_window() {
+    assert_eq!(run(MovingAverage::<4>::new(), &[10, 20, 30]), [10, 15, 20]);
+    assert_eq!(run(MovingAverage::<0>::new(), &[7, 8]), [7, 8]);
+}
+
+#[test]
+fn median_removes_spikes_completely() {
+    // Clean level with a spike or a dropout every 10 samples
+    let samples: Vec<u16> = (0..1_000)
+        .map(|i| match i % 10 {
+            3 => 4095,
+            7 => 0,
+            _ => 1234,
+        })
+        .collect();
+    let out = run(Median::<5>::new(), &samples);
+    assert!(out[5..].iter().all(|&v| v == 1234));
+
+    // A moving average smears the same spikes over its window instead
+    let smeared = run(MovingAverage::<5>::new(), &samples);
+    assert!(smeared[5..].iter().any(|&v| v.abs_diff(1234) > 100));
+}
+
+#[test]
+fn median_keeps_edges_sharp() {
+    let mut step = vec![100u16; 10];
+    step.extend([900; 10]);
+    let out = run(Median::<3>::new(), &step);
+    // One sample late, but no intermediate values
+    assert!(out.iter().all(|&v| v == 100 || v == 900));
+    assert_eq!(out.iter().position(|&v| v == 900), Some(11));
+}
+
+#[test]
+fn ema_reduces_noise_by_its_alpha() {
+    let samples = noisy(2500.0, 40.0, 20_000);
+    let out = run(Ema::new(32), &samples);
+    let sd = std_dev(&out[200..], 2500.0);
+    // sqrt(alpha / (2 - alpha)) = 0.258 for alpha = 1/8
+    assert!((8.0..13.0).contains(&sd), "{sd}");
+}
+
+#[test]
+fn ema_starts_at_the_first_sample_and_follows_a_step() {
+    let mut step = vec![1000u16; 4];
+    step.extend([2000; 40]);
+    let out = run(Ema::new(64), &step);
+    assert_eq!(&out[..4], [1000; 4]);
+    // 1 - (3/4)^k of the way after k samples
+    assert_eq!(out[4], 1250);
+    assert_eq!(out[5], 1438);
+    assert!(out[4..].windows(2).all(|w| w[0] <= w[1]));
+    assert_eq!(*out.last().unwrap(), 2000);
+
+    // 256 passes samples through, 0 is clamped to 1
+    assert_eq!(run(Ema::new(256), &[1, 500, 3]), [1, 500, 3]);
+    assert_eq!(run(Ema::new(0), &[0, 256]), [0, 1]);
+}
+
+#[test]
+fn hysteresis_does_not_chatter_on_a_noisy_crossing() {
+    // Slow ramp from 1000 to 3000 with noise, crossing 2000 once
+    let mut noise = Noise::new();
+    let samples: Vec<u16> = (0..2_000)
+        .map(|i| adc(1000.0 + i as f64 + noise.gaussian(50.0)))
+        .collect();
+
+    let plain: Vec<bool> = samples.iter().map(|&v| v >= 2000).collect();
+    let plain_switches = plain.windows(2).filter(|w| w[0] != w[1]).count();
+    assert!(plain_switches > 10, "{plain_switches}");
+
+    let out = run(Hysteresis::new(1800, 2200), &samples);
+    let switches = out.windows(2).filter(|w| w[0] != w[1]).count();
+    assert_eq!(switches, 1);
+    assert!(!out[0] && *out.last().unwrap());
+}
+
+#[test]
+fn hysteresis_holds_between_thresholds() {
+    let mut h = Hysteresis::new(10, 20);
+    let out: Vec<bool> = [15, 20, 15, 11, 10, 15]
+        .into_iter()
+        .map(|v| h.update(v).unwrap())
+        .collect();
+    assert_eq!(out, [false, true, true, true, false, false]);
+    h.update(30);
+    h.reset();
+    assert!(!h.state());
+}
+
+#[test]
+fn pipeline_tracks_a_slow_signal_through_noise_and_spikes() {
+    // Slow sine, Gaussian noise and occasional full-scale spikes
+    let mut noise = Noise::new();
+    let truth = |i: usize| 2000.0 + 800.0 * (i as f64 / 2000.0 * std::f64::consts::TAU).sin();
+    let samples: Vec<u16> = (0..8_000)
+        .map(|i| {
+            if noise.uniform() < 0.005 {
+                4095
+            } else {
+                adc(truth(i) + noise.gaussian(60.0))
+            }
+        })
+        .collect();
+
+    let pipeline = Oversample::<4>::new()
+        .then(Median::<5>::new())
+        .then(Ema::new(64));
+    let out = run(pipeline, &samples);
+    assert_eq!(out.len(), 2_000);
+
+    // Compare with the truth after the filters have warmed up, delayed by
+    // their lag: half a block for the oversampling, 2 blocks for the median
+    // and about 3 for the EMA, 22 samples in all
+    let worst = out
+        .iter()
+        .enumerate()
+        .skip(10)
+        .map(|(k, &v)| (v as f64 - truth(k * 4 + 2 - 22)).abs())
+        .fold(0.0, f64::max);
+    assert!(worst < 60.0, "{worst}");
+
+    // Without the filters the spikes alone are more than 1000 off
+    let raw_worst = samples
+        .iter()
+        .enumerate()
+        .map(|(i, &v)| (v as f64 - truth(i)).abs())
+        .fold(0.0, f64::max);
+    assert!(raw_worst > 1000.0, "{raw_worst}");
+}
+
+#[test]
+fn reset_forgets_history() {
+    let mut chain = Oversample::<2>::new()
+        .then(MovingAverage::<4>::new())
+        .then(Ema::new(16));
+    chain.update(4000);
+    chain.update(4000);
+    chain.update(10);
+    chain.reset();
+    // Starts over: the pending 10 and the 4000 average are gone
+    assert_eq!(chain.update(100), None);
+    assert_eq!(chain.update(100), Some(100));
+}
+
+#[test]
+fn filtered_iterator_hands_back_its_parts() {
+    let mut filtered = [1u16, 2, 3, 4, 5]
+        .into_iter()
+        .filtered(Oversample::<2>::new());
+    assert_eq!(filtered.next(), Some(2));
+    let (mut rest, _) = filtered.into_inner();
+    assert_eq!(rest.next(), Some(3));
+
+    // The trailing incomplete block produces nothing
+    let out: Vec<u16> = [1u16, 2, 3]
+        .into_iter()
+        .filtered(Oversample::<2>::new())
+        .collect();
+    assert_eq!(out, [2]);
+}
@@ -0,0 +1,303 @@
+//! Overflow-safe range mapping for sensor-to-actuator scaling.
+//!
+//! The classic Arduino-style `map()`:
+//!
+//! ```rust,ignore
+//! (x - in_min) * (out_max - out_min) / (in_max - in_min) + out_min
+//! ```
+//!
+//! underflows when `x < in_min` on unsigned types, overflows when the
+//! product does not fit, divides by zero when `in_min == in_max` and cannot
+//! express a falling output. [`Linear`] does the same interpolation in a
+//! wider intermediate type and lets you choose what happens outside the
+//! input range:
+//!
+//! * [`Linear::map_clamped`] clamps the input, so the output always stays
+//!   inside the output range;
+//! * [`Linear::map_saturating`] extrapolates and saturates at the bounds of
+//!   the type;
+//! * [`Linear::try_map`] rejects inputs outside the range.
+//!
+//! Either range may be reversed (`start > end`), e.g. to map a rising
+//! temperature to a shrinking blink delay. [`Piecewise`] chains several
+//! linear segments for non-linear curves.
+//!
+//! Everything is generic over [`Scalar`], which is implemented for the 8, 16
+//! and 32-bit integers, `f32`, and (with the `fixed` feature) the fixed-point
+//! types of the `fixed` crate.
+//!
+//! ```rust
+//! use scaling::{Linear, Range};
+//!
+//! // 10..25 °C -> 1000..100 ms
+//! let delay = Linear::new(Range::new(10u32, 25), Range::new(1000, 100));
+//! assert_eq!(delay.map_clamped(0), 1000);
+//! assert_eq!(delay.map_clamped(15), 700);
+//! assert_eq!(delay.map_clamped(40), 100);
+//! ```
+
+#![no_std]
+
+use core::cmp::Ordering;
+
+/// Why a mapping could not be computed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The input is outside the input range.
+    OutOfRange,
+    /// The input range is empty (`start == end`).
+    EmptyRange,
+    /// A piecewise curve needs at least two points.
+    TooFewPoints,
+    /// The input coordinates of a piecewise curve are not strictly increasing.
+    Unsorted,
+}
+
+/// A number that can be linearly interpolated without overflow.
+pub trait Scalar: Copy + PartialOrd {
+    /// Returns `to.start + (x - from.start) * (to.end - to.start) / (from.end - from.start)`,
+    /// rounded to the nearest representable value and saturated to the
+    /// bounds of the type. `from` must not be empty.
+    fn interpolate(x: Self, from: Range<Self>, to: Range<Self>) -> Self;
+}
+
+/// A range of values from `start` to `end`. Unlike [`core::ops::Range`],
+/// `end` is included and `start` may be greater than `end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Range<T> {
+    pub start: T,
+    pub end: T,
+}
+
+impl<T> Range<T> {
+    pub const fn new(start: T, end: T) -> Self {
+        Self { start, end }
+    }
+}
+
+impl<T: Scalar> Range<T> {
+    /// The smaller of the two ends.
+    pub fn min(&self) -> T {
+        if self.start <= self.end {
+            self.start
+        } else {
+            self.end
+        }
+    }
+
+    /// The larger of the two ends.
+    pub fn max(&self) -> T {
+        if self.start <= self.end {
+            self.end
+        } else {
+            self.start
+        }
+    }
+
+    pub fn is_reversed(&self) -> bool {
+        self.start > self.end
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn contains(&self, x: T) -> bool {
+        x >= self.min() && x <= self.max()
+    }
+
+    /// Limits `x` to the range.
+    pub fn clamp(&self, x: T) -> T {
+        if x < self.min() {
+            self.min()
+        } else if x > self.max() {
+            self.max()
+        } else {
+            x
+        }
+    }
+
+    /// The range with `start` and `end` swapped.
+    pub fn reversed(&self) -> Self {
+        Self::new(self.end, self.start)
+    }
+}
+
+/// A linear mapping from one range to another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Linear<T> {
+    pub from: Range<T>,
+    pub to: Range<T>,
+}
+
+impl<T> Linear<T> {
+    pub const fn new(from: Range<T>, to: Range<T>) -> Self {
+        Self { from, to }
+    }
+}
+
+impl<T: Scalar> Linear<T> {
+    /// Maps `x`, clamping it to the input range first. The result is always
+    /// inside the output range. An empty input range maps to `to.start`.
+    pub fn map_clamped(&self, x: T) -> T {
+        if self.from.is_empty() {
+            return self.to.start;
+        }
+        T::interpolate(self.from.clamp(x), self.from, self.to)
+    }
+
+    /// Maps `x`, extrapolating outside the input range. The result saturates
+    /// at the bounds of `T`. An empty input range maps to `to.start`.
+    pub fn map_saturating(&self, x: T) -> T {
+        if self.from.is_empty() {
+            return self.to.start;
+        }
+        T::interpolate(x, self.from, self.to)
+    }
+
+    /// Maps `x` if it is inside the input range.
+    pub fn try_map(&self, x: T) -> Result<T, Error> {
+        if self.from.is_empty() {
+            Err(Error::EmptyRange)
+        } else if !self.from.contains(x) {
+            Err(Error::OutOfRange)
+        } else {
+            Ok(T::interpolate(x, self.from, self.to))
+        }
+    }
+
+    /// The mapping in the opposite direction.
+    pub fn inverse(&self) -> Self {
+        Self::new(self.to, self.from)
+    }
+}
+
+/// A piecewise-linear curve through `(input, output)` points, sorted by
+/// strictly increasing input. Inputs outside the first and last point are
+/// clamped. An input that compares with nothing, i.e. a float NaN, goes
+/// through the first segment, so it maps to NaN like [`Linear::map_clamped`]
+/// does.
+#[derive(Debug, Clone, Copy)]
+pub struct Piecewise<'a, T> {
+    points: &'a [(T, T)],
+}
+
+impl<'a, T: Scalar> Piecewise<'a, T> {
+    pub fn new(points: &'a [(T, T)]) -> Result<Self, Error> {
+        if points.len() < 2 {
+            return Err(Error::TooFewPoints);
+        }
+        // Also rejects NaN inputs, which compare with nothing
+        if points
+            .windows(2)
+            .any(|w| w[0].0.partial_cmp(&w[1].0) != Some(Ordering::Less))
+        {
+            return Err(Error::Unsorted);
+        }
+        Ok(Self { points })
+    }
+
+    pub fn points(&self) -> &'a [(T, T)] {
+        self.points
+    }
+
+    pub fn map(&self, x: T) -> T {
+        let first = self.points[0];
+        let last = self.points[self.points.len() - 1];
+        if x <= first.0 {
+            return first.1;
+        }
+        if x >= last.0 {
+            return last.1;
+        }
+        // `x` lies strictly between the first and the last point, or is NaN
+        // and finds no point at all
+        let i = self.points.partition_point(|p| p.0 <= x).max(1);
+        let (x0, y0) = self.points[i - 1];
+        let (x1, y1) = self.points[i];
+        T::interpolate(x, Range::new(x0, x1), Range::new(y0, y1))
+    }
+}
+
+/// Divides rounding to the nearest integer, ties away from zero.
+/// `den` must be positive.
+fn div_round(num: i128, den: i128) -> i128 {
+    if num >= 0 {
+        (num + den / 2) / den
+    } else {
+        (num - den / 2) / den
+    }
+}
+
+/// Interpolates in `i128`. Every operand fits in 33 bits, so the product
+/// fits in 66 bits and nothing can overflow.
+fn interpolate_wide(x: i128, from: Range<i128>, to: Range<i128>) -> i128 {
+    let mut num = (x - from.start) * (to.end - to.start);
+    let mut den = from.end - from.start;
+    if den < 0 {
+        num = -num;
+        den = -den;
+    }
+    to.start + div_round(num, den)
+}
+
+macro_rules! impl_scalar_int {
+    ($($t:ty),*) => {$(
+        impl Scalar for $t {
+            fn interpolate(x: Self, from: Range<Self>, to: Range<Self>) -> Self {
+                let y = interpolate_wide(
+                    x as i128,
+                    Range::new(from.start as i128, from.end as i128),
+                    Range::new(to.start as i128, to.end as i128),
+                );
+                y.clamp(<$t>::MIN as i128, <$t>::MAX as i128) as $t
+            }
+        }
+    )*};
+}
+
+impl_scalar_int!(u8, u16, u32, i8, i16, i32);
+
+impl Scalar for f32 {
+    fn interpolate(x: Self, from: Range<Self>, to: Range<Self>) -> Self {
+        // Compute in `f64` so that the result is correctly rounded to `f32`
+        let t = (x as f64 - from.start as f64) / (from.end as f64 - from.start as f64);
+        let y = to.start as f64 + t * (to.end as f64 - to.start as f64);
+        y.clamp(f32::MIN as f64, f32::MAX as f64) as f32
+    }
+}
+
+#[cfg(feature = "fixed")]
+mod fixed_impls {
+    use fixed::{
+        types::extra::{LeEqU16, LeEqU32, LeEqU8},
+        FixedI16, FixedI32, FixedI8, FixedU16, FixedU32, FixedU8,
+    };
+
+    use super::{Range, Scalar};
+
+    // All four values share the same number of fractional bits, which cancel
+    // out in the ratio, so the integer interpolation of the raw bits is exact.
+    macro_rules! impl_scalar_fixed {
+        ($($fixed:ident, $leq:ident;)*) => {$(
+            impl<Frac: $leq> Scalar for $fixed<Frac> {
+                fn interpolate(x: Self, from: Range<Self>, to: Range<Self>) -> Self {
+                    Self::from_bits(Scalar::interpolate(
+                        x.to_bits(),
+                        Range::new(from.start.to_bits(), from.end.to_bits()),
+                        Range::new(to.start.to_bits(), to.end.to_bits()),
+                    ))
+                }
+            }
+        )*};
+    }
+
+    impl_scalar_fixed! {
+        FixedI8, LeEqU8;
+        FixedI16, LeEqU16;
+        FixedI32, LeEqU32;
+        FixedU8, LeEqU8;
+        FixedU16, LeEqU16;
+        FixedU32, LeEqU32;
+    }
+}
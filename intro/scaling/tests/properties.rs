@@ -0,0 +1,193 @@
+use proptest::prelude::*;
+use scaling::{Error, Linear, Piecewise, Range, Scalar};
+
+fn range<T: Scalar>(a: T, b: T) -> Range<T> {
+    Range::new(a, b)
+}
+
+proptest! {
+    #[test]
+    fn clamped_output_stays_in_output_range(
+        a in any::<u32>(), b in any::<u32>(), c in any::<u32>(), d in any::<u32>(), x in any::<u32>()
+    ) {
+        let map = Linear::new(range(a, b), range(c, d));
+        let y = map.map_clamped(x);
+        prop_assert!(map.to.contains(y));
+    }
+
+    #[test]
+    fn endpoints_map_to_endpoints(
+        a in any::<i32>(), b in any::<i32>(), c in any::<i32>(), d in any::<i32>()
+    ) {
+        prop_assume!(a != b);
+        let map = Linear::new(range(a, b), range(c, d));
+        prop_assert_eq!(map.map_clamped(a), c);
+        prop_assert_eq!(map.map_clamped(b), d);
+    }
+
+    #[test]
+    fn matches_exact_rational_result(
+        a in any::<i16>(), b in any::<i16>(), c in any::<i16>(), d in any::<i16>(), x in any::<i16>()
+    ) {
+        prop_assume!(a != b);
+        let map = Linear::new(range(a, b), range(c, d));
+        let x = map.from.clamp(x);
+        let exact = c as f64 + (x as f64 - a as f64) * (d as f64 - c as f64) / (b as f64 - a as f64);
+        let y = map.map_clamped(x) as f64;
+        prop_assert!((y - exact).abs() <= 0.5, "y = {}, exact = {}", y, exact);
+    }
+
+    #[test]
+    fn monotonic_in_input(
+        a in any::<u16>(), b in any::<u16>(), c in any::<u16>(), d in any::<u16>(),
+        x1 in any::<u16>(), x2 in any::<u16>()
+    ) {
+        prop_assume!(a < b);
+        let map = Linear::new(range(a, b), range(c, d));
+        let (lo, hi) = if x1 <= x2 { (x1, x2) } else { (x2, x1) };
+        let (y_lo, y_hi) = (map.map_clamped(lo), map.map_clamped(hi));
+        if c <= d {
+            prop_assert!(y_lo <= y_hi);
+        } else {
+            prop_assert!(y_lo >= y_hi);
+        }
+    }
+
+    #[test]
+    fn reversing_both_ranges_is_identity(
+        a in any::<i32>(), b in any::<i32>(), c in any::<i32>(), d in any::<i32>(), x in any::<i32>()
+    ) {
+        prop_assume!(a != b);
+        let map = Linear::new(range(a, b), range(c, d));
+        let reversed = Linear::new(map.from.reversed(), map.to.reversed());
+        let x = map.from.clamp(x);
+        let (y1, y2) = (map.map_clamped(x) as i64, reversed.map_clamped(x) as i64);
+        // Only rounding of exact halves may differ
+        prop_assert!((y1 - y2).abs() <= 1);
+    }
+
+    #[test]
+    fn saturating_never_wraps(
+        a in any::<u8>(), b in any::<u8>(), c in any::<u8>(), d in any::<u8>(), x in any::<u8>()
+    ) {
+        prop_assume!(a != b);
+        let map = Linear::new(range(a, b), range(c, d));
+        let exact = c as f64 + (x as f64 - a as f64) * (d as f64 - c as f64) / (b as f64 - a as f64);
+        let y = map.map_saturating(x);
+        prop_assert!((y as f64 - exact.clamp(0.0, 255.0)).abs() <= 0.5);
+    }
+
+    #[test]
+    fn try_map_rejects_only_outside_inputs(
+        a in any::<i8>(), b in any::<i8>(), c in any::<i8>(), d in any::<i8>(), x in any::<i8>()
+    ) {
+        let map = Linear::new(range(a, b), range(c, d));
+        match map.try_map(x) {
+            Ok(y) => prop_assert_eq!(y, map.map_clamped(x)),
+            Err(Error::EmptyRange) => prop_assert_eq!(a, b),
+            Err(Error::OutOfRange) => prop_assert!(!map.from.contains(x)),
+            Err(e) => prop_assert!(false, "unexpected error {:?}", e),
+        }
+    }
+
+    #[test]
+    fn piecewise_passes_through_its_points(
+        mut xs in proptest::collection::btree_set(any::<i32>(), 2..8),
+        ys in proptest::collection::vec(any::<i32>(), 8)
+    ) {
+        let points: Vec<(i32, i32)> = core::mem::take(&mut xs).into_iter().zip(ys).collect();
+        let curve = Piecewise::new(&points).unwrap();
+        for &(x, y) in &points {
+            prop_assert_eq!(curve.map(x), y);
+        }
+        let (min, max) = points.iter().fold((i32::MAX, i32::MIN), |(lo, hi), p| (lo.min(p.1), hi.max(p.1)));
+        prop_assert!((min..=max).contains(&curve.map(i32::MIN)));
+        prop_assert!((min..=max).contains(&curve.map(i32::MAX)));
+    }
+
+    #[test]
+    fn f32_clamped_output_stays_in_output_range(
+        a in -1e6f32..1e6, b in -1e6f32..1e6, c in -1e6f32..1e6, d in -1e6f32..1e6, x in -1e7f32..1e7
+    ) {
+        let map = Linear::new(range(a, b), range(c, d));
+        prop_assert!(map.to.contains(map.map_clamped(x)));
+    }
+}
+
+#[test]
+fn empty_input_range_does_not_divide_by_zero() {
+    let map = Linear::new(range(5u32, 5), range(0, 100));
+    assert_eq!(map.map_clamped(0), 0);
+    assert_eq!(map.map_saturating(10), 0);
+    assert_eq!(map.try_map(5), Err(Error::EmptyRange));
+}
+
+#[test]
+fn below_unsigned_input_range_does_not_underflow() {
+    // The old `map()` panicked here in debug builds
+    let leds = Linear::new(range(0u32, 30), range(0, 4));
+    assert_eq!(leds.map_clamped(0), 0);
+    let delay = Linear::new(range(10u32, 25), range(1000, 100));
+    assert_eq!(delay.map_clamped(3), 1000);
+    assert_eq!(delay.map_saturating(3), 1420);
+}
+
+#[test]
+fn piecewise_rejects_bad_points() {
+    assert_eq!(
+        Piecewise::<u8>::new(&[(1, 1)]).err(),
+        Some(Error::TooFewPoints)
+    );
+    assert_eq!(
+        Piecewise::new(&[(1u8, 1), (1, 2)]).err(),
+        Some(Error::Unsorted)
+    );
+    assert_eq!(
+        Piecewise::new(&[(2u8, 1), (1, 2)]).err(),
+        Some(Error::Unsorted)
+    );
+    assert_eq!(
+        Piecewise::new(&[(0.0, 1.0), (f32::NAN, 2.0)]).err(),
+        Some(Error::Unsorted)
+    );
+}
+
+#[test]
+fn piecewise_maps_nan_to_nan() {
+    let curve = Piecewise::new(&[(0.0f32, 10.0), (1.0, 20.0), (2.0, 40.0)]).unwrap();
+    assert!(curve.map(f32::NAN).is_nan());
+    assert_eq!(curve.map(-1.0), 10.0);
+    assert_eq!(curve.map(1.5), 30.0);
+    assert_eq!(curve.map(f32::INFINITY), 40.0);
+}
+
+#[cfg(feature = "fixed")]
+mod fixed_point {
+    use fixed::types::{I16F16, U8F8};
+
+    use super::*;
+
+    #[test]
+    fn fixed_point_matches_float() {
+        let map = Linear::new(
+            range(I16F16::from_num(-40), I16F16::from_num(125)),
+            range(I16F16::from_num(0), I16F16::from_num(1)),
+        );
+        let y = map.map_clamped(I16F16::from_num(42.5));
+        assert!((y.to_num::<f64>() - 82.5 / 165.0).abs() < 1e-4);
+        assert_eq!(map.map_clamped(I16F16::from_num(-100)), I16F16::ZERO);
+    }
+
+    proptest! {
+        #[test]
+        fn fixed_clamped_output_stays_in_output_range(
+            a in any::<u16>(), b in any::<u16>(), c in any::<u16>(), d in any::<u16>(), x in any::<u16>()
+        ) {
+            let map = Linear::new(
+                range(U8F8::from_bits(a), U8F8::from_bits(b)),
+                range(U8F8::from_bits(c), U8F8::from_bits(d)),
+            );
+            prop_assert!(map.to.contains(map.map_clamped(U8F8::from_bits(x))));
+        }
+    }
+}
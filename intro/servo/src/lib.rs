@@ -0,0 +1,422 @@
+//! Pulse-width math for hobby servos and ESCs driven by a PWM peripheral.
+//!
+//! A servo (or an ESC, which speaks the same protocol) expects a pulse every
+//! 20 ms (50 Hz) whose width, nominally 1000–2000 µs, encodes the position
+//! (or throttle). A PWM peripheral such as the ESP32's LEDC only knows duty
+//! cycles, so every pulse width has to be converted to a duty value for the
+//! timer's frequency and resolution. At 50 Hz and 14 bits one duty step is
+//! about 1.22 µs; at 8 bits it is 78 µs, too coarse for smooth motion.
+//!
+//! This crate contains only the hardware-independent parts, so they can be
+//! tested on the host:
+//!
+//! * [`PwmTiming`] converts between pulse widths and duty values;
+//! * [`ServoCalibration`] maps angles to calibrated pulse widths;
+//! * [`Motion`] moves towards a target angle at a limited speed;
+//! * [`EscArming`] runs an ESC arming sequence and then maps throttle to
+//!   pulse widths.
+//!
+//! ```rust
+//! use servo::{PwmTiming, ServoCalibration};
+//!
+//! let timing = PwmTiming::new(50, 14).unwrap();
+//! let servo = ServoCalibration::default();
+//! let pulse = servo.pulse_for_angle(90.0);
+//! assert_eq!(pulse, 1500);
+//! assert_eq!(timing.duty_for_pulse(pulse), 1229);
+//! ```
+//!
+//! The `esp32s3-demo` crate wraps these in drivers for LEDC channels.
+
+#![no_std]
+
+use scaling::{Linear, Range};
+
+/// The usual servo frame rate.
+pub const SERVO_FREQUENCY_HZ: u32 = 50;
+
+/// Why a configuration was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The frequency is 0.
+    ZeroFrequency,
+    /// The duty resolution is outside 1..=16 bits.
+    Resolution,
+    /// The pulse range is empty, reversed or longer than the PWM period.
+    PulseRange,
+    /// The angle range is empty.
+    AngleRange,
+}
+
+/// PWM frequency and duty resolution of a timer, as configured on LEDC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PwmTiming {
+    period_us: u32,
+    resolution_bits: u8,
+}
+
+impl PwmTiming {
+    pub const fn new(frequency_hz: u32, resolution_bits: u8) -> Result<Self, Error> {
+        if frequency_hz == 0 {
+            return Err(Error::ZeroFrequency);
+        }
+        if resolution_bits == 0 || resolution_bits > 16 {
+            return Err(Error::Resolution);
+        }
+        Ok(Self {
+            period_us: 1_000_000 / frequency_hz,
+            resolution_bits,
+        })
+    }
+
+    pub const fn period_us(&self) -> u32 {
+        self.period_us
+    }
+
+    pub const fn resolution_bits(&self) -> u8 {
+        self.resolution_bits
+    }
+
+    /// Duty value of a 100 % duty cycle, `2^bits`. This is what LEDC reports
+    /// as `max_duty_cycle()`.
+    pub const fn full_duty(&self) -> u32 {
+        1 << self.resolution_bits
+    }
+
+    /// Duty value producing a pulse as close as possible to `pulse_us`.
+    /// Pulses longer than the period give a 100 % duty cycle.
+    pub const fn duty_for_pulse(&self, pulse_us: u32) -> u32 {
+        let pulse = if pulse_us > self.period_us {
+            self.period_us
+        } else {
+            pulse_us
+        };
+        let scaled = pulse as u64 * self.full_duty() as u64;
+        ((scaled + self.period_us as u64 / 2) / self.period_us as u64) as u32
+    }
+
+    /// [`duty_for_pulse`](Self::duty_for_pulse) for `SetDutyCycle`, which
+    /// takes a `u16`. At 16 bits a 100 % duty cycle is 65536, one more than
+    /// fits: it saturates to 65535 instead of wrapping to 0.
+    pub const fn duty_u16_for_pulse(&self, pulse_us: u32) -> u16 {
+        let duty = self.duty_for_pulse(pulse_us);
+        if duty > u16::MAX as u32 {
+            u16::MAX
+        } else {
+            duty as u16
+        }
+    }
+
+    /// Pulse width produced by `duty`, rounded to the nearest microsecond.
+    pub const fn pulse_for_duty(&self, duty: u32) -> u32 {
+        let duty = if duty > self.full_duty() {
+            self.full_duty()
+        } else {
+            duty
+        };
+        let scaled = duty as u64 * self.period_us as u64;
+        ((scaled + self.full_duty() as u64 / 2) / self.full_duty() as u64) as u32
+    }
+
+    /// Length of one duty step in nanoseconds: the best pulse-width
+    /// resolution this timing can reach.
+    pub const fn step_ns(&self) -> u32 {
+        (self.period_us as u64 * 1000 / self.full_duty() as u64) as u32
+    }
+}
+
+/// Pulse widths of a servo at the two ends of its travel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ServoCalibration {
+    /// Pulse width at `angle.start`.
+    pub min_pulse_us: u32,
+    /// Pulse width at `angle.end`.
+    pub max_pulse_us: u32,
+    /// Mechanical travel in degrees, usually `0.0..=180.0`.
+    pub angle: Range<f32>,
+}
+
+impl Default for ServoCalibration {
+    /// 1000–2000 µs over 0–180°, the nominal values most servos accept.
+    fn default() -> Self {
+        Self {
+            min_pulse_us: 1000,
+            max_pulse_us: 2000,
+            angle: Range::new(0.0, 180.0),
+        }
+    }
+}
+
+impl ServoCalibration {
+    /// Calibration measured on a particular servo, e.g. 500–2500 µs for a
+    /// full 180° on many SG90s.
+    pub fn new(min_pulse_us: u32, max_pulse_us: u32, angle: Range<f32>) -> Self {
+        Self {
+            min_pulse_us,
+            max_pulse_us,
+            angle,
+        }
+    }
+
+    /// Checks the calibration against the PWM period.
+    pub fn validate(&self, timing: &PwmTiming) -> Result<(), Error> {
+        if self.min_pulse_us >= self.max_pulse_us || self.max_pulse_us > timing.period_us() {
+            return Err(Error::PulseRange);
+        }
+        if self.angle.is_empty() {
+            return Err(Error::AngleRange);
+        }
+        Ok(())
+    }
+
+    fn mapping(&self) -> Linear<f32> {
+        Linear::new(
+            self.angle,
+            Range::new(self.min_pulse_us as f32, self.max_pulse_us as f32),
+        )
+    }
+
+    /// Pulse width for `angle_deg`, clamped to the calibrated travel.
+    pub fn pulse_for_angle(&self, angle_deg: f32) -> u32 {
+        let pulse = self.mapping().map_clamped(angle_deg);
+        (pulse + 0.5) as u32
+    }
+
+    /// Angle corresponding to `pulse_us`, clamped to the calibrated travel.
+    pub fn angle_for_pulse(&self, pulse_us: u32) -> f32 {
+        self.mapping().inverse().map_clamped(pulse_us as f32)
+    }
+
+    /// Limits `angle_deg` to the calibrated travel.
+    pub fn clamp_angle(&self, angle_deg: f32) -> f32 {
+        self.angle.clamp(angle_deg)
+    }
+}
+
+/// Moves an angle towards a target at a limited speed.
+///
+/// Call [`Motion::update`] periodically (e.g. once per 20 ms servo frame)
+/// with a monotonic millisecond timestamp.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Motion {
+    current: f32,
+    target: f32,
+    /// Degrees per second; `None` jumps straight to the target.
+    max_speed: Option<f32>,
+    last_ms: Option<u64>,
+}
+
+impl Motion {
+    pub const fn new(angle_deg: f32, max_speed_deg_per_s: Option<f32>) -> Self {
+        Self {
+            current: angle_deg,
+            target: angle_deg,
+            max_speed: max_speed_deg_per_s,
+            last_ms: None,
+        }
+    }
+
+    pub fn current(&self) -> f32 {
+        self.current
+    }
+
+    pub fn target(&self) -> f32 {
+        self.target
+    }
+
+    pub fn is_settled(&self) -> bool {
+        self.current == self.target
+    }
+
+    pub fn set_target(&mut self, angle_deg: f32) {
+        self.target = angle_deg;
+    }
+
+    pub fn set_max_speed(&mut self, max_speed_deg_per_s: Option<f32>) {
+        self.max_speed = max_speed_deg_per_s;
+    }
+
+    /// Jumps to `angle_deg` immediately.
+    pub fn jump_to(&mut self, angle_deg: f32) {
+        self.current = angle_deg;
+        self.target = angle_deg;
+    }
+
+    /// Advances to `now_ms` and returns the new angle.
+    pub fn update(&mut self, now_ms: u64) -> f32 {
+        let elapsed_ms = match self.last_ms {
+            Some(last) => now_ms.saturating_sub(last),
+            None => 0,
+        };
+        self.last_ms = Some(now_ms);
+
+        match self.max_speed {
+            None => self.current = self.target,
+            Some(speed) => {
+                let max_step = speed * elapsed_ms as f32 / 1000.0;
+                let delta = self.target - self.current;
+                if -max_step <= delta && delta <= max_step {
+                    self.current = self.target;
+                } else if delta > 0.0 {
+                    self.current += max_step;
+                } else {
+                    self.current -= max_step;
+                }
+            }
+        }
+        self.current
+    }
+}
+
+/// One step of an ESC arming sequence: hold `pulse_us` for `duration_ms`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArmStep {
+    pub pulse_us: u32,
+    pub duration_ms: u32,
+}
+
+/// ESC pulse range and arming sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EscConfig<'a> {
+    /// Pulse width at zero throttle.
+    pub min_pulse_us: u32,
+    /// Pulse width at full throttle.
+    pub max_pulse_us: u32,
+    /// Pulses the ESC must see before it accepts throttle commands.
+    pub arming: &'a [ArmStep],
+}
+
+impl EscConfig<'static> {
+    /// Zero throttle for two seconds, which arms most ESCs.
+    pub const ARM_AT_ZERO: &'static [ArmStep] = &[ArmStep {
+        pulse_us: 1000,
+        duration_ms: 2000,
+    }];
+
+    /// Full throttle, then zero throttle: the throttle range calibration
+    /// most ESCs enter when they power up at full throttle.
+    pub const CALIBRATE: &'static [ArmStep] = &[
+        ArmStep {
+            pulse_us: 2000,
+            duration_ms: 3000,
+        },
+        ArmStep {
+            pulse_us: 1000,
+            duration_ms: 3000,
+        },
+    ];
+}
+
+impl Default for EscConfig<'static> {
+    fn default() -> Self {
+        Self {
+            min_pulse_us: 1000,
+            max_pulse_us: 2000,
+            arming: EscConfig::ARM_AT_ZERO,
+        }
+    }
+}
+
+/// State of an ESC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EscState {
+    /// Outputs zero throttle and ignores throttle commands.
+    Disarmed,
+    /// Running step `step` of the arming sequence, started at `since_ms`.
+    Arming { step: usize, since_ms: u64 },
+    /// Follows throttle commands.
+    Armed,
+}
+
+/// ESC arming state machine and throttle mapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EscArming<'a> {
+    config: EscConfig<'a>,
+    state: EscState,
+    /// Throttle in 0.1 % steps (0..=1000).
+    throttle_permille: u16,
+}
+
+impl<'a> EscArming<'a> {
+    pub const fn new(config: EscConfig<'a>) -> Self {
+        Self {
+            config,
+            state: EscState::Disarmed,
+            throttle_permille: 0,
+        }
+    }
+
+    pub fn config(&self) -> &EscConfig<'a> {
+        &self.config
+    }
+
+    pub fn state(&self) -> EscState {
+        self.state
+    }
+
+    pub fn is_armed(&self) -> bool {
+        self.state == EscState::Armed
+    }
+
+    /// Starts the arming sequence at `now_ms`. Throttle is reset to zero.
+    pub fn arm(&mut self, now_ms: u64) {
+        self.throttle_permille = 0;
+        self.state = if self.config.arming.is_empty() {
+            EscState::Armed
+        } else {
+            EscState::Arming {
+                step: 0,
+                since_ms: now_ms,
+            }
+        };
+    }
+
+    /// Returns to zero throttle and ignores throttle commands until the
+    /// next [`EscArming::arm`].
+    pub fn disarm(&mut self) {
+        self.throttle_permille = 0;
+        self.state = EscState::Disarmed;
+    }
+
+    /// Sets the throttle in 0.1 % steps (values above 1000 are clamped).
+    /// Ignored unless armed.
+    pub fn set_throttle_permille(&mut self, permille: u16) {
+        if self.is_armed() {
+            self.throttle_permille = permille.min(1000);
+        }
+    }
+
+    pub fn throttle_permille(&self) -> u16 {
+        self.throttle_permille
+    }
+
+    /// Advances the arming sequence to `now_ms` and returns the pulse width
+    /// to output.
+    pub fn update(&mut self, now_ms: u64) -> u32 {
+        while let EscState::Arming { step, since_ms } = self.state {
+            let current = self.config.arming[step];
+            let end_ms = since_ms + current.duration_ms as u64;
+            if now_ms < end_ms {
+                return current.pulse_us;
+            }
+            self.state = if step + 1 < self.config.arming.len() {
+                EscState::Arming {
+                    step: step + 1,
+                    since_ms: end_ms,
+                }
+            } else {
+                EscState::Armed
+            };
+        }
+        self.pulse_us()
+    }
+
+    /// Pulse width for the current throttle (zero throttle unless armed).
+    pub fn pulse_us(&self) -> u32 {
+        let map = Linear::new(
+            Range::new(0u32, 1000),
+            Range::new(self.config.min_pulse_us, self.config.max_pulse_us),
+        );
+        map.map_clamped(self.throttle_permille as u32)
+    }
+}
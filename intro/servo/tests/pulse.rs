@@ -0,0 +1,237 @@
+use scaling::Range;
+use servo::{
+    ArmStep, Error, EscArming, EscConfig, EscState, Motion, PwmTiming, ServoCalibration,
+    SERVO_FREQUENCY_HZ,
+};
+
+fn servo_timing(bits: u8) -> PwmTiming {
+    PwmTiming::new(SERVO_FREQUENCY_HZ, bits).unwrap()
+}
+
+#[test]
+fn rejects_invalid_timing() {
+    assert_eq!(PwmTiming::new(0, 14), Err(Error::ZeroFrequency));
+    assert_eq!(PwmTiming::new(50, 0), Err(Error::Resolution));
+    assert_eq!(PwmTiming::new(50, 17), Err(Error::Resolution));
+}
+
+#[test]
+fn period_and_step_at_50_hz() {
+    let timing = servo_timing(14);
+    assert_eq!(timing.period_us(), 20_000);
+    assert_eq!(timing.full_duty(), 16_384);
+    assert_eq!(timing.step_ns(), 1220);
+    assert_eq!(servo_timing(8).step_ns(), 78_125);
+}
+
+#[test]
+fn known_duty_values_at_14_bits() {
+    let timing = servo_timing(14);
+    assert_eq!(timing.duty_for_pulse(0), 0);
+    assert_eq!(timing.duty_for_pulse(1000), 819);
+    assert_eq!(timing.duty_for_pulse(1500), 1229);
+    assert_eq!(timing.duty_for_pulse(2000), 1638);
+    assert_eq!(timing.duty_for_pulse(20_000), 16_384);
+    assert_eq!(timing.duty_for_pulse(50_000), 16_384);
+}
+
+#[test]
+fn u16_duty_saturates_at_16_bits() {
+    let timing = servo_timing(16);
+    assert_eq!(timing.full_duty(), 65_536);
+    assert_eq!(timing.duty_for_pulse(20_000), 65_536);
+    // Would wrap to 0, i.e. no pulse at all, with `as u16`
+    assert_eq!(timing.duty_u16_for_pulse(20_000), u16::MAX);
+    assert_eq!(timing.duty_u16_for_pulse(19_999), 65_533);
+    assert_eq!(timing.duty_u16_for_pulse(1500), 4915);
+}
+
+#[test]
+fn u16_duty_matches_below_16_bits() {
+    for bits in 1..=15 {
+        let timing = servo_timing(bits);
+        for pulse in (0..=21_000).step_by(250) {
+            assert_eq!(
+                timing.duty_u16_for_pulse(pulse) as u32,
+                timing.duty_for_pulse(pulse),
+                "{bits} bits, {pulse} us"
+            );
+        }
+    }
+}
+
+#[test]
+fn round_trip_error_is_at_most_half_a_step_at_every_resolution() {
+    for bits in 1..=16 {
+        let timing = servo_timing(bits);
+        // Half a duty step, rounded up to whole microseconds, plus the
+        // rounding of the returned pulse itself
+        let tolerance = timing.step_ns().div_ceil(2000) + 1;
+        for pulse in (0..=timing.period_us()).step_by(7) {
+            let back = timing.pulse_for_duty(timing.duty_for_pulse(pulse));
+            assert!(
+                back.abs_diff(pulse) <= tolerance,
+                "{bits} bits: {pulse} us -> {back} us"
+            );
+        }
+    }
+}
+
+#[test]
+fn duty_is_monotonic_at_every_resolution() {
+    for bits in 1..=16 {
+        let timing = servo_timing(bits);
+        let mut previous = 0;
+        for pulse in 0..=timing.period_us() {
+            let duty = timing.duty_for_pulse(pulse);
+            assert!(duty >= previous, "{bits} bits: not monotonic at {pulse} us");
+            assert!(duty <= timing.full_duty());
+            previous = duty;
+        }
+    }
+}
+
+#[test]
+fn distinct_servo_positions_grow_with_resolution() {
+    let servo = ServoCalibration::default();
+    let positions = |bits| {
+        let timing = servo_timing(bits);
+        timing.duty_for_pulse(servo.max_pulse_us) - timing.duty_for_pulse(servo.min_pulse_us)
+    };
+    assert_eq!(positions(8), 13);
+    assert_eq!(positions(10), 51);
+    assert_eq!(positions(14), 819);
+    assert_eq!(positions(16), 3277);
+}
+
+#[test]
+fn full_duty_at_other_frequencies() {
+    for (frequency, bits) in [(50, 14), (330, 12), (400, 10), (1000, 8)] {
+        let timing = PwmTiming::new(frequency, bits).unwrap();
+        assert_eq!(
+            timing.duty_for_pulse(timing.period_us()),
+            timing.full_duty()
+        );
+        assert_eq!(
+            timing.pulse_for_duty(timing.full_duty()),
+            timing.period_us()
+        );
+    }
+}
+
+#[test]
+fn angle_maps_to_calibrated_pulse() {
+    let servo = ServoCalibration::new(500, 2500, Range::new(0.0, 180.0));
+    assert_eq!(servo.pulse_for_angle(0.0), 500);
+    assert_eq!(servo.pulse_for_angle(45.0), 1000);
+    assert_eq!(servo.pulse_for_angle(90.0), 1500);
+    assert_eq!(servo.pulse_for_angle(180.0), 2500);
+    // Clamped to the travel
+    assert_eq!(servo.pulse_for_angle(-10.0), 500);
+    assert_eq!(servo.pulse_for_angle(200.0), 2500);
+    assert_eq!(servo.angle_for_pulse(1500), 90.0);
+}
+
+#[test]
+fn reversed_angle_range_reverses_direction() {
+    let servo = ServoCalibration::new(1000, 2000, Range::new(90.0, -90.0));
+    assert_eq!(servo.pulse_for_angle(90.0), 1000);
+    assert_eq!(servo.pulse_for_angle(0.0), 1500);
+    assert_eq!(servo.pulse_for_angle(-90.0), 2000);
+}
+
+#[test]
+fn calibration_is_validated_against_the_period() {
+    let timing = servo_timing(14);
+    assert_eq!(ServoCalibration::default().validate(&timing), Ok(()));
+    let too_long = ServoCalibration::new(1000, 25_000, Range::new(0.0, 180.0));
+    assert_eq!(too_long.validate(&timing), Err(Error::PulseRange));
+    let reversed = ServoCalibration::new(2000, 1000, Range::new(0.0, 180.0));
+    assert_eq!(reversed.validate(&timing), Err(Error::PulseRange));
+    let no_travel = ServoCalibration::new(1000, 2000, Range::new(90.0, 90.0));
+    assert_eq!(no_travel.validate(&timing), Err(Error::AngleRange));
+}
+
+#[test]
+fn motion_is_speed_limited() {
+    let mut motion = Motion::new(0.0, Some(90.0));
+    motion.update(0);
+    motion.set_target(90.0);
+    assert_eq!(motion.update(500), 45.0);
+    assert!(!motion.is_settled());
+    assert_eq!(motion.update(1000), 90.0);
+    assert!(motion.is_settled());
+    // Does not overshoot
+    assert_eq!(motion.update(2000), 90.0);
+
+    motion.set_target(0.0);
+    assert_eq!(motion.update(2500), 45.0);
+}
+
+#[test]
+fn motion_without_limit_jumps() {
+    let mut motion = Motion::new(0.0, None);
+    motion.set_target(180.0);
+    assert_eq!(motion.update(0), 180.0);
+}
+
+#[test]
+fn esc_arms_after_sequence() {
+    let mut esc = EscArming::new(EscConfig::default());
+    assert_eq!(esc.state(), EscState::Disarmed);
+    esc.set_throttle_permille(500);
+    assert_eq!(esc.update(0), 1000, "throttle ignored while disarmed");
+
+    esc.arm(100);
+    esc.set_throttle_permille(500);
+    assert_eq!(esc.update(1000), 1000);
+    assert!(!esc.is_armed());
+    assert_eq!(esc.update(2100), 1000);
+    assert!(esc.is_armed());
+
+    esc.set_throttle_permille(500);
+    assert_eq!(esc.update(2200), 1500);
+    esc.set_throttle_permille(2000);
+    assert_eq!(esc.update(2300), 2000);
+
+    esc.disarm();
+    assert_eq!(esc.update(2400), 1000);
+}
+
+#[test]
+fn esc_calibration_sequence_runs_in_order() {
+    let mut esc = EscArming::new(EscConfig {
+        arming: EscConfig::CALIBRATE,
+        ..EscConfig::default()
+    });
+    esc.arm(0);
+    assert_eq!(esc.update(0), 2000);
+    assert_eq!(esc.update(2999), 2000);
+    assert_eq!(esc.update(3000), 1000);
+    assert_eq!(
+        esc.state(),
+        EscState::Arming {
+            step: 1,
+            since_ms: 3000
+        }
+    );
+    // A late update can skip over several steps
+    let steps = [
+        ArmStep {
+            pulse_us: 1500,
+            duration_ms: 10,
+        },
+        ArmStep {
+            pulse_us: 1000,
+            duration_ms: 10,
+        },
+    ];
+    let mut esc = EscArming::new(EscConfig {
+        arming: &steps,
+        ..EscConfig::default()
+    });
+    esc.arm(0);
+    assert_eq!(esc.update(100), 1000);
+    assert!(esc.is_armed());
+    assert_eq!(esc.update(6000), 1000);
+}
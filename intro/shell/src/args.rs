@@ -0,0 +1,134 @@
+//! Typed command arguments.
+
+use core::{fmt, str::SplitAsciiWhitespace};
+
+/// Why an argument was rejected. Names are the placeholders of the
+/// command's usage string, e.g. `pin`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgError {
+    Missing(&'static str),
+    Invalid {
+        name: &'static str,
+        expected: &'static str,
+    },
+    TooMany,
+}
+
+impl fmt::Display for ArgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing(name) => write!(f, "missing <{name}>"),
+            Self::Invalid { name, expected } => write!(f, "<{name}> must be {expected}"),
+            Self::TooMany => f.write_str("too many arguments"),
+        }
+    }
+}
+
+/// A value that can be parsed from one whitespace-separated word.
+pub trait FromArg<'a>: Sized {
+    /// Shown in [`ArgError::Invalid`].
+    const EXPECTED: &'static str;
+
+    fn from_arg(word: &'a str) -> Option<Self>;
+}
+
+impl<'a> FromArg<'a> for &'a str {
+    const EXPECTED: &'static str = "a word";
+
+    fn from_arg(word: &'a str) -> Option<Self> {
+        Some(word)
+    }
+}
+
+/// `1`/`0`, `on`/`off`, `high`/`low`, `true`/`false`.
+impl FromArg<'_> for bool {
+    const EXPECTED: &'static str = "1/0, on/off or high/low";
+
+    fn from_arg(word: &str) -> Option<Self> {
+        match word {
+            "1" | "on" | "high" | "true" => Some(true),
+            "0" | "off" | "low" | "false" => Some(false),
+            _ => None,
+        }
+    }
+}
+
+impl FromArg<'_> for f32 {
+    const EXPECTED: &'static str = "a number";
+
+    fn from_arg(word: &str) -> Option<Self> {
+        word.parse().ok()
+    }
+}
+
+/// Decimal, or hexadecimal and binary with a `0x` / `0b` prefix.
+macro_rules! integer {
+    ($($ty:ty => $expected:literal),* $(,)?) => {
+        $(
+            impl FromArg<'_> for $ty {
+                const EXPECTED: &'static str = $expected;
+
+                fn from_arg(word: &str) -> Option<Self> {
+                    if let Some(hex) = word.strip_prefix("0x") {
+                        <$ty>::from_str_radix(hex, 16).ok()
+                    } else if let Some(bin) = word.strip_prefix("0b") {
+                        <$ty>::from_str_radix(bin, 2).ok()
+                    } else {
+                        word.parse().ok()
+                    }
+                }
+            }
+        )*
+    };
+}
+
+integer! {
+    u8 => "an integer from 0 to 255",
+    u16 => "an integer from 0 to 65535",
+    u32 => "a non-negative integer",
+    usize => "a non-negative integer",
+    i8 => "an integer from -128 to 127",
+    i16 => "an integer from -32768 to 32767",
+    i32 => "an integer",
+}
+
+/// The words after the command name.
+#[derive(Debug, Clone)]
+pub struct Args<'a> {
+    words: SplitAsciiWhitespace<'a>,
+}
+
+impl<'a> Args<'a> {
+    pub fn new(args: &'a str) -> Self {
+        Self {
+            words: args.split_ascii_whitespace(),
+        }
+    }
+
+    /// The next word as a `T`.
+    pub fn next<T: FromArg<'a>>(&mut self, name: &'static str) -> Result<T, ArgError> {
+        self.optional(name)?.ok_or(ArgError::Missing(name))
+    }
+
+    /// Like [`Args::next`], but `None` when there are no more words.
+    pub fn optional<T: FromArg<'a>>(&mut self, name: &'static str) -> Result<Option<T>, ArgError> {
+        self.words
+            .next()
+            .map(|word| {
+                T::from_arg(word).ok_or(ArgError::Invalid {
+                    name,
+                    expected: T::EXPECTED,
+                })
+            })
+            .transpose()
+    }
+
+    /// Fails if there are words left. Call it before acting on the
+    /// arguments, so that a typo does not run half a command.
+    pub fn end(&mut self) -> Result<(), ArgError> {
+        match self.words.next() {
+            Some(_) => Err(ArgError::TooMany),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//! Ready-made commands for board bring-up.
+//!
+//! Each command works with any context that implements its trait, so the
+//! board code only has to map pin and channel numbers to its peripherals:
+//!
+//! ```text
+//! gpio read 4          GPIO4 = 1
+//! gpio write 10 on     GPIO10 <- 1
+//! adc 1 100            GPIO1: 100 samples, min 1203 mV, mean 1210 mV, max 1218 mV
+//! reboot
+//! ```
+
+use core::fmt;
+
+use crate::{ArgError, Args, Command, Error};
+
+/// Most samples a single `adc` command takes.
+pub const MAX_ADC_SAMPLES: u16 = 1000;
+
+/// GPIO access by pin number.
+pub trait Gpio {
+    fn read(&mut self, pin: u8) -> Result<bool, &'static str>;
+
+    /// The implementation decides which pins may be driven.
+    fn write(&mut self, pin: u8, high: bool) -> Result<(), &'static str>;
+}
+
+/// Calibrated ADC readings by pin number.
+pub trait Adc {
+    fn read_mv(&mut self, pin: u8) -> Result<u16, &'static str>;
+}
+
+pub trait Reboot {
+    /// Called after "Rebooting..." has been written; give the transmitter
+    /// time to send it.
+    fn reboot(&mut self) -> !;
+}
+
+/// `gpio read <pin>` / `gpio write <pin> <level>`
+pub fn gpio<C: Gpio>() -> Command<C> {
+    Command {
+        name: "gpio",
+        usage: "read <pin> | write <pin> <level>",
+        help: "read or drive a GPIO",
+        run: run_gpio::<C>,
+    }
+}
+
+/// `adc <pin> [count]`
+pub fn adc<C: Adc>() -> Command<C> {
+    Command {
+        name: "adc",
+        usage: "<pin> [count]",
+        help: "sample an ADC pin, in millivolts",
+        run: run_adc::<C>,
+    }
+}
+
+/// `reboot`
+pub fn reboot<C: Reboot>() -> Command<C> {
+    Command {
+        name: "reboot",
+        usage: "",
+        help: "restart the chip",
+        run: run_reboot::<C>,
+    }
+}
+
+fn run_gpio<C: Gpio>(
+    ctx: &mut C,
+    args: &mut Args<'_>,
+    out: &mut dyn fmt::Write,
+) -> Result<(), Error> {
+    match args.next("read|write")? {
+        "read" => {
+            let pin: u8 = args.next("pin")?;
+            args.end()?;
+            let high = ctx.read(pin).map_err(Error::Failed)?;
+            writeln!(out, "GPIO{pin} = {}", u8::from(high))?;
+        }
+        "write" => {
+            let pin: u8 = args.next("pin")?;
+            let high: bool = args.next("level")?;
+            args.end()?;
+            ctx.write(pin, high).map_err(Error::Failed)?;
+            writeln!(out, "GPIO{pin} <- {}", u8::from(high))?;
+        }
+        _ => {
+            return Err(ArgError::Invalid {
+                name: "read|write",
+                expected: "read or write",
+            }
+            .into())
+        }
+    }
+    Ok(())
+}
+
+fn run_adc<C: Adc>(
+    ctx: &mut C,
+    args: &mut Args<'_>,
+    out: &mut dyn fmt::Write,
+) -> Result<(), Error> {
+    let pin: u8 = args.next("pin")?;
+    let count: u16 = args.optional("count")?.unwrap_or(1);
+    args.end()?;
+    if !(1..=MAX_ADC_SAMPLES).contains(&count) {
+        return Err(ArgError::Invalid {
+            name: "count",
+            expected: "from 1 to 1000",
+        }
+        .into());
+    }
+
+    let first = ctx.read_mv(pin).map_err(Error::Failed)?;
+    if count == 1 {
+        writeln!(out, "GPIO{pin}: {first} mV")?;
+        return Ok(());
+    }
+    let (mut min, mut max, mut sum) = (first, first, u32::from(first));
+    for _ in 1..count {
+        let mv = ctx.read_mv(pin).map_err(Error::Failed)?;
+        min = min.min(mv);
+        max = max.max(mv);
+        sum += u32::from(mv);
+    }
+    let mean = (sum + u32::from(count) / 2) / u32::from(count);
+    writeln!(
+        out,
+        "GPIO{pin}: {count} samples, min {min} mV, mean {mean} mV, max {max} mV"
+    )?;
+    Ok(())
+}
+
+fn run_reboot<C: Reboot>(
+    ctx: &mut C,
+    args: &mut Args<'_>,
+    out: &mut dyn fmt::Write,
+) -> Result<(), Error> {
+    args.end()?;
+    writeln!(out, "Rebooting...")?;
+    ctx.reboot()
+}
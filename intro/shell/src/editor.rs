@@ -0,0 +1,368 @@
+//! Line editor for a VT100-style terminal.
+//!
+//! [`Editor::feed`] takes one received byte at a time and writes the echo
+//! and cursor movements for it, so it works the same on a UART interrupt, a
+//! polling loop or a byte script in a test. Only printable ASCII is stored;
+//! other bytes are either editing keys or ignored.
+//!
+//! | Keys                       | Action                              |
+//! |----------------------------|-------------------------------------|
+//! | Enter (CR, LF or CR LF)    | submit the line                     |
+//! | Backspace / DEL, Delete    | delete before / at the cursor       |
+//! | Left, Right, Home, End     | move the cursor (also Ctrl-A/Ctrl-E)|
+//! | Up, Down                   | step through the history            |
+//! | Tab                        | [`Event::Complete`]                 |
+//! | Ctrl-C                     | discard the line                    |
+//! | Ctrl-U, Ctrl-K             | delete to the start / end of line   |
+
+use embedded_io::Write;
+use heapless::{Deque, String, Vec};
+
+const BELL: &[u8] = b"\x07";
+const CLEAR_TO_END: &[u8] = b"\x1b[K";
+
+/// What a byte completed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event<const LINE: usize> {
+    /// Enter was pressed; the line has been added to the history.
+    Line(String<LINE>),
+    /// Tab was pressed; answer with [`Editor::complete`].
+    Complete,
+    /// Ctrl-C discarded the line.
+    Cancel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Escape {
+    None,
+    Esc,
+    /// `ESC [` followed by the numeric parameter read so far.
+    Csi(u8),
+    /// `ESC O`, sent for Home/End by some terminals.
+    Ss3,
+}
+
+/// A line of at most `LINE` bytes and the last `HISTORY` submitted lines.
+pub struct Editor<const LINE: usize, const HISTORY: usize> {
+    prompt: &'static str,
+    line: Vec<u8, LINE>,
+    cursor: usize,
+    /// Newest first.
+    history: Deque<String<LINE>, HISTORY>,
+    /// Index into `history` while stepping through it.
+    recall: Option<usize>,
+    /// The line that was being typed before Up was pressed.
+    draft: Vec<u8, LINE>,
+    escape: Escape,
+    after_cr: bool,
+}
+
+impl<const LINE: usize, const HISTORY: usize> Editor<LINE, HISTORY> {
+    pub const fn new(prompt: &'static str) -> Self {
+        Self {
+            prompt,
+            line: Vec::new(),
+            cursor: 0,
+            history: Deque::new(),
+            recall: None,
+            draft: Vec::new(),
+            escape: Escape::None,
+            after_cr: false,
+        }
+    }
+
+    pub fn line(&self) -> &str {
+        // Only printable ASCII is ever inserted
+        core::str::from_utf8(&self.line).unwrap_or_default()
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Submitted lines, oldest first.
+    pub fn history(&self) -> impl Iterator<Item = &str> {
+        self.history.iter().rev().map(String::as_str)
+    }
+
+    /// Writes the prompt and the line at the start of a fresh row.
+    pub fn prompt<W: Write>(&self, out: &mut W) -> Result<(), W::Error> {
+        out.write_all(self.prompt.as_bytes())?;
+        out.write_all(&self.line)?;
+        cursor_left(out, self.line.len() - self.cursor)
+    }
+
+    /// Rewrites the prompt and the line over the current row, e.g. after
+    /// output that did not end with a newline.
+    pub fn redraw<W: Write>(&self, out: &mut W) -> Result<(), W::Error> {
+        out.write_all(b"\r")?;
+        self.prompt(out)?;
+        out.write_all(CLEAR_TO_END)
+    }
+
+    pub fn feed<W: Write>(
+        &mut self,
+        byte: u8,
+        out: &mut W,
+    ) -> Result<Option<Event<LINE>>, W::Error> {
+        match self.escape {
+            Escape::None => {}
+            Escape::Esc => {
+                self.escape = match byte {
+                    b'[' => Escape::Csi(0),
+                    b'O' => Escape::Ss3,
+                    _ => Escape::None,
+                };
+                return Ok(None);
+            }
+            Escape::Ss3 => {
+                self.escape = Escape::None;
+                match byte {
+                    b'H' => self.home(out)?,
+                    b'F' => self.end(out)?,
+                    _ => {}
+                }
+                return Ok(None);
+            }
+            Escape::Csi(n) => {
+                if byte.is_ascii_digit() {
+                    self.escape = Escape::Csi(n.saturating_mul(10).saturating_add(byte - b'0'));
+                    return Ok(None);
+                }
+                self.escape = Escape::None;
+                match (byte, n) {
+                    (b'A', _) => self.history_up(out)?,
+                    (b'B', _) => self.history_down(out)?,
+                    (b'C', _) => self.right(out)?,
+                    (b'D', _) => self.left(out)?,
+                    (b'H', _) | (b'~', 1 | 7) => self.home(out)?,
+                    (b'F', _) | (b'~', 4 | 8) => self.end(out)?,
+                    (b'~', 3) => self.delete(out)?,
+                    _ => {}
+                }
+                return Ok(None);
+            }
+        }
+
+        let after_cr = core::mem::replace(&mut self.after_cr, byte == b'\r');
+        match byte {
+            b'\n' if after_cr => {}
+            b'\r' | b'\n' => return self.submit(out).map(Some),
+            b'\t' => return Ok(Some(Event::Complete)),
+            0x03 => {
+                out.write_all(b"^C\r\n")?;
+                self.line.clear();
+                self.cursor = 0;
+                self.recall = None;
+                return Ok(Some(Event::Cancel));
+            }
+            0x08 | 0x7f => self.backspace(out)?,
+            0x01 => self.home(out)?,
+            0x05 => self.end(out)?,
+            0x0b => {
+                self.line.truncate(self.cursor);
+                out.write_all(CLEAR_TO_END)?;
+            }
+            0x15 => self.kill_to_start(out)?,
+            0x1b => self.escape = Escape::Esc,
+            0x20..=0x7e => self.insert(&[byte], out)?,
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    /// Completes the word before the cursor from `words`, if it is the first
+    /// word of the line.
+    ///
+    /// A single match is inserted with a trailing space. Several matches are
+    /// extended to their common prefix, or listed below the line when there
+    /// is nothing to extend.
+    pub fn complete<'w, W, I>(&mut self, words: I, out: &mut W) -> Result<(), W::Error>
+    where
+        W: Write,
+        I: Iterator<Item = &'w str> + Clone,
+    {
+        let prefix = &self.line[..self.cursor];
+        if prefix.contains(&b' ') {
+            return out.write_all(BELL);
+        }
+        let matches = words.filter(|w| w.as_bytes().starts_with(prefix));
+        let Some(first) = matches.clone().next() else {
+            return out.write_all(BELL);
+        };
+        let common = matches.clone().fold(first.len(), |len, w| {
+            first
+                .bytes()
+                .zip(w.bytes())
+                .take(len)
+                .take_while(|(a, b)| a == b)
+                .count()
+        });
+        let typed = prefix.len();
+
+        if matches.clone().count() == 1 {
+            self.insert(&first.as_bytes()[typed..], out)?;
+            self.insert(b" ", out)
+        } else if common > typed {
+            self.insert(&first.as_bytes()[typed..common], out)
+        } else {
+            out.write_all(b"\r\n")?;
+            for word in matches {
+                out.write_all(word.as_bytes())?;
+                out.write_all(b"  ")?;
+            }
+            out.write_all(b"\r\n")?;
+            self.prompt(out)
+        }
+    }
+
+    fn submit<W: Write>(&mut self, out: &mut W) -> Result<Event<LINE>, W::Error> {
+        out.write_all(b"\r\n")?;
+        let mut line = String::new();
+        // Same capacity, cannot fail
+        let _ = line.push_str(self.line());
+        if !line.trim().is_empty() && self.history.front() != Some(&line) {
+            if self.history.is_full() {
+                self.history.pop_back();
+            }
+            let _ = self.history.push_front(line.clone());
+        }
+        self.line.clear();
+        self.cursor = 0;
+        self.recall = None;
+        Ok(Event::Line(line))
+    }
+
+    fn insert<W: Write>(&mut self, bytes: &[u8], out: &mut W) -> Result<(), W::Error> {
+        if self.line.len() + bytes.len() > LINE {
+            return out.write_all(BELL);
+        }
+        for (i, &b) in bytes.iter().enumerate() {
+            // Capacity checked above
+            let _ = self.line.insert(self.cursor + i, b);
+        }
+        let start = self.cursor;
+        self.cursor += bytes.len();
+        out.write_all(&self.line[start..])?;
+        cursor_left(out, self.line.len() - self.cursor)
+    }
+
+    fn backspace<W: Write>(&mut self, out: &mut W) -> Result<(), W::Error> {
+        if self.cursor == 0 {
+            return out.write_all(BELL);
+        }
+        self.cursor -= 1;
+        self.line.remove(self.cursor);
+        out.write_all(b"\x08")?;
+        self.redraw_tail(out)
+    }
+
+    fn delete<W: Write>(&mut self, out: &mut W) -> Result<(), W::Error> {
+        if self.cursor == self.line.len() {
+            return out.write_all(BELL);
+        }
+        self.line.remove(self.cursor);
+        self.redraw_tail(out)
+    }
+
+    fn kill_to_start<W: Write>(&mut self, out: &mut W) -> Result<(), W::Error> {
+        let tail: Vec<u8, LINE> = Vec::from_slice(&self.line[self.cursor..]).unwrap_or_default();
+        cursor_left(out, self.cursor)?;
+        self.line = tail;
+        self.cursor = 0;
+        self.redraw_tail(out)
+    }
+
+    fn left<W: Write>(&mut self, out: &mut W) -> Result<(), W::Error> {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            out.write_all(b"\x1b[D")?;
+        }
+        Ok(())
+    }
+
+    fn right<W: Write>(&mut self, out: &mut W) -> Result<(), W::Error> {
+        if self.cursor < self.line.len() {
+            self.cursor += 1;
+            out.write_all(b"\x1b[C")?;
+        }
+        Ok(())
+    }
+
+    fn home<W: Write>(&mut self, out: &mut W) -> Result<(), W::Error> {
+        cursor_left(out, self.cursor)?;
+        self.cursor = 0;
+        Ok(())
+    }
+
+    fn end<W: Write>(&mut self, out: &mut W) -> Result<(), W::Error> {
+        out.write_all(&self.line[self.cursor..])?;
+        self.cursor = self.line.len();
+        Ok(())
+    }
+
+    fn history_up<W: Write>(&mut self, out: &mut W) -> Result<(), W::Error> {
+        let next = self.recall.map_or(0, |i| i + 1);
+        let Some(entry) = self.history.iter().nth(next) else {
+            return out.write_all(BELL);
+        };
+        let entry: Vec<u8, LINE> = Vec::from_slice(entry.as_bytes()).unwrap_or_default();
+        if self.recall.is_none() {
+            self.draft = self.line.clone();
+        }
+        self.recall = Some(next);
+        self.replace_line(entry, out)
+    }
+
+    fn history_down<W: Write>(&mut self, out: &mut W) -> Result<(), W::Error> {
+        let entry = match self.recall {
+            None => return out.write_all(BELL),
+            Some(0) => {
+                self.recall = None;
+                core::mem::take(&mut self.draft)
+            }
+            Some(i) => {
+                self.recall = Some(i - 1);
+                let entry = self.history.iter().nth(i - 1).map_or("", String::as_str);
+                Vec::from_slice(entry.as_bytes()).unwrap_or_default()
+            }
+        };
+        self.replace_line(entry, out)
+    }
+
+    fn replace_line<W: Write>(&mut self, line: Vec<u8, LINE>, out: &mut W) -> Result<(), W::Error> {
+        cursor_left(out, self.cursor)?;
+        self.line = line;
+        self.cursor = self.line.len();
+        out.write_all(&self.line)?;
+        out.write_all(CLEAR_TO_END)
+    }
+
+    /// Rewrites everything from the cursor to the end of the line.
+    fn redraw_tail<W: Write>(&self, out: &mut W) -> Result<(), W::Error> {
+        out.write_all(&self.line[self.cursor..])?;
+        out.write_all(CLEAR_TO_END)?;
+        cursor_left(out, self.line.len() - self.cursor)
+    }
+}
+
+/// `ESC [ n D`, nothing for 0.
+fn cursor_left<W: Write>(out: &mut W, n: usize) -> Result<(), W::Error> {
+    match n {
+        0 => Ok(()),
+        1 => out.write_all(b"\x1b[D"),
+        _ => {
+            let mut digits = [0u8; 20];
+            let mut i = digits.len();
+            let mut n = n;
+            while n > 0 {
+                i -= 1;
+                digits[i] = b'0' + (n % 10) as u8;
+                n /= 10;
+            }
+            out.write_all(b"\x1b[")?;
+            out.write_all(&digits[i..])?;
+            out.write_all(b"D")
+        }
+    }
+}
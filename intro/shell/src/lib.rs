@@ -0,0 +1,290 @@
+//! `no_std` interactive command shell over any [`embedded_io`] byte stream.
+//!
+//! The pieces are independent so each can be tested on the host:
+//!
+//! * [`Editor`] turns received bytes into an edited line, with history and
+//!   the terminal output (echo, cursor movement) that goes with it.
+//! * [`Args`] parses the words of a line into typed values.
+//! * [`Shell`] ties an editor to a table of [`Command`]s, which run with a
+//!   caller-supplied context `C` (pins, ADC, ...) and print through
+//!   [`core::fmt::Write`]. `help` and `history` are always available.
+//! * [`builtins`] has GPIO, ADC and `reboot` commands for any context that
+//!   implements the matching trait.
+//!
+//! ```rust
+//! use core::fmt::Write as _;
+//! use shell::{Args, Command, Error, Shell};
+//!
+//! struct Counter(u32);
+//!
+//! fn add(ctx: &mut Counter, args: &mut Args<'_>, out: &mut dyn core::fmt::Write) -> Result<(), Error> {
+//!     let n: u32 = args.next("n")?;
+//!     args.end()?;
+//!     ctx.0 += n;
+//!     writeln!(out, "total {}", ctx.0)?;
+//!     Ok(())
+//! }
+//!
+//! let commands = [Command { name: "add", usage: "<n>", help: "add to the total", run: add }];
+//! let mut shell: Shell<'_, Counter> = Shell::new("> ", &commands);
+//! let mut ctx = Counter(0);
+//! let mut out = Vec::new();
+//! for &byte in b"ad\t0x10\r" {
+//!     shell.feed(byte, &mut ctx, &mut out).unwrap();
+//! }
+//! assert_eq!(ctx.0, 16);
+//! assert!(out.ends_with(b"total 16\r\n> "));
+//! ```
+
+#![no_std]
+
+use core::fmt::{self, Write as _};
+
+use embedded_io::{Read, Write};
+
+mod args;
+pub mod builtins;
+mod editor;
+
+pub use args::{ArgError, Args, FromArg};
+pub use editor::{Editor, Event};
+
+/// Why a command did not complete.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// Printed with the command's usage.
+    Arg(ArgError),
+    /// Printed as is.
+    Failed(&'static str),
+    /// Writing the output failed; the shell returns the I/O error.
+    Output,
+}
+
+impl From<ArgError> for Error {
+    fn from(e: ArgError) -> Self {
+        Self::Arg(e)
+    }
+}
+
+impl From<fmt::Error> for Error {
+    fn from(_: fmt::Error) -> Self {
+        Self::Output
+    }
+}
+
+/// Runs a command: the context, the words after the name and the terminal.
+/// `\n` written to the terminal is sent as `\r\n`.
+pub type Handler<C> = fn(&mut C, &mut Args<'_>, &mut dyn fmt::Write) -> Result<(), Error>;
+
+/// One entry of the command table.
+pub struct Command<C> {
+    pub name: &'static str,
+    /// Arguments, as shown by `help`, e.g. `<pin> [count]`.
+    pub usage: &'static str,
+    /// One line for `help`.
+    pub help: &'static str,
+    pub run: Handler<C>,
+}
+
+impl<C> Clone for Command<C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C> Copy for Command<C> {}
+
+/// Shell commands that need the shell itself rather than the context.
+const SHELL_COMMANDS: [(&str, &str, &str); 2] = [
+    (
+        "help",
+        "[command]",
+        "list commands, or show one command's usage",
+    ),
+    ("history", "", "list previous lines"),
+];
+
+/// A line editor and a command table.
+///
+/// Lines are limited to `LINE` bytes and the last `HISTORY` lines are kept.
+pub struct Shell<'a, C, const LINE: usize = 64, const HISTORY: usize = 8> {
+    commands: &'a [Command<C>],
+    editor: Editor<LINE, HISTORY>,
+}
+
+impl<'a, C, const LINE: usize, const HISTORY: usize> Shell<'a, C, LINE, HISTORY> {
+    pub fn new(prompt: &'static str, commands: &'a [Command<C>]) -> Self {
+        Self {
+            commands,
+            editor: Editor::new(prompt),
+        }
+    }
+
+    pub fn editor(&self) -> &Editor<LINE, HISTORY> {
+        &self.editor
+    }
+
+    /// Prints the prompt.
+    pub fn start<W: Write>(&mut self, out: &mut W) -> Result<(), W::Error> {
+        self.editor.prompt(out)
+    }
+
+    /// Handles one received byte. A completed line is executed before this
+    /// returns, followed by a new prompt.
+    pub fn feed<W: Write>(&mut self, byte: u8, ctx: &mut C, out: &mut W) -> Result<(), W::Error> {
+        match self.editor.feed(byte, out)? {
+            None => Ok(()),
+            Some(Event::Line(line)) => {
+                self.execute(&line, ctx, out)?;
+                self.editor.prompt(out)
+            }
+            Some(Event::Complete) => {
+                let commands = self.commands;
+                let names = SHELL_COMMANDS
+                    .iter()
+                    .map(|(name, _, _)| *name)
+                    .chain(commands.iter().map(|c| c.name));
+                self.editor.complete(names, out)
+            }
+            Some(Event::Cancel) => self.editor.prompt(out),
+        }
+    }
+
+    /// Prints the prompt, then reads and handles bytes until `io` reports
+    /// the end of the stream, which a UART never does.
+    pub fn run<IO: Read + Write>(&mut self, ctx: &mut C, io: &mut IO) -> Result<(), IO::Error> {
+        self.start(io)?;
+        let mut buf = [0u8; 16];
+        loop {
+            let n = io.read(&mut buf)?;
+            if n == 0 {
+                return Ok(());
+            }
+            for &byte in &buf[..n] {
+                self.feed(byte, ctx, io)?;
+            }
+        }
+    }
+
+    /// Runs one line without touching the editor, e.g. a startup script.
+    pub fn execute<W: Write>(&self, line: &str, ctx: &mut C, out: &mut W) -> Result<(), W::Error> {
+        let line = line.trim_start();
+        let (name, rest) = line
+            .split_once(|c: char| c.is_ascii_whitespace())
+            .unwrap_or((line, ""));
+        if name.is_empty() {
+            return Ok(());
+        }
+        let mut args = Args::new(rest);
+        let mut term = Terminal::new(out);
+
+        let result = match name {
+            "help" => self.help(&mut args, &mut term),
+            "history" => self.history(&mut args, &mut term),
+            _ => match self.commands.iter().find(|c| c.name == name) {
+                Some(command) => (command.run)(ctx, &mut args, &mut term),
+                None => {
+                    let _ = writeln!(term, "unknown command `{name}`, try `help`");
+                    Ok(())
+                }
+            },
+        };
+        match result {
+            Ok(()) | Err(Error::Output) => {}
+            Err(Error::Arg(e)) => {
+                let usage = self.usage(name).unwrap_or("");
+                let _ = writeln!(term, "error: {e}\nusage: {name} {usage}");
+            }
+            Err(Error::Failed(message)) => {
+                let _ = writeln!(term, "error: {message}");
+            }
+        }
+        term.finish()
+    }
+
+    fn usage(&self, name: &str) -> Option<&'static str> {
+        SHELL_COMMANDS
+            .iter()
+            .find(|(n, _, _)| *n == name)
+            .map(|(_, usage, _)| *usage)
+            .or_else(|| {
+                self.commands
+                    .iter()
+                    .find(|c| c.name == name)
+                    .map(|c| c.usage)
+            })
+    }
+
+    fn help(&self, args: &mut Args<'_>, out: &mut dyn fmt::Write) -> Result<(), Error> {
+        let topic: Option<&str> = args.optional("command")?;
+        args.end()?;
+        let mut all = SHELL_COMMANDS
+            .iter()
+            .copied()
+            .chain(self.commands.iter().map(|c| (c.name, c.usage, c.help)));
+        match topic {
+            None => {
+                for (name, usage, help) in all {
+                    writeln!(out, "  {name:<8} {usage:<32} {help}")?;
+                }
+            }
+            Some(topic) => match all.find(|(name, _, _)| *name == topic) {
+                Some((name, usage, help)) => writeln!(out, "{help}\nusage: {name} {usage}")?,
+                None => return Err(Error::Failed("no such command")),
+            },
+        }
+        Ok(())
+    }
+
+    fn history(&self, args: &mut Args<'_>, out: &mut dyn fmt::Write) -> Result<(), Error> {
+        args.end()?;
+        for (i, line) in self.editor.history().enumerate() {
+            writeln!(out, "{:>3}  {line}", i + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// [`fmt::Write`] over an [`embedded_io::Write`] that sends `\n` as `\r\n`
+/// and keeps the first I/O error.
+struct Terminal<'w, W: Write> {
+    io: &'w mut W,
+    error: Option<W::Error>,
+}
+
+impl<'w, W: Write> Terminal<'w, W> {
+    fn new(io: &'w mut W) -> Self {
+        Self { io, error: None }
+    }
+
+    fn raw(&mut self, bytes: &[u8]) -> fmt::Result {
+        if self.error.is_some() {
+            return Err(fmt::Error);
+        }
+        self.io.write_all(bytes).map_err(|e| {
+            self.error = Some(e);
+            fmt::Error
+        })
+    }
+
+    fn finish(self) -> Result<(), W::Error> {
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<W: Write> fmt::Write for Terminal<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut lines = s.split('\n');
+        if let Some(first) = lines.next() {
+            self.raw(first.as_bytes())?;
+        }
+        for line in lines {
+            self.raw(b"\r\n")?;
+            self.raw(line.as_bytes())?;
+        }
+        Ok(())
+    }
+}
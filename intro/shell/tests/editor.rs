@@ -0,0 +1,230 @@
+//! Line editor against scripted terminal input.
+
+use shell::{Editor, Event};
+
+type Ed = Editor<16, 3>;
+
+const UP: &[u8] = b"\x1b[A";
+const DOWN: &[u8] = b"\x1b[B";
+const LEFT: &[u8] = b"\x1b[D";
+const RIGHT: &[u8] = b"\x1b[C";
+
+/// Feeds `input` and returns the events and everything written back.
+fn feed(editor: &mut Ed, input: &[u8]) -> (Vec<Event<16>>, Vec<u8>) {
+    let mut out = Vec::new();
+    let events = input
+        .iter()
+        .filter_map(|&b| editor.feed(b, &mut out).unwrap())
+        .collect();
+    (events, out)
+}
+
+fn submit(editor: &mut Ed, input: &[u8]) -> String {
+    let (events, _) = feed(editor, input);
+    match events.as_slice() {
+        [Event::Line(line)] => line.to_string(),
+        other => panic!("expected one line, got {other:?}"),
+    }
+}
+
+fn script(parts: &[&[u8]]) -> Vec<u8> {
+    parts.concat()
+}
+
+#[test]
+fn typing_echoes_and_enter_submits() {
+    let mut ed = Ed::new("> ");
+    let (events, out) = feed(&mut ed, b"led on\r");
+    assert_eq!(out, b"led on\r\n");
+    assert_eq!(events, [Event::Line("led on".try_into().unwrap())]);
+    assert_eq!(ed.line(), "");
+}
+
+#[test]
+fn cr_lf_is_one_enter_but_lf_alone_submits() {
+    let mut ed = Ed::new("> ");
+    let (events, _) = feed(&mut ed, b"a\r\nb\n\r");
+    let lines: Vec<_> = events
+        .iter()
+        .map(|e| match e {
+            Event::Line(l) => l.as_str(),
+            _ => panic!("{e:?}"),
+        })
+        .collect();
+    assert_eq!(lines, ["a", "b", ""]);
+}
+
+#[test]
+fn backspace_and_delete_both_codes() {
+    let mut ed = Ed::new("> ");
+    let (_, out) = feed(&mut ed, b"abc\x08\x7f");
+    assert_eq!(ed.line(), "a");
+    assert_eq!(out, b"abc\x08\x1b[K\x08\x1b[K");
+
+    // Nothing to delete rings the bell
+    let (_, out) = feed(&mut ed, b"\x08\x08");
+    assert_eq!(ed.line(), "");
+    assert_eq!(out, b"\x08\x1b[K\x07");
+}
+
+#[test]
+fn insert_in_the_middle_redraws_the_tail() {
+    let mut ed = Ed::new("> ");
+    let (_, out) = feed(&mut ed, &script(&[b"ac", LEFT, b"b"]));
+    assert_eq!(ed.line(), "abc");
+    assert_eq!(ed.cursor(), 2);
+    assert_eq!(out, b"ac\x1b[Dbc\x1b[D");
+}
+
+#[test]
+fn backspace_in_the_middle() {
+    let mut ed = Ed::new("> ");
+    let (_, out) = feed(&mut ed, &script(&[b"abcd", LEFT, LEFT, b"\x7f"]));
+    assert_eq!(ed.line(), "acd");
+    assert_eq!(out, b"abcd\x1b[D\x1b[D\x08cd\x1b[K\x1b[2D");
+}
+
+#[test]
+fn home_end_and_delete_keys() {
+    let mut ed = Ed::new("> ");
+    // Home as CSI H, Delete as CSI 3 ~, End as SS3 F
+    feed(
+        &mut ed,
+        &script(&[b"xabc", b"\x1b[H", b"\x1b[3~", b"\x1bOF", b"!"]),
+    );
+    assert_eq!(ed.line(), "abc!");
+    // Home as CSI 1 ~ and Ctrl-E
+    feed(&mut ed, &script(&[b"\x1b[1~", b">", b"\x05", b"<"]));
+    assert_eq!(ed.line(), ">abc!<");
+    // Cursor does not move past either end
+    let (_, out) = feed(&mut ed, RIGHT);
+    assert!(out.is_empty());
+    feed(&mut ed, b"\x01");
+    let (_, out) = feed(&mut ed, LEFT);
+    assert!(out.is_empty());
+}
+
+#[test]
+fn kill_to_start_and_end() {
+    let mut ed = Ed::new("> ");
+    feed(&mut ed, &script(&[b"gpio read 4", LEFT, LEFT, b"\x0b"]));
+    assert_eq!(ed.line(), "gpio read");
+    feed(&mut ed, &script(&[LEFT, LEFT, LEFT, LEFT, b"\x15"]));
+    assert_eq!(ed.line(), "read");
+    assert_eq!(ed.cursor(), 0);
+}
+
+#[test]
+fn line_is_limited_to_capacity() {
+    let mut ed = Ed::new("> ");
+    let (_, out) = feed(&mut ed, b"0123456789abcdefXY");
+    assert_eq!(ed.line(), "0123456789abcdef");
+    assert!(out.ends_with(b"f\x07\x07"));
+}
+
+#[test]
+fn ctrl_c_discards_the_line() {
+    let mut ed = Ed::new("> ");
+    let (events, out) = feed(&mut ed, b"reboo\x03");
+    assert_eq!(events, [Event::Cancel]);
+    assert!(out.ends_with(b"^C\r\n"));
+    assert_eq!(ed.line(), "");
+    assert_eq!(ed.history().count(), 0);
+}
+
+#[test]
+fn history_up_down_restores_the_draft() {
+    let mut ed = Ed::new("> ");
+    submit(&mut ed, b"one\r");
+    submit(&mut ed, b"two\r");
+    feed(&mut ed, b"dra");
+
+    feed(&mut ed, UP);
+    assert_eq!(ed.line(), "two");
+    let (_, out) = feed(&mut ed, UP);
+    assert_eq!(ed.line(), "one");
+    assert_eq!(out, b"\x1b[3Done\x1b[K");
+    // Past the oldest entry
+    let (_, out) = feed(&mut ed, UP);
+    assert_eq!(out, b"\x07");
+
+    feed(&mut ed, DOWN);
+    assert_eq!(ed.line(), "two");
+    feed(&mut ed, DOWN);
+    assert_eq!(ed.line(), "dra");
+    let (_, out) = feed(&mut ed, DOWN);
+    assert_eq!(out, b"\x07");
+}
+
+#[test]
+fn recalled_line_can_be_edited_and_resubmitted() {
+    let mut ed = Ed::new("> ");
+    submit(&mut ed, b"adc 1\r");
+    let line = submit(&mut ed, &script(&[UP, b"0 10\r"]));
+    assert_eq!(line, "adc 10 10");
+    assert_eq!(ed.history().collect::<Vec<_>>(), ["adc 1", "adc 10 10"]);
+}
+
+#[test]
+fn history_skips_blank_and_repeated_lines_and_drops_the_oldest() {
+    let mut ed = Ed::new("> ");
+    for line in [&b"a\r"[..], b"  \r", b"b\r", b"b\r", b"c\r", b"d\r"] {
+        submit(&mut ed, line);
+    }
+    assert_eq!(ed.history().collect::<Vec<_>>(), ["b", "c", "d"]);
+}
+
+#[test]
+fn unknown_escape_sequences_are_swallowed() {
+    let mut ed = Ed::new("> ");
+    // F5 and Alt-x
+    let (_, out) = feed(&mut ed, b"\x1b[15~\x1bxok");
+    assert_eq!(ed.line(), "ok");
+    assert_eq!(out, b"ok");
+}
+
+#[test]
+fn tab_completes_a_unique_command() {
+    let mut ed = Ed::new("> ");
+    let words = ["gpio", "adc", "help", "history"];
+    let (events, _) = feed(&mut ed, b"gp\t");
+    assert_eq!(events, [Event::Complete]);
+    let mut out = Vec::new();
+    ed.complete(words.iter().copied(), &mut out).unwrap();
+    assert_eq!(ed.line(), "gpio ");
+    assert_eq!(out, b"io ");
+}
+
+#[test]
+fn tab_extends_to_the_common_prefix_then_lists() {
+    let mut ed = Ed::new("> ");
+    let words = ["gpio", "adc", "help", "history"];
+    feed(&mut ed, b"h");
+    let mut out = Vec::new();
+    ed.complete(words.iter().copied(), &mut out).unwrap();
+    // "help" and "history" share only "h"
+    assert_eq!(out, b"\r\nhelp  history  \r\n> h");
+    assert_eq!(ed.line(), "h");
+
+    let words = ["history", "histogram"];
+    let mut out = Vec::new();
+    ed.complete(words.iter().copied(), &mut out).unwrap();
+    assert_eq!(ed.line(), "histo");
+    assert_eq!(out, b"isto");
+}
+
+#[test]
+fn tab_only_completes_the_first_word() {
+    let mut ed = Ed::new("> ");
+    feed(&mut ed, b"gpio r");
+    let mut out = Vec::new();
+    ed.complete(["read"].into_iter(), &mut out).unwrap();
+    assert_eq!(out, b"\x07");
+
+    let mut ed = Ed::new("> ");
+    feed(&mut ed, b"zz");
+    let mut out = Vec::new();
+    ed.complete(["gpio"].into_iter(), &mut out).unwrap();
+    assert_eq!(out, b"\x07");
+    assert_eq!(ed.line(), "zz");
+}
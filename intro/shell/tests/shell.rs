@@ -0,0 +1,245 @@
+//! Command table, argument parsing and the built-in commands against a
+//! fake board.
+
+use std::collections::VecDeque;
+
+use shell::{
+    builtins::{self, Adc, Gpio, Reboot},
+    ArgError, Args, Command, Shell,
+};
+
+#[derive(Default)]
+struct Board {
+    levels: [bool; 8],
+    adc: VecDeque<u16>,
+}
+
+impl Gpio for Board {
+    fn read(&mut self, pin: u8) -> Result<bool, &'static str> {
+        self.levels.get(pin as usize).copied().ok_or("no such pin")
+    }
+
+    fn write(&mut self, pin: u8, high: bool) -> Result<(), &'static str> {
+        match pin {
+            4..=7 => {
+                self.levels[pin as usize] = high;
+                Ok(())
+            }
+            _ => Err("not an output"),
+        }
+    }
+}
+
+impl Adc for Board {
+    fn read_mv(&mut self, pin: u8) -> Result<u16, &'static str> {
+        if pin != 1 {
+            return Err("not an ADC pin");
+        }
+        self.adc.pop_front().ok_or("no sample")
+    }
+}
+
+impl Reboot for Board {
+    fn reboot(&mut self) -> ! {
+        panic!("reboot");
+    }
+}
+
+fn commands() -> [Command<Board>; 3] {
+    [builtins::gpio(), builtins::adc(), builtins::reboot()]
+}
+
+/// Runs `input` through a fresh shell and returns the output after the
+/// first prompt.
+fn session(board: &mut Board, input: &[u8]) -> String {
+    let commands = commands();
+    let mut shell: Shell<'_, Board> = Shell::new("> ", &commands);
+    let mut io = Script {
+        input: input.iter().copied().collect(),
+        output: Vec::new(),
+    };
+    shell.run(board, &mut io).unwrap();
+    let out = String::from_utf8(io.output).unwrap();
+    out.strip_prefix("> ").unwrap().to_string()
+}
+
+/// Byte script in, terminal output out. Reads at most 3 bytes at a time,
+/// like a UART FIFO that is drained before it fills.
+struct Script {
+    input: VecDeque<u8>,
+    output: Vec<u8>,
+}
+
+impl embedded_io::ErrorType for Script {
+    type Error = core::convert::Infallible;
+}
+
+impl embedded_io::Read for Script {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let n = buf.len().min(3).min(self.input.len());
+        for b in &mut buf[..n] {
+            *b = self.input.pop_front().unwrap();
+        }
+        Ok(n)
+    }
+}
+
+impl embedded_io::Write for Script {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        self.output.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[test]
+fn args_parse_typed_values() {
+    let mut args = Args::new("  0x1f 0b101 -3 on 2.5 word ");
+    assert_eq!(args.next::<u8>("a"), Ok(31));
+    assert_eq!(args.next::<u16>("b"), Ok(5));
+    assert_eq!(args.next::<i32>("c"), Ok(-3));
+    assert_eq!(args.next::<bool>("d"), Ok(true));
+    assert_eq!(args.next::<f32>("e"), Ok(2.5));
+    assert_eq!(args.next::<&str>("f"), Ok("word"));
+    assert_eq!(args.optional::<u8>("g"), Ok(None));
+    assert_eq!(args.next::<u8>("g"), Err(ArgError::Missing("g")));
+    assert_eq!(args.end(), Ok(()));
+}
+
+#[test]
+fn args_reject_out_of_range_and_leftovers() {
+    let mut args = Args::new("256 x");
+    assert_eq!(
+        args.next::<u8>("pin"),
+        Err(ArgError::Invalid {
+            name: "pin",
+            expected: "an integer from 0 to 255"
+        })
+    );
+    assert_eq!(args.end(), Err(ArgError::TooMany));
+}
+
+#[test]
+fn gpio_read_and_write() {
+    let mut board = Board::default();
+    let out = session(&mut board, b"gpio write 5 high\rgpio read 5\rgpio read 2\r");
+    assert!(board.levels[5]);
+    assert_eq!(
+        out,
+        "gpio write 5 high\r\nGPIO5 <- 1\r\n> \
+         gpio read 5\r\nGPIO5 = 1\r\n> \
+         gpio read 2\r\nGPIO2 = 0\r\n> "
+    );
+}
+
+#[test]
+fn board_errors_and_bad_arguments_are_reported() {
+    let mut board = Board::default();
+    let out = session(
+        &mut board,
+        b"gpio write 1 1\rgpio write 5 maybe\rgpio toggle\r",
+    );
+    assert!(out.contains("gpio write 1 1\r\nerror: not an output\r\n> "));
+    assert!(out.contains(
+        "error: <level> must be 1/0, on/off or high/low\r\n\
+         usage: gpio read <pin> | write <pin> <level>\r\n"
+    ));
+    assert!(out.contains("error: <read|write> must be read or write\r\n"));
+    assert_eq!(board.levels, [false; 8]);
+}
+
+#[test]
+fn extra_arguments_stop_the_command() {
+    let mut board = Board::default();
+    let out = session(&mut board, b"gpio write 5 1 2\r");
+    assert!(out.contains("error: too many arguments\r\n"));
+    assert!(!board.levels[5]);
+}
+
+#[test]
+fn adc_single_and_averaged() {
+    let mut board = Board {
+        adc: [1200, 1000, 1100, 1201].into_iter().collect(),
+        ..Board::default()
+    };
+    let out = session(&mut board, b"adc 1\radc 1 3\r");
+    assert!(out.contains("adc 1\r\nGPIO1: 1200 mV\r\n"));
+    assert!(out.contains("GPIO1: 3 samples, min 1000 mV, mean 1100 mV, max 1201 mV\r\n"));
+}
+
+#[test]
+fn adc_count_is_limited() {
+    let mut board = Board::default();
+    let out = session(&mut board, b"adc 1 0\radc 1 1001\radc 3\r");
+    assert_eq!(
+        out.matches("error: <count> must be from 1 to 1000").count(),
+        2
+    );
+    assert!(out.contains("error: not an ADC pin"));
+}
+
+#[test]
+#[should_panic(expected = "reboot")]
+fn reboot_calls_the_board() {
+    session(&mut Board::default(), b"reboot\r");
+}
+
+#[test]
+fn reboot_takes_no_arguments() {
+    let out = session(&mut Board::default(), b"reboot now\r");
+    assert!(out.contains("error: too many arguments\r\nusage: reboot \r\n"));
+}
+
+#[test]
+fn unknown_commands_and_blank_lines() {
+    let out = session(&mut Board::default(), b"frobnicate\r   \r");
+    assert_eq!(
+        out,
+        "frobnicate\r\nunknown command `frobnicate`, try `help`\r\n> \
+         \x20  \r\n> "
+    );
+}
+
+#[test]
+fn help_lists_everything_and_describes_one_command() {
+    let out = session(&mut Board::default(), b"help\rhelp adc\rhelp nope\r");
+    for name in ["help", "history", "gpio", "adc", "reboot"] {
+        assert!(out.contains(&format!("  {name:<8} ")), "{name}");
+    }
+    assert!(out.contains("sample an ADC pin, in millivolts\r\nusage: adc <pin> [count]\r\n"));
+    assert!(out.contains("error: no such command"));
+}
+
+#[test]
+fn history_command_lists_previous_lines() {
+    let out = session(&mut Board::default(), b"gpio read 1\rhelp gpio\rhistory\r");
+    assert!(out.ends_with("  1  gpio read 1\r\n  2  help gpio\r\n  3  history\r\n> "));
+}
+
+#[test]
+fn tab_and_history_drive_commands() {
+    let mut board = Board {
+        adc: [10, 20].into_iter().collect(),
+        ..Board::default()
+    };
+    // "ad<Tab>" -> "adc ", then Up recalls it
+    session(&mut board, b"ad\t1\r\x1b[A\r");
+    assert!(board.adc.is_empty());
+}
+
+#[test]
+fn execute_runs_a_line_without_the_editor() {
+    let commands = commands();
+    let shell: Shell<'_, Board> = Shell::new("> ", &commands);
+    let mut board = Board::default();
+    let mut out = Vec::new();
+    shell
+        .execute("gpio write 4 on", &mut board, &mut out)
+        .unwrap();
+    assert!(board.levels[4]);
+    assert_eq!(out, b"GPIO4 <- 1\r\n");
+    assert_eq!(shell.editor().history().count(), 0);
+}
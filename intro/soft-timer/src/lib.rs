@@ -0,0 +1,394 @@
+//! Any number of one-shot and periodic software timers on one hardware
+//! timer.
+//!
+//! The hardware timer interrupts at a fixed tick (10 ms, say), and the
+//! interrupt handler only tells the main loop. The main loop calls
+//! [`TimerWheel::advance`] with the current time, gets back the timers that
+//! are due and runs their callbacks itself, outside the interrupt.
+//!
+//! The timers sit in a hashed timing wheel: `SLOTS` slots, a timer due at
+//! tick `t` in slot `t % SLOTS`. Each tick only looks at one slot, and
+//! starting or cancelling a timer is O(1). Every timer has a
+//! [`TimerHandle`] to cancel, reschedule or change the period with; handles
+//! carry a generation, so the handle of a timer that has finished or been
+//! cancelled stops working even once its entry is reused.
+//!
+//! The wheel never reads the time itself, so tests drive it with a fake
+//! clock:
+//!
+//! ```rust
+//! use soft_timer::TimerWheel;
+//!
+//! let mut timers: TimerWheel<char, 4, 8> = TimerWheel::new(10);
+//! let blink = timers.start_periodic(30, 'b').unwrap();
+//! timers.start_once(45, 'o').unwrap();
+//!
+//! let due = |fired: &[soft_timer::Fired<char>]| fired.iter().map(|f| f.payload).collect::<String>();
+//! assert_eq!(due(&timers.advance(29)), "");
+//! assert_eq!(due(&timers.advance(30)), "b");
+//! assert_eq!(due(&timers.advance(50)), "o");
+//! assert_eq!(due(&timers.advance(60)), "b");
+//! timers.cancel(blink).unwrap();
+//! assert!(timers.is_empty());
+//! ```
+//!
+//! The `esp32s3-demo` crate drives the wheel from a `TimerGroup` interrupt.
+
+#![no_std]
+
+use heapless::Vec;
+
+// End of a slot's list
+const NIL: u16 = u16::MAX;
+
+/// Refers to one started timer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerHandle {
+    index: u16,
+    generation: u16,
+}
+
+/// Why an operation failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimerError {
+    /// All `N` timers are running.
+    Full,
+    /// The timer has finished or been cancelled.
+    InvalidHandle,
+    /// A period of 0 would fire on every tick forever.
+    ZeroPeriod,
+}
+
+/// A timer that is due.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fired<T> {
+    pub handle: TimerHandle,
+    pub payload: T,
+    /// Periods of a periodic timer skipped because the main loop called
+    /// `advance` too late.
+    pub missed: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Active<T> {
+    deadline: u64,
+    // 0 for a one-shot timer
+    period: u64,
+    payload: T,
+    prev: u16,
+    next: u16,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry<T> {
+    generation: u16,
+    active: Option<Active<T>>,
+}
+
+/// Up to `N` timers in `SLOTS` slots, each carrying a `T` (a callback, for
+/// instance) that is handed back when it fires.
+pub struct TimerWheel<T: Copy, const N: usize, const SLOTS: usize> {
+    tick_ms: u64,
+    // Last tick processed
+    current: u64,
+    // Last time passed to `advance`, which may be between ticks
+    last_ms: u64,
+    entries: [Entry<T>; N],
+    slots: [u16; SLOTS],
+    len: usize,
+}
+
+impl<T: Copy, const N: usize, const SLOTS: usize> TimerWheel<T, N, SLOTS> {
+    /// `tick_ms` is the resolution, the period of the hardware timer.
+    pub const fn new(tick_ms: u32) -> Self {
+        assert!(tick_ms > 0, "tick must not be 0");
+        assert!(SLOTS > 0, "wheel needs at least one slot");
+        assert!(N < NIL as usize, "too many timers");
+        Self {
+            tick_ms: tick_ms as u64,
+            current: 0,
+            last_ms: 0,
+            entries: [Entry {
+                generation: 0,
+                active: None,
+            }; N],
+            slots: [NIL; SLOTS],
+            len: 0,
+        }
+    }
+
+    pub fn tick_ms(&self) -> u32 {
+        self.tick_ms as u32
+    }
+
+    /// The time of the last tick processed by [`advance`](Self::advance).
+    pub fn now_ms(&self) -> u64 {
+        self.current * self.tick_ms
+    }
+
+    /// Running timers.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Fires once, `delay_ms` after the last time given to `advance`.
+    pub fn start_once(&mut self, delay_ms: u64, payload: T) -> Result<TimerHandle, TimerError> {
+        self.insert(self.deadline_for(delay_ms), 0, payload)
+    }
+
+    /// Fires every `period_ms`, starting `period_ms` after the last time
+    /// given to `advance`.
+    pub fn start_periodic(
+        &mut self,
+        period_ms: u64,
+        payload: T,
+    ) -> Result<TimerHandle, TimerError> {
+        if period_ms == 0 {
+            return Err(TimerError::ZeroPeriod);
+        }
+        let period = self.ticks_for(period_ms);
+        self.insert(self.deadline_for(period_ms), period, payload)
+    }
+
+    /// Stops the timer and hands back its payload.
+    pub fn cancel(&mut self, handle: TimerHandle) -> Result<T, TimerError> {
+        let index = self.check(handle)?;
+        self.unlink(index);
+        let entry = &mut self.entries[index];
+        let active = entry.active.take().unwrap();
+        entry.generation = entry.generation.wrapping_add(1);
+        self.len -= 1;
+        Ok(active.payload)
+    }
+
+    /// Restarts the countdown: due `delay_ms` from now. A periodic timer
+    /// keeps its period afterwards.
+    pub fn reschedule(&mut self, handle: TimerHandle, delay_ms: u64) -> Result<(), TimerError> {
+        let index = self.check(handle)?;
+        let deadline = self.deadline_for(delay_ms);
+        self.unlink(index);
+        self.active_mut(index).deadline = deadline;
+        self.link(index);
+        Ok(())
+    }
+
+    /// Changes the period, `None` for one-shot, from the next time the timer
+    /// fires.
+    pub fn set_period(
+        &mut self,
+        handle: TimerHandle,
+        period_ms: Option<u64>,
+    ) -> Result<(), TimerError> {
+        let index = self.check(handle)?;
+        let period = match period_ms {
+            Some(0) => return Err(TimerError::ZeroPeriod),
+            Some(ms) => self.ticks_for(ms),
+            None => 0,
+        };
+        self.active_mut(index).period = period;
+        Ok(())
+    }
+
+    /// Whether the timer is still running.
+    pub fn is_active(&self, handle: TimerHandle) -> bool {
+        self.check(handle).is_ok()
+    }
+
+    /// Time left until the timer fires, from the last tick processed.
+    pub fn remaining_ms(&self, handle: TimerHandle) -> Option<u64> {
+        let index = self.check(handle).ok()?;
+        let deadline = self.entries[index].active.as_ref()?.deadline;
+        Some((deadline - self.current) * self.tick_ms)
+    }
+
+    /// When the next timer fires, e.g. to work out how long the main loop
+    /// may sleep.
+    pub fn next_deadline_ms(&self) -> Option<u64> {
+        self.entries
+            .iter()
+            .filter_map(|e| e.active.as_ref().map(|a| a.deadline))
+            .min()
+            .map(|tick| tick * self.tick_ms)
+    }
+
+    /// Processes every tick up to `now_ms` and returns the timers that are
+    /// due, each at most once.
+    pub fn advance(&mut self, now_ms: u64) -> Vec<Fired<T>, N> {
+        let target = now_ms / self.tick_ms;
+        self.last_ms = self.last_ms.max(now_ms);
+        let mut fired = Vec::new();
+
+        while self.current < target {
+            if self.len == 0 {
+                // Nothing to fire, jump straight there
+                self.current = target;
+                break;
+            }
+            self.current += 1;
+
+            let slot = (self.current % SLOTS as u64) as usize;
+            let mut index = self.slots[slot];
+            while index != NIL {
+                // Expiring may move this timer to another slot
+                let next = self.active(index as usize).next;
+                if self.active(index as usize).deadline <= self.current {
+                    let fire = self.expire(index as usize, target);
+                    // At most once per timer per advance, so at most N
+                    let _ = fired.push(fire);
+                }
+                index = next;
+            }
+        }
+        fired
+    }
+
+    fn expire(&mut self, index: usize, target: u64) -> Fired<T> {
+        self.unlink(index);
+        let generation = self.entries[index].generation;
+        let handle = TimerHandle {
+            index: index as u16,
+            generation,
+        };
+        let active = self.active_mut(index);
+        let payload = active.payload;
+
+        if active.period == 0 {
+            let entry = &mut self.entries[index];
+            entry.active = None;
+            entry.generation = generation.wrapping_add(1);
+            self.len -= 1;
+            return Fired {
+                handle,
+                payload,
+                missed: 0,
+            };
+        }
+
+        // A periodic timer several periods behind skips them and fires once
+        let mut deadline = active.deadline + active.period;
+        let mut missed = 0;
+        if deadline <= target {
+            let behind = (target - deadline) / active.period + 1;
+            missed = behind as u32;
+            deadline += behind * active.period;
+        }
+        active.deadline = deadline;
+        self.link(index);
+        Fired {
+            handle,
+            payload,
+            missed,
+        }
+    }
+
+    fn ticks_for(&self, ms: u64) -> u64 {
+        // Rounded up and at least one tick, so never early
+        ms.div_ceil(self.tick_ms).max(1)
+    }
+
+    // The first tick at or after `delay_ms` from the last `advance`, which
+    // may be up to a tick later than the last tick processed
+    fn deadline_for(&self, delay_ms: u64) -> u64 {
+        (self.last_ms + delay_ms)
+            .div_ceil(self.tick_ms)
+            .max(self.current + 1)
+    }
+
+    fn insert(
+        &mut self,
+        deadline: u64,
+        period: u64,
+        payload: T,
+    ) -> Result<TimerHandle, TimerError> {
+        let index = self
+            .entries
+            .iter()
+            .position(|e| e.active.is_none())
+            .ok_or(TimerError::Full)?;
+        let entry = &mut self.entries[index];
+        entry.active = Some(Active {
+            deadline,
+            period,
+            payload,
+            prev: NIL,
+            next: NIL,
+        });
+        let generation = entry.generation;
+        self.link(index);
+        self.len += 1;
+        Ok(TimerHandle {
+            index: index as u16,
+            generation,
+        })
+    }
+
+    fn check(&self, handle: TimerHandle) -> Result<usize, TimerError> {
+        let index = handle.index as usize;
+        match self.entries.get(index) {
+            Some(entry) if entry.generation == handle.generation && entry.active.is_some() => {
+                Ok(index)
+            }
+            _ => Err(TimerError::InvalidHandle),
+        }
+    }
+
+    fn active(&self, index: usize) -> &Active<T> {
+        self.entries[index].active.as_ref().unwrap()
+    }
+
+    fn active_mut(&mut self, index: usize) -> &mut Active<T> {
+        self.entries[index].active.as_mut().unwrap()
+    }
+
+    // Pushes onto the front of the timer's slot list
+    fn link(&mut self, index: usize) {
+        let slot = (self.active(index).deadline % SLOTS as u64) as usize;
+        let head = self.slots[slot];
+        {
+            let active = self.active_mut(index);
+            active.prev = NIL;
+            active.next = head;
+        }
+        if head != NIL {
+            self.active_mut(head as usize).prev = index as u16;
+        }
+        self.slots[slot] = index as u16;
+    }
+
+    fn unlink(&mut self, index: usize) {
+        let Active {
+            deadline,
+            prev,
+            next,
+            ..
+        } = *self.active(index);
+        if prev == NIL {
+            let slot = (deadline % SLOTS as u64) as usize;
+            self.slots[slot] = next;
+        } else {
+            self.active_mut(prev as usize).next = next;
+        }
+        if next != NIL {
+            self.active_mut(next as usize).prev = prev;
+        }
+    }
+}
+
+/// A callback payload: called from the main loop with a shared context.
+pub type Callback<C> = fn(&mut C, TimerHandle);
+
+impl<C, const N: usize, const SLOTS: usize> TimerWheel<Callback<C>, N, SLOTS> {
+    /// Advances to `now_ms` and calls every callback that is due; returns
+    /// how many ran.
+    pub fn run(&mut self, now_ms: u64, context: &mut C) -> usize {
+        let fired = self.advance(now_ms);
+        for f in &fired {
+            (f.payload)(context, f.handle);
+        }
+        fired.len()
+    }
+}
@@ -0,0 +1,301 @@
+use soft_timer::{Callback, Fired, TimerError, TimerHandle, TimerWheel};
+
+const TICK: u64 = 10;
+
+type Wheel = TimerWheel<u32, 8, 16>;
+
+/// A hand-advanced clock driving a wheel, as the main loop would with the
+/// hardware tick interrupt.
+struct FakeClock {
+    now_ms: u64,
+    step_ms: u64,
+}
+
+impl FakeClock {
+    fn new(step_ms: u64) -> Self {
+        Self { now_ms: 0, step_ms }
+    }
+
+    /// Advances until `until_ms`, returning `(time, payload, missed)` of
+    /// every firing.
+    fn run(&mut self, wheel: &mut Wheel, until_ms: u64) -> Vec<(u64, u32, u32)> {
+        let mut log = Vec::new();
+        while self.now_ms < until_ms {
+            self.now_ms = (self.now_ms + self.step_ms).min(until_ms);
+            for f in wheel.advance(self.now_ms) {
+                log.push((self.now_ms, f.payload, f.missed));
+            }
+        }
+        log
+    }
+}
+
+fn wheel() -> Wheel {
+    TimerWheel::new(TICK as u32)
+}
+
+fn times(log: &[(u64, u32, u32)], payload: u32) -> Vec<u64> {
+    log.iter()
+        .filter(|&&(_, p, _)| p == payload)
+        .map(|&(t, _, _)| t)
+        .collect()
+}
+
+#[test]
+fn one_shot_fires_once_on_its_tick() {
+    let mut timers = wheel();
+    let mut clock = FakeClock::new(1);
+    timers.start_once(50, 1).unwrap();
+    timers.start_once(55, 2).unwrap();
+    let log = clock.run(&mut timers, 200);
+    assert_eq!(log, [(50, 1, 0), (60, 2, 0)]);
+    assert!(timers.is_empty());
+}
+
+#[test]
+fn periodic_fires_every_period() {
+    let mut timers = wheel();
+    let mut clock = FakeClock::new(1);
+    timers.start_periodic(30, 1).unwrap();
+    timers.start_periodic(100, 2).unwrap();
+    let log = clock.run(&mut timers, 300);
+    assert_eq!(times(&log, 1), (1..=10).map(|k| k * 30).collect::<Vec<_>>());
+    assert_eq!(times(&log, 2), [100, 200, 300]);
+    assert!(log.iter().all(|&(_, _, missed)| missed == 0));
+}
+
+#[test]
+fn period_rounds_up_to_whole_ticks() {
+    let mut timers = wheel();
+    let mut clock = FakeClock::new(1);
+    timers.start_periodic(25, 1).unwrap();
+    let log = clock.run(&mut timers, 120);
+    assert_eq!(times(&log, 1), [30, 60, 90, 120]);
+}
+
+#[test]
+fn started_between_ticks_is_never_early() {
+    // The main loop runs at 7 ms, the wheel has processed tick 0 only
+    let mut timers = wheel();
+    let mut clock = FakeClock::new(1);
+    clock.run(&mut timers, 7);
+    assert_eq!(timers.now_ms(), 0);
+
+    timers.start_once(10, 1).unwrap();
+    timers.start_periodic(20, 2).unwrap();
+    let log = clock.run(&mut timers, 70);
+    // 17 ms is due at the 20 ms tick, not at 10 ms
+    assert_eq!(times(&log, 1), [20]);
+    assert_eq!(times(&log, 2), [30, 50, 70]);
+}
+
+#[test]
+fn random_delays_fire_within_one_tick() {
+    let mut seed = 0x9E37_79B9u32;
+    let mut next = move |n: u64| {
+        seed ^= seed << 13;
+        seed ^= seed >> 17;
+        seed ^= seed << 5;
+        seed as u64 % n
+    };
+
+    for _ in 0..500 {
+        let mut timers = wheel();
+        let mut clock = FakeClock::new(1);
+        let start = next(1_000);
+        clock.run(&mut timers, start);
+        let delay = next(2_000);
+        timers.start_once(delay, 1).unwrap();
+        let log = clock.run(&mut timers, start + delay + 2 * TICK);
+        let fired = times(&log, 1);
+        assert_eq!(fired.len(), 1, "start {start}, delay {delay}");
+        let late = fired[0] - (start + delay);
+        assert!(
+            fired[0] >= start + delay.max(1) && late <= TICK,
+            "start {start}, delay {delay}: {fired:?}"
+        );
+    }
+}
+
+#[test]
+fn timers_beyond_one_revolution_wait_their_turn() {
+    // 16 slots of 10 ms: one revolution is 160 ms
+    let mut timers = wheel();
+    let mut clock = FakeClock::new(1);
+    timers.start_once(20, 1).unwrap();
+    timers.start_once(180, 2).unwrap();
+    timers.start_once(500, 3).unwrap();
+    let log = clock.run(&mut timers, 600);
+    assert_eq!(log, [(20, 1, 0), (180, 2, 0), (500, 3, 0)]);
+}
+
+#[test]
+fn many_timers_in_one_slot() {
+    let mut timers = wheel();
+    let mut clock = FakeClock::new(TICK);
+    let handles: Vec<TimerHandle> = (0..8).map(|i| timers.start_once(40, i).unwrap()).collect();
+    // Unlink from the middle, the head and the tail of the slot's list
+    timers.cancel(handles[3]).unwrap();
+    timers.cancel(handles[7]).unwrap();
+    timers.cancel(handles[0]).unwrap();
+    let mut fired: Vec<u32> = clock
+        .run(&mut timers, 40)
+        .iter()
+        .map(|&(_, p, _)| p)
+        .collect();
+    fired.sort();
+    assert_eq!(fired, [1, 2, 4, 5, 6]);
+}
+
+#[test]
+fn cancelled_timer_never_fires() {
+    let mut timers = wheel();
+    let mut clock = FakeClock::new(1);
+    let once = timers.start_once(30, 1).unwrap();
+    let periodic = timers.start_periodic(20, 2).unwrap();
+    clock.run(&mut timers, 25);
+    assert_eq!(timers.cancel(once), Ok(1));
+    assert_eq!(timers.cancel(periodic), Ok(2));
+    assert_eq!(timers.cancel(once), Err(TimerError::InvalidHandle));
+    assert!(timers.is_empty());
+    assert!(clock.run(&mut timers, 200).is_empty());
+}
+
+#[test]
+fn stale_handle_does_not_reach_a_reused_entry() {
+    let mut timers = wheel();
+    let mut clock = FakeClock::new(1);
+    let old = timers.start_once(10, 1).unwrap();
+    clock.run(&mut timers, 10);
+    assert!(!timers.is_active(old));
+
+    // Same entry, new generation
+    let new = timers.start_once(10, 2).unwrap();
+    assert_ne!(old, new);
+    assert_eq!(timers.cancel(old), Err(TimerError::InvalidHandle));
+    assert_eq!(timers.reschedule(old, 50), Err(TimerError::InvalidHandle));
+    assert!(timers.is_active(new));
+    assert_eq!(clock.run(&mut timers, 20), [(20, 2, 0)]);
+}
+
+#[test]
+fn reschedule_restarts_the_countdown() {
+    let mut timers = wheel();
+    let mut clock = FakeClock::new(1);
+    let once = timers.start_once(50, 1).unwrap();
+    let periodic = timers.start_periodic(100, 2).unwrap();
+    clock.run(&mut timers, 40);
+    // Pushed back, and pulled forward
+    timers.reschedule(once, 50).unwrap();
+    timers.reschedule(periodic, 10).unwrap();
+    let log = clock.run(&mut timers, 300);
+    assert_eq!(times(&log, 1), [90]);
+    // Keeps its period after the early firing
+    assert_eq!(times(&log, 2), [50, 150, 250]);
+}
+
+#[test]
+fn set_period_applies_from_the_next_firing() {
+    let mut timers = wheel();
+    let mut clock = FakeClock::new(1);
+    let h = timers.start_periodic(100, 1).unwrap();
+    clock.run(&mut timers, 50);
+    timers.set_period(h, Some(30)).unwrap();
+    let log = clock.run(&mut timers, 200);
+    assert_eq!(times(&log, 1), [100, 130, 160, 190]);
+
+    // None turns it into a one-shot
+    timers.set_period(h, None).unwrap();
+    let log = clock.run(&mut timers, 400);
+    assert_eq!(times(&log, 1), [220]);
+    assert!(!timers.is_active(h));
+    assert_eq!(
+        timers.set_period(h, Some(10)),
+        Err(TimerError::InvalidHandle)
+    );
+}
+
+#[test]
+fn late_main_loop_skips_missed_periods() {
+    let mut timers = wheel();
+    let mut clock = FakeClock::new(1);
+    timers.start_periodic(20, 1).unwrap();
+    timers.start_once(30, 2).unwrap();
+    timers.start_once(60, 3).unwrap();
+
+    // The main loop was busy for 105 ms
+    let fired: Vec<Fired<u32>> = timers.advance(105).into_iter().collect();
+    let mut summary: Vec<(u32, u32)> = fired.iter().map(|f| (f.payload, f.missed)).collect();
+    summary.sort();
+    // Periodic once, with 20 .. 100 minus the one it fired for missed
+    assert_eq!(summary, [(1, 4), (2, 0), (3, 0)]);
+
+    // Back in phase afterwards
+    clock.now_ms = 105;
+    let log = clock.run(&mut timers, 160);
+    assert_eq!(times(&log, 1), [120, 140, 160]);
+}
+
+#[test]
+fn errors() {
+    let mut timers: TimerWheel<u32, 2, 4> = TimerWheel::new(10);
+    assert_eq!(timers.start_periodic(0, 0), Err(TimerError::ZeroPeriod));
+    let h = timers.start_once(10, 1).unwrap();
+    assert_eq!(timers.set_period(h, Some(0)), Err(TimerError::ZeroPeriod));
+    timers.start_once(10, 2).unwrap();
+    assert_eq!(timers.start_once(10, 3), Err(TimerError::Full));
+    assert_eq!(timers.len(), 2);
+
+    // Zero delay still waits for the next tick
+    let mut timers = wheel();
+    timers.start_once(0, 1).unwrap();
+    assert!(timers.advance(9).is_empty());
+    assert_eq!(timers.advance(10).len(), 1);
+}
+
+#[test]
+fn deadlines_and_remaining_time() {
+    let mut timers = wheel();
+    let mut clock = FakeClock::new(TICK);
+    assert_eq!(timers.next_deadline_ms(), None);
+    let a = timers.start_once(200, 1).unwrap();
+    let b = timers.start_periodic(70, 2).unwrap();
+    assert_eq!(timers.next_deadline_ms(), Some(70));
+    assert_eq!(timers.remaining_ms(a), Some(200));
+    clock.run(&mut timers, 80);
+    assert_eq!(timers.next_deadline_ms(), Some(140));
+    assert_eq!(timers.remaining_ms(a), Some(120));
+    assert_eq!(timers.remaining_ms(b), Some(60));
+    timers.cancel(a).unwrap();
+    assert_eq!(timers.remaining_ms(a), None);
+}
+
+#[test]
+fn callbacks_run_in_the_caller_with_a_context() {
+    #[derive(Default)]
+    struct App {
+        ticks: u32,
+        done: Option<TimerHandle>,
+    }
+
+    fn tick(app: &mut App, _: TimerHandle) {
+        app.ticks += 1;
+    }
+
+    fn done(app: &mut App, handle: TimerHandle) {
+        app.done = Some(handle);
+    }
+
+    let mut timers: TimerWheel<Callback<App>, 4, 8> = TimerWheel::new(10);
+    let mut app = App::default();
+    timers.start_periodic(10, tick).unwrap();
+    let once = timers.start_once(35, done).unwrap();
+
+    let mut ran = 0;
+    for now in 1..=50 {
+        ran += timers.run(now, &mut app);
+    }
+    assert_eq!(app.ticks, 5);
+    assert_eq!(app.done, Some(once));
+    assert_eq!(ran, 6);
+}
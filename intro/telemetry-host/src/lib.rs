@@ -0,0 +1,237 @@
+//! Host end of the [`telemetry`] protocol, over any byte stream: a serial
+//! port, a TCP socket or, in tests, an in-process pipe.
+//!
+//! [`Client::request`] sends a request and waits for its response, sending
+//! the request again when the response does not arrive within the retry
+//! timeout. Telemetry that arrives meanwhile is acknowledged, deduplicated
+//! and queued for [`Client::next_telemetry`].
+//!
+//! Reads must time out, or retransmission never happens: open the serial
+//! port with a read timeout well below [`RetryPolicy::timeout_ms`], e.g.
+//! 10ms. Timed-out reads are treated as "no data yet".
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use telemetry_host::{Client, Request, Response};
+//!
+//! # fn open_port() -> std::net::TcpStream { unimplemented!() }
+//! let mut client = Client::new(open_port());
+//! match client.request(&Request::ReadVoltage { pin: 1 })? {
+//!     Response::Voltage { mv } => println!("{mv} mV"),
+//!     other => println!("unexpected {other:?}"),
+//! }
+//! client.request(&Request::SetTelemetryRate { hz: 10 })?;
+//! while let Some(telemetry) = client.next_telemetry(Duration::from_secs(1))? {
+//!     println!("{telemetry:?}");
+//! }
+//! # Ok::<(), telemetry_host::Error>(())
+//! ```
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    io::{self, ErrorKind, Read, Write},
+    time::{Duration, Instant, SystemTime},
+};
+
+use telemetry::{frame::encode, Decoder, Kind, Poll, Retransmit, MAX_ENCODED};
+pub use telemetry::{Failure, ImuSample, Request, Response, RetryPolicy, Stats, Telemetry};
+
+/// Why a request or a read failed.
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    /// The stream reached its end.
+    Closed,
+    /// No response after [`RetryPolicy::attempts`] sends.
+    Timeout,
+    /// The request did not encode, or the response did not decode.
+    Frame(telemetry::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "I/O error: {e}"),
+            Self::Closed => f.write_str("connection closed"),
+            Self::Timeout => f.write_str("no response from the device"),
+            Self::Frame(e) => write!(f, "bad frame: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<telemetry::Error> for Error {
+    fn from(e: telemetry::Error) -> Self {
+        Self::Frame(e)
+    }
+}
+
+/// One request at a time over `port`.
+pub struct Client<P> {
+    port: P,
+    decoder: Decoder,
+    policy: RetryPolicy,
+    next_seq: u16,
+    started: Instant,
+    telemetry: VecDeque<Telemetry>,
+    last_telemetry: Option<u16>,
+    stats: Stats,
+}
+
+impl<P: Read + Write> Client<P> {
+    pub fn new(port: P) -> Self {
+        Self::with_policy(port, RetryPolicy::default())
+    }
+
+    pub fn with_policy(port: P, policy: RetryPolicy) -> Self {
+        // The device answers a repeated sequence number from its cache, so
+        // do not start where the previous session did
+        let seed = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |d| d.subsec_micros() as u16);
+        Self {
+            port,
+            decoder: Decoder::new(),
+            policy,
+            next_seq: seed,
+            started: Instant::now(),
+            telemetry: VecDeque::new(),
+            last_telemetry: None,
+            stats: Stats::default(),
+        }
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    pub fn into_inner(self) -> P {
+        self.port
+    }
+
+    /// Sends `request` and waits for its response, retrying per the policy.
+    pub fn request(&mut self, request: &Request) -> Result<Response, Error> {
+        let seq = self.next_seq;
+        self.next_seq = seq.wrapping_add(1);
+
+        let mut buf = [0u8; MAX_ENCODED];
+        let n = encode(Kind::Request, seq, request, &mut buf)?;
+        self.send(&buf[..n])?;
+        let mut retransmit = Retransmit::new(self.policy);
+        retransmit.sent(seq, &buf[..n], self.now_ms())?;
+
+        loop {
+            if let Some(response) = self.receive(Some(seq))? {
+                return Ok(response);
+            }
+            match retransmit.poll(self.now_ms()) {
+                Poll::Resend(frame) => {
+                    self.stats.retransmits += 1;
+                    self.send(frame)?;
+                }
+                Poll::Failed(_) => {
+                    self.stats.lost += 1;
+                    return Err(Error::Timeout);
+                }
+                Poll::Idle | Poll::Waiting => {}
+            }
+        }
+    }
+
+    /// The next telemetry message, or `None` if none arrived within
+    /// `timeout`.
+    pub fn next_telemetry(&mut self, timeout: Duration) -> Result<Option<Telemetry>, Error> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(telemetry) = self.telemetry.pop_front() {
+                return Ok(Some(telemetry));
+            }
+            if Instant::now() >= deadline {
+                return Ok(None);
+            }
+            self.receive(None)?;
+        }
+    }
+
+    fn now_ms(&self) -> u64 {
+        self.started.elapsed().as_millis() as u64
+    }
+
+    fn send(&mut self, frame: &[u8]) -> io::Result<()> {
+        self.port.write_all(frame)?;
+        self.port.flush()
+    }
+
+    /// Reads once and handles every frame in what was read. Returns the
+    /// response to `expected`, if it was among them.
+    fn receive(&mut self, expected: Option<u16>) -> Result<Option<Response>, Error> {
+        let mut buf = [0u8; 64];
+        let n = match self.port.read(&mut buf) {
+            Ok(0) => return Err(Error::Closed),
+            Ok(n) => n,
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    ErrorKind::TimedOut | ErrorKind::WouldBlock | ErrorKind::Interrupted
+                ) =>
+            {
+                return Ok(None)
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut response = None;
+        for &byte in &buf[..n] {
+            let frame = match self.decoder.feed(byte) {
+                None => continue,
+                Some(Err(_)) => {
+                    self.stats.bad_frames += 1;
+                    continue;
+                }
+                Some(Ok(frame)) => frame,
+            };
+            self.stats.frames += 1;
+
+            match frame.kind {
+                // Responses to earlier, already answered sends are dropped
+                Kind::Response if Some(frame.seq) == expected => {
+                    response = Some(frame.payload());
+                }
+                Kind::Telemetry => {
+                    // Acknowledge even a duplicate: the previous ACK was lost
+                    let mut ack = [0u8; MAX_ENCODED];
+                    let len = encode(Kind::Ack, frame.seq, &(), &mut ack)?;
+                    self.port.write_all(&ack[..len])?;
+                    self.port.flush()?;
+
+                    if self.last_telemetry == Some(frame.seq) {
+                        self.stats.duplicates += 1;
+                        continue;
+                    }
+                    self.last_telemetry = Some(frame.seq);
+                    match frame.payload() {
+                        Ok(telemetry) => self.telemetry.push_back(telemetry),
+                        Err(_) => self.stats.bad_frames += 1,
+                    }
+                }
+                Kind::Response | Kind::Request | Kind::Ack => {}
+            }
+        }
+        Ok(response.transpose()?)
+    }
+}
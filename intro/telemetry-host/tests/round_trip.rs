@@ -0,0 +1,415 @@
+//! The device and host ends of the protocol running against each other in
+//! one process, over pipes that can lose or corrupt frames.
+
+use std::{
+    collections::VecDeque,
+    convert::Infallible,
+    io,
+    sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use telemetry::Device;
+use telemetry_host::{
+    Client, Error, Failure, ImuSample, Request, Response, RetryPolicy, Stats, Telemetry,
+};
+
+const POLICY: RetryPolicy = RetryPolicy {
+    timeout_ms: 20,
+    attempts: 4,
+};
+
+/// One end of a duplex pipe. Both protocol ends write a whole frame per
+/// write call, so dropping a write loses exactly one frame.
+struct End {
+    tx: Sender<Vec<u8>>,
+    rx: Receiver<Vec<u8>>,
+    received: VecDeque<u8>,
+    writes: usize,
+    drop: Vec<usize>,
+    corrupt: Vec<usize>,
+}
+
+fn pipe() -> (End, End) {
+    let (a_tx, b_rx) = channel();
+    let (b_tx, a_rx) = channel();
+    let end = |tx, rx| End {
+        tx,
+        rx,
+        received: VecDeque::new(),
+        writes: 0,
+        drop: Vec::new(),
+        corrupt: Vec::new(),
+    };
+    (end(a_tx, a_rx), end(b_tx, b_rx))
+}
+
+impl End {
+    /// Loses the frames of these write calls, counted from 0.
+    fn dropping(mut self, writes: &[usize]) -> Self {
+        self.drop = writes.to_vec();
+        self
+    }
+
+    /// Flips a bit in the frames of these write calls.
+    fn corrupting(mut self, writes: &[usize]) -> Self {
+        self.corrupt = writes.to_vec();
+        self
+    }
+
+    fn send(&mut self, bytes: &[u8]) {
+        let index = self.writes;
+        self.writes += 1;
+        if self.drop.contains(&index) {
+            return;
+        }
+        let mut bytes = bytes.to_vec();
+        if self.corrupt.contains(&index) {
+            bytes[1] ^= 0x04;
+        }
+        // The other end may have finished already
+        let _ = self.tx.send(bytes);
+    }
+}
+
+impl io::Read for End {
+    /// Times out like a serial port opened with a read timeout.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.received.is_empty() {
+            match self.rx.recv_timeout(Duration::from_millis(2)) {
+                Ok(bytes) => self.received.extend(bytes),
+                Err(RecvTimeoutError::Timeout) => return Err(io::ErrorKind::TimedOut.into()),
+                Err(RecvTimeoutError::Disconnected) => return Ok(0),
+            }
+        }
+        let n = buf.len().min(self.received.len());
+        for (slot, byte) in buf.iter_mut().zip(self.received.drain(..n)) {
+            *slot = byte;
+        }
+        Ok(n)
+    }
+}
+
+impl io::Write for End {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.send(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl embedded_io::ErrorType for End {
+    type Error = Infallible;
+}
+
+impl embedded_io::Write for End {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Infallible> {
+        self.send(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+/// What the simulated board did, reported when the host hangs up.
+#[derive(Debug)]
+struct Report {
+    stats: Stats,
+    led_writes: u32,
+    led: bool,
+}
+
+/// Runs a [`Device`] in a thread, like the firmware's main loop: feed
+/// received bytes, retransmit, and send a voltage reading at the telemetry
+/// rate. Readings count up from 0 so the host can spot gaps and repeats.
+fn spawn_device(mut end: End) -> JoinHandle<Report> {
+    thread::spawn(move || {
+        let started = Instant::now();
+        let mut device = Device::new(POLICY);
+        let mut report = Report {
+            stats: Stats::default(),
+            led_writes: 0,
+            led: false,
+        };
+        let mut rate_hz = 0u8;
+        let mut next_reading_ms = 0;
+        let mut reading = 0u16;
+        let mut buf = [0u8; 16];
+
+        loop {
+            let now_ms = started.elapsed().as_millis() as u64;
+            let n = match io::Read::read(&mut end, &mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(_) => 0,
+            };
+            for &byte in &buf[..n] {
+                device
+                    .on_byte(byte, &mut end, |request| match request {
+                        Request::Ping => Response::Pong { uptime_ms: now_ms },
+                        Request::ReadImu => Response::Imu(ImuSample {
+                            accel_g: [0.0, 0.0, 1.0],
+                            ..Default::default()
+                        }),
+                        Request::ReadVoltage { pin: 1 } => Response::Voltage { mv: 1650 },
+                        Request::ReadVoltage { .. } => Response::Error(Failure::Unsupported),
+                        Request::SetLed { on } => {
+                            report.led_writes += 1;
+                            report.led = on;
+                            Response::Ok
+                        }
+                        Request::SetTelemetryRate { hz } if hz <= 100 => {
+                            rate_hz = hz;
+                            Response::Ok
+                        }
+                        Request::SetTelemetryRate { .. } => Response::Error(Failure::OutOfRange),
+                    })
+                    .unwrap();
+            }
+            device.poll(now_ms, &mut end).unwrap();
+
+            if rate_hz > 0 && now_ms >= next_reading_ms {
+                next_reading_ms = now_ms + 1000 / rate_hz as u64;
+                let telemetry = Telemetry::Voltage {
+                    uptime_ms: now_ms,
+                    pin: 1,
+                    mv: reading,
+                };
+                if device.send_telemetry(&telemetry, now_ms, &mut end).unwrap() {
+                    reading += 1;
+                }
+            }
+        }
+        report.stats = device.stats();
+        report
+    })
+}
+
+fn voltage(telemetry: Option<Telemetry>) -> u16 {
+    match telemetry {
+        Some(Telemetry::Voltage { mv, .. }) => mv,
+        other => panic!("expected a voltage reading, got {other:?}"),
+    }
+}
+
+#[test]
+fn requests_are_answered() {
+    let (host, device) = pipe();
+    let board = spawn_device(device);
+    let mut client = Client::with_policy(host, POLICY);
+
+    assert!(matches!(
+        client.request(&Request::Ping).unwrap(),
+        Response::Pong { .. }
+    ));
+    assert_eq!(
+        client.request(&Request::ReadVoltage { pin: 1 }).unwrap(),
+        Response::Voltage { mv: 1650 }
+    );
+    assert_eq!(
+        client.request(&Request::ReadVoltage { pin: 7 }).unwrap(),
+        Response::Error(Failure::Unsupported)
+    );
+    match client.request(&Request::ReadImu).unwrap() {
+        Response::Imu(sample) => assert_eq!(sample.accel_g, [0.0, 0.0, 1.0]),
+        other => panic!("{other:?}"),
+    }
+    assert_eq!(
+        client.request(&Request::SetLed { on: true }).unwrap(),
+        Response::Ok
+    );
+    assert_eq!(client.stats().retransmits, 0);
+
+    drop(client);
+    let report = board.join().unwrap();
+    assert!(report.led);
+    assert_eq!(report.stats.frames, 5);
+    assert_eq!(report.stats.duplicates, 0);
+}
+
+#[test]
+fn lost_request_is_sent_again() {
+    let (host, device) = pipe();
+    let board = spawn_device(device);
+    let mut client = Client::with_policy(host.dropping(&[0, 1]), POLICY);
+
+    assert_eq!(
+        client.request(&Request::SetLed { on: true }).unwrap(),
+        Response::Ok
+    );
+    assert_eq!(client.stats().retransmits, 2);
+
+    drop(client);
+    let report = board.join().unwrap();
+    assert_eq!(report.led_writes, 1);
+}
+
+#[test]
+fn lost_response_is_replayed_without_running_the_request_twice() {
+    let (host, device) = pipe();
+    let board = spawn_device(device.dropping(&[0]));
+    let mut client = Client::with_policy(host, POLICY);
+
+    assert_eq!(
+        client.request(&Request::SetLed { on: true }).unwrap(),
+        Response::Ok
+    );
+    assert_eq!(
+        client.request(&Request::SetLed { on: false }).unwrap(),
+        Response::Ok
+    );
+    assert_eq!(client.stats().retransmits, 1);
+
+    drop(client);
+    let report = board.join().unwrap();
+    assert_eq!(report.led_writes, 2);
+    assert!(!report.led);
+    assert_eq!(report.stats.duplicates, 1);
+}
+
+#[test]
+fn corrupted_frames_are_dropped_and_recovered() {
+    let (host, device) = pipe();
+    let board = spawn_device(device.corrupting(&[0]));
+    let mut client = Client::with_policy(host.corrupting(&[0]), POLICY);
+
+    // Request corrupted, then the response to the retransmission
+    assert_eq!(
+        client.request(&Request::ReadVoltage { pin: 1 }).unwrap(),
+        Response::Voltage { mv: 1650 }
+    );
+    assert_eq!(client.stats().retransmits, 2);
+    assert_eq!(client.stats().bad_frames, 1);
+
+    drop(client);
+    let report = board.join().unwrap();
+    assert_eq!(report.stats.bad_frames, 1);
+    assert_eq!(report.stats.duplicates, 1);
+}
+
+#[test]
+fn silent_device_times_out() {
+    let (host, device) = pipe();
+    let mut client = Client::with_policy(host, POLICY);
+
+    let started = Instant::now();
+    assert!(matches!(
+        client.request(&Request::Ping),
+        Err(Error::Timeout)
+    ));
+    assert!(started.elapsed() >= Duration::from_millis(POLICY.timeout_ms * 4));
+    assert_eq!(client.stats().retransmits, 3);
+    // Every attempt reached the pipe
+    assert_eq!(device.rx.try_iter().count(), 4);
+}
+
+#[test]
+fn closed_stream_is_reported() {
+    let (host, device) = pipe();
+    drop(device);
+    let mut client = Client::with_policy(host, POLICY);
+    assert!(matches!(client.request(&Request::Ping), Err(Error::Closed)));
+}
+
+#[test]
+fn telemetry_is_acknowledged_in_order() {
+    let (host, device) = pipe();
+    let board = spawn_device(device);
+    let mut client = Client::with_policy(host, POLICY);
+
+    assert_eq!(
+        client
+            .request(&Request::SetTelemetryRate { hz: 100 })
+            .unwrap(),
+        Response::Ok
+    );
+    for expected in 0..10 {
+        assert_eq!(
+            voltage(client.next_telemetry(Duration::from_secs(1)).unwrap()),
+            expected
+        );
+    }
+
+    drop(client);
+    let report = board.join().unwrap();
+    assert_eq!(report.stats.lost, 0);
+}
+
+#[test]
+fn lost_acks_make_the_device_resend_and_the_host_deduplicate() {
+    let (host, device) = pipe();
+    let board = spawn_device(device);
+    // Write 0 is the rate request. Lost: the first two ACKs of reading 0 and
+    // the first ACKs of readings 1 and 2
+    let mut client = Client::with_policy(host.dropping(&[1, 2, 4, 6]), POLICY);
+
+    client
+        .request(&Request::SetTelemetryRate { hz: 100 })
+        .unwrap();
+    for expected in 0..8 {
+        assert_eq!(
+            voltage(client.next_telemetry(Duration::from_secs(1)).unwrap()),
+            expected
+        );
+    }
+    assert_eq!(client.stats().duplicates, 4);
+
+    drop(client);
+    let report = board.join().unwrap();
+    assert!(report.stats.retransmits >= 4);
+    assert_eq!(report.stats.lost, 0);
+}
+
+#[test]
+fn telemetry_arriving_during_a_request_is_kept() {
+    let (host, device) = pipe();
+    let board = spawn_device(device);
+    let mut client = Client::with_policy(host, POLICY);
+
+    client
+        .request(&Request::SetTelemetryRate { hz: 100 })
+        .unwrap();
+    thread::sleep(Duration::from_millis(30));
+    // Readings queue up while the host waits for this response
+    for _ in 0..5 {
+        client.request(&Request::ReadVoltage { pin: 1 }).unwrap();
+    }
+    client
+        .request(&Request::SetTelemetryRate { hz: 0 })
+        .unwrap();
+
+    let mut readings = Vec::new();
+    while let Some(telemetry) = client.next_telemetry(Duration::from_millis(100)).unwrap() {
+        readings.push(voltage(Some(telemetry)));
+    }
+    assert!(!readings.is_empty());
+    assert_eq!(readings, (0..readings.len() as u16).collect::<Vec<_>>());
+
+    drop(client);
+    board.join().unwrap();
+}
+
+#[test]
+fn rejected_rate_is_an_error_response() {
+    let (host, device) = pipe();
+    let board = spawn_device(device);
+    let mut client = Client::with_policy(host, POLICY);
+    assert_eq!(
+        client
+            .request(&Request::SetTelemetryRate { hz: 200 })
+            .unwrap(),
+        Response::Error(Failure::OutOfRange)
+    );
+    assert_eq!(
+        client.next_telemetry(Duration::from_millis(50)).unwrap(),
+        None
+    );
+    drop(client);
+    board.join().unwrap();
+}
@@ -0,0 +1,130 @@
+//! The device end of the link.
+
+use embedded_io::Write;
+use heapless::Vec;
+
+use crate::{
+    frame::{encode, Decoder, Kind, MAX_ENCODED},
+    message::{Failure, Request, Response, Telemetry},
+    retransmit::{Poll, Retransmit, RetryPolicy},
+    Stats,
+};
+
+/// Answers requests and sends telemetry, retransmitting it until the host
+/// acknowledges it.
+///
+/// The last response is kept: when the host repeats a request because the
+/// response was lost, the device sends the same response again instead of
+/// running the request twice. The host should therefore not restart its
+/// sequence numbers at the same value every time it connects.
+#[derive(Debug)]
+pub struct Device {
+    decoder: Decoder,
+    telemetry: Retransmit,
+    next_seq: u16,
+    last_response: Option<(u16, Vec<u8, MAX_ENCODED>)>,
+    stats: Stats,
+}
+
+impl Device {
+    /// `policy` applies to telemetry.
+    pub fn new(policy: RetryPolicy) -> Self {
+        Self {
+            decoder: Decoder::new(),
+            telemetry: Retransmit::new(policy),
+            next_seq: 0,
+            last_response: None,
+            stats: Stats::default(),
+        }
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    /// Whether a telemetry frame is waiting for its acknowledgement.
+    pub fn telemetry_pending(&self) -> bool {
+        self.telemetry.pending().is_some()
+    }
+
+    /// Handles one received byte. A complete request is passed to `handle`
+    /// and its response written to `out` before this returns.
+    pub fn on_byte<W, F>(&mut self, byte: u8, out: &mut W, handle: F) -> Result<(), W::Error>
+    where
+        W: Write,
+        F: FnOnce(Request) -> Response,
+    {
+        let frame = match self.decoder.feed(byte) {
+            None => return Ok(()),
+            Some(Err(_)) => {
+                self.stats.bad_frames += 1;
+                return Ok(());
+            }
+            Some(Ok(frame)) => frame,
+        };
+        self.stats.frames += 1;
+
+        match frame.kind {
+            Kind::Request => {
+                if let Some((seq, response)) = &self.last_response {
+                    if *seq == frame.seq {
+                        self.stats.duplicates += 1;
+                        return out.write_all(response);
+                    }
+                }
+                let response = match frame.payload() {
+                    Ok(request) => handle(request),
+                    Err(_) => Response::Error(Failure::BadRequest),
+                };
+                let mut buf = [0u8; MAX_ENCODED];
+                // Every message fits in MAX_PAYLOAD, see tests/frame.rs
+                let n = encode(Kind::Response, frame.seq, &response, &mut buf).unwrap();
+                self.last_response = Some((frame.seq, Vec::from_slice(&buf[..n]).unwrap()));
+                out.write_all(&buf[..n])
+            }
+            Kind::Ack => {
+                self.telemetry.ack(frame.seq);
+                Ok(())
+            }
+            // Only the device sends these
+            Kind::Response | Kind::Telemetry => Ok(()),
+        }
+    }
+
+    /// Sends `telemetry` unless the previous one is still waiting for its
+    /// acknowledgement, in which case it is skipped and `false` returned.
+    pub fn send_telemetry<W: Write>(
+        &mut self,
+        telemetry: &Telemetry,
+        now_ms: u64,
+        out: &mut W,
+    ) -> Result<bool, W::Error> {
+        if self.telemetry_pending() {
+            return Ok(false);
+        }
+        let seq = self.next_seq;
+        self.next_seq = seq.wrapping_add(1);
+
+        let mut buf = [0u8; MAX_ENCODED];
+        let n = encode(Kind::Telemetry, seq, telemetry, &mut buf).unwrap();
+        self.telemetry.sent(seq, &buf[..n], now_ms).unwrap();
+        out.write_all(&buf[..n])?;
+        Ok(true)
+    }
+
+    /// Retransmits unacknowledged telemetry. Call at least as often as the
+    /// retry timeout.
+    pub fn poll<W: Write>(&mut self, now_ms: u64, out: &mut W) -> Result<(), W::Error> {
+        match self.telemetry.poll(now_ms) {
+            Poll::Resend(frame) => {
+                self.stats.retransmits += 1;
+                out.write_all(frame)
+            }
+            Poll::Failed(_) => {
+                self.stats.lost += 1;
+                Ok(())
+            }
+            Poll::Idle | Poll::Waiting => Ok(()),
+        }
+    }
+}
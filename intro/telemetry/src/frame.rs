@@ -0,0 +1,181 @@
+//! Framing: a header, a postcard payload and a CRC-16, COBS-encoded and
+//! terminated by a zero byte.
+//!
+//! Before COBS encoding a frame is
+//!
+//! ```text
+//! [kind: u8][seq: u16 LE][payload: postcard][crc: u16 LE]
+//! ```
+//!
+//! with the CRC (CRC-16/CCITT-FALSE) over kind, seq and payload. COBS
+//! removes every zero byte, so the zero terminator marks the end of a frame
+//! even after bytes were lost or corrupted, and the receiver resynchronises
+//! on the next one.
+
+use core::fmt;
+
+use crc::{Crc, CRC_16_IBM_3740};
+use heapless::Vec;
+use serde::{Deserialize, Serialize};
+
+/// Largest postcard payload.
+pub const MAX_PAYLOAD: usize = 64;
+/// Kind and sequence number.
+pub const HEADER_LEN: usize = 3;
+pub const CRC_LEN: usize = 2;
+/// Largest frame before COBS encoding.
+pub const MAX_FRAME: usize = HEADER_LEN + MAX_PAYLOAD + CRC_LEN;
+/// Largest frame on the wire, terminator included. Buffers passed to
+/// [`encode`] of this size never fail for lack of room.
+pub const MAX_ENCODED: usize = cobs::max_encoding_length(MAX_FRAME) + 1;
+
+const CRC16: Crc<u16> = Crc::<u16>::new(&CRC_16_IBM_3740);
+
+/// What a frame carries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Kind {
+    /// Host to device, answered by a [`Kind::Response`] with the same
+    /// sequence number.
+    Request = 1,
+    /// Device to host. Doubles as the acknowledgement of the request.
+    Response = 2,
+    /// Device to host, unsolicited. Acknowledged by a [`Kind::Ack`].
+    Telemetry = 3,
+    /// Host to device, empty payload.
+    Ack = 4,
+}
+
+impl Kind {
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(Self::Request),
+            2 => Some(Self::Response),
+            3 => Some(Self::Telemetry),
+            4 => Some(Self::Ack),
+            _ => None,
+        }
+    }
+}
+
+/// Why a frame could not be built or was dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The payload does not fit in [`MAX_PAYLOAD`] bytes, the output buffer
+    /// is too small, or a received frame exceeded [`MAX_ENCODED`] bytes.
+    TooLong,
+    /// Invalid COBS, too short for a header and CRC, or an unknown kind.
+    Malformed,
+    /// The CRC does not match: the frame was corrupted on the way.
+    Crc,
+    /// The CRC matched but the payload is not the expected type.
+    Payload,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::TooLong => "frame too long",
+            Self::Malformed => "malformed frame",
+            Self::Crc => "CRC mismatch",
+            Self::Payload => "unexpected payload",
+        })
+    }
+}
+
+/// Encodes one frame into `out`, terminator included, and returns its
+/// length.
+pub fn encode<T: Serialize>(
+    kind: Kind,
+    seq: u16,
+    payload: &T,
+    out: &mut [u8],
+) -> Result<usize, Error> {
+    let mut raw = [0u8; MAX_FRAME];
+    raw[0] = kind as u8;
+    raw[1..HEADER_LEN].copy_from_slice(&seq.to_le_bytes());
+    let payload_len = postcard::to_slice(payload, &mut raw[HEADER_LEN..HEADER_LEN + MAX_PAYLOAD])
+        .map_err(|_| Error::TooLong)?
+        .len();
+    let crc_at = HEADER_LEN + payload_len;
+    let crc = CRC16.checksum(&raw[..crc_at]);
+    raw[crc_at..crc_at + CRC_LEN].copy_from_slice(&crc.to_le_bytes());
+
+    let n = cobs::try_encode(&raw[..crc_at + CRC_LEN], out).map_err(|_| Error::TooLong)?;
+    *out.get_mut(n).ok_or(Error::TooLong)? = 0;
+    Ok(n + 1)
+}
+
+/// A received frame whose CRC matched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Frame<'a> {
+    pub kind: Kind,
+    pub seq: u16,
+    /// Postcard bytes, see [`Frame::payload`].
+    pub payload: &'a [u8],
+}
+
+impl<'a> Frame<'a> {
+    /// Deserializes the payload, which must be exactly one `T`.
+    pub fn payload<T: Deserialize<'a>>(&self) -> Result<T, Error> {
+        match postcard::take_from_bytes(self.payload) {
+            Ok((value, [])) => Ok(value),
+            _ => Err(Error::Payload),
+        }
+    }
+}
+
+/// Collects received bytes into frames.
+#[derive(Debug, Default)]
+pub struct Decoder {
+    buf: Vec<u8, MAX_ENCODED>,
+    overflow: bool,
+    done: bool,
+}
+
+impl Decoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Handles one received byte. At a terminator, returns the frame or why
+    /// it was dropped; empty frames (consecutive zeros) are skipped.
+    pub fn feed(&mut self, byte: u8) -> Option<Result<Frame<'_>, Error>> {
+        if self.done {
+            self.buf.clear();
+            self.done = false;
+        }
+        if byte != 0 {
+            // Keep discarding until the terminator, then report the frame once
+            if self.buf.push(byte).is_err() {
+                self.overflow = true;
+            }
+            return None;
+        }
+
+        self.done = true;
+        if core::mem::take(&mut self.overflow) {
+            return Some(Err(Error::TooLong));
+        }
+        if self.buf.is_empty() {
+            return None;
+        }
+        Some(parse(&mut self.buf))
+    }
+}
+
+fn parse(buf: &mut [u8]) -> Result<Frame<'_>, Error> {
+    let n = cobs::decode_in_place(buf).map_err(|_| Error::Malformed)?;
+    if n < HEADER_LEN + CRC_LEN {
+        return Err(Error::Malformed);
+    }
+    let (body, crc) = buf[..n].split_at(n - CRC_LEN);
+    if CRC16.checksum(body) != u16::from_le_bytes([crc[0], crc[1]]) {
+        return Err(Error::Crc);
+    }
+    Ok(Frame {
+        kind: Kind::from_u8(body[0]).ok_or(Error::Malformed)?,
+        seq: u16::from_le_bytes([body[1], body[2]]),
+        payload: &body[HEADER_LEN..],
+    })
+}
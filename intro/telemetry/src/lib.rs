@@ -0,0 +1,70 @@
+//! `no_std` framed binary protocol between the demo board and a host over
+//! UART.
+//!
+//! * [`frame`] puts a [`postcard`] payload behind a kind and sequence number,
+//!   appends a CRC-16 and COBS-encodes the result, so that a zero byte ends
+//!   every frame and corrupted frames are dropped.
+//! * [`Retransmit`] keeps the last frame until it is acknowledged and sends
+//!   it again after a timeout.
+//! * [`Device`] is the board's end: it answers [`Request`]s, caching the
+//!   response in case the host asks again, and sends [`Telemetry`] until the
+//!   host acknowledges it.
+//! * [`message`] defines what is said. The host end, which needs `std`, is
+//!   the `telemetry-host` crate.
+//!
+//! ```rust
+//! use telemetry::{frame, Decoder, Kind, Request, Response, MAX_ENCODED};
+//!
+//! // Host: encode a request
+//! let mut wire = [0u8; MAX_ENCODED];
+//! let n = frame::encode(Kind::Request, 7, &Request::ReadVoltage { pin: 1 }, &mut wire).unwrap();
+//! assert_eq!(wire[n - 1], 0);
+//! assert!(!wire[..n - 1].contains(&0));
+//!
+//! // Device: answer it
+//! let mut device = telemetry::Device::new(Default::default());
+//! let mut reply = Vec::new();
+//! for &byte in &wire[..n] {
+//!     device
+//!         .on_byte(byte, &mut reply, |request| {
+//!             assert_eq!(request, Request::ReadVoltage { pin: 1 });
+//!             Response::Voltage { mv: 1650 }
+//!         })
+//!         .unwrap();
+//! }
+//!
+//! // Host: decode the response, which carries the request's sequence number
+//! let mut decoder = Decoder::new();
+//! let (last, rest) = reply.split_last().unwrap();
+//! assert!(rest.iter().all(|&byte| decoder.feed(byte).is_none()));
+//! let frame = decoder.feed(*last).unwrap().unwrap();
+//! assert_eq!((frame.kind, frame.seq), (Kind::Response, 7));
+//! assert_eq!(frame.payload(), Ok(Response::Voltage { mv: 1650 }));
+//! ```
+
+#![no_std]
+
+mod device;
+pub mod frame;
+pub mod message;
+mod retransmit;
+
+pub use device::Device;
+pub use frame::{Decoder, Error, Frame, Kind, MAX_ENCODED};
+pub use message::{Failure, ImuSample, Request, Response, Telemetry};
+pub use retransmit::{Poll, Retransmit, RetryPolicy};
+
+/// Counters kept by each end of the link.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Frames received intact.
+    pub frames: u32,
+    /// Frames dropped as too long, malformed or failing the CRC.
+    pub bad_frames: u32,
+    /// Frames received again because our acknowledgement was lost.
+    pub duplicates: u32,
+    /// Frames sent again after a timeout.
+    pub retransmits: u32,
+    /// Frames given up after the last attempt.
+    pub lost: u32,
+}
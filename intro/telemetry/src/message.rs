@@ -0,0 +1,66 @@
+//! What the demo board and the host say to each other.
+//!
+//! Postcard encodes enum variants by index, so new variants go at the end
+//! and both sides must be built from the same version of this file.
+
+use serde::{Deserialize, Serialize};
+
+/// One MPU-6050 reading in physical units.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImuSample {
+    pub accel_g: [f32; 3],
+    pub gyro_dps: [f32; 3],
+    pub temperature_c: f32,
+}
+
+/// Host to device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Request {
+    Ping,
+    ReadImu,
+    ReadVoltage {
+        pin: u8,
+    },
+    SetLed {
+        on: bool,
+    },
+    /// 0 stops telemetry.
+    SetTelemetryRate {
+        hz: u8,
+    },
+}
+
+/// Why the device could not serve a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Failure {
+    /// The request did not decode, e.g. the host is newer than the device.
+    BadRequest,
+    /// The device has no such pin or peripheral.
+    Unsupported,
+    /// The sensor did not answer.
+    Sensor,
+    /// The argument is out of range.
+    OutOfRange,
+}
+
+/// Device to host, one per [`Request`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Response {
+    Pong {
+        uptime_ms: u64,
+    },
+    Imu(ImuSample),
+    Voltage {
+        mv: u16,
+    },
+    /// The request was carried out and has nothing to report.
+    Ok,
+    Error(Failure),
+}
+
+/// Device to host, sent unprompted at the telemetry rate.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Telemetry {
+    Imu { uptime_ms: u64, sample: ImuSample },
+    Voltage { uptime_ms: u64, pin: u8, mv: u16 },
+}
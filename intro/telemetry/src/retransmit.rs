@@ -0,0 +1,111 @@
+//! Stop-and-wait retransmission of one outstanding frame.
+
+use heapless::Vec;
+
+use crate::frame::{Error, MAX_ENCODED};
+
+/// How long to wait for an acknowledgement and how often to try.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub timeout_ms: u64,
+    /// Sends in total, the first one included.
+    pub attempts: u8,
+}
+
+impl Default for RetryPolicy {
+    /// 50ms is several round trips of a 64-byte frame at 115200 baud.
+    fn default() -> Self {
+        Self {
+            timeout_ms: 50,
+            attempts: 4,
+        }
+    }
+}
+
+/// What [`Retransmit::poll`] wants done.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Poll<'a> {
+    /// Nothing is waiting for an acknowledgement.
+    Idle,
+    /// Still within the timeout.
+    Waiting,
+    /// The timeout passed: send these bytes again.
+    Resend(&'a [u8]),
+    /// The last attempt timed out; the frame with this sequence number is
+    /// given up.
+    Failed(u16),
+}
+
+#[derive(Debug)]
+struct Pending {
+    seq: u16,
+    frame: Vec<u8, MAX_ENCODED>,
+    deadline_ms: u64,
+    sent: u8,
+}
+
+/// Keeps a copy of the last sent frame until it is acknowledged or has
+/// been sent [`RetryPolicy::attempts`] times.
+#[derive(Debug)]
+pub struct Retransmit {
+    policy: RetryPolicy,
+    pending: Option<Pending>,
+}
+
+impl Retransmit {
+    pub fn new(policy: RetryPolicy) -> Self {
+        Self {
+            policy,
+            pending: None,
+        }
+    }
+
+    pub fn policy(&self) -> RetryPolicy {
+        self.policy
+    }
+
+    /// Sequence number of the frame waiting for an acknowledgement.
+    pub fn pending(&self) -> Option<u16> {
+        self.pending.as_ref().map(|p| p.seq)
+    }
+
+    /// Records `frame` as sent at `now_ms`, replacing any pending frame.
+    pub fn sent(&mut self, seq: u16, frame: &[u8], now_ms: u64) -> Result<(), Error> {
+        self.pending = Some(Pending {
+            seq,
+            frame: Vec::from_slice(frame).map_err(|_| Error::TooLong)?,
+            deadline_ms: now_ms + self.policy.timeout_ms,
+            sent: 1,
+        });
+        Ok(())
+    }
+
+    /// Handles an acknowledgement. Returns `false` for a stale or unknown
+    /// sequence number, which is ignored.
+    pub fn ack(&mut self, seq: u16) -> bool {
+        if self.pending() == Some(seq) {
+            self.pending = None;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Call regularly, at least as often as the timeout.
+    pub fn poll(&mut self, now_ms: u64) -> Poll<'_> {
+        let Some(pending) = self.pending.as_mut() else {
+            return Poll::Idle;
+        };
+        if now_ms < pending.deadline_ms {
+            return Poll::Waiting;
+        }
+        if pending.sent >= self.policy.attempts {
+            let seq = pending.seq;
+            self.pending = None;
+            return Poll::Failed(seq);
+        }
+        pending.sent += 1;
+        pending.deadline_ms = now_ms + self.policy.timeout_ms;
+        Poll::Resend(&self.pending.as_ref().unwrap().frame)
+    }
+}
@@ -0,0 +1,209 @@
+use telemetry::{
+    frame::{encode, MAX_PAYLOAD},
+    Decoder, Error, ImuSample, Kind, Request, Response, Telemetry, MAX_ENCODED,
+};
+
+fn wire<T: serde::Serialize>(kind: Kind, seq: u16, payload: &T) -> Vec<u8> {
+    let mut buf = [0u8; MAX_ENCODED];
+    let n = encode(kind, seq, payload, &mut buf).unwrap();
+    buf[..n].to_vec()
+}
+
+/// `(kind, seq, payload)` of a decoded frame.
+type Owned = (Kind, u16, Vec<u8>);
+
+/// Feeds `bytes` and collects what each terminator produced.
+fn decode(decoder: &mut Decoder, bytes: &[u8]) -> Vec<Result<Owned, Error>> {
+    bytes
+        .iter()
+        .filter_map(|&byte| {
+            decoder
+                .feed(byte)
+                .map(|r| r.map(|f| (f.kind, f.seq, f.payload.to_vec())))
+        })
+        .collect()
+}
+
+const IMU: ImuSample = ImuSample {
+    accel_g: [0.01, -0.02, 1.0],
+    gyro_dps: [0.5, -0.25, 0.0],
+    temperature_c: 24.5,
+};
+
+#[test]
+fn round_trips_every_kind() {
+    let mut decoder = Decoder::new();
+    let cases = [
+        wire(Kind::Request, 0, &Request::SetLed { on: true }),
+        wire(Kind::Response, 1, &Response::Imu(IMU)),
+        wire(
+            Kind::Telemetry,
+            0xfffe,
+            &Telemetry::Imu {
+                uptime_ms: 123_456,
+                sample: IMU,
+            },
+        ),
+        wire(Kind::Ack, 0x1234, &()),
+    ];
+    let stream: Vec<u8> = cases.concat();
+    let frames = decode(&mut decoder, &stream);
+    assert_eq!(frames.len(), 4);
+
+    let (kind, seq, payload) = frames[1].clone().unwrap();
+    assert_eq!((kind, seq), (Kind::Response, 1));
+    assert_eq!(
+        postcard::from_bytes::<Response>(&payload).unwrap(),
+        Response::Imu(IMU)
+    );
+    let (kind, seq, payload) = frames[3].clone().unwrap();
+    assert_eq!((kind, seq, payload), (Kind::Ack, 0x1234, vec![]));
+}
+
+#[test]
+fn encoded_frames_have_a_single_zero_at_the_end() {
+    // The sequence number 0x0000 and the payload's zeros must all be stuffed
+    let bytes = wire(
+        Kind::Telemetry,
+        0,
+        &Telemetry::Voltage {
+            uptime_ms: 0,
+            pin: 0,
+            mv: 0,
+        },
+    );
+    assert_eq!(bytes.last(), Some(&0));
+    assert_eq!(bytes.iter().filter(|&&b| b == 0).count(), 1);
+}
+
+#[test]
+fn largest_messages_fit() {
+    let largest = [
+        postcard::experimental::serialized_size(&Response::Imu(IMU)).unwrap(),
+        postcard::experimental::serialized_size(&Response::Pong {
+            uptime_ms: u64::MAX,
+        })
+        .unwrap(),
+        postcard::experimental::serialized_size(&Telemetry::Imu {
+            uptime_ms: u64::MAX,
+            sample: IMU,
+        })
+        .unwrap(),
+    ];
+    assert!(largest.iter().all(|&len| len <= MAX_PAYLOAD));
+}
+
+#[test]
+fn oversized_payload_is_rejected_when_encoding() {
+    let mut buf = [0u8; MAX_ENCODED];
+    let payload = [0xaau8; MAX_PAYLOAD];
+    // A slice has a one-byte length prefix below 128 elements
+    let fits: &[u8] = &payload[..MAX_PAYLOAD - 1];
+    assert!(encode(Kind::Telemetry, 0, &fits, &mut buf).is_ok());
+    let too_long: &[u8] = &payload;
+    assert_eq!(
+        encode(Kind::Telemetry, 0, &too_long, &mut buf),
+        Err(Error::TooLong)
+    );
+}
+
+#[test]
+fn small_output_buffer_is_an_error() {
+    let mut buf = [0u8; 8];
+    assert_eq!(
+        encode(Kind::Response, 0, &Response::Imu(IMU), &mut buf),
+        Err(Error::TooLong)
+    );
+}
+
+#[test]
+fn every_single_bit_flip_is_caught() {
+    let good = wire(Kind::Response, 42, &Response::Voltage { mv: 3300 });
+    for i in 0..good.len() - 1 {
+        for bit in 0..8 {
+            let mut bad = good.clone();
+            bad[i] ^= 1 << bit;
+            let mut decoder = Decoder::new();
+            let frames = decode(&mut decoder, &bad);
+            // A flip to zero splits the frame in two, both of which fail
+            assert!(
+                frames.iter().all(|f| f.is_err()),
+                "byte {i} bit {bit}: {frames:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn crc_mismatch_is_reported() {
+    let mut bytes = wire(Kind::Request, 3, &Request::Ping);
+    // The last data byte holds the CRC's high byte: no effect on COBS
+    let at = bytes.len() - 2;
+    bytes[at] = if bytes[at] == 1 { 2 } else { 1 };
+    assert_eq!(decode(&mut Decoder::new(), &bytes), vec![Err(Error::Crc)]);
+}
+
+#[test]
+fn resynchronises_after_noise_and_truncation() {
+    let ping = wire(Kind::Request, 1, &Request::Ping);
+    let led = wire(Kind::Request, 2, &Request::SetLed { on: false });
+    let mut stream = vec![0x55, 0xaa, 0x13, 0x00];
+    // The first frame loses its tail and runs into the second one
+    stream.extend_from_slice(&ping[..ping.len() / 2]);
+    stream.extend_from_slice(&led);
+    stream.extend_from_slice(&ping);
+
+    let frames = decode(&mut Decoder::new(), &stream);
+    assert_eq!(frames.len(), 3);
+    assert!(frames[0].is_err());
+    assert!(frames[1].is_err());
+    assert_eq!(frames[2].clone().unwrap().1, 1);
+}
+
+#[test]
+fn runaway_frame_is_dropped_once_and_decoding_continues() {
+    let mut stream = vec![0x11; MAX_ENCODED * 3];
+    stream.push(0);
+    stream.extend(wire(Kind::Ack, 9, &()));
+    let frames = decode(&mut Decoder::new(), &stream);
+    assert_eq!(frames[0], Err(Error::TooLong));
+    assert_eq!(frames[1], Ok((Kind::Ack, 9, vec![])));
+}
+
+#[test]
+fn empty_frames_are_skipped() {
+    let mut stream = vec![0, 0, 0];
+    stream.extend(wire(Kind::Ack, 5, &()));
+    stream.extend([0, 0]);
+    assert_eq!(
+        decode(&mut Decoder::new(), &stream),
+        vec![Ok((Kind::Ack, 5, vec![]))]
+    );
+}
+
+#[test]
+fn unknown_kind_is_malformed() {
+    let mut raw = vec![9, 5, 0];
+    let crc = crc::Crc::<u16>::new(&crc::CRC_16_IBM_3740).checksum(&raw);
+    raw.extend(crc.to_le_bytes());
+    let mut stream = vec![0u8; MAX_ENCODED];
+    let n = cobs::encode(&raw, &mut stream);
+    stream.truncate(n);
+    stream.push(0);
+    assert_eq!(
+        decode(&mut Decoder::new(), &stream),
+        vec![Err(Error::Malformed)]
+    );
+}
+
+#[test]
+fn payload_of_the_wrong_type_is_rejected() {
+    let bytes = wire(Kind::Response, 1, &Response::Imu(IMU));
+    let mut decoder = Decoder::new();
+    let (last, rest) = bytes.split_last().unwrap();
+    rest.iter()
+        .for_each(|&b| assert!(decoder.feed(b).is_none()));
+    let frame = decoder.feed(*last).unwrap().unwrap();
+    assert_eq!(frame.payload::<Request>(), Err(Error::Payload));
+    assert_eq!(frame.payload::<()>(), Err(Error::Payload));
+}
@@ -0,0 +1,202 @@
+use telemetry::{
+    frame::encode, Decoder, Device, ImuSample, Kind, Poll, Request, Response, Retransmit,
+    RetryPolicy, Telemetry, MAX_ENCODED,
+};
+
+const POLICY: RetryPolicy = RetryPolicy {
+    timeout_ms: 50,
+    attempts: 3,
+};
+
+fn wire<T: serde::Serialize>(kind: Kind, seq: u16, payload: &T) -> Vec<u8> {
+    let mut buf = [0u8; MAX_ENCODED];
+    let n = encode(kind, seq, payload, &mut buf).unwrap();
+    buf[..n].to_vec()
+}
+
+/// Decodes everything the device wrote as `(kind, seq, payload)`.
+fn frames(bytes: &[u8]) -> Vec<(Kind, u16, Vec<u8>)> {
+    let mut decoder = Decoder::new();
+    bytes
+        .iter()
+        .filter_map(|&byte| {
+            decoder
+                .feed(byte)
+                .map(|r| r.map(|f| (f.kind, f.seq, f.payload.to_vec())).unwrap())
+        })
+        .collect()
+}
+
+fn response(payload: &[u8]) -> Response {
+    postcard::from_bytes(payload).unwrap()
+}
+
+#[test]
+fn retransmit_resends_until_the_attempts_run_out() {
+    let mut rt = Retransmit::new(POLICY);
+    assert_eq!(rt.poll(0), Poll::Idle);
+    rt.sent(7, b"frame\0", 1000).unwrap();
+    assert_eq!(rt.pending(), Some(7));
+
+    assert_eq!(rt.poll(1049), Poll::Waiting);
+    assert_eq!(rt.poll(1050), Poll::Resend(b"frame\0"));
+    // The timeout restarts from the resend, not from the deadline
+    assert_eq!(rt.poll(1120), Poll::Resend(b"frame\0"));
+    assert_eq!(rt.poll(1169), Poll::Waiting);
+    assert_eq!(rt.poll(1170), Poll::Failed(7));
+    assert_eq!(rt.poll(2000), Poll::Idle);
+}
+
+#[test]
+fn retransmit_ignores_stale_acks() {
+    let mut rt = Retransmit::new(POLICY);
+    rt.sent(1, b"a\0", 0).unwrap();
+    rt.sent(2, b"b\0", 10).unwrap();
+    assert!(!rt.ack(1));
+    assert_eq!(rt.pending(), Some(2));
+    assert!(rt.ack(2));
+    assert!(!rt.ack(2));
+    assert_eq!(rt.poll(1000), Poll::Idle);
+}
+
+#[test]
+fn single_attempt_never_resends() {
+    let mut rt = Retransmit::new(RetryPolicy {
+        timeout_ms: 10,
+        attempts: 1,
+    });
+    rt.sent(3, b"x\0", 0).unwrap();
+    assert_eq!(rt.poll(10), Poll::Failed(3));
+}
+
+#[test]
+fn device_answers_requests_with_the_same_sequence_number() {
+    let mut device = Device::new(POLICY);
+    let mut out = Vec::new();
+    for &byte in &wire(Kind::Request, 0x4242, &Request::Ping) {
+        device
+            .on_byte(byte, &mut out, |request| {
+                assert_eq!(request, Request::Ping);
+                Response::Pong { uptime_ms: 99 }
+            })
+            .unwrap();
+    }
+    let frames = frames(&out);
+    assert_eq!(frames.len(), 1);
+    assert_eq!((frames[0].0, frames[0].1), (Kind::Response, 0x4242));
+    assert_eq!(response(&frames[0].2), Response::Pong { uptime_ms: 99 });
+    assert_eq!(device.stats().frames, 1);
+}
+
+#[test]
+fn repeated_request_is_answered_from_the_cache() {
+    let mut device = Device::new(POLICY);
+    let mut out = Vec::new();
+    let mut led_toggles = 0;
+    let request = wire(Kind::Request, 5, &Request::SetLed { on: true });
+    for _ in 0..3 {
+        for &byte in &request {
+            device
+                .on_byte(byte, &mut out, |_| {
+                    led_toggles += 1;
+                    Response::Ok
+                })
+                .unwrap();
+        }
+    }
+    assert_eq!(led_toggles, 1);
+    assert_eq!(device.stats().duplicates, 2);
+    let frames = frames(&out);
+    assert_eq!(frames.len(), 3);
+    assert!(frames
+        .iter()
+        .all(|f| f.1 == 5 && response(&f.2) == Response::Ok));
+
+    // A new sequence number runs the handler again
+    for &byte in &wire(Kind::Request, 6, &Request::SetLed { on: true }) {
+        device
+            .on_byte(byte, &mut out, |_| {
+                led_toggles += 1;
+                Response::Ok
+            })
+            .unwrap();
+    }
+    assert_eq!(led_toggles, 2);
+}
+
+#[test]
+fn undecodable_request_gets_an_error_response() {
+    let mut device = Device::new(POLICY);
+    let mut out = Vec::new();
+    // Variant 200 does not exist
+    for &byte in &wire(Kind::Request, 1, &200u8) {
+        device.on_byte(byte, &mut out, |_| unreachable!()).unwrap();
+    }
+    let frames = frames(&out);
+    assert_eq!(
+        response(&frames[0].2),
+        Response::Error(telemetry::Failure::BadRequest)
+    );
+}
+
+#[test]
+fn corrupted_request_is_counted_and_not_answered() {
+    let mut device = Device::new(POLICY);
+    let mut out = Vec::new();
+    let mut request = wire(Kind::Request, 1, &Request::ReadImu);
+    request[2] ^= 0x10;
+    for &byte in &request {
+        device.on_byte(byte, &mut out, |_| unreachable!()).unwrap();
+    }
+    assert!(out.is_empty());
+    assert_eq!(device.stats().bad_frames, 1);
+}
+
+#[test]
+fn telemetry_is_resent_until_acknowledged() {
+    let mut device = Device::new(POLICY);
+    let mut out = Vec::new();
+    let sample = Telemetry::Imu {
+        uptime_ms: 0,
+        sample: ImuSample::default(),
+    };
+    assert!(device.send_telemetry(&sample, 0, &mut out).unwrap());
+    // Only one outstanding frame: the next sample is skipped
+    assert!(!device.send_telemetry(&sample, 10, &mut out).unwrap());
+    device.poll(50, &mut out).unwrap();
+
+    let sent = frames(&out);
+    assert_eq!(sent.len(), 2);
+    assert_eq!(sent[0], sent[1]);
+    assert_eq!(device.stats().retransmits, 1);
+
+    for &byte in &wire(Kind::Ack, sent[0].1, &()) {
+        device.on_byte(byte, &mut out, |_| unreachable!()).unwrap();
+    }
+    assert!(!device.telemetry_pending());
+    out.clear();
+    device.poll(200, &mut out).unwrap();
+    assert!(out.is_empty());
+
+    // The next frame has the next sequence number
+    assert!(device.send_telemetry(&sample, 200, &mut out).unwrap());
+    assert_eq!(frames(&out)[0].1, sent[0].1 + 1);
+}
+
+#[test]
+fn unacknowledged_telemetry_is_given_up() {
+    let mut device = Device::new(POLICY);
+    let mut out = Vec::new();
+    let reading = Telemetry::Voltage {
+        uptime_ms: 0,
+        pin: 1,
+        mv: 1200,
+    };
+    device.send_telemetry(&reading, 0, &mut out).unwrap();
+    for now in (0..=200).step_by(10) {
+        device.poll(now, &mut out).unwrap();
+    }
+    assert_eq!(frames(&out).len(), 3);
+    assert_eq!(device.stats().lost, 1);
+    assert!(device.send_telemetry(&reading, 200, &mut out).unwrap());
+}
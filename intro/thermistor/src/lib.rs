@@ -0,0 +1,306 @@
+//! NTC thermistor readings to temperatures.
+//!
+//! Two steps, each replaceable:
+//!
+//! 1. [`Divider`] turns a raw ADC reading into the thermistor's resistance,
+//!    for either [`Topology`] of the voltage divider;
+//! 2. a [`Model`] turns the resistance into a temperature: the [`Beta`]
+//!    equation, the three-term [`SteinhartHart`] equation, or linear
+//!    interpolation of the datasheet R/T table ([`LookupTable`]).
+//!
+//! Temperatures come out as `f32` degrees Celsius or as [`CentiCelsius`]
+//! fixed point.
+//!
+//! ```rust
+//! use thermistor::{ntc_10k_3950, CentiCelsius};
+//!
+//! let ntc = ntc_10k_3950();
+//! // Half scale: the NTC equals the 10 kΩ series resistor, so 25 °C
+//! let t = ntc.centi_celsius(2048).unwrap();
+//! assert_eq!(t, CentiCelsius(2499));
+//! assert_eq!(t.to_string(), "24.99");
+//! ```
+//!
+//! The `esp32s3-demo` crate reads the thermistor through the ESP32's ADC.
+
+#![no_std]
+
+use libm::{cbrtf, expf, logf, sqrtf};
+
+/// 0 °C in kelvin.
+pub const ZERO_CELSIUS_K: f32 = 273.15;
+
+/// Why a conversion failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The reading is 0 or full scale, or the resistance is not positive:
+    /// the thermistor is open or shorted.
+    OutOfRange,
+    /// The resistance is outside the lookup table.
+    OutsideTable,
+}
+
+/// Where the thermistor sits in the divider.
+///
+/// ```text
+///   NtcToGround:        NtcToSupply:
+///   VCC                 VCC
+///    |                   |
+///   [R_series]          [NTC]
+///    |---- ADC           |---- ADC
+///   [NTC]               [R_series]
+///    |                   |
+///   GND                 GND
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Topology {
+    /// The reading falls as the temperature rises.
+    NtcToGround,
+    /// The reading rises with the temperature.
+    NtcToSupply,
+}
+
+/// The voltage divider in front of the ADC.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Divider {
+    pub topology: Topology,
+    pub series_ohms: f32,
+    /// Full-scale reading, e.g. 4095 for a 12-bit ADC.
+    pub adc_max: u16,
+}
+
+impl Divider {
+    /// A divider read by an `adc_bits`-bit ADC.
+    pub fn new(topology: Topology, series_ohms: f32, adc_bits: u8) -> Self {
+        Self {
+            topology,
+            series_ohms,
+            adc_max: ((1u32 << adc_bits) - 1) as u16,
+        }
+    }
+
+    /// Raw reading to thermistor resistance, in ohms.
+    pub fn resistance(&self, raw: u16) -> Result<f32, Error> {
+        if raw == 0 || raw >= self.adc_max {
+            return Err(Error::OutOfRange);
+        }
+        let raw = raw as f32;
+        let max = self.adc_max as f32;
+        Ok(match self.topology {
+            Topology::NtcToGround => self.series_ohms * raw / (max - raw),
+            Topology::NtcToSupply => self.series_ohms * (max - raw) / raw,
+        })
+    }
+
+    /// Thermistor resistance to the raw reading, e.g. for alarm thresholds.
+    pub fn raw_for_resistance(&self, ohms: f32) -> u16 {
+        let max = self.adc_max as f32;
+        let ratio = match self.topology {
+            Topology::NtcToGround => ohms / (self.series_ohms + ohms),
+            Topology::NtcToSupply => self.series_ohms / (self.series_ohms + ohms),
+        };
+        (ratio * max + 0.5) as u16
+    }
+}
+
+/// Resistance to temperature.
+pub trait Model {
+    /// Ohms to degrees Celsius.
+    fn celsius(&self, ohms: f32) -> Result<f32, Error>;
+}
+
+// ln(R) of zero, negative or NaN resistances is not a temperature
+fn positive(ohms: f32) -> Result<(), Error> {
+    if ohms > 0.0 {
+        Ok(())
+    } else {
+        Err(Error::OutOfRange)
+    }
+}
+
+/// The Beta equation, `1/T = 1/T0 + ln(R/R0)/B`. Exact at `T0` and at the
+/// second temperature the datasheet B value was measured at (B25/50, B25/85,
+/// ...), and increasingly off away from them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Beta {
+    /// Resistance at `t0_celsius`, usually 10 kΩ at 25 °C.
+    pub r0: f32,
+    pub t0_celsius: f32,
+    pub beta: f32,
+}
+
+impl Beta {
+    pub const fn new(r0: f32, t0_celsius: f32, beta: f32) -> Self {
+        Self {
+            r0,
+            t0_celsius,
+            beta,
+        }
+    }
+
+    /// Degrees Celsius to ohms.
+    pub fn resistance(&self, celsius: f32) -> f32 {
+        let t = celsius + ZERO_CELSIUS_K;
+        let t0 = self.t0_celsius + ZERO_CELSIUS_K;
+        self.r0 * expf(self.beta * (1.0 / t - 1.0 / t0))
+    }
+}
+
+impl Model for Beta {
+    fn celsius(&self, ohms: f32) -> Result<f32, Error> {
+        positive(ohms)?;
+        let t0 = self.t0_celsius + ZERO_CELSIUS_K;
+        let inv_t = 1.0 / t0 + logf(ohms / self.r0) / self.beta;
+        Ok(1.0 / inv_t - ZERO_CELSIUS_K)
+    }
+}
+
+/// The three-term Steinhart-Hart equation, `1/T = A + B·ln(R) + C·ln(R)³`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SteinhartHart {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+}
+
+impl SteinhartHart {
+    pub const fn new(a: f32, b: f32, c: f32) -> Self {
+        Self { a, b, c }
+    }
+
+    /// Fits the coefficients to three `(°C, Ω)` points from the datasheet,
+    /// ideally the low end, the middle and the high end of the range used.
+    pub fn from_points(p1: (f32, f32), p2: (f32, f32), p3: (f32, f32)) -> Self {
+        // f64: the differences of ln(R)^3 lose too much in f32
+        let l = |r: f32| libm::log(r as f64);
+        let y = |t: f32| 1.0 / (t as f64 + ZERO_CELSIUS_K as f64);
+        let (l1, l2, l3) = (l(p1.1), l(p2.1), l(p3.1));
+        let (y1, y2, y3) = (y(p1.0), y(p2.0), y(p3.0));
+
+        let g2 = (y2 - y1) / (l2 - l1);
+        let g3 = (y3 - y1) / (l3 - l1);
+        let c = (g3 - g2) / (l3 - l2) / (l1 + l2 + l3);
+        let b = g2 - c * (l1 * l1 + l1 * l2 + l2 * l2);
+        let a = y1 - (b + l1 * l1 * c) * l1;
+        Self {
+            a: a as f32,
+            b: b as f32,
+            c: c as f32,
+        }
+    }
+
+    /// Degrees Celsius to ohms, solving the cubic.
+    pub fn resistance(&self, celsius: f32) -> f32 {
+        let inv_t = 1.0 / (celsius + ZERO_CELSIUS_K);
+        let x = (self.a - inv_t) / self.c;
+        let k = self.b / (3.0 * self.c);
+        let y = sqrtf(k * k * k + x * x / 4.0);
+        expf(cbrtf(y - x / 2.0) - cbrtf(y + x / 2.0))
+    }
+}
+
+impl Model for SteinhartHart {
+    fn celsius(&self, ohms: f32) -> Result<f32, Error> {
+        positive(ohms)?;
+        let ln_r = logf(ohms);
+        let inv_t = self.a + self.b * ln_r + self.c * ln_r * ln_r * ln_r;
+        Ok(1.0 / inv_t - ZERO_CELSIUS_K)
+    }
+}
+
+/// The datasheet R/T table, `(°C, Ω)` in ascending temperature order,
+/// interpolated linearly in `ln(R)`, which is close to linear in temperature.
+#[derive(Debug, Clone, Copy)]
+pub struct LookupTable<'a> {
+    pub points: &'a [(f32, f32)],
+}
+
+impl<'a> LookupTable<'a> {
+    pub const fn new(points: &'a [(f32, f32)]) -> Self {
+        Self { points }
+    }
+}
+
+impl Model for LookupTable<'_> {
+    fn celsius(&self, ohms: f32) -> Result<f32, Error> {
+        // Ascending temperature, so descending resistance
+        for w in self.points.windows(2) {
+            let (t_lo, r_lo) = w[0];
+            let (t_hi, r_hi) = w[1];
+            if ohms <= r_lo && ohms >= r_hi {
+                let (ln_lo, ln_hi) = (logf(r_lo), logf(r_hi));
+                if ln_lo == ln_hi {
+                    return Ok(t_lo);
+                }
+                let f = (logf(ohms) - ln_lo) / (ln_hi - ln_lo);
+                return Ok(t_lo + f * (t_hi - t_lo));
+            }
+        }
+        Err(Error::OutsideTable)
+    }
+}
+
+/// Fixed-point temperature in 0.01 °C, for targets without float formatting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CentiCelsius(pub i32);
+
+impl CentiCelsius {
+    pub fn from_celsius(celsius: f32) -> Self {
+        // Round half away from zero
+        let scaled = celsius * 100.0;
+        Self(if scaled >= 0.0 {
+            (scaled + 0.5) as i32
+        } else {
+            (scaled - 0.5) as i32
+        })
+    }
+
+    /// Whole degrees, rounded towards zero.
+    pub fn whole(self) -> i32 {
+        self.0 / 100
+    }
+
+    /// Hundredths, without the sign.
+    pub fn fraction(self) -> u32 {
+        (self.0 % 100).unsigned_abs()
+    }
+}
+
+impl core::fmt::Display for CentiCelsius {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        write!(f, "{}{}.{:02}", sign, self.whole().abs(), self.fraction())
+    }
+}
+
+/// A divider and a model: raw readings to temperatures.
+#[derive(Debug, Clone, Copy)]
+pub struct Thermistor<M> {
+    pub divider: Divider,
+    pub model: M,
+}
+
+impl<M: Model> Thermistor<M> {
+    pub const fn new(divider: Divider, model: M) -> Self {
+        Self { divider, model }
+    }
+
+    /// Raw reading to degrees Celsius.
+    pub fn celsius(&self, raw: u16) -> Result<f32, Error> {
+        let ohms = self.divider.resistance(raw)?;
+        self.model.celsius(ohms)
+    }
+
+    pub fn centi_celsius(&self, raw: u16) -> Result<CentiCelsius, Error> {
+        self.celsius(raw).map(CentiCelsius::from_celsius)
+    }
+}
+
+/// The common 10 kΩ, B = 3950 NTC to ground below a 10 kΩ resistor, read
+/// by a 12-bit ADC.
+pub fn ntc_10k_3950() -> Thermistor<Beta> {
+    Thermistor::new(
+        Divider::new(Topology::NtcToGround, 10_000.0, 12),
+        Beta::new(10_000.0, 25.0, 3950.0),
+    )
+}
@@ -0,0 +1,195 @@
+//! Checks the models against the R/T table of a 10 kΩ NTC with
+//! B25/85 = 3977 K (Vishay NTCLE100E3103), 5 °C steps from -40 °C to 60 °C
+//! and 10 °C steps above.
+
+use thermistor::{
+    ntc_10k_3950, Beta, CentiCelsius, Divider, Error, LookupTable, Model, SteinhartHart,
+    Thermistor, Topology,
+};
+
+const TABLE: [(f32, f32); 26] = [
+    (-40.0, 332_094.0),
+    (-35.0, 239_900.0),
+    (-30.0, 175_200.0),
+    (-25.0, 129_287.0),
+    (-20.0, 96_358.0),
+    (-15.0, 72_500.0),
+    (-10.0, 55_046.0),
+    (-5.0, 42_157.0),
+    (0.0, 32_554.0),
+    (5.0, 25_339.0),
+    (10.0, 19_872.0),
+    (15.0, 15_698.0),
+    (20.0, 12_488.0),
+    (25.0, 10_000.0),
+    (30.0, 8_059.0),
+    (35.0, 6_535.0),
+    (40.0, 5_330.0),
+    (45.0, 4_372.0),
+    (50.0, 3_605.0),
+    (55.0, 2_989.0),
+    (60.0, 2_490.0),
+    (70.0, 1_753.0),
+    (80.0, 1_256.0),
+    (85.0, 1_070.0),
+    (90.0, 915.4),
+    (100.0, 679.1),
+];
+
+const BETA_25_85: Beta = Beta::new(10_000.0, 25.0, 3977.0);
+
+fn fitted() -> SteinhartHart {
+    SteinhartHart::from_points((-20.0, 96_358.0), (25.0, 10_000.0), (85.0, 1_070.0))
+}
+
+/// Largest deviation from the table, in °C.
+fn worst_error(model: &impl Model, range: core::ops::RangeInclusive<f32>) -> f32 {
+    TABLE
+        .iter()
+        .filter(|(t, _)| range.contains(t))
+        .map(|&(t, r)| (model.celsius(r).unwrap() - t).abs())
+        .fold(0.0, f32::max)
+}
+
+#[test]
+fn steinhart_hart_matches_the_whole_table() {
+    assert!(worst_error(&fitted(), -40.0..=100.0) < 0.1);
+}
+
+#[test]
+fn steinhart_hart_resistance_inverts_celsius() {
+    let sh = fitted();
+    for &(t, r) in &TABLE {
+        let back = sh.resistance(t);
+        assert!((back - r).abs() / r < 0.005, "{t} °C: {back} Ω vs {r} Ω");
+        let t_back = sh.celsius(sh.resistance(t)).unwrap();
+        assert!((t_back - t).abs() < 0.01);
+    }
+}
+
+#[test]
+fn beta_is_exact_at_its_reference_points_only() {
+    assert!((BETA_25_85.celsius(10_000.0).unwrap() - 25.0).abs() < 0.01);
+    assert!((BETA_25_85.celsius(1_070.0).unwrap() - 85.0).abs() < 0.05);
+    // Good to a degree around room temperature...
+    assert!(worst_error(&BETA_25_85, 0.0..=100.0) < 1.0);
+    // ...but several degrees off at the cold end, where the real B is lower
+    let cold = BETA_25_85.celsius(332_094.0).unwrap();
+    assert!(cold - -40.0 > 2.0, "{cold}");
+}
+
+#[test]
+fn beta_resistance_inverts_celsius() {
+    for t in [-40.0, 0.0, 25.0, 60.0, 100.0] {
+        let r = BETA_25_85.resistance(t);
+        assert!((BETA_25_85.celsius(r).unwrap() - t).abs() < 0.01);
+    }
+    assert!((BETA_25_85.resistance(25.0) - 10_000.0).abs() < 0.01);
+}
+
+#[test]
+fn lookup_table_is_exact_at_the_points() {
+    let table = LookupTable::new(&TABLE);
+    for &(t, r) in &TABLE {
+        assert!((table.celsius(r).unwrap() - t).abs() < 1e-3, "{t} °C");
+    }
+}
+
+#[test]
+fn lookup_table_interpolates_between_points() {
+    let table = LookupTable::new(&TABLE);
+    let sh = fitted();
+    // Halfway between table points, compared with the fitted curve
+    for w in TABLE.windows(2) {
+        let t = (w[0].0 + w[1].0) / 2.0;
+        let r = sh.resistance(t);
+        let interpolated = table.celsius(r).unwrap();
+        assert!((interpolated - t).abs() < 0.15, "{t} °C: {interpolated}");
+    }
+}
+
+#[test]
+fn lookup_table_rejects_values_outside() {
+    let table = LookupTable::new(&TABLE);
+    assert_eq!(table.celsius(400_000.0), Err(Error::OutsideTable));
+    assert_eq!(table.celsius(500.0), Err(Error::OutsideTable));
+    assert_eq!(
+        LookupTable::new(&[]).celsius(10_000.0),
+        Err(Error::OutsideTable)
+    );
+}
+
+#[test]
+fn models_reject_non_positive_resistance() {
+    assert_eq!(BETA_25_85.celsius(0.0), Err(Error::OutOfRange));
+    assert_eq!(fitted().celsius(-1.0), Err(Error::OutOfRange));
+    assert_eq!(fitted().celsius(f32::NAN), Err(Error::OutOfRange));
+}
+
+#[test]
+fn divider_round_trips_both_topologies() {
+    for topology in [Topology::NtcToGround, Topology::NtcToSupply] {
+        let divider = Divider::new(topology, 10_000.0, 12);
+        assert_eq!(divider.adc_max, 4095);
+        for &(t, r) in &TABLE[4..22] {
+            let raw = divider.raw_for_resistance(r);
+            let back = divider.resistance(raw).unwrap();
+            // One LSB of quantisation
+            assert!((back - r).abs() / r < 0.02, "{topology:?} {t} °C");
+        }
+    }
+}
+
+#[test]
+fn divider_direction() {
+    let to_ground = Divider::new(Topology::NtcToGround, 10_000.0, 12);
+    let to_supply = Divider::new(Topology::NtcToSupply, 10_000.0, 12);
+    // Hotter NTC, lower resistance
+    assert!(to_ground.raw_for_resistance(5_330.0) < to_ground.raw_for_resistance(32_554.0));
+    assert!(to_supply.raw_for_resistance(5_330.0) > to_supply.raw_for_resistance(32_554.0));
+    assert_eq!(to_ground.raw_for_resistance(10_000.0), 2048);
+}
+
+#[test]
+fn open_or_shorted_reads_are_errors() {
+    let divider = Divider::new(Topology::NtcToGround, 10_000.0, 12);
+    assert_eq!(divider.resistance(0), Err(Error::OutOfRange));
+    assert_eq!(divider.resistance(4095), Err(Error::OutOfRange));
+    assert!(divider.resistance(1).is_ok());
+    assert!(divider.resistance(4094).is_ok());
+}
+
+#[test]
+fn thermistor_reads_the_table_through_the_adc() {
+    let ntc = Thermistor::new(
+        Divider::new(Topology::NtcToGround, 10_000.0, 12),
+        LookupTable::new(&TABLE),
+    );
+    for &(t, r) in &TABLE[4..22] {
+        let raw = ntc.divider.raw_for_resistance(r);
+        let read = ntc.celsius(raw).unwrap();
+        // ADC steps are coarsest at the ends of the range
+        assert!((read - t).abs() < 0.5, "{t} °C read as {read}");
+    }
+}
+
+#[test]
+fn default_ntc_at_room_temperature() {
+    let ntc = ntc_10k_3950();
+    let t = ntc.celsius(2048).unwrap();
+    assert!((t - 25.0).abs() < 0.05);
+    // Warmer NTC to ground, lower reading
+    assert!(ntc.celsius(1500).unwrap() > 35.0);
+}
+
+#[test]
+fn centi_celsius_rounds_and_formats() {
+    assert_eq!(CentiCelsius::from_celsius(21.456), CentiCelsius(2146));
+    assert_eq!(CentiCelsius::from_celsius(-21.454), CentiCelsius(-2145));
+    assert_eq!(CentiCelsius(2146).to_string(), "21.46");
+    assert_eq!(CentiCelsius(-2105).to_string(), "-21.05");
+    assert_eq!(CentiCelsius(-5).to_string(), "-0.05");
+    assert_eq!(CentiCelsius(0).to_string(), "0.00");
+    assert_eq!(CentiCelsius(-2105).whole(), -21);
+    assert_eq!(CentiCelsius(-2105).fraction(), 5);
+}
@@ -0,0 +1,221 @@
+//! Per-task deadlines in front of a hardware watchdog.
+//!
+//! Feeding a watchdog from the main loop only proves that the main loop runs;
+//! a task that is stuck waiting on a bus or a queue goes unnoticed as long as
+//! something else keeps the loop spinning. A [`Supervisor`] instead tracks
+//! every task separately: each task registers with its own deadline and
+//! checks in while it works, and [`Supervisor::health`] only reports
+//! [`Health::Healthy`] while every task has checked in within its deadline.
+//! The caller feeds the watchdog only then, so one stuck task is enough to
+//! let it fire.
+//!
+//! When it does, the task to blame is written as a [`StallRecord`] into
+//! memory that survives the reset, and read back on the next boot.
+//!
+//! Everything works on millisecond timestamps and plain words, so it can be
+//! tested on the host:
+//!
+//! ```rust
+//! use watchdog_supervisor::{Health, Supervisor};
+//!
+//! let mut supervisor = Supervisor::<2>::new();
+//! let sensor = supervisor.register("sensor", 100, 0).unwrap();
+//! let radio = supervisor.register("radio", 500, 0).unwrap();
+//!
+//! supervisor.check_in(sensor, 90);
+//! assert_eq!(supervisor.health(150), Health::Healthy);
+//! assert_eq!(
+//!     supervisor.health(220),
+//!     Health::Stalled { task: sensor, overdue_ms: 30 }
+//! );
+//! # let _ = radio;
+//! ```
+//!
+//! The `esp32s3-demo` crate feeds the MWDT with it and keeps the stall record
+//! in RTC FAST memory.
+
+#![no_std]
+
+use heapless::Vec;
+
+/// Handle returned by [`Supervisor::register`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TaskId(u8);
+
+impl TaskId {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// The supervisor already holds its capacity of tasks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TooManyTasks;
+
+/// State of one task.
+#[derive(Debug, Clone, Copy)]
+pub struct TaskEntry {
+    pub name: &'static str,
+    /// Longest allowed time between two check-ins.
+    pub deadline_ms: u64,
+    pub last_check_in_ms: u64,
+}
+
+impl TaskEntry {
+    /// How far past its deadline the task is, or `None` if it is not.
+    pub fn overdue_ms(&self, now_ms: u64) -> Option<u64> {
+        let elapsed = now_ms.saturating_sub(self.last_check_in_ms);
+        if elapsed > self.deadline_ms {
+            Some(elapsed - self.deadline_ms)
+        } else {
+            None
+        }
+    }
+}
+
+/// Result of [`Supervisor::health`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Health {
+    /// Every task checked in on time: feed the watchdog.
+    Healthy,
+    /// The task furthest past its deadline.
+    Stalled { task: TaskId, overdue_ms: u64 },
+}
+
+/// Tracks up to `N` tasks.
+pub struct Supervisor<const N: usize> {
+    tasks: Vec<TaskEntry, N>,
+}
+
+impl<const N: usize> Supervisor<N> {
+    pub const fn new() -> Self {
+        Self { tasks: Vec::new() }
+    }
+
+    /// Adds a task. Registering counts as its first check-in.
+    pub fn register(
+        &mut self,
+        name: &'static str,
+        deadline_ms: u64,
+        now_ms: u64,
+    ) -> Result<TaskId, TooManyTasks> {
+        let id = TaskId(u8::try_from(self.tasks.len()).map_err(|_| TooManyTasks)?);
+        self.tasks
+            .push(TaskEntry {
+                name,
+                deadline_ms,
+                last_check_in_ms: now_ms,
+            })
+            .map_err(|_| TooManyTasks)?;
+        Ok(id)
+    }
+
+    /// Records that `task` is alive. Unknown ids are ignored.
+    pub fn check_in(&mut self, task: TaskId, now_ms: u64) {
+        if let Some(entry) = self.tasks.get_mut(task.index()) {
+            entry.last_check_in_ms = now_ms;
+        }
+    }
+
+    pub fn task(&self, task: TaskId) -> Option<&TaskEntry> {
+        self.tasks.get(task.index())
+    }
+
+    pub fn tasks(&self) -> &[TaskEntry] {
+        &self.tasks
+    }
+
+    /// Checks every task and reports the one furthest past its deadline.
+    /// Ties go to the task registered first.
+    pub fn health(&self, now_ms: u64) -> Health {
+        let mut worst: Option<(TaskId, u64)> = None;
+        for (i, entry) in self.tasks.iter().enumerate() {
+            if let Some(overdue) = entry.overdue_ms(now_ms) {
+                if worst.is_none_or(|(_, w)| overdue > w) {
+                    worst = Some((TaskId(i as u8), overdue));
+                }
+            }
+        }
+        match worst {
+            Some((task, overdue_ms)) => Health::Stalled { task, overdue_ms },
+            None => Health::Healthy,
+        }
+    }
+}
+
+impl<const N: usize> Default for Supervisor<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Bytes of the task name kept in a [`StallRecord`].
+pub const STALL_NAME_LEN: usize = 16;
+
+/// Words a [`StallRecord`] occupies: magic, task index, overdue time, name
+/// length and the name itself.
+pub const STALL_WORDS: usize = 4 + STALL_NAME_LEN / 4;
+
+/// Marks valid records; memory that survives a reset is random after power-on.
+const STALL_MAGIC: u32 = 0x5754_4447; // "WTDG"
+
+/// Which task stalled, as written before a watchdog reset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StallRecord {
+    pub task_index: u8,
+    pub overdue_ms: u32,
+    name: [u8; STALL_NAME_LEN],
+    name_len: u8,
+}
+
+impl StallRecord {
+    /// Names are cut to [`STALL_NAME_LEN`] bytes, at a character boundary,
+    /// and the overdue time saturates at `u32::MAX`.
+    pub fn new(task: TaskId, name: &str, overdue_ms: u64) -> Self {
+        let mut len = name.len().min(STALL_NAME_LEN);
+        while !name.is_char_boundary(len) {
+            len -= 1;
+        }
+        let mut bytes = [0u8; STALL_NAME_LEN];
+        bytes[..len].copy_from_slice(&name.as_bytes()[..len]);
+        Self {
+            task_index: task.0,
+            overdue_ms: overdue_ms.min(u32::MAX as u64) as u32,
+            name: bytes,
+            name_len: len as u8,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        core::str::from_utf8(&self.name[..self.name_len as usize]).unwrap_or("?")
+    }
+
+    pub fn to_words(&self) -> [u32; STALL_WORDS] {
+        let mut words = [0u32; STALL_WORDS];
+        words[0] = STALL_MAGIC;
+        words[1] = self.task_index as u32;
+        words[2] = self.overdue_ms;
+        words[3] = self.name_len as u32;
+        for (i, b) in self.name.iter().enumerate() {
+            words[4 + i / 4] |= (*b as u32) << ((i % 4) * 8);
+        }
+        words
+    }
+
+    /// `None` unless `words` hold a record written by [`Self::to_words`].
+    pub fn from_words(words: &[u32; STALL_WORDS]) -> Option<Self> {
+        if words[0] != STALL_MAGIC {
+            return None;
+        }
+        let mut name = [0u8; STALL_NAME_LEN];
+        for (i, b) in name.iter_mut().enumerate() {
+            *b = (words[4 + i / 4] >> ((i % 4) * 8)) as u8;
+        }
+        Some(Self {
+            task_index: words[1] as u8,
+            overdue_ms: words[2],
+            name,
+            name_len: (words[3] as usize).min(STALL_NAME_LEN) as u8,
+        })
+    }
+}
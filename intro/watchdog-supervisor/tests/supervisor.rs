@@ -0,0 +1,187 @@
+use watchdog_supervisor::{
+    Health, StallRecord, Supervisor, TaskEntry, TooManyTasks, STALL_NAME_LEN, STALL_WORDS,
+};
+
+#[test]
+fn empty_supervisor_is_healthy() {
+    let supervisor = Supervisor::<4>::new();
+    assert_eq!(supervisor.health(u64::MAX), Health::Healthy);
+}
+
+#[test]
+fn registering_counts_as_a_check_in() {
+    let mut supervisor = Supervisor::<1>::new();
+    let task = supervisor.register("sensor", 100, 1_000).unwrap();
+    assert_eq!(supervisor.task(task).unwrap().last_check_in_ms, 1_000);
+    assert_eq!(supervisor.health(1_100), Health::Healthy);
+    assert_eq!(
+        supervisor.health(1_101),
+        Health::Stalled {
+            task,
+            overdue_ms: 1
+        }
+    );
+}
+
+#[test]
+fn deadline_is_inclusive() {
+    let entry = TaskEntry {
+        name: "t",
+        deadline_ms: 50,
+        last_check_in_ms: 10,
+    };
+    assert_eq!(entry.overdue_ms(60), None);
+    assert_eq!(entry.overdue_ms(61), Some(1));
+    // A clock behind the last check-in is not overdue
+    assert_eq!(entry.overdue_ms(0), None);
+}
+
+#[test]
+fn check_in_restarts_the_deadline() {
+    let mut supervisor = Supervisor::<2>::new();
+    let task = supervisor.register("loop", 100, 0).unwrap();
+    for now in (0..1_000).step_by(80) {
+        supervisor.check_in(task, now);
+        assert_eq!(supervisor.health(now + 100), Health::Healthy);
+    }
+}
+
+#[test]
+fn one_stuck_task_is_enough() {
+    let mut supervisor = Supervisor::<3>::new();
+    let fast = supervisor.register("fast", 20, 0).unwrap();
+    let slow = supervisor.register("slow", 1_000, 0).unwrap();
+    // `fast` keeps checking in, `slow` stops after its first check-in
+    supervisor.check_in(slow, 100);
+    let mut now = 0;
+    while now < 1_100 {
+        now += 10;
+        supervisor.check_in(fast, now);
+        assert_eq!(supervisor.health(now), Health::Healthy);
+    }
+    now += 10;
+    supervisor.check_in(fast, now);
+    assert_eq!(
+        supervisor.health(now),
+        Health::Stalled {
+            task: slow,
+            overdue_ms: 10
+        }
+    );
+}
+
+#[test]
+fn the_worst_task_is_reported() {
+    let mut supervisor = Supervisor::<3>::new();
+    let a = supervisor.register("a", 100, 0).unwrap();
+    let b = supervisor.register("b", 50, 0).unwrap();
+    let c = supervisor.register("c", 10, 0).unwrap();
+    assert_eq!(
+        supervisor.health(200),
+        Health::Stalled {
+            task: c,
+            overdue_ms: 190
+        }
+    );
+    supervisor.check_in(c, 200);
+    assert_eq!(
+        supervisor.health(200),
+        Health::Stalled {
+            task: b,
+            overdue_ms: 150
+        }
+    );
+    // On a tie the task registered first wins
+    supervisor.check_in(b, 150);
+    assert_eq!(
+        supervisor.health(260),
+        Health::Stalled {
+            task: a,
+            overdue_ms: 160
+        }
+    );
+    supervisor.check_in(a, 210);
+    assert_eq!(
+        supervisor.health(260),
+        Health::Stalled {
+            task: b,
+            overdue_ms: 60
+        }
+    );
+}
+
+#[test]
+fn capacity_is_enforced() {
+    let mut supervisor = Supervisor::<2>::new();
+    supervisor.register("a", 1, 0).unwrap();
+    supervisor.register("b", 1, 0).unwrap();
+    assert_eq!(supervisor.register("c", 1, 0), Err(TooManyTasks));
+    assert_eq!(supervisor.tasks().len(), 2);
+}
+
+#[test]
+fn check_in_with_a_foreign_id_is_ignored() {
+    let mut big = Supervisor::<4>::new();
+    big.register("a", 1, 0).unwrap();
+    let foreign = big.register("b", 1, 0).unwrap();
+
+    let mut small = Supervisor::<1>::new();
+    let own = small.register("x", 10, 0).unwrap();
+    small.check_in(foreign, 1_000);
+    assert_eq!(small.task(foreign).map(|t| t.name), None);
+    assert_eq!(
+        small.health(20),
+        Health::Stalled {
+            task: own,
+            overdue_ms: 10
+        }
+    );
+}
+
+#[test]
+fn stall_record_round_trips() {
+    let mut supervisor = Supervisor::<2>::new();
+    supervisor.register("idle", 1, 0).unwrap();
+    let task = supervisor.register("imu", 1, 0).unwrap();
+    let record = StallRecord::new(task, "imu", 1234);
+    let words = record.to_words();
+    let read = StallRecord::from_words(&words).unwrap();
+    assert_eq!(read, record);
+    assert_eq!(read.task_index, 1);
+    assert_eq!(read.overdue_ms, 1234);
+    assert_eq!(read.name(), "imu");
+}
+
+#[test]
+fn stall_record_truncates_and_saturates() {
+    let mut supervisor = Supervisor::<1>::new();
+    let task = supervisor.register("t", 1, 0).unwrap();
+
+    let record = StallRecord::new(task, "a-task-name-longer-than-sixteen", u64::MAX);
+    assert_eq!(record.name(), "a-task-name-long");
+    assert_eq!(record.name().len(), STALL_NAME_LEN);
+    assert_eq!(record.overdue_ms, u32::MAX);
+
+    // Not in the middle of a multi-byte character
+    let record = StallRecord::new(task, "传感器传感器", 0);
+    assert_eq!(record.name(), "传感器传感");
+    assert_eq!(
+        StallRecord::from_words(&record.to_words()).unwrap().name(),
+        "传感器传感"
+    );
+}
+
+#[test]
+fn random_memory_is_not_a_record() {
+    assert_eq!(StallRecord::from_words(&[0; STALL_WORDS]), None);
+    assert_eq!(StallRecord::from_words(&[0xA5A5_A5A5; STALL_WORDS]), None);
+
+    // A valid magic with a garbage length is cut to the name buffer
+    let mut supervisor = Supervisor::<1>::new();
+    let task = supervisor.register("t", 1, 0).unwrap();
+    let mut words = StallRecord::new(task, "abc", 1).to_words();
+    words[3] = 1_000;
+    let record = StallRecord::from_words(&words).unwrap();
+    assert_eq!(record.name().len(), STALL_NAME_LEN);
+    assert!(record.name().starts_with("abc"));
+}
@@ -0,0 +1,162 @@
+//! WS2812 (NeoPixel) pulse-code encoder for the ESP32 RMT peripheral.
+//!
+//! A WS2812 tells a 0 bit from a 1 bit by the width of a high pulse, and
+//! the widths must be right to within about 150 ns. Bit-banging them with
+//! busy loops breaks with every change of optimisation level, CPU clock or
+//! interrupt load. The RMT peripheral instead plays back a list of 32-bit
+//! pulse codes in hardware, so all the CPU has to do is build that list.
+//!
+//! This crate builds it, without depending on a HAL, so it can be tested on
+//! the host:
+//!
+//! * [`Timing`] converts the datasheet pulse widths to RMT ticks;
+//! * [`pulse_code`] packs two pulse segments into the RMT word format;
+//! * [`encode_color`] and [`encode_frame`] turn colours into 24 codes per
+//!   LED, green first, most significant bit first, and add the end marker.
+//!
+//! ```rust
+//! use rgb::RGB8;
+//! use ws2812::{buffer_size, encode_frame, Timing};
+//!
+//! let mut buffer = [0u32; buffer_size(2)];
+//! let timing = Timing::default();
+//! let len = encode_frame([RGB8::new(255, 0, 0), RGB8::new(0, 0, 1)], &timing, &mut buffer).unwrap();
+//! assert_eq!(len, 49);
+//! assert_eq!(buffer[48], 0);
+//! ```
+//!
+//! The `esp32s3-demo` crate sends the encoded frames through an RMT channel.
+
+#![no_std]
+
+use rgb::RGB8;
+
+/// High time of a 0 bit, ns.
+pub const T0H_NS: u32 = 400;
+/// Low time of a 0 bit, ns.
+pub const T0L_NS: u32 = 850;
+/// High time of a 1 bit, ns.
+pub const T1H_NS: u32 = 800;
+/// Low time of a 1 bit, ns.
+pub const T1L_NS: u32 = 450;
+
+/// Green, red and blue, 8 bits each, one pulse code per bit.
+pub const BITS_PER_LED: usize = 24;
+
+/// RMT source clock (APB) with a divider of 1: 12.5 ns per tick.
+pub const DEFAULT_RMT_CLOCK_MHZ: u32 = 80;
+
+/// Largest segment length the 15-bit field of a pulse code holds.
+pub const MAX_TICKS: u16 = 0x7FFF;
+
+/// Pulse codes needed for `leds` LEDs, end marker included.
+pub const fn buffer_size(leds: usize) -> usize {
+    leds * BITS_PER_LED + 1
+}
+
+/// High and low times of both bit values, in RMT ticks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timing {
+    pub t0h: u16,
+    pub t0l: u16,
+    pub t1h: u16,
+    pub t1l: u16,
+}
+
+impl Timing {
+    /// The datasheet times for an RMT channel clocked at `clock_mhz`.
+    pub const fn from_clock_mhz(clock_mhz: u32) -> Self {
+        Self {
+            t0h: ns_to_ticks(T0H_NS, clock_mhz),
+            t0l: ns_to_ticks(T0L_NS, clock_mhz),
+            t1h: ns_to_ticks(T1H_NS, clock_mhz),
+            t1l: ns_to_ticks(T1L_NS, clock_mhz),
+        }
+    }
+}
+
+impl Default for Timing {
+    fn default() -> Self {
+        Self::from_clock_mhz(DEFAULT_RMT_CLOCK_MHZ)
+    }
+}
+
+/// Rounds to the nearest tick and saturates at [`MAX_TICKS`].
+pub const fn ns_to_ticks(ns: u32, clock_mhz: u32) -> u16 {
+    let ticks = (ns as u64 * clock_mhz as u64 + 500) / 1000;
+    if ticks > MAX_TICKS as u64 {
+        MAX_TICKS
+    } else {
+        ticks as u16
+    }
+}
+
+/// One RMT pulse code: the first segment in bits 0..=15, the second in
+/// bits 16..=31, each a 15-bit length with the level in the top bit.
+/// Lengths are truncated to 15 bits.
+pub const fn pulse_code(level1: bool, length1: u16, level2: bool, length2: u16) -> u32 {
+    ((level2 as u32) << 31)
+        | (((length2 & MAX_TICKS) as u32) << 16)
+        | ((level1 as u32) << 15)
+        | (length1 & MAX_TICKS) as u32
+}
+
+/// High then low, with the widths of `bit`.
+pub const fn encode_bit(bit: bool, timing: &Timing) -> u32 {
+    if bit {
+        pulse_code(true, timing.t1h, false, timing.t1l)
+    } else {
+        pulse_code(true, timing.t0h, false, timing.t0l)
+    }
+}
+
+/// Most significant bit first.
+pub fn encode_byte(byte: u8, timing: &Timing) -> [u32; 8] {
+    let mut codes = [0u32; 8];
+    for (i, code) in codes.iter_mut().enumerate() {
+        let bit = (byte >> (7 - i)) & 1 == 1;
+        *code = encode_bit(bit, timing);
+    }
+    codes
+}
+
+/// In the order the WS2812 expects: green, red, blue.
+pub fn encode_color(color: RGB8, timing: &Timing) -> [u32; BITS_PER_LED] {
+    let mut codes = [0u32; BITS_PER_LED];
+    let (chunks, _) = codes.as_chunks_mut::<8>();
+    for (chunk, byte) in chunks.iter_mut().zip([color.g, color.r, color.b]) {
+        *chunk = encode_byte(byte, timing);
+    }
+    codes
+}
+
+/// The buffer cannot hold the frame and its end marker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferTooSmall;
+
+/// Encodes `colors` into `buffer` followed by the end marker (0) and
+/// returns the number of codes written, end marker included.
+pub fn encode_frame<I>(
+    colors: I,
+    timing: &Timing,
+    buffer: &mut [u32],
+) -> Result<usize, BufferTooSmall>
+where
+    I: IntoIterator<Item = RGB8>,
+{
+    let mut len = 0;
+    for color in colors {
+        let end = len + BITS_PER_LED;
+        // Keep a slot for the end marker
+        if end >= buffer.len() {
+            return Err(BufferTooSmall);
+        }
+        buffer[len..end].copy_from_slice(&encode_color(color, timing));
+        len = end;
+    }
+    if len >= buffer.len() {
+        return Err(BufferTooSmall);
+    }
+    buffer[len] = 0;
+    Ok(len + 1)
+}
@@ -0,0 +1,147 @@
+use rgb::RGB8;
+use ws2812::{
+    buffer_size, encode_bit, encode_byte, encode_color, encode_frame, ns_to_ticks, pulse_code,
+    BufferTooSmall, Timing, BITS_PER_LED, MAX_TICKS,
+};
+
+const TIMING: Timing = Timing::from_clock_mhz(80);
+
+fn one() -> u32 {
+    encode_bit(true, &TIMING)
+}
+
+fn zero() -> u32 {
+    encode_bit(false, &TIMING)
+}
+
+/// Reads the bits back from a run of codes.
+fn bits(codes: &[u32]) -> Vec<bool> {
+    codes
+        .iter()
+        .map(|&code| {
+            assert!(
+                code == one() || code == zero(),
+                "not a data bit: {code:#010x}"
+            );
+            code == one()
+        })
+        .collect()
+}
+
+fn byte(bits: &[bool]) -> u8 {
+    bits.iter().fold(0, |acc, &b| acc << 1 | b as u8)
+}
+
+#[test]
+fn datasheet_times_at_80_mhz() {
+    // 12.5 ns per tick
+    assert_eq!(
+        TIMING,
+        Timing {
+            t0h: 32,
+            t0l: 68,
+            t1h: 64,
+            t1l: 36,
+        }
+    );
+    assert_eq!(Timing::default(), TIMING);
+}
+
+#[test]
+fn ticks_round_to_nearest() {
+    // 400 ns at 1 MHz is 0.4 ticks, at 3 MHz 1.2, at 4 MHz 1.6
+    assert_eq!(ns_to_ticks(400, 1), 0);
+    assert_eq!(ns_to_ticks(400, 3), 1);
+    assert_eq!(ns_to_ticks(400, 4), 2);
+    // Exactly half a tick rounds up
+    assert_eq!(ns_to_ticks(500, 1), 1);
+    assert_eq!(ns_to_ticks(499, 1), 0);
+    assert_eq!(Timing::from_clock_mhz(40).t0l, 34);
+}
+
+#[test]
+fn ticks_saturate_at_15_bits() {
+    assert_eq!(ns_to_ticks(1_000_000, 80), MAX_TICKS);
+    assert_eq!(ns_to_ticks(u32::MAX, u32::MAX), MAX_TICKS);
+}
+
+#[test]
+fn pulse_code_layout() {
+    assert_eq!(pulse_code(true, 32, false, 68), 68 << 16 | 1 << 15 | 32);
+    assert_eq!(pulse_code(false, 1, true, 2), 1 << 31 | 2 << 16 | 1);
+    // Lengths are cut to 15 bits instead of spilling into the level bits
+    assert_eq!(pulse_code(false, 0xFFFF, false, 0xFFFF), 0x7FFF_7FFF);
+}
+
+#[test]
+fn bits_are_high_then_low() {
+    assert_eq!(one(), pulse_code(true, 64, false, 36));
+    assert_eq!(zero(), pulse_code(true, 32, false, 68));
+}
+
+#[test]
+fn bytes_are_sent_msb_first() {
+    let codes = encode_byte(0b1000_0001, &TIMING);
+    assert_eq!(
+        bits(&codes),
+        [true, false, false, false, false, false, false, true]
+    );
+    for value in [0x00, 0x5A, 0xA5, 0xFF] {
+        assert_eq!(byte(&bits(&encode_byte(value, &TIMING))), value);
+    }
+}
+
+#[test]
+fn colors_are_sent_green_red_blue() {
+    let codes = encode_color(RGB8::new(0x12, 0x34, 0x56), &TIMING);
+    let bits = bits(&codes);
+    assert_eq!(byte(&bits[0..8]), 0x34);
+    assert_eq!(byte(&bits[8..16]), 0x12);
+    assert_eq!(byte(&bits[16..24]), 0x56);
+}
+
+#[test]
+fn frame_ends_with_the_end_marker() {
+    let colors = [RGB8::new(1, 2, 3), RGB8::new(4, 5, 6), RGB8::new(7, 8, 9)];
+    let mut buffer = [0xDEAD_BEEFu32; buffer_size(3)];
+    let len = encode_frame(colors, &TIMING, &mut buffer).unwrap();
+    assert_eq!(len, 3 * BITS_PER_LED + 1);
+    assert_eq!(buffer[len - 1], 0);
+    for (i, color) in colors.into_iter().enumerate() {
+        let led = &buffer[i * BITS_PER_LED..(i + 1) * BITS_PER_LED];
+        assert_eq!(led, encode_color(color, &TIMING));
+    }
+}
+
+#[test]
+fn shorter_frame_leaves_the_rest_of_the_buffer_alone() {
+    let mut buffer = [0xDEAD_BEEFu32; buffer_size(4)];
+    let len = encode_frame([RGB8::default()], &TIMING, &mut buffer).unwrap();
+    assert_eq!(len, BITS_PER_LED + 1);
+    assert!(buffer[len..].iter().all(|&code| code == 0xDEAD_BEEF));
+}
+
+#[test]
+fn empty_frame_is_just_the_end_marker() {
+    let mut buffer = [1u32; 1];
+    assert_eq!(encode_frame([], &TIMING, &mut buffer), Ok(1));
+    assert_eq!(buffer, [0]);
+    assert_eq!(encode_frame([], &TIMING, &mut []), Err(BufferTooSmall));
+}
+
+#[test]
+fn buffer_without_room_for_the_end_marker_is_too_small() {
+    let colors = [RGB8::default(); 2];
+    let mut exact = [0u32; buffer_size(2)];
+    assert!(encode_frame(colors, &TIMING, &mut exact).is_ok());
+    let mut no_marker = [0u32; 2 * BITS_PER_LED];
+    assert_eq!(
+        encode_frame(colors, &TIMING, &mut no_marker),
+        Err(BufferTooSmall)
+    );
+    let mut one_led = [0u32; buffer_size(1)];
+    assert_eq!(
+        encode_frame(colors, &TIMING, &mut one_led),
+        Err(BufferTooSmall)
+    );
+}